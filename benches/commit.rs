@@ -0,0 +1,83 @@
+//! Benchmarks Pedersen commitments to vectors with and without a fixed-base table over the generators,
+//! and commitments to the mostly-boolean witnesses of SHA-256 with and without `commit_small`
+use bellpepper::{gadgets::sha256::sha256, util_cs::witness_cs::WitnessCS};
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  ConstraintSystem,
+};
+use core::time::Duration;
+use criterion::*;
+use ff::Field;
+use rand::rngs::OsRng;
+use spartan2::{
+  provider::{bn256_grumpkin::bn256, pedersen::CommitmentEngine},
+  traits::{commitment::CommitmentEngineTrait, Group},
+};
+
+type G = bn256::Point;
+type CE = CommitmentEngine<G>;
+
+criterion_group! {
+name = commit;
+config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+targets = bench_commit, bench_commit_witness
+}
+
+criterion_main!(commit);
+
+fn bench_commit(c: &mut Criterion) {
+  for num_vars in [8, 10, 12] {
+    let mut group = c.benchmark_group(format!("Commit-len-{}", 1 << num_vars));
+    group.sample_size(10);
+
+    let mut ck = CE::setup(b"bench", 1 << num_vars).unwrap();
+    let v = (0..1 << num_vars)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+
+    group.bench_function("MSM", |b| {
+      b.iter(|| CE::commit(black_box(&ck), black_box(&v)));
+    });
+
+    ck.precompute();
+    group.bench_function("FixedBase", |b| {
+      b.iter(|| CE::commit(black_box(&ck), black_box(&v)));
+    });
+    group.finish();
+  }
+}
+
+fn bench_commit_witness(c: &mut Criterion) {
+  for len in [1 << 6, 1 << 8] {
+    let mut group = c.benchmark_group(format!("CommitWitness-Sha256-message-len-{len}"));
+    group.sample_size(10);
+
+    let mut cs = WitnessCS::<<G as Group>::Scalar>::new();
+    let preimage_bits = (0..len * 8)
+      .map(|i| {
+        AllocatedBit::alloc(
+          cs.namespace(|| format!("preimage bit {i}")),
+          Some(i % 3 == 0),
+        )
+      })
+      .map(|b| b.map(Boolean::from))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    sha256(cs.namespace(|| "sha256"), &preimage_bits).unwrap();
+    let witness = cs.scalar_aux();
+
+    let mut ck = CE::setup(b"bench", witness.len()).unwrap();
+    for table in ["MSM", "FixedBase"] {
+      if table == "FixedBase" {
+        ck.precompute();
+      }
+      group.bench_function(format!("{table}/Commit"), |b| {
+        b.iter(|| CE::commit(black_box(&ck), black_box(&witness)));
+      });
+      group.bench_function(format!("{table}/CommitSmall"), |b| {
+        b.iter(|| CE::commit_small(black_box(&ck), black_box(&witness)));
+      });
+    }
+    group.finish();
+  }
+}
@@ -10,9 +10,18 @@ use bellpepper_core::{
 };
 use core::time::Duration;
 use criterion::*;
-use ff::{PrimeField, PrimeFieldBits};
+use ff::{Field, PrimeField, PrimeFieldBits};
+use rand::Rng;
 use sha2::{Digest, Sha256};
-use spartan2::{traits::Group, SNARK};
+use spartan2::{
+  provider::keccak::Keccak256Transcript,
+  spartan::{
+    polys::{eq::EqPolynomial, multilinear::MultilinearPolynomial},
+    sumcheck::SumcheckProof,
+  },
+  traits::{Group, TranscriptEngineTrait},
+  SNARK,
+};
 use std::marker::PhantomData;
 
 type G = pasta_curves::pallas::Point;
@@ -105,7 +114,7 @@ impl<Scalar: PrimeField> Circuit<Scalar> for Sha256Circuit<Scalar> {
 criterion_group! {
 name = snark;
 config = Criterion::default().warm_up_time(Duration::from_millis(3000));
-targets = bench_snark
+targets = bench_snark, bench_outer_sumcheck
 }
 
 criterion_main!(snark);
@@ -148,3 +157,65 @@ fn bench_snark(c: &mut Criterion) {
     group.finish();
   }
 }
+
+fn bench_outer_sumcheck(c: &mut Criterion) {
+  type F = <G as Group>::Scalar;
+  let mut rng = rand::thread_rng();
+
+  for num_vars in [16, 18, 20] {
+    let mut group = c.benchmark_group(format!("OuterSumcheck-num-cons-{}", 1 << num_vars));
+    group.sample_size(10);
+
+    // like those of SHA-256, the entries of Az, Bz, and Cz are mostly bits and otherwise 32-bit words
+    let mut entry = |i: usize| {
+      if i.is_multiple_of(16) {
+        F::from(rng.gen::<u32>() as u64)
+      } else {
+        F::from(rng.gen_range(0..2u64))
+      }
+    };
+    let Az = (0..1 << num_vars).map(&mut entry).collect::<Vec<_>>();
+    let Bz = (0..1 << num_vars).map(|i| entry(i + 1)).collect::<Vec<_>>();
+    let Cz = Az
+      .iter()
+      .zip(Bz.iter())
+      .map(|(a, b)| *a * b)
+      .collect::<Vec<_>>();
+    let tau = (0..num_vars)
+      .map(|_| F::random(&mut rng))
+      .collect::<Vec<_>>();
+
+    group.bench_function("Field", |b| {
+      b.iter(|| {
+        let mut transcript = Keccak256Transcript::<G>::new(b"bench");
+        SumcheckProof::<G>::prove_cubic_with_additive_term(
+          &F::ZERO,
+          num_vars,
+          &mut MultilinearPolynomial::new(EqPolynomial::new(tau.clone()).evals()),
+          &mut MultilinearPolynomial::new(Az.clone()),
+          &mut MultilinearPolynomial::new(Bz.clone()),
+          &mut MultilinearPolynomial::new(Cz.clone()),
+          |A: &F, B: &F, C: &F, D: &F| *A * (*B * *C - *D),
+          &mut transcript,
+        )
+        .unwrap()
+      });
+    });
+
+    group.bench_function("SmallValue", |b| {
+      b.iter(|| {
+        let mut transcript = Keccak256Transcript::<G>::new(b"bench");
+        SumcheckProof::<G>::prove_cubic_with_additive_term_small_value(
+          &F::ZERO,
+          &tau,
+          &mut MultilinearPolynomial::new(Az.clone()),
+          &mut MultilinearPolynomial::new(Bz.clone()),
+          &mut MultilinearPolynomial::new(Cz.clone()),
+          &mut transcript,
+        )
+        .unwrap()
+      });
+    });
+    group.finish();
+  }
+}
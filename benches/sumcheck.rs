@@ -0,0 +1,176 @@
+//! Benchmarks the outer sum-check of Spartan over the Az, Bz, and Cz of SHA-256,
+//! with and without the small-value optimization
+#![allow(non_snake_case)]
+use bellpepper::gadgets::sha256::sha256;
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
+};
+use core::time::Duration;
+use criterion::*;
+use ff::Field;
+use rand::rngs::OsRng;
+use spartan2::{
+  provider::{bn256_grumpkin::bn256, keccak::Keccak256Transcript},
+  spartan::{
+    polys::{eq::EqPolynomial, multilinear::MultilinearPolynomial},
+    sumcheck::SumcheckProof,
+  },
+  traits::{Group, TranscriptEngineTrait},
+};
+
+type G = bn256::Point;
+type Scalar = <G as Group>::Scalar;
+type TE = Keccak256Transcript<G>;
+
+criterion_group! {
+name = sumcheck;
+config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+targets = bench_outer_sumcheck
+}
+
+criterion_main!(sumcheck);
+
+/// A constraint system that records the evaluations of the three linear combinations of each constraint
+struct ProductsCS {
+  inputs: Vec<Scalar>,
+  aux: Vec<Scalar>,
+  Az: Vec<Scalar>,
+  Bz: Vec<Scalar>,
+  Cz: Vec<Scalar>,
+}
+
+impl ConstraintSystem<Scalar> for ProductsCS {
+  type Root = Self;
+
+  fn new() -> Self {
+    Self {
+      inputs: vec![Scalar::ONE],
+      aux: Vec::new(),
+      Az: Vec::new(),
+      Bz: Vec::new(),
+      Cz: Vec::new(),
+    }
+  }
+
+  fn alloc<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+  where
+    F: FnOnce() -> Result<Scalar, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.aux.push(f()?);
+    Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+  }
+
+  fn alloc_input<F, A, AR>(&mut self, _: A, f: F) -> Result<Variable, SynthesisError>
+  where
+    F: FnOnce() -> Result<Scalar, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.inputs.push(f()?);
+    Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+  }
+
+  fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+    LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+  {
+    let eval = |lc: LinearCombination<Scalar>| lc.eval(&self.inputs, &self.aux);
+    let (a, b, c) = (
+      eval(a(LinearCombination::zero())),
+      eval(b(LinearCombination::zero())),
+      eval(c(LinearCombination::zero())),
+    );
+    self.Az.push(a);
+    self.Bz.push(b);
+    self.Cz.push(c);
+  }
+
+  fn push_namespace<NR, N>(&mut self, _: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+  }
+
+  fn pop_namespace(&mut self) {}
+
+  fn get_root(&mut self) -> &mut Self::Root {
+    self
+  }
+}
+
+fn bench_outer_sumcheck(c: &mut Criterion) {
+  for len in [1 << 6, 1 << 8] {
+    let mut group = c.benchmark_group(format!("OuterSumcheck-Sha256-message-len-{len}"));
+    group.sample_size(10);
+
+    let mut cs = ProductsCS::new();
+    let preimage_bits = (0..len * 8)
+      .map(|i| {
+        AllocatedBit::alloc(
+          cs.namespace(|| format!("preimage bit {i}")),
+          Some(i % 3 == 0),
+        )
+      })
+      .map(|b| b.map(Boolean::from))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    sha256(cs.namespace(|| "sha256"), &preimage_bits).unwrap();
+
+    // pad the tables with satisfied constraints, so the claim is zero
+    let num_rounds = cs.Az.len().next_power_of_two().trailing_zeros() as usize;
+    let [Az, Bz, Cz] = [cs.Az, cs.Bz, cs.Cz].map(|mut t| {
+      t.resize(1 << num_rounds, Scalar::ZERO);
+      t
+    });
+    let tau = (0..num_rounds)
+      .map(|_| Scalar::random(OsRng))
+      .collect::<Vec<_>>();
+    let eq_tau = EqPolynomial::new(tau.clone()).evals();
+
+    group.bench_function("Cubic", |b| {
+      b.iter_batched(
+        || [eq_tau.clone(), Az.clone(), Bz.clone(), Cz.clone()].map(MultilinearPolynomial::new),
+        |[mut poly_tau, mut poly_Az, mut poly_Bz, mut poly_Cz]| {
+          SumcheckProof::<G>::prove_cubic_with_additive_term(
+            &Scalar::ZERO,
+            num_rounds,
+            &mut poly_tau,
+            &mut poly_Az,
+            &mut poly_Bz,
+            &mut poly_Cz,
+            |A: &Scalar, B: &Scalar, C: &Scalar, D: &Scalar| *A * (*B * *C - *D),
+            &mut TE::new(b"bench"),
+          )
+          .unwrap()
+        },
+        BatchSize::LargeInput,
+      );
+    });
+    group.bench_function("CubicSmallValue", |b| {
+      b.iter_batched(
+        || [Az.clone(), Bz.clone(), Cz.clone()].map(MultilinearPolynomial::new),
+        |[mut poly_Az, mut poly_Bz, mut poly_Cz]| {
+          SumcheckProof::<G>::prove_cubic_with_additive_term_small_value(
+            &Scalar::ZERO,
+            &tau,
+            &mut poly_Az,
+            &mut poly_Bz,
+            &mut poly_Cz,
+            &mut TE::new(b"bench"),
+          )
+          .unwrap()
+        },
+        BatchSize::LargeInput,
+      );
+    });
+    group.finish();
+  }
+}
@@ -5,12 +5,16 @@
 use super::{shape_cs::ShapeCS, solver::SatisfyingAssignment, test_shape_cs::TestShapeCS};
 use crate::{
   errors::SpartanError,
-  r1cs::{R1CSInstance, R1CSShape, R1CSWitness, R1CS},
-  traits::Group,
+  r1cs::{R1CSInstance, R1CSShape, R1CSStateWiring, R1CSWitness, R1CS},
+  traits::{circuit::StepCircuit, commitment::CommitmentEngineTrait, Group},
   CommitmentKey,
 };
-use bellpepper_core::{Index, LinearCombination};
-use ff::PrimeField;
+use bellpepper_core::{
+  num::AllocatedNum, Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
+};
+use core::cmp::max;
+use ff::{Field, PrimeField};
+use rayon::prelude::*;
 
 /// `SpartanWitness` provide a method for acquiring an `R1CSInstance` and `R1CSWitness` from implementers.
 pub trait SpartanWitness<G: Group> {
@@ -25,7 +29,7 @@ pub trait SpartanWitness<G: Group> {
 /// `SpartanShape` provides methods for acquiring `R1CSShape` and `CommitmentKey` from implementers.
 pub trait SpartanShape<G: Group> {
   /// Return an appropriate `R1CSShape` and `CommitmentKey` structs.
-  fn r1cs_shape(&self) -> (R1CSShape<G>, CommitmentKey<G>);
+  fn r1cs_shape(&self) -> Result<(R1CSShape<G>, CommitmentKey<G>), SpartanError>;
 }
 
 impl<G: Group> SpartanWitness<G> for SatisfyingAssignment<G>
@@ -53,7 +57,7 @@ macro_rules! impl_spartan_shape {
     where
       G::Scalar: PrimeField,
     {
-      fn r1cs_shape(&self) -> (R1CSShape<G>, CommitmentKey<G>) {
+      fn r1cs_shape(&self) -> Result<(R1CSShape<G>, CommitmentKey<G>), SpartanError> {
         let mut A: Vec<(usize, usize, G::Scalar)> = Vec::new();
         let mut B: Vec<(usize, usize, G::Scalar)> = Vec::new();
         let mut C: Vec<(usize, usize, G::Scalar)> = Vec::new();
@@ -83,9 +87,9 @@ macro_rules! impl_spartan_shape {
           res.unwrap()
         };
 
-        let ck = R1CS::<G>::commitment_key(&S);
+        let ck = R1CS::<G>::commitment_key(&S)?;
 
-        (S, ck)
+        Ok((S, ck))
       }
     }
   };
@@ -95,10 +99,47 @@ impl_spartan_shape!(ShapeCS);
 impl_spartan_shape!(TestShapeCS);
 
 impl<G: Group> ShapeCS<G> {
-  /// r1cs_shape but with extrpolates from one step of a uniform computation 
-  pub fn r1cs_shape_uniform(&self, N: usize) -> (R1CSShape<G>, R1CSShape<G>, CommitmentKey<G>) {
-    let S_single = self.r1cs_shape().0;
+  /// r1cs_shape but for `N` steps of a uniform computation, whose shape repeats a single step's shape.
+  /// Returns the single step's shape and a commitment key for the witness of all `N` steps,
+  /// without materializing the repeated shape.
+  pub fn r1cs_shape_uniform(
+    &self,
+    N: usize,
+  ) -> Result<(R1CSShape<G>, CommitmentKey<G>), SpartanError> {
+    if N == 0 {
+      return Err(SpartanError::InvalidNumSteps);
+    }
+
+    let S_single = self.r1cs_shape_single()?;
+    let ck = Self::commitment_key_uniform(&S_single, N)?;
+
+    Ok((S_single, ck))
+  }
+
+  /// Returns the single step's shape of a uniform computation, for callers that bring their own commitment key
+  pub(crate) fn r1cs_shape_single(&self) -> Result<R1CSShape<G>, SpartanError> {
+    let (A, B, C, num_cons_added) = self.r1cs_matrices();
+    // Don't count One as an input for shape's purposes.
+    R1CSShape::new(
+      num_cons_added,
+      self.num_aux(),
+      self.num_inputs() - 1,
+      &A,
+      &B,
+      &C,
+    )
+  }
 
+  /// Returns the entries of the single step's A, B, and C matrices, and the number of constraints
+  #[allow(clippy::type_complexity)]
+  fn r1cs_matrices(
+    &self,
+  ) -> (
+    Vec<(usize, usize, G::Scalar)>,
+    Vec<(usize, usize, G::Scalar)>,
+    Vec<(usize, usize, G::Scalar)>,
+    usize,
+  ) {
     let mut A: Vec<(usize, usize, G::Scalar)> = Vec::new();
     let mut B: Vec<(usize, usize, G::Scalar)> = Vec::new();
     let mut C: Vec<(usize, usize, G::Scalar)> = Vec::new();
@@ -106,44 +147,361 @@ impl<G: Group> ShapeCS<G> {
     let mut num_cons_added = 0;
     let mut X = (&mut A, &mut B, &mut C, &mut num_cons_added);
 
-    // HACK(arasuarun): assuming this is = 1
-    let num_inputs = self.num_inputs();
-    let num_constraints_per_step = self.num_constraints();
-    let num_aux_per_step= self.num_aux(); // Arasu: this doesn't include the 1
-
-    let num_constraints_total = num_constraints_per_step * N;
-    let num_aux_total = num_aux_per_step * N;
+    let num_vars = self.num_aux();
 
-    let span = tracing::span!(tracing::Level::INFO, "r1cs matrix creation");
-    let _guard = span.enter();
     for constraint in self.constraints.iter() {
-      add_constraint_uniform(
+      add_constraint(
         &mut X,
-        num_aux_total,
+        num_vars,
         &constraint.0,
         &constraint.1,
         &constraint.2,
-        N,
-        num_aux_per_step,
       );
-    }  
-    drop(_guard);
-    drop(span);
-
-    // assert_eq!(num_cons_added, num_constraints);
-
-    let S: R1CSShape<G> = {
-      // Don't count One as an input for shape's purposes.
-      // Arasu: num_vars is actually supposed to be num_aux (and not including 1)
-      // Witness format is [W || 1 || x]
-      let res = R1CSShape::new(num_constraints_total, num_aux_total, num_inputs - 1, &A, &B, &C);
-      res.unwrap()
+    }
+
+    (A, B, C, num_cons_added)
+  }
+
+  /// Returns a commitment key for the witness of `N` steps of `S_single`. The uniform prover and verifier
+  /// work with `S_single`, so every step's witness is padded to `S_single`'s padded witness, and the
+  /// number of steps is padded to a power of two (see `R1CSShape::pad_witness_uniform`).
+  /// As in `R1CS::commitment_key`, each step covers the larger of the padded numbers of constraints and variables.
+  fn commitment_key_uniform(
+    S_single: &R1CSShape<G>,
+    N: usize,
+  ) -> Result<CommitmentKey<G>, SpartanError> {
+    let n = max(S_single.num_cons, S_single.num_vars).next_power_of_two();
+    G::CE::setup(b"ck", n * N.next_power_of_two())
+  }
+
+  /// r1cs_shape_uniform but for a step that reads its state from `z_in` and writes its next state to `z_out`
+  /// (see `synthesize_step`). Appends the rows that wire the state of consecutive steps together to the
+  /// single step's shape, and adds the initial and final states to its IO.
+  pub fn r1cs_shape_uniform_with_state(
+    &self,
+    N: usize,
+    z_in: &[AllocatedNum<G::Scalar>],
+    z_out: &[AllocatedNum<G::Scalar>],
+  ) -> Result<(R1CSShape<G>, R1CSStateWiring, CommitmentKey<G>), SpartanError> {
+    // the state is passed through the witness, so a step cannot have public inputs of its own
+    if self.num_inputs() != 1 {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    if z_in.len() != z_out.len() {
+      return Err(SpartanError::InvalidStepOutputLength);
+    }
+    if N == 0 {
+      return Err(SpartanError::InvalidNumSteps);
+    }
+
+    let (mut A, mut B, C, num_cons_added) = self.r1cs_matrices();
+    let num_vars = self.num_aux();
+
+    let wiring = R1CSStateWiring {
+      state_in: z_in.iter().map(aux_index).collect::<Result<_, _>>()?,
+      state_out: z_out.iter().map(aux_index).collect::<Result<_, _>>()?,
+      row: num_cons_added,
+      io: 0,
     };
 
+    // the step-local part of the wiring rows: the input state (resp. nothing) times one; the
+    // previous step's output state and the IO are subtracted by the uniform methods of `R1CSShape`
+    let arity = wiring.arity();
+    for k in 0..arity {
+      A.push((wiring.row + k, wiring.state_in[k], G::Scalar::ONE));
+      B.push((wiring.row + k, num_vars, G::Scalar::ONE));
+      B.push((wiring.row + arity + k, num_vars, G::Scalar::ONE));
+    }
+
+    let S_single = R1CSShape::new(
+      wiring.row + 2 * arity,
+      num_vars,
+      wiring.io + 2 * arity,
+      &A,
+      &B,
+      &C,
+    )?;
+
+    let ck = Self::commitment_key_uniform(&S_single, N)?;
+
+    Ok((S_single, wiring, ck))
+  }
+}
+
+/// Returns the witness index of a variable that holds a state element
+fn aux_index<F: PrimeField>(z: &AllocatedNum<F>) -> Result<usize, SpartanError> {
+  match z.get_variable().get_unchecked() {
+    Index::Aux(idx) => Ok(idx),
+    Index::Input(_) => Err(SpartanError::InvalidInputLength),
+  }
+}
+
+/// Synthesizes a single step of a state machine into `cs`, allocating the input state `z` as witness variables.
+/// Returns the variables that hold the input and output states.
+pub(crate) fn synthesize_step<F, CS, SC>(
+  cs: &mut CS,
+  step: &SC,
+  z: &[F],
+) -> Result<(Vec<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SpartanError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+  SC: StepCircuit<F>,
+{
+  if z.len() != step.arity() {
+    return Err(SpartanError::InvalidInitialInputLength);
+  }
+
+  let z_in = z
+    .iter()
+    .enumerate()
+    .map(|(i, z_i)| AllocatedNum::alloc(cs.namespace(|| format!("z_in_{i}")), || Ok(*z_i)))
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|_| SpartanError::InternalError)?;
+
+  let z_out = step
+    .synthesize(&mut cs.namespace(|| "step"), &z_in)
+    .map_err(|_| SpartanError::InternalError)?;
+  if z_out.len() != step.arity() {
+    return Err(SpartanError::InvalidStepOutputLength);
+  }
+
+  Ok((z_in, z_out))
+}
+
+/// Runs `steps` one after the other from the initial state `z0`, checking that every step has `num_aux` witness
+/// variables and the constraints of `S_single` before the rows of `wiring`.
+/// Returns the witness of all steps, one after the other, and the final state.
+pub(crate) fn synthesize_steps<G, SC>(
+  S_single: &R1CSShape<G>,
+  num_aux: usize,
+  wiring: &R1CSStateWiring,
+  steps: &[SC],
+  z0: &[G::Scalar],
+) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
+where
+  G: Group,
+  SC: StepCircuit<G::Scalar>,
+{
+  let mut W = Vec::with_capacity(steps.len() * num_aux);
+  let mut z = z0.to_vec();
+  for step in steps {
+    let mut cs = UniformStepCS::new(S_single, num_aux, wiring.row);
+    let (_z_in, z_out) = synthesize_step(&mut cs, step, &z)?;
+    let cs = cs.finish()?;
+
+    // every step must have the inputs of a single step
+    if cs.input_assignment.len() != 1 {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    z = z_out
+      .iter()
+      .map(|z_i| z_i.get_value().ok_or(SpartanError::IncorrectWitness))
+      .collect::<Result<Vec<_>, _>>()?;
+    W.extend(cs.aux_assignment);
+  }
+
+  Ok((W, z))
+}
+
+/// Synthesizes each of `steps` into its own `SatisfyingAssignment` in parallel, checking that every step has the
+/// inputs of `S_single`, `num_aux` witness variables, and the constraints of `S_single`.
+/// Returns the witness of all steps, one after the other, and the public IO: the inputs of each step one after
+/// the other if `io_per_step`, and otherwise the inputs shared by all steps.
+pub(crate) fn synthesize_uniform<G, C>(
+  S_single: &R1CSShape<G>,
+  num_aux: usize,
+  io_per_step: bool,
+  steps: Vec<C>,
+) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
+where
+  G: Group,
+  C: Circuit<G::Scalar> + Send,
+{
+  let assignments = steps
+    .into_par_iter()
+    .map(|step| {
+      let mut cs = UniformStepCS::new(S_single, num_aux, S_single.num_cons);
+      step
+        .synthesize(&mut cs)
+        .map_err(|_| SpartanError::InternalError)?;
+      let cs = cs.finish()?;
+
+      // every step must have the inputs of a single step
+      if cs.input_assignment.len() != S_single.num_io + 1 {
+        return Err(SpartanError::InvalidInputLength);
+      }
+
+      Ok(cs)
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let X = if io_per_step {
+    assignments
+      .iter()
+      .flat_map(|cs| cs.input_assignment[1..].iter().copied())
+      .collect()
+  } else {
+    // the inputs are shared, so every step must agree on them
+    let X = assignments
+      .first()
+      .map(|cs| cs.input_assignment[1..].to_vec())
+      .unwrap_or_default();
+    if assignments.iter().any(|cs| cs.input_assignment[1..] != X[..]) {
+      return Err(SpartanError::IncorrectWitness);
+    }
+    X
+  };
+
+  let W = assignments
+    .into_iter()
+    .flat_map(|cs| cs.aux_assignment)
+    .collect();
+
+  Ok((W, X))
+}
+
+/// A `SatisfyingAssignment` for one step of a uniform circuit, which checks while the step is synthesized that
+/// its constraints are those of the first `num_cons` rows of `S_single`. The entries of `S_single` are ordered by
+/// row, and within a row in the order in which `add_constraint` adds them, so the constraints of the step must
+/// produce exactly the sequence of entries of each of `S_single`'s matrices, without materializing them.
+struct UniformStepCS<'a, G: Group> {
+  cs: SatisfyingAssignment<G>,
+  S_single: &'a R1CSShape<G>,
+  num_aux: usize,
+  num_cons: usize,
+  row: usize,       // the row of the next constraint
+  next: [usize; 3], // the next entry of each of A, B, and C to match
+  is_matching: bool,
+}
+
+impl<'a, G: Group> UniformStepCS<'a, G> {
+  fn new(S_single: &'a R1CSShape<G>, num_aux: usize, num_cons: usize) -> Self {
+    UniformStepCS {
+      cs: SatisfyingAssignment::new(),
+      S_single,
+      num_aux,
+      num_cons,
+      row: 0,
+      next: [0; 3],
+      is_matching: true,
+    }
+  }
+
+  /// Returns the assignment of the step, if it has `num_aux` witness variables and the constraints of `S_single`
+  fn finish(self) -> Result<SatisfyingAssignment<G>, SpartanError> {
+    if self.cs.aux_assignment.len() != self.num_aux {
+      return Err(SpartanError::InvalidWitnessLength);
+    }
 
-    let ck = R1CS::<G>::commitment_key(&S);
+    let S = self.S_single;
+    let ends = [&S.A, &S.B, &S.C].map(|M| M.partition_point(|(row, _, _)| *row < self.num_cons));
+    if !self.is_matching || self.next != ends {
+      return Err(SpartanError::InvalidStepShape);
+    }
 
-    (S, S_single, ck) 
+    Ok(self.cs)
+  }
+}
+
+impl<'a, G: Group> ConstraintSystem<G::Scalar> for UniformStepCS<'a, G> {
+  type Root = Self;
+
+  fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+  where
+    F: FnOnce() -> Result<G::Scalar, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.cs.alloc(annotation, f)
+  }
+
+  fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+  where
+    F: FnOnce() -> Result<G::Scalar, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.cs.alloc_input(annotation, f)
+  }
+
+  fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+    LA: FnOnce(LinearCombination<G::Scalar>) -> LinearCombination<G::Scalar>,
+    LB: FnOnce(LinearCombination<G::Scalar>) -> LinearCombination<G::Scalar>,
+    LC: FnOnce(LinearCombination<G::Scalar>) -> LinearCombination<G::Scalar>,
+  {
+    let row = self.row;
+    self.row += 1;
+    if !self.is_matching || row >= self.num_cons {
+      self.is_matching = false;
+      return;
+    }
+
+    // the columns of `S_single`, whose inputs come after its padded witness
+    let S = self.S_single;
+    let lcs = [
+      a(LinearCombination::zero()),
+      b(LinearCombination::zero()),
+      c(LinearCombination::zero()),
+    ];
+    for ((lc, M), next) in lcs.iter().zip([&S.A, &S.B, &S.C]).zip(self.next.iter_mut()) {
+      for (var, coeff) in lc.iter() {
+        let col = match var.get_unchecked() {
+          Index::Input(idx) => idx + S.num_vars,
+          Index::Aux(idx) => idx,
+        };
+        if M.get(*next) != Some(&(row, col, *coeff)) {
+          self.is_matching = false;
+          return;
+        }
+        *next += 1;
+      }
+    }
+  }
+
+  fn push_namespace<NR, N>(&mut self, _: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+  }
+
+  fn pop_namespace(&mut self) {}
+
+  fn get_root(&mut self) -> &mut Self::Root {
+    self
+  }
+
+  fn is_witness_generator(&self) -> bool {
+    true
+  }
+
+  fn extend_inputs(&mut self, new_inputs: &[G::Scalar]) {
+    self.cs.extend_inputs(new_inputs);
+  }
+
+  fn extend_aux(&mut self, new_aux: &[G::Scalar]) {
+    self.cs.extend_aux(new_aux);
+  }
+
+  fn allocate_empty(
+    &mut self,
+    aux_n: usize,
+    inputs_n: usize,
+  ) -> (&mut [G::Scalar], &mut [G::Scalar]) {
+    self.cs.allocate_empty(aux_n, inputs_n)
+  }
+
+  fn inputs_slice(&self) -> &[G::Scalar] {
+    self.cs.inputs_slice()
+  }
+
+  fn aux_slice(&self) -> &[G::Scalar] {
+    self.cs.aux_slice()
   }
 }
 
@@ -189,69 +547,3 @@ fn add_constraint<S: PrimeField>(
 
   **nn += 1;
 }
-
-
-fn add_constraint_uniform<S: PrimeField>(
-  X: &mut (
-    &mut Vec<(usize, usize, S)>,
-    &mut Vec<(usize, usize, S)>,
-    &mut Vec<(usize, usize, S)>,
-    &mut usize,
-  ),
-  num_vars: usize,
-  a_lc: &LinearCombination<S>,
-  b_lc: &LinearCombination<S>,
-  c_lc: &LinearCombination<S>,
-  num_steps: usize, 
-  num_aux_per_step: usize,
-) {
-  let (A, B, C, nn) = X;
-  let n = **nn; // Arasu: this is just the row number, I think 
-  let one = S::ONE;
-
-  let add_constraint_component = |index: Index, coeff, V: &mut Vec<_>| {
-    match index {
-      Index::Input(idx) => {
-        // Inputs come last, with input 0, reprsenting 'one',
-        // at position num_vars within the witness vector.
-        let i = idx + num_vars;
-        for step in 0..num_steps {
-          V.push((n + step, i, one * coeff)); // the column of the input is the same for all steps
-        }
-      }
-      Index::Aux(idx) => {
-        for step in 0..num_steps {
-          V.push((n + step, idx + num_aux_per_step * step, one * coeff));
-        }
-      }
-    }
-  };
-
-  // for (index, coeff) in a_lc.iter() {
-  //   add_constraint_component(index.0, coeff, A);
-  // }
-  // for (index, coeff) in b_lc.iter() {
-  //   add_constraint_component(index.0, coeff, B)
-  // }
-  // for (index, coeff) in c_lc.iter() {
-  //   add_constraint_component(index.0, coeff, C)
-  // }
-
-  rayon::join(|| {
-    a_lc.iter().for_each(|(index, coeff)| {
-        add_constraint_component(index.0, coeff, A);
-    });
-  }, || {
-    rayon::join(|| {
-        b_lc.iter().for_each(|(index, coeff)| {
-            add_constraint_component(index.0, coeff, B);
-        });
-    }, || {
-        c_lc.iter().for_each(|(index, coeff)| {
-            add_constraint_component(index.0, coeff, C);
-        });
-    });
-  });
-
-  **nn += num_steps;
-}
@@ -13,6 +13,7 @@ use ff::{Field, PrimeField};
 #[derive(Clone, Copy)]
 struct OrderedVariable(Variable);
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum NamedObject {
   Constraint(usize),
@@ -50,6 +50,21 @@ pub enum SpartanError {
   /// returned when the consistency with public IO and assignment used fails
   #[error("IncorrectWitness")]
   IncorrectWitness,
+  /// returned when a commitment key, such as one read from a ceremony file, is malformed or inconsistent
+  #[error("InvalidCommitmentKey")]
+  InvalidCommitmentKey,
+  /// returned when a commitment engine cannot sample its own key, and the key must come from a trusted setup
+  #[error("MissingCommitmentKey")]
+  MissingCommitmentKey,
+  /// returned when a step of a uniform circuit does not have the constraints of a single step
+  #[error("InvalidStepShape")]
+  InvalidStepShape,
+  /// returned when a linear combination of commitments that are not homomorphic is requested
+  #[error("NonHomomorphicCommitment")]
+  NonHomomorphicCommitment,
+  /// returned when a memory budget is below what the prover needs, or the prover has no memory-bounded mode
+  #[error("InvalidMemoryBudget")]
+  InvalidMemoryBudget,
   /// returned when the library encounters an internal error
   #[error("InternalError")]
   InternalError,
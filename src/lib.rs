@@ -45,6 +45,19 @@ where
   pk: S::ProverKey,
 }
 
+impl<G: Group, S: UniformSNARKTrait<G>> ProverKey<G, S> {
+  /// Bounds the memory of the tables of the prover to about `budget` bytes
+  /// (see `UniformSNARKTrait::set_memory_budget`)
+  pub fn set_memory_budget(&mut self, budget: Option<usize>) -> Result<(), SpartanError> {
+    S::set_memory_budget(&mut self.pk, budget)
+  }
+
+  /// Returns the smallest budget that `set_memory_budget` accepts, if the prover has a memory-bounded mode
+  pub fn min_memory_budget(&self) -> Option<usize> {
+    S::min_memory_budget(&self.pk)
+  }
+}
+
 /// A type that holds the verifier key
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -73,25 +86,13 @@ where
   _p2: PhantomData<C>,
 }
 
-impl<G: Group, S: RelaxedR1CSSNARKTrait<G> + UniformSNARKTrait<G> + PrecommittedSNARKTrait<G>, C: Circuit<G::Scalar>> SNARK<G, S, C> {
+impl<G: Group, S: RelaxedR1CSSNARKTrait<G>, C: Circuit<G::Scalar>> SNARK<G, S, C> {
   /// Produces prover and verifier keys for the direct SNARK
   pub fn setup(circuit: C) -> Result<(ProverKey<G, S>, VerifierKey<G, S>), SpartanError> {
     let (pk, vk) = S::setup(circuit)?;
     Ok((ProverKey { pk }, VerifierKey { vk }))
   }
 
-  /// Produces prover and verifier keys for the direct SNARK
-  pub fn setup_uniform(circuit: C, n: usize) -> Result<(ProverKey<G, S>, VerifierKey<G, S>), SpartanError> {
-    let (pk, vk) = S::setup_uniform(circuit, n)?;
-    Ok((ProverKey { pk }, VerifierKey { vk }))
-  }
-
-  /// Produces prover and verifier keys for the direct SNARK
-  pub fn setup_precommitted(circuit: C, n: usize) -> Result<(ProverKey<G, S>, VerifierKey<G, S>), SpartanError> {
-    let (pk, vk) = S::setup_precommitted(circuit, n)?;
-    Ok((ProverKey { pk }, VerifierKey { vk }))
-  }
-
   /// Produces a proof of satisfiability of the provided circuit
   pub fn prove(pk: &ProverKey<G, S>, circuit: C) -> Result<Self, SpartanError> {
     // prove the instance using Spartan
@@ -111,6 +112,58 @@ impl<G: Group, S: RelaxedR1CSSNARKTrait<G> + UniformSNARKTrait<G> + Precommitted
   }
 }
 
+impl<G: Group, S: UniformSNARKTrait<G>, C: Circuit<G::Scalar>> SNARK<G, S, C> {
+  /// Produces prover and verifier keys for a uniform circuit repeated for `n` steps
+  pub fn setup_uniform(circuit: C, n: usize) -> Result<(ProverKey<G, S>, VerifierKey<G, S>), SpartanError> {
+    let (pk, vk) = S::setup_uniform(circuit, n)?;
+    Ok((ProverKey { pk }, VerifierKey { vk }))
+  }
+
+  /// Produces prover and verifier keys for a uniform circuit repeated for `n` steps, each with its own public inputs
+  pub fn setup_uniform_with_io_per_step(circuit: C, n: usize) -> Result<(ProverKey<G, S>, VerifierKey<G, S>), SpartanError> {
+    let (pk, vk) = S::setup_uniform_with_io_per_step(circuit, n)?;
+    Ok((ProverKey { pk }, VerifierKey { vk }))
+  }
+
+  /// Produces a proof of satisfiability of a uniform circuit from one circuit per step, synthesized in parallel
+  pub fn prove_uniform(pk: &ProverKey<G, S>, steps: Vec<C>) -> Result<Self, SpartanError>
+  where
+    C: Send,
+  {
+    let snark = S::prove_uniform(&pk.pk, steps)?;
+
+    Ok(SNARK {
+      snark,
+      _p: Default::default(),
+      _p2: Default::default(),
+    })
+  }
+}
+
+impl<G: Group, S: PrecommittedSNARKTrait<G>, C: Circuit<G::Scalar>> SNARK<G, S, C> {
+  /// Produces prover and verifier keys for a uniform circuit whose witness is committed externally with `ck`
+  pub fn setup_precommitted(circuit: C, n: usize, ck: CommitmentKey<G>) -> Result<(ProverKey<G, S>, VerifierKey<G, S>), SpartanError> {
+    let (pk, vk) = S::setup_precommitted(circuit, n, ck)?;
+    Ok((ProverKey { pk }, VerifierKey { vk }))
+  }
+
+  /// Produces a proof of satisfiability of the provided circuit, whose witness is committed externally in `comm_W`
+  pub fn prove_precommitted(pk: &ProverKey<G, S>, circuit: C, comm_W: Commitment<G>) -> Result<Self, SpartanError> {
+    let snark = S::prove_precommitted(&pk.pk, circuit, comm_W)?;
+
+    Ok(SNARK {
+      snark,
+      _p: Default::default(),
+      _p2: Default::default(),
+    })
+  }
+
+  /// Verifies a proof of satisfiability against the external commitment `comm_W` to the witness
+  pub fn verify_precommitted(&self, vk: &VerifierKey<G, S>, io: &[G::Scalar], comm_W: &Commitment<G>) -> Result<(), SpartanError> {
+    self.snark.verify_precommitted(&vk.vk, io, comm_W)
+  }
+}
+
 type CommitmentKey<G> = <<G as traits::Group>::CE as CommitmentEngineTrait<G>>::CommitmentKey;
 type Commitment<G> = <<G as Group>::CE as CommitmentEngineTrait<G>>::Commitment;
 type CompressedCommitment<G> = <<<G as Group>::CE as CommitmentEngineTrait<G>>::Commitment as CommitmentTrait<G>>::CompressedCommitment;
@@ -119,10 +172,19 @@ type CE<G> = <G as Group>::CE;
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::provider::{bn256_grumpkin::bn256, secp_secq::secp256k1};
-  use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+  use crate::{
+    provider::{bls12_381, bn256_grumpkin::bn256, ristretto, secp_secq::secp256k1},
+    traits::circuit::StepCircuit,
+  };
+  use ::bellpepper::gadgets::sha256::sha256;
+  use bellpepper_core::{
+    boolean::{AllocatedBit, Boolean},
+    num::{AllocatedNum, Num},
+    ConstraintSystem, SynthesisError,
+  };
   use core::marker::PhantomData;
-  use ff::PrimeField;
+  use ff::{Field, PrimeField, PrimeFieldBits};
+  use sha2::{Digest, Sha256};
 
   #[derive(Clone, Debug, Default)]
   struct CubicCircuit<F: PrimeField> {
@@ -171,6 +233,570 @@ mod tests {
     }
   }
 
+  // proves knowledge of a preimage of a SHA-256 digest, which is exposed as two 128-bit inputs
+  #[derive(Clone, Debug)]
+  struct Sha256Circuit<F: PrimeField> {
+    preimage: Vec<u8>,
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField + PrimeFieldBits> Sha256Circuit<F> {
+    fn new(preimage: Vec<u8>) -> Self {
+      Self {
+        preimage,
+        _p: PhantomData,
+      }
+    }
+
+    // packs the digest bits, most significant bit of each byte first, into little-endian 128-bit chunks
+    fn io(&self) -> Vec<F> {
+      let digest = Sha256::digest(&self.preimage);
+      let bits = digest
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1u8 == 1u8))
+        .collect::<Vec<_>>();
+      bits
+        .chunks(128)
+        .map(|chunk| {
+          chunk.iter().rev().fold(F::ZERO, |acc, bit| {
+            acc.double() + if *bit { F::ONE } else { F::ZERO }
+          })
+        })
+        .collect()
+    }
+  }
+
+  impl<F: PrimeField + PrimeFieldBits> Circuit<F> for Sha256Circuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+      let preimage_bits = self
+        .preimage
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1u8 == 1u8))
+        .enumerate()
+        .map(|(i, b)| AllocatedBit::alloc(cs.namespace(|| format!("preimage bit {i}")), Some(b)))
+        .map(|b| b.map(Boolean::from))
+        .collect::<Result<Vec<_>, _>>()?;
+
+      let hash_bits = sha256(cs.namespace(|| "sha256"), &preimage_bits)?;
+
+      for (i, hash_bits) in hash_bits.chunks(128).enumerate() {
+        let mut num = Num::<F>::zero();
+        let mut coeff = F::ONE;
+        for bit in hash_bits {
+          num = num.add_bool_with_coeff(CS::one(), bit, coeff);
+          coeff = coeff.double();
+        }
+
+        let hash = AllocatedNum::alloc(cs.namespace(|| format!("input {i}")), || {
+          num.get_value().ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // num * 1 = hash
+        cs.enforce(
+          || format!("packing constraint {i}"),
+          |_| num.lc(F::ONE),
+          |lc| lc + CS::one(),
+          |lc| lc + hash.get_variable(),
+        );
+        hash.inputize(cs.namespace(|| format!("output {i}")))?;
+      }
+
+      Ok(())
+    }
+  }
+
+  #[derive(Clone, Debug)]
+  struct UniformCubicCircuit<F: PrimeField> {
+    num_steps: usize,
+    constant: u64,
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> UniformCubicCircuit<F> {
+    fn new(num_steps: usize) -> Self {
+      Self {
+        num_steps,
+        constant: 5,
+        _p: PhantomData,
+      }
+    }
+
+    // the same computation with `x^3 + x + constant = y`, which has a different shape
+    fn with_constant(self, constant: u64) -> Self {
+      Self { constant, ..self }
+    }
+
+    // the witness of each step is (x, x^2, x^3, y) with x = 2
+    fn witness(&self) -> Vec<F> {
+      let x = F::from(2u64);
+      (0..self.num_steps)
+        .flat_map(|_| {
+          let y = x.square() * x + x + F::from(self.constant);
+          [x, x.square(), x.square() * x, y]
+        })
+        .collect()
+    }
+  }
+
+  impl<F: PrimeField> Circuit<F> for UniformCubicCircuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+      for step in 0..self.num_steps {
+        let mut cs = cs.namespace(|| format!("step {step}"));
+        // `x^3 + x + 5 = y`, where every step shares the output `y` as its only input
+        let x = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(F::from(2u64)))?;
+        let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+        let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), &x)?;
+        let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+          Ok(x_cu.get_value().unwrap() + x.get_value().unwrap() + F::from(self.constant))
+        })?;
+
+        cs.enforce(
+          || "y = x^3 + x + 5",
+          |lc| lc + x_cu.get_variable() + x.get_variable() + (F::from(self.constant), CS::one()),
+          |lc| lc + CS::one(),
+          |lc| lc + y.get_variable(),
+        );
+
+        // the input is allocated once, the uniform shape reuses its column for every step
+        if step == 0 {
+          let _ = y.inputize(cs.namespace(|| "output"));
+        }
+      }
+
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_uniform_snark() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    type Szk = crate::spartan::zksnark::R1CSSNARK<G>;
+    test_uniform_snark_with::<G, S>();
+    test_uniform_snark_with::<G, Szk>();
+
+    type G2 = bn256::Point;
+    type EE2 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G2>;
+    type S2 = crate::spartan::upsnark::R1CSSNARK<G2, EE2>;
+    test_uniform_snark_with::<G2, S2>();
+
+    type G3 = bls12_381::Point;
+    type EE3 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G3>;
+    type S3 = crate::spartan::upsnark::R1CSSNARK<G3, EE3>;
+    test_uniform_snark_with::<G3, S3>();
+  }
+
+  fn test_uniform_snark_with<G: Group, S: UniformSNARKTrait<G>>() {
+    for num_steps in [1, 2, 3, 5, 8] {
+      let (pk, vk) = SNARK::<G, S, UniformCubicCircuit<<G as Group>::Scalar>>::setup_uniform(
+        UniformCubicCircuit::new(1),
+        num_steps,
+      )
+      .unwrap();
+
+      let snark = SNARK::prove(&pk, UniformCubicCircuit::new(num_steps)).unwrap();
+      assert!(snark
+        .verify(&vk, &[<G as Group>::Scalar::from(15u64)])
+        .is_ok());
+      assert!(snark
+        .verify(&vk, &[<G as Group>::Scalar::from(16u64)])
+        .is_err());
+
+      // the steps can also be synthesized separately, in parallel
+      let steps = vec![UniformCubicCircuit::new(1); num_steps];
+      let snark = SNARK::prove_uniform(&pk, steps).unwrap();
+      assert!(snark
+        .verify(&vk, &[<G as Group>::Scalar::from(15u64)])
+        .is_ok());
+
+      // every step must have the shape of a single step
+      let mut steps = vec![UniformCubicCircuit::new(1); num_steps];
+      steps[num_steps - 1] = UniformCubicCircuit::new(2);
+      assert!(matches!(
+        SNARK::prove_uniform(&pk, steps),
+        Err(SpartanError::InvalidWitnessLength)
+      ));
+      assert!(matches!(
+        SNARK::prove_uniform(&pk, vec![UniformCubicCircuit::new(1); num_steps + 1]),
+        Err(SpartanError::InvalidNumSteps)
+      ));
+      let mut steps = vec![UniformCubicCircuit::new(1); num_steps];
+      steps[num_steps - 1] = UniformCubicCircuit::new(1).with_constant(6);
+      assert!(matches!(
+        SNARK::prove_uniform(&pk, steps),
+        Err(SpartanError::InvalidStepShape)
+      ));
+    }
+
+    // a uniform circuit has at least one step
+    assert!(matches!(
+      SNARK::<G, S, UniformCubicCircuit<<G as Group>::Scalar>>::setup_uniform(
+        UniformCubicCircuit::new(1),
+        0,
+      ),
+      Err(SpartanError::InvalidNumSteps)
+    ));
+  }
+
+  // independent instances of `x^3 + x + 5 = y`, one per step, where every step exposes its own `y`
+  #[derive(Clone, Debug)]
+  struct IndependentCubicCircuit<F: PrimeField> {
+    xs: Vec<u64>,
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> IndependentCubicCircuit<F> {
+    fn new(xs: Vec<u64>) -> Self {
+      Self { xs, _p: PhantomData }
+    }
+
+    fn io(&self) -> Vec<F> {
+      self
+        .xs
+        .iter()
+        .map(|x| F::from(x * x * x + x + 5))
+        .collect()
+    }
+  }
+
+  impl<F: PrimeField> Circuit<F> for IndependentCubicCircuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+      for (step, x_val) in self.xs.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("step {step}"));
+        let x = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(F::from(*x_val)))?;
+        let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+        let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), &x)?;
+        let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+          Ok(x_cu.get_value().unwrap() + x.get_value().unwrap() + F::from(5u64))
+        })?;
+
+        cs.enforce(
+          || "y = x^3 + x + 5",
+          |lc| lc + x_cu.get_variable() + x.get_variable() + (F::from(5u64), CS::one()),
+          |lc| lc + CS::one(),
+          |lc| lc + y.get_variable(),
+        );
+
+        let _ = y.inputize(cs.namespace(|| "output"));
+      }
+
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_uniform_snark_with_io_per_step() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    type Szk = crate::spartan::zksnark::R1CSSNARK<G>;
+    test_uniform_snark_with_io_per_step_with::<G, S>();
+    test_uniform_snark_with_io_per_step_with::<G, Szk>();
+
+    type G2 = bn256::Point;
+    type EE2 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G2>;
+    type S2 = crate::spartan::upsnark::R1CSSNARK<G2, EE2>;
+    test_uniform_snark_with_io_per_step_with::<G2, S2>();
+  }
+
+  fn test_uniform_snark_with_io_per_step_with<G: Group, S: UniformSNARKTrait<G>>() {
+    for num_steps in [1, 2, 3, 5, 8] {
+      let (pk, vk) =
+        SNARK::<G, S, IndependentCubicCircuit<<G as Group>::Scalar>>::setup_uniform_with_io_per_step(
+          IndependentCubicCircuit::new(vec![0]),
+          num_steps,
+        )
+        .unwrap();
+
+      let circuit = IndependentCubicCircuit::new((0..num_steps as u64).map(|s| s + 2).collect());
+      let io = circuit.io();
+      let snark = SNARK::prove(&pk, circuit.clone()).unwrap();
+      assert!(snark.verify(&vk, &io).is_ok());
+
+      // synthesizing each step separately lays out the inputs in the same order
+      let steps = circuit
+        .xs
+        .iter()
+        .map(|x| IndependentCubicCircuit::new(vec![*x]))
+        .collect();
+      let snark_steps = SNARK::prove_uniform(&pk, steps).unwrap();
+      assert!(snark_steps.verify(&vk, &io).is_ok());
+
+      // every step is bound to its own output
+      let mut io_wrong = io.clone();
+      io_wrong[num_steps - 1] += <G as Group>::Scalar::ONE;
+      assert!(snark.verify(&vk, &io_wrong).is_err());
+      if num_steps > 1 {
+        io_wrong = io.clone();
+        io_wrong.swap(0, 1);
+        assert!(snark.verify(&vk, &io_wrong).is_err());
+      }
+      assert_eq!(
+        snark.verify(&vk, &io[..num_steps - 1]).err(),
+        Some(SpartanError::InvalidInputLength)
+      );
+    }
+  }
+
+  // a step of the state machine `(x, i) -> (x^3 + x + 5, i + 1)`
+  #[derive(Clone, Debug, Default)]
+  struct CubicStepCircuit<F: PrimeField> {
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> CubicStepCircuit<F> {
+    fn output(z: &[F]) -> Vec<F> {
+      vec![z[0].square() * z[0] + z[0] + F::from(5u64), z[1] + F::ONE]
+    }
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for CubicStepCircuit<F> {
+    fn arity(&self) -> usize {
+      2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+      let (x, i) = (&z[0], &z[1]);
+      let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+      let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), x)?;
+      let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+        Ok(x_cu.get_value().unwrap() + x.get_value().unwrap() + F::from(5u64))
+      })?;
+      let i_next = AllocatedNum::alloc(cs.namespace(|| "i_next"), || {
+        Ok(i.get_value().unwrap() + F::ONE)
+      })?;
+
+      cs.enforce(
+        || "y = x^3 + x + 5",
+        |lc| lc + x_cu.get_variable() + x.get_variable() + (F::from(5u64), CS::one()),
+        |lc| lc + CS::one(),
+        |lc| lc + y.get_variable(),
+      );
+      cs.enforce(
+        || "i_next = i + 1",
+        |lc| lc + i.get_variable() + CS::one(),
+        |lc| lc + CS::one(),
+        |lc| lc + i_next.get_variable(),
+      );
+
+      Ok(vec![y, i_next])
+    }
+  }
+
+  #[test]
+  fn test_uniform_snark_with_state() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    type Szk = crate::spartan::zksnark::R1CSSNARK<G>;
+    test_uniform_snark_with_state_with::<G, S>();
+    test_uniform_snark_with_state_with::<G, Szk>();
+
+    type G2 = bn256::Point;
+    type EE2 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G2>;
+    type S2 = crate::spartan::upsnark::R1CSSNARK<G2, EE2>;
+    test_uniform_snark_with_state_with::<G2, S2>();
+  }
+
+  fn test_uniform_snark_with_state_with<G: Group, S: UniformSNARKTrait<G>>() {
+    let step = CubicStepCircuit::<<G as Group>::Scalar>::default();
+    for num_steps in [1, 2, 3, 5, 8] {
+      let (pk, vk) = S::setup_uniform_with_state(&step, num_steps).unwrap();
+
+      let z0 = vec![<G as Group>::Scalar::from(2u64), <G as Group>::Scalar::ZERO];
+      let steps = vec![step.clone(); num_steps];
+      let (snark, zN) = S::prove_uniform_with_state(&pk, &steps, &z0).unwrap();
+
+      let expected = (0..num_steps).fold(z0.clone(), |z, _| CubicStepCircuit::output(&z));
+      assert_eq!(zN, expected);
+      assert!(snark.verify(&vk, &[z0.clone(), zN.clone()].concat()).is_ok());
+
+      // the proof is bound to both the initial and the final state
+      let zN_wrong = vec![zN[0], zN[1] + <G as Group>::Scalar::ONE];
+      assert!(snark.verify(&vk, &[z0.clone(), zN_wrong].concat()).is_err());
+      let z0_wrong = vec![z0[0] + <G as Group>::Scalar::ONE, z0[1]];
+      assert!(snark.verify(&vk, &[z0_wrong, zN].concat()).is_err());
+
+      // the number of steps is fixed at setup
+      assert_eq!(
+        S::prove_uniform_with_state(&pk, &steps[1..], &z0).err(),
+        Some(SpartanError::InvalidNumSteps)
+      );
+
+      // the steps must start from an initial state
+      assert_eq!(
+        S::prove_uniform(&pk, vec![UniformCubicCircuit::new(1); num_steps]).err(),
+        Some(SpartanError::InvalidInitialInputLength)
+      );
+    }
+  }
+
+  #[test]
+  fn test_uniform_snark_proof_regression() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+
+    // proofs are deterministic, so changes to the prover that must not change the proofs
+    // (e.g., in how the uniform shape is represented) are checked against these digests
+    let expected = [
+      "f487f606d94b29c78f3dccf49af1005fa2d49ac6691c1042864ad04ac6310120",
+      "c53e20374d40e84846fdc940e22a229cddc39c543468a6dd92ba5de38eb838e5",
+      "014b243c26ef8d281ecb9b5aed0d21118854f138a38e5145cc3dd73b39a87db4",
+    ];
+    for (num_steps, expected) in [1, 2, 8].into_iter().zip(expected) {
+      let (pk, vk) = SNARK::<G, S, UniformCubicCircuit<<G as Group>::Scalar>>::setup_uniform(
+        UniformCubicCircuit::new(1),
+        num_steps,
+      )
+      .unwrap();
+
+      let snark = SNARK::prove(&pk, UniformCubicCircuit::new(num_steps)).unwrap();
+      assert!(snark
+        .verify(&vk, &[<G as Group>::Scalar::from(15u64)])
+        .is_ok());
+      let proof = bincode::serialize(&snark).unwrap();
+      assert_eq!(hex::encode(Sha256::digest(proof)), expected);
+    }
+  }
+
+  #[test]
+  fn test_uniform_vk_digest_binds_layout() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    type C = UniformCubicCircuit<<G as Group>::Scalar>;
+
+    // 3 and 4 steps share a padded layout, and so the commitment key
+    let (_, vk3) = SNARK::<G, S, C>::setup_uniform(UniformCubicCircuit::new(1), 3).unwrap();
+    let (_, vk4) = SNARK::<G, S, C>::setup_uniform(UniformCubicCircuit::new(1), 4).unwrap();
+    let (_, vk4_io) =
+      SNARK::<G, S, C>::setup_uniform_with_io_per_step(UniformCubicCircuit::new(1), 4).unwrap();
+    assert_ne!(vk3.vk.digest(), vk4.vk.digest());
+    assert_ne!(vk4.vk.digest(), vk4_io.vk.digest());
+  }
+
+  #[test]
+  fn test_upsnark_memory_budget() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    type F = <G as Group>::Scalar;
+
+    // budgets from a single point per chunk to all of them; the proofs do not depend on the budget,
+    // and budgets below the tables that are not produced in chunks are rejected
+    let budgets = |min: usize| [Some(min), Some(min + (1 << 10)), Some(1 << 30)];
+
+    // the wiring rows of the state machine are added after the step's rows
+    let step = CubicStepCircuit::<F>::default();
+    let z0 = vec![F::from(2u64), F::ZERO];
+    for num_steps in [1, 3, 8] {
+      let (mut pk, vk) = S::setup_uniform_with_state(&step, num_steps).unwrap();
+      let steps = vec![step.clone(); num_steps];
+      let (snark, zN) = S::prove_uniform_with_state(&pk, &steps, &z0).unwrap();
+      let io = [z0.clone(), zN].concat();
+      let min = S::min_memory_budget(&pk).unwrap();
+      assert!(matches!(
+        S::set_memory_budget(&mut pk, Some(min - 1)),
+        Err(SpartanError::InvalidMemoryBudget)
+      ));
+      let unbounded = bincode::serialize(&pk).unwrap();
+      for budget in budgets(min) {
+        S::set_memory_budget(&mut pk, budget).unwrap();
+        let (snark_budget, _) = S::prove_uniform_with_state(&pk, &steps, &z0).unwrap();
+        assert!(snark_budget.verify(&vk, &io).is_ok());
+        assert_eq!(
+          bincode::serialize(&snark).unwrap(),
+          bincode::serialize(&snark_budget).unwrap()
+        );
+
+        // the budget is serialized with the key
+        let bytes = bincode::serialize(&pk).unwrap();
+        let pk_copy: crate::spartan::upsnark::ProverKey<G, EE> =
+          bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&pk_copy).unwrap(), bytes);
+        assert_ne!(bytes, unbounded);
+      }
+    }
+
+    // every step has its own inputs
+    for num_steps in [1, 5] {
+      let (mut pk, vk) = SNARK::<G, S, IndependentCubicCircuit<F>>::setup_uniform_with_io_per_step(
+        IndependentCubicCircuit::new(vec![0]),
+        num_steps,
+      )
+      .unwrap();
+      let circuit = IndependentCubicCircuit::new((0..num_steps as u64).map(|s| s + 2).collect());
+      let io = circuit.io();
+      let snark = SNARK::prove(&pk, circuit.clone()).unwrap();
+      let min = pk.min_memory_budget().unwrap();
+      assert!(pk.set_memory_budget(Some(min - 1)).is_err());
+      for budget in budgets(min) {
+        pk.set_memory_budget(budget).unwrap();
+        let snark_budget = SNARK::prove(&pk, circuit.clone()).unwrap();
+        assert!(snark_budget.verify(&vk, &io).is_ok());
+        assert_eq!(
+          bincode::serialize(&snark).unwrap(),
+          bincode::serialize(&snark_budget).unwrap()
+        );
+      }
+    }
+
+    // the zero-knowledge prover has no memory-bounded mode
+    type Szk = crate::spartan::zksnark::R1CSSNARK<G>;
+    let (mut pk, _vk) = Szk::setup_uniform_with_state(&step, 2).unwrap();
+    assert!(Szk::min_memory_budget(&pk).is_none());
+    assert!(Szk::set_memory_budget(&mut pk, None).is_ok());
+    assert!(matches!(
+      Szk::set_memory_budget(&mut pk, Some(1 << 30)),
+      Err(SpartanError::InvalidMemoryBudget)
+    ));
+  }
+
+  #[test]
+  fn test_upsnark_precommitted() {
+    type G = pasta_curves::pallas::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    test_upsnark_precommitted_with::<G, S>();
+
+    type G2 = bn256::Point;
+    type EE2 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G2>;
+    type S2 = crate::spartan::upsnark::R1CSSNARK<G2, EE2>;
+    test_upsnark_precommitted_with::<G2, S2>();
+  }
+
+  fn test_upsnark_precommitted_with<G: Group, S: PrecommittedSNARKTrait<G>>() {
+    let num_steps = 4;
+    let circuit = UniformCubicCircuit::<<G as Group>::Scalar>::new(num_steps);
+
+    // the trace is committed outside of the SNARK, with generators fixed ahead of setup
+    let witness = circuit.witness();
+    let ck = <G as Group>::CE::setup(b"ck", witness.len()).unwrap();
+    let comm_W = <G as Group>::CE::commit(&ck, &witness);
+
+    let (pk, vk) = SNARK::<G, S, UniformCubicCircuit<<G as Group>::Scalar>>::setup_precommitted(
+      UniformCubicCircuit::new(1),
+      num_steps,
+      ck.clone(),
+    )
+    .unwrap();
+
+    let snark = SNARK::prove_precommitted(&pk, circuit, comm_W.clone()).unwrap();
+    let io = [<G as Group>::Scalar::from(15u64)];
+    assert!(snark.verify_precommitted(&vk, &io, &comm_W).is_ok());
+
+    // the proof is bound to the external commitment
+    let other_witness = vec![<G as Group>::Scalar::ONE; witness.len()];
+    let other_comm_W = <G as Group>::CE::commit(&ck, &other_witness);
+    assert!(snark.verify_precommitted(&vk, &io, &other_comm_W).is_err());
+  }
+
   #[test]
   fn test_snark_hyrax_pc() {
     type G = pasta_curves::pallas::Point;
@@ -192,6 +818,245 @@ mod tests {
     //type S3pp = crate::spartan::ppsnark::RelaxedR1CSSNARK<G3, EE3>;
     test_snark_with::<G3, S3>();
     //test_snark_with::<G3, S3pp>();
+
+    type G4 = bls12_381::Point;
+    type EE4 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G4>;
+    type S4 = crate::spartan::snark::RelaxedR1CSSNARK<G4, EE4>;
+    test_snark_with::<G4, S4>();
+
+    type G5 = ristretto::Point;
+    type EE5 = crate::provider::hyrax_pc::HyraxEvaluationEngine<G5>;
+    type S5 = crate::spartan::snark::RelaxedR1CSSNARK<G5, EE5>;
+    test_snark_with::<G5, S5>();
+  }
+
+  #[test]
+  fn test_snark_ipa_pc() {
+    type G = ristretto::ipa::Point;
+    type EE = crate::provider::ipa_pc::EvaluationEngine<G>;
+    type S = crate::spartan::snark::RelaxedR1CSSNARK<G, EE>;
+    type Spp = crate::spartan::ppsnark::RelaxedR1CSSNARK<G, EE>;
+    test_snark_with::<G, S>();
+    test_snark_with::<G, Spp>();
+  }
+
+  #[test]
+  fn test_snark_kzg_pc() {
+    type G = crate::provider::bn256_kzg::Point;
+    type EE = crate::provider::kzg_pc::HyperKZGEvaluationEngine<G>;
+    type S = crate::spartan::snark::RelaxedR1CSSNARK<G, EE>;
+    type Spp = crate::spartan::ppsnark::RelaxedR1CSSNARK<G, EE>;
+    type Sup = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    test_snark_with::<G, S>();
+    test_snark_with::<G, Spp>();
+    test_uniform_snark_with::<G, Sup>();
+  }
+
+  #[test]
+  fn test_snark_zeromorph_pc() {
+    type G = crate::provider::bn256_kzg::Point;
+    type EE = crate::provider::zeromorph_pc::ZeromorphEvaluationEngine<G>;
+    type S = crate::spartan::snark::RelaxedR1CSSNARK<G, EE>;
+    type Spp = crate::spartan::ppsnark::RelaxedR1CSSNARK<G, EE>;
+    test_snark_with::<G, S>();
+    test_snark_with::<G, Spp>();
+    test_sha256_snark_with::<G, S>();
+    test_sha256_snark_with::<G, Spp>();
+  }
+
+  #[test]
+  fn test_snark_ligero_pc() {
+    type G = crate::provider::bn256_ligero::Engine;
+    type EE = crate::provider::ligero_pc::LigeroEvaluationEngine<G>;
+    type S = crate::spartan::snark::RelaxedR1CSSNARK<G, EE>;
+    type Spp = crate::spartan::ppsnark::RelaxedR1CSSNARK<G, EE>;
+    type Sup = crate::spartan::upsnark::R1CSSNARK<G, EE>;
+    test_snark_with::<G, S>();
+    test_snark_with::<G, Spp>();
+    test_uniform_snark_with::<G, Sup>();
+    test_sha256_snark_with::<G, S>();
+    test_sha256_snark_with::<G, Spp>();
+  }
+
+  #[test]
+  fn test_snark_poseidon_transcript() {
+    type G = bn256::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type TE = crate::provider::poseidon::PoseidonTranscript<G>;
+    type S = crate::spartan::snark::RelaxedR1CSSNARK<G, EE, TE>;
+    type Spp = crate::spartan::ppsnark::RelaxedR1CSSNARK<G, EE, TE>;
+    type Sup = crate::spartan::upsnark::R1CSSNARK<G, EE, TE>;
+    type Szk = crate::spartan::zksnark::R1CSSNARK<G, TE>;
+    test_snark_with::<G, S>();
+    test_snark_with::<G, Spp>();
+    test_uniform_snark_with::<G, Sup>();
+    test_snark_with::<G, Szk>();
+
+    // a proof produced with one transcript does not verify with another
+    let circuit = CubicCircuit::default();
+    let (pk, vk) =
+      SNARK::<G, S, CubicCircuit<<G as Group>::Scalar>>::setup(circuit.clone()).unwrap();
+    let snark = SNARK::prove(&pk, circuit).unwrap();
+    type Skeccak = crate::spartan::snark::RelaxedR1CSSNARK<G, EE>;
+    let snark_keccak: SNARK<G, Skeccak, CubicCircuit<<G as Group>::Scalar>> =
+      bincode::deserialize(&bincode::serialize(&snark).unwrap()).unwrap();
+    let vk_keccak = bincode::deserialize(&bincode::serialize(&vk).unwrap()).unwrap();
+    assert!(snark.verify(&vk, &[<G as Group>::Scalar::from(15u64)]).is_ok());
+    assert!(snark_keccak
+      .verify(&vk_keccak, &[<G as Group>::Scalar::from(15u64)])
+      .is_err());
+  }
+
+  #[test]
+  fn test_snark_recording_transcript() {
+    use crate::provider::recording::{first_divergence, record, RecordingTranscript, TranscriptEvent};
+
+    type G = bn256::Point;
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    type S = crate::spartan::snark::RelaxedR1CSSNARK<G, EE, RecordingTranscript<G>>;
+
+    let circuit = CubicCircuit::default();
+    let (pk, vk) =
+      SNARK::<G, S, CubicCircuit<<G as Group>::Scalar>>::setup(circuit.clone()).unwrap();
+    let (snark, prover_log) = record(|| SNARK::prove(&pk, circuit).unwrap());
+
+    // an honest verifier performs the same operations as the prover
+    let io = [<G as Group>::Scalar::from(15u64)];
+    let (res, verifier_log) = record(|| snark.verify(&vk, &io));
+    assert!(res.is_ok());
+    assert_eq!(first_divergence(&prover_log, &verifier_log), None);
+
+    // with the wrong io, the transcripts diverge when the instance is absorbed
+    let io = [<G as Group>::Scalar::from(16u64)];
+    let (res, verifier_log) = record(|| snark.verify(&vk, &io));
+    assert!(res.is_err());
+    let d = first_divergence(&prover_log, &verifier_log).unwrap();
+    assert!(matches!(d.verifier, Some(TranscriptEvent::Absorb(b"U", _))));
+  }
+
+  fn test_sha256_snark_with<G: Group, S: RelaxedR1CSSNARKTrait<G>>() {
+    let circuit = Sha256Circuit::<<G as Group>::Scalar>::new(vec![0u8; 32]);
+    let io = circuit.io();
+
+    let (pk, vk) =
+      SNARK::<G, S, Sha256Circuit<<G as Group>::Scalar>>::setup(circuit.clone()).unwrap();
+    let snark = SNARK::prove(&pk, circuit).unwrap();
+    assert!(snark.verify(&vk, &io).is_ok());
+
+    // the proof is bound to the digest
+    let io_wrong = vec![io[0], io[1] + <G as Group>::Scalar::ONE];
+    assert!(snark.verify(&vk, &io_wrong).is_err());
+  }
+
+  #[derive(Clone, Debug)]
+  struct SquareRootCircuit<F: PrimeField> {
+    x: F,
+  }
+
+  impl<F: PrimeField> Circuit<F> for SquareRootCircuit<F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+      // proves knowledge of a square root `x` of the public output `y = x^2`
+      let x = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(self.x))?;
+      let y = x.square(cs.namespace(|| "y"))?;
+      y.inputize(cs.namespace(|| "output"))
+    }
+  }
+
+  #[test]
+  fn test_zksnark_hyrax_pc() {
+    type G = pasta_curves::pallas::Point;
+    type S = crate::spartan::zksnark::R1CSSNARK<G>;
+    type Srelaxed = crate::spartan::zksnark::RelaxedR1CSSNARK<G>;
+    test_snark_with::<G, S>();
+    test_snark_with::<G, Srelaxed>();
+    test_zksnark_hiding_with::<G, S>();
+
+    type G2 = bn256::Point;
+    type S2 = crate::spartan::zksnark::R1CSSNARK<G2>;
+    type S2relaxed = crate::spartan::zksnark::RelaxedR1CSSNARK<G2>;
+    test_snark_with::<G2, S2>();
+    test_snark_with::<G2, S2relaxed>();
+    test_zksnark_hiding_with::<G2, S2relaxed>();
+
+    type G3 = secp256k1::Point;
+    type S3 = crate::spartan::zksnark::R1CSSNARK<G3>;
+    type S3relaxed = crate::spartan::zksnark::RelaxedR1CSSNARK<G3>;
+    test_snark_with::<G3, S3>();
+    test_snark_with::<G3, S3relaxed>();
+
+    type G4 = bls12_381::Point;
+    type S4 = crate::spartan::zksnark::R1CSSNARK<G4>;
+    type S4relaxed = crate::spartan::zksnark::RelaxedR1CSSNARK<G4>;
+    test_snark_with::<G4, S4>();
+    test_snark_with::<G4, S4relaxed>();
+
+    type G5 = ristretto::Point;
+    type S5 = crate::spartan::zksnark::R1CSSNARK<G5>;
+    test_snark_with::<G5, S5>();
+
+    // without blinding, the proofs reveal which square root is known
+    type EE = crate::provider::hyrax_pc::HyraxEvaluationEngine<G>;
+    assert!(distinguishes_square_roots::<
+      G,
+      crate::spartan::snark::RelaxedR1CSSNARK<G, EE>,
+    >());
+  }
+
+  /// Returns whether proofs for the square roots `3` and `-3` of the same public `9` can be told
+  /// apart, by comparing the bytes that stay fixed across repeated proofs of each witness
+  fn distinguishes_square_roots<G: Group, S: RelaxedR1CSSNARKTrait<G>>() -> bool {
+    let three = <G as Group>::Scalar::from(3u64);
+    let (pk, vk) =
+      SNARK::<G, S, SquareRootCircuit<<G as Group>::Scalar>>::setup(SquareRootCircuit { x: three })
+        .unwrap();
+
+    let proofs = |x: <G as Group>::Scalar| {
+      (0..8)
+        .map(|_| {
+          let snark = SNARK::prove(&pk, SquareRootCircuit { x }).unwrap();
+          assert!(snark
+            .verify(&vk, &[<G as Group>::Scalar::from(9u64)])
+            .is_ok());
+          bincode::serialize(&snark).unwrap()
+        })
+        .collect::<Vec<_>>()
+    };
+    let (proofs1, proofs2) = (proofs(three), proofs(-three));
+    if proofs1
+      .iter()
+      .chain(&proofs2)
+      .any(|p| p.len() != proofs1[0].len())
+    {
+      return true;
+    }
+
+    let fixed = |proofs: &[Vec<u8>], i: usize| proofs.iter().all(|p| p[i] == proofs[0][i]);
+    (0..proofs1[0].len()).any(|i| {
+      fixed(&proofs1, i) != fixed(&proofs2, i)
+        || (fixed(&proofs1, i) && proofs1[0][i] != proofs2[0][i])
+    })
+  }
+
+  fn test_zksnark_hiding_with<G: Group, S: RelaxedR1CSSNARKTrait<G>>() {
+    let circuit = CubicCircuit::default();
+    let (pk, vk) =
+      SNARK::<G, S, CubicCircuit<<G as Group>::Scalar>>::setup(circuit.clone()).unwrap();
+
+    // two proofs of the same witness share no randomness
+    let snark1 = SNARK::prove(&pk, circuit.clone()).unwrap();
+    let snark2 = SNARK::prove(&pk, circuit).unwrap();
+    assert_ne!(
+      bincode::serialize(&snark1).unwrap(),
+      bincode::serialize(&snark2).unwrap()
+    );
+    assert!(snark1.verify(&vk, &[<G as Group>::Scalar::from(15u64)]).is_ok());
+    assert!(snark2.verify(&vk, &[<G as Group>::Scalar::from(15u64)]).is_ok());
+
+    // a proof does not verify against a different statement
+    assert!(snark1.verify(&vk, &[<G as Group>::Scalar::from(16u64)]).is_err());
+
+    // proofs for two different witnesses of the same statement are identically distributed
+    assert!(!distinguishes_square_roots::<G, S>());
   }
 
   fn test_snark_with<G: Group, S: RelaxedR1CSSNARKTrait<G>>() {
@@ -0,0 +1,80 @@
+//! This module implements the Spartan traits for `bls12_381::Point` and `bls12_381::Scalar`,
+//! the G1 group of BLS12-381 and its scalar field.
+use crate::{
+  impl_traits,
+  provider::hyrax_pc::HyraxCommitmentEngine,
+  traits::{
+    field_to_transcript_scalars, CompressedGroup, Group, PrimeFieldExt, PrimeGroup,
+    TranscriptReprTrait,
+  },
+};
+use digest::{ExtendableOutput, Update};
+use ff::{Field, FromUniformBytes, PrimeField};
+use group::{cofactor::CofactorCurveAffine, Curve, Group as AnotherGroup, GroupEncoding};
+use halo2curves::{
+  bls12381::{G1Affine as Bls12381Affine, G1 as Bls12381Point},
+  msm::msm_best,
+  {CurveAffine, CurveExt},
+};
+use num_bigint::BigInt;
+use num_traits::Num;
+use rayon::prelude::*;
+use sha3::Shake256;
+use std::io::Read;
+
+/// Re-exports that give access to the standard aliases used in the code base, for BLS12-381
+pub mod bls12381 {
+  pub use super::Bls12381CompressedElementWrapper as Compressed;
+  pub use halo2curves::bls12381::{Fq as Base, Fr as Scalar, G1Affine as Affine, G1 as Point};
+}
+
+pub use bls12381::{Affine, Base, Point, Scalar};
+
+impl<G: Group> TranscriptReprTrait<G> for Base {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
+  }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for Scalar {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
+  }
+}
+
+impl_traits!(
+  bls12381,
+  Bls12381CompressedElementWrapper,
+  Bls12381Point,
+  Bls12381Affine,
+  "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  type G = Point;
+
+  #[test]
+  fn test_compress_decompress() {
+    for p in [
+      G::zero(),
+      G::get_generator(),
+      G::get_generator() * Scalar::from(7u64),
+    ] {
+      let c = p.compress();
+      assert_eq!(c.decompress(), Some(p));
+      let c_deserialized: bls12381::Compressed =
+        bincode::deserialize(&bincode::serialize(&c).unwrap()).unwrap();
+      assert_eq!(c_deserialized, c);
+    }
+  }
+}
@@ -1,11 +1,14 @@
 //! This module implements the Spartan traits for `bn256::Point`, `bn256::Scalar`, `grumpkin::Point`, `grumpkin::Scalar`.
 use crate::{
   impl_traits,
-  provider::{hyrax_pc::HyraxCommitmentEngine, keccak::Keccak256Transcript},
-  traits::{CompressedGroup, Group, PrimeFieldExt, TranscriptReprTrait},
+  provider::hyrax_pc::HyraxCommitmentEngine,
+  traits::{
+    field_to_transcript_scalars, CompressedGroup, Group, PrimeFieldExt, PrimeGroup,
+    TranscriptReprTrait,
+  },
 };
 use digest::{ExtendableOutput, Update};
-use ff::{FromUniformBytes, PrimeField};
+use ff::{Field, FromUniformBytes, PrimeField};
 use group::{cofactor::CofactorCurveAffine, Curve, Group as AnotherGroup, GroupEncoding};
 use num_bigint::BigInt;
 use num_traits::Num;
@@ -15,43 +18,45 @@ use rayon::prelude::*;
 use sha3::Shake256;
 use std::io::Read;
 
-use halo2curves::bn256::{
-  G1Affine as Bn256Affine, G1Compressed as Bn256Compressed, G1 as Bn256Point,
-};
-use halo2curves::grumpkin::{
-  G1Affine as GrumpkinAffine, G1Compressed as GrumpkinCompressed, G1 as GrumpkinPoint,
-};
-use halo2curves::msm::best_multiexp;
+use halo2curves::bn256::{G1Affine as Bn256Affine, G1 as Bn256Point};
+use halo2curves::grumpkin::{G1Affine as GrumpkinAffine, G1 as GrumpkinPoint};
+use halo2curves::msm::msm_best;
 
 /// Re-exports that give access to the standard aliases used in the code base, for bn256
 pub mod bn256 {
-  pub use halo2curves::bn256::{
-    Fq as Base, Fr as Scalar, G1Affine as Affine, G1Compressed as Compressed, G1 as Point,
-  };
+  pub use super::Bn256CompressedElementWrapper as Compressed;
+  pub use halo2curves::bn256::{Fq as Base, Fr as Scalar, G1Affine as Affine, G1 as Point};
 }
 
 /// Re-exports that give access to the standard aliases used in the code base, for grumpkin
 pub mod grumpkin {
-  pub use halo2curves::grumpkin::{
-    Fq as Base, Fr as Scalar, G1Affine as Affine, G1Compressed as Compressed, G1 as Point,
-  };
+  pub use super::GrumpkinCompressedElementWrapper as Compressed;
+  pub use halo2curves::grumpkin::{Fq as Base, Fr as Scalar, G1Affine as Affine, G1 as Point};
 }
 
 impl<G: Group> TranscriptReprTrait<G> for grumpkin::Base {
   fn to_transcript_bytes(&self) -> Vec<u8> {
-    self.to_repr().to_vec()
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
   }
 }
 
 impl<G: Group> TranscriptReprTrait<G> for grumpkin::Scalar {
   fn to_transcript_bytes(&self) -> Vec<u8> {
-    self.to_repr().to_vec()
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
   }
 }
 
 impl_traits!(
   bn256,
-  Bn256Compressed,
+  Bn256CompressedElementWrapper,
   Bn256Point,
   Bn256Affine,
   "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"
@@ -59,7 +64,7 @@ impl_traits!(
 
 impl_traits!(
   grumpkin,
-  GrumpkinCompressed,
+  GrumpkinCompressedElementWrapper,
   GrumpkinPoint,
   GrumpkinAffine,
   "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47"
@@ -90,7 +95,7 @@ mod tests {
     for n in [
       1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 1021,
     ] {
-      let ck_par = <G as Group>::from_label(label, n);
+      let ck_par = <G as PrimeGroup>::from_label(label, n);
       let ck_ser = from_label_serial(label, n);
       assert_eq!(ck_par.len(), n);
       assert_eq!(ck_ser.len(), n);
@@ -0,0 +1,282 @@
+//! This module implements the Spartan traits for `bn256_kzg::Point`, a wrapper of `bn256::Point`
+//! whose commitment engine is KZG instead of Hyrax.
+//! The wrapper shares the scalar field, the base field, and the generators of `bn256::Point`.
+//! It also reads KZG commitment keys from the files of powers-of-tau ceremonies over BN256.
+use crate::{
+  errors::SpartanError,
+  provider::{
+    impl_wrapped_bn256_point,
+    kzg_pc::{KZGCommitmentEngine, KZGCommitmentKey, PairingGroup},
+  },
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use ff::{Field, PrimeField};
+use group::Curve;
+use halo2curves::{
+  bn256::{Bn256, Fq2, Gt},
+  pairing::{MillerLoopResult, MultiMillerLoop},
+  CurveAffine,
+};
+use std::io::{self, Read};
+
+pub use halo2curves::bn256::{Fq as Base, Fr as Scalar, G1Affine as Affine, G2Affine as G2};
+
+impl_wrapped_bn256_point!(
+  KZGCommitmentEngine,
+  "A point of the first source group of BN256, committed to with KZG"
+);
+
+impl PairingGroup for Point {
+  type G2 = G2;
+
+  fn g2_generator() -> Self::G2 {
+    G2::generator()
+  }
+
+  fn g2_mul(g: &Self::G2, s: &Self::Scalar) -> Self::G2 {
+    (*g * s).to_affine()
+  }
+
+  fn pairing_check(terms: &[(Self, Self::G2)]) -> bool {
+    let g1 = terms
+      .iter()
+      .map(|(p, _)| p.0.to_affine())
+      .collect::<Vec<_>>();
+    let terms = g1
+      .iter()
+      .zip(terms.iter().map(|(_, q)| q))
+      .collect::<Vec<_>>();
+    Bn256::multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+  }
+}
+
+// the sections of a `.ptau` file that hold the parameters of the ceremony and the powers of tau
+const PTAU_HEADER: u32 = 1;
+const PTAU_TAU_G1: u32 = 2;
+const PTAU_TAU_G2: u32 = 3;
+
+/// Reads a commitment key for vectors of size `n` from the `.ptau` file of a powers-of-tau ceremony over BN256,
+/// in the format of snarkjs, such as the files of the Perpetual Powers of Tau ceremony.
+/// Returns an error if the file is malformed, is for another curve, holds fewer than `n` powers,
+/// or holds powers that are not consistent with each other (see `KZGCommitmentKey::from_powers`).
+/// Uniform SNARKs take such a key with `SNARK::setup_precommitted`.
+pub fn load_ptau<R: Read>(
+  reader: &mut R,
+  n: usize,
+) -> Result<KZGCommitmentKey<Point>, SpartanError> {
+  let n = n.next_power_of_two();
+  // `.ptau` files store elements of the base field `x` in Montgomery form, as `x * 2^256`
+  let r_inv = Base::from(2u64).pow_vartime([256]).invert().unwrap();
+
+  let mut magic = [0u8; 4];
+  reader.read_exact(&mut magic).map_err(ptau_error)?;
+  if &magic != b"ptau" || reader.read_u32::<LittleEndian>().map_err(ptau_error)? != 1 {
+    return Err(SpartanError::InvalidCommitmentKey);
+  }
+  let num_sections = reader.read_u32::<LittleEndian>().map_err(ptau_error)?;
+
+  let (mut has_header, mut tau_g1, mut tau_g2) = (false, None, None);
+  for _ in 0..num_sections {
+    let section = reader.read_u32::<LittleEndian>().map_err(ptau_error)?;
+    let size = reader.read_u64::<LittleEndian>().map_err(ptau_error)?;
+    let mut reader = reader.by_ref().take(size);
+
+    match section {
+      PTAU_HEADER => {
+        let n8 = reader.read_u32::<LittleEndian>().map_err(ptau_error)?;
+        let mut q = [0u8; 32];
+        reader.read_exact(&mut q).map_err(ptau_error)?;
+        let power = reader.read_u32::<LittleEndian>().map_err(ptau_error)?;
+
+        // the modulus is one more than the (even) largest element
+        let mut q_minus_one: [u8; 32] = (-Base::ONE).to_repr().into();
+        q_minus_one[0] += 1;
+        let num_powers = 1usize.checked_shl(power).unwrap_or(0);
+        if n8 != 32 || q != q_minus_one || num_powers < n {
+          return Err(SpartanError::InvalidCommitmentKey);
+        }
+        has_header = true;
+      }
+      PTAU_TAU_G1 if has_header => {
+        tau_g1 = Some(
+          (0..n)
+            .map(|_| read_g1(&mut reader, &r_inv))
+            .collect::<Result<Vec<_>, _>>()?,
+        );
+      }
+      PTAU_TAU_G2 if has_header => {
+        tau_g2 = Some((read_g2(&mut reader, &r_inv)?, read_g2(&mut reader, &r_inv)?));
+      }
+      _ => (),
+    }
+
+    if tau_g1.is_some() && tau_g2.is_some() {
+      break;
+    }
+
+    // skips the rest of the section
+    io::copy(&mut reader, &mut io::sink()).map_err(ptau_error)?;
+  }
+
+  match (tau_g1, tau_g2) {
+    (Some(tau_g1), Some((h, tau_h))) => KZGCommitmentKey::from_powers(tau_g1, h, tau_h),
+    _ => Err(SpartanError::InvalidCommitmentKey),
+  }
+}
+
+fn ptau_error(_: io::Error) -> SpartanError {
+  SpartanError::InvalidCommitmentKey
+}
+
+/// Reads an element of the base field in Montgomery form, which `r_inv` converts back
+fn read_base<R: Read>(reader: &mut R, r_inv: &Base) -> Result<Base, SpartanError> {
+  let mut repr = [0u8; 32];
+  reader.read_exact(&mut repr).map_err(ptau_error)?;
+  let x =
+    Option::<Base>::from(Base::from_repr(repr.into())).ok_or(SpartanError::InvalidCommitmentKey)?;
+  Ok(x * r_inv)
+}
+
+fn read_g1<R: Read>(reader: &mut R, r_inv: &Base) -> Result<Affine, SpartanError> {
+  let (x, y) = (read_base(reader, r_inv)?, read_base(reader, r_inv)?);
+  Option::from(Affine::from_xy(x, y)).ok_or(SpartanError::InvalidCommitmentKey)
+}
+
+fn read_g2<R: Read>(reader: &mut R, r_inv: &Base) -> Result<G2, SpartanError> {
+  let x = Fq2::new(read_base(reader, r_inv)?, read_base(reader, r_inv)?);
+  let y = Fq2::new(read_base(reader, r_inv)?, read_base(reader, r_inv)?);
+  Option::from(G2::from_xy(x, y)).ok_or(SpartanError::InvalidCommitmentKey)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{keccak::Keccak256Transcript, kzg_pc::HyperKZGEvaluationEngine},
+    spartan::polys::multilinear::MultilinearPolynomial,
+    traits::{
+      commitment::CommitmentEngineTrait, evaluation::EvaluationEngineTrait, Group,
+      TranscriptEngineTrait,
+    },
+  };
+  use byteorder::WriteBytesExt;
+  use halo2curves::bn256::G1Affine;
+  use rand_core::OsRng;
+
+  /// Writes the `.ptau` file of a ceremony with `2^power` powers of `tau`, in which `tampered` is the wrong power
+  fn write_ptau(tau: &Scalar, power: u32, tampered: Option<usize>) -> Vec<u8> {
+    let r = Base::from(2u64).pow_vartime([256]);
+    let write_base =
+      |bytes: &mut Vec<u8>, x: &Base| bytes.extend_from_slice((*x * r).to_repr().as_ref());
+
+    let num_powers = 1usize << power;
+    let mut tau_powers = Vec::new();
+    let mut tau_power = Scalar::ONE;
+    for i in 0..2 * num_powers - 1 {
+      let p = if tampered == Some(i) {
+        tau_power.double()
+      } else {
+        tau_power
+      };
+      tau_powers.push((G1Affine::generator() * p).to_affine());
+      tau_power *= tau;
+    }
+
+    let mut header = Vec::new();
+    header.write_u32::<LittleEndian>(32).unwrap();
+    let mut q: [u8; 32] = (-Base::ONE).to_repr().into();
+    q[0] += 1;
+    header.extend(q);
+    header.write_u32::<LittleEndian>(power).unwrap();
+    header.write_u32::<LittleEndian>(power).unwrap();
+
+    let mut tau_g1 = Vec::new();
+    for p in &tau_powers {
+      write_base(&mut tau_g1, &p.x);
+      write_base(&mut tau_g1, &p.y);
+    }
+
+    let mut tau_g2 = Vec::new();
+    for p in [G2::generator(), (G2::generator() * tau).to_affine()] {
+      // the coefficients c0 and c1 of each coordinate
+      for c in p.x.to_bytes().chunks(32).chain(p.y.to_bytes().chunks(32)) {
+        write_base(
+          &mut tau_g2,
+          &Base::from_bytes(c.try_into().unwrap()).unwrap(),
+        );
+      }
+    }
+
+    // an unrelated section, which the reader skips
+    let contributions = vec![0u8; 17];
+
+    let sections = [
+      (PTAU_HEADER, header),
+      (7, contributions),
+      (PTAU_TAU_G1, tau_g1),
+      (PTAU_TAU_G2, tau_g2),
+    ];
+    let mut bytes = b"ptau".to_vec();
+    bytes.write_u32::<LittleEndian>(1).unwrap();
+    bytes
+      .write_u32::<LittleEndian>(sections.len() as u32)
+      .unwrap();
+    for (section, data) in sections {
+      bytes.write_u32::<LittleEndian>(section).unwrap();
+      bytes.write_u64::<LittleEndian>(data.len() as u64).unwrap();
+      bytes.extend(data);
+    }
+    bytes
+  }
+
+  #[test]
+  fn test_load_ptau() {
+    type EE = HyperKZGEvaluationEngine<Point>;
+
+    let tau = Scalar::random(&mut OsRng);
+    let ptau = write_ptau(&tau, 4, None);
+
+    for ell in 1..5 {
+      let n = 1 << ell;
+      let ck = load_ptau(&mut ptau.as_slice(), n).unwrap();
+
+      let poly = (0..n)
+        .map(|_| Scalar::random(&mut OsRng))
+        .collect::<Vec<_>>();
+      let point = (0..ell)
+        .map(|_| Scalar::random(&mut OsRng))
+        .collect::<Vec<_>>();
+      let eval = MultilinearPolynomial::new(poly.clone()).evaluate(&point);
+
+      let comm = <Point as Group>::CE::commit(&ck, &poly);
+      let (pk, vk) = EE::setup(&ck);
+
+      let mut transcript = Keccak256Transcript::<Point>::new(b"test");
+      let arg = EE::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval).unwrap();
+
+      let mut transcript = Keccak256Transcript::<Point>::new(b"test");
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).is_ok());
+    }
+
+    // the ceremony must have enough powers
+    assert_eq!(
+      load_ptau(&mut ptau.as_slice(), 1 << 5).err(),
+      Some(SpartanError::InvalidCommitmentKey)
+    );
+
+    // and the powers must be consistent with each other
+    let ptau = write_ptau(&tau, 4, Some(5));
+    assert_eq!(
+      load_ptau(&mut ptau.as_slice(), 1 << 4).err(),
+      Some(SpartanError::InvalidCommitmentKey)
+    );
+    assert!(load_ptau(&mut ptau.as_slice(), 1 << 2).is_ok());
+
+    // a truncated file is rejected
+    let ptau = write_ptau(&tau, 4, None);
+    assert_eq!(
+      load_ptau(&mut &ptau[..ptau.len() - 1], 1 << 4).err(),
+      Some(SpartanError::InvalidCommitmentKey)
+    );
+  }
+}
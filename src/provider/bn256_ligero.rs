@@ -0,0 +1,17 @@
+//! This module implements the Spartan traits for `bn256_ligero::Engine`, which runs the SNARKs
+//! over the scalar field of BN256 with Ligero commitments.
+//! Ligero commitments are transparent and hash-based, and never use the group operations,
+//! so the engine is not a point of the curve and only implements `Group`.
+use crate::{provider::ligero_pc::LigeroCommitmentEngine, traits::Group};
+use serde::{Deserialize, Serialize};
+
+pub use halo2curves::bn256::Fr as Scalar;
+
+/// Runs the SNARKs over the scalar field of BN256, whose scalars are committed to with Ligero
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Engine;
+
+impl Group for Engine {
+  type Scalar = Scalar;
+  type CE = LigeroCommitmentEngine<Self>;
+}
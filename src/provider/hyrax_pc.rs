@@ -9,17 +9,23 @@ use crate::{
     CompressedCommitment as PedersenCompressedCommitment,
   },
   spartan::{
+    self,
     math::Math,
+    nizk::{random_scalar, DotProductProof},
     polys::{eq::EqPolynomial, multilinear::MultilinearPolynomial},
   },
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait},
-    evaluation::EvaluationEngineTrait,
-    Group, TranscriptEngineTrait, TranscriptReprTrait,
+    evaluation::{BatchEvaluationArgument, EvaluationEngineTrait},
+    Group, PrimeGroup, TranscriptEngineTrait, TranscriptReprTrait,
   },
   Commitment, CommitmentKey,
 };
-use core::ops::{Add, AddAssign, Mul, MulAssign};
+use core::{
+  cmp::min,
+  ops::{Add, AddAssign, Mul, MulAssign},
+};
+use ff::{Field, PrimeField};
 use itertools::{
   EitherOrBoth::{Both, Left, Right},
   Itertools,
@@ -31,14 +37,44 @@ use std::marker::PhantomData;
 /// A type that holds commitment generators for Hyrax commitments
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxCommitmentKey<G: Group> {
+pub struct HyraxCommitmentKey<G: PrimeGroup> {
   ck: PedersenCommitmentKey<G>,
+  h: PedersenCommitmentKey<G>, // blinding generator for hiding commitments
+  num_vars_right: usize,       // number of variables indexing the columns of committed matrices
+}
+
+impl<G: PrimeGroup> HyraxCommitmentKey<G> {
+  /// Returns the numbers of variables indexing the rows and the columns of the matrix
+  /// in which a polynomial in `ell` variables is committed.
+  /// Every matrix has the key's row width, and a polynomial that does not fill a row is a single row,
+  /// so padding a polynomial with zeros keeps the commitments to its rows. Batched openings of
+  /// polynomials of different sizes rely on this, so smaller polynomials are not laid out nearly square
+  pub fn factored_lens(&self, ell: usize) -> (usize, usize) {
+    let right_num_vars = min(ell, self.num_vars_right);
+    (ell - right_num_vars, right_num_vars)
+  }
+
+  /// Precomputes a fixed-base table over the row generators, which speeds up every row commitment
+  pub fn precompute(&mut self) {
+    self.ck.precompute();
+  }
+
+  /// Returns the generator used for blinding factors in hiding commitments
+  pub(crate) fn blinding_key(&self) -> &PedersenCommitmentKey<G> {
+    &self.h
+  }
+}
+
+/// Blinding factors of a hiding Hyrax commitment, one for each row
+#[derive(Clone, Debug)]
+pub struct HyraxBlinds<G: PrimeGroup> {
+  blinds: Vec<G::Scalar>,
 }
 
 /// Structure that holds commitments
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxCommitment<G: Group> {
+pub struct HyraxCommitment<G: PrimeGroup> {
   comm: Vec<PedersenCommitment<G>>,
   is_default: bool,
 }
@@ -46,12 +82,12 @@ pub struct HyraxCommitment<G: Group> {
 /// Structure that holds compressed commitments
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxCompressedCommitment<G: Group> {
+pub struct HyraxCompressedCommitment<G: PrimeGroup> {
   comm: Vec<PedersenCompressedCommitment<G>>,
   is_default: bool,
 }
 
-impl<G: Group> Default for HyraxCommitment<G> {
+impl<G: PrimeGroup> Default for HyraxCommitment<G> {
   fn default() -> Self {
     HyraxCommitment {
       comm: vec![],
@@ -60,7 +96,7 @@ impl<G: Group> Default for HyraxCommitment<G> {
   }
 }
 
-impl<G: Group> CommitmentTrait<G> for HyraxCommitment<G> {
+impl<G: PrimeGroup> CommitmentTrait<G> for HyraxCommitment<G> {
   type CompressedCommitment = HyraxCompressedCommitment<G>;
 
   fn compress(&self) -> Self::CompressedCommitment {
@@ -81,9 +117,18 @@ impl<G: Group> CommitmentTrait<G> for HyraxCommitment<G> {
       is_default: c.is_default,
     })
   }
+
+  fn combine(comms: &[Self], coeffs: &[G::Scalar]) -> Result<Self, SpartanError> {
+    Ok(
+      comms
+        .iter()
+        .zip(coeffs.iter())
+        .fold(Self::default(), |acc, (c, s)| acc + c * s),
+    )
+  }
 }
 
-impl<G: Group> MulAssign<G::Scalar> for HyraxCommitment<G> {
+impl<G: PrimeGroup> MulAssign<G::Scalar> for HyraxCommitment<G> {
   fn mul_assign(&mut self, scalar: G::Scalar) {
     let result = (self as &HyraxCommitment<G>)
       .comm
@@ -97,7 +142,7 @@ impl<G: Group> MulAssign<G::Scalar> for HyraxCommitment<G> {
   }
 }
 
-impl<'a, 'b, G: Group> Mul<&'b G::Scalar> for &'a HyraxCommitment<G> {
+impl<'b, G: PrimeGroup> Mul<&'b G::Scalar> for &HyraxCommitment<G> {
   type Output = HyraxCommitment<G>;
   fn mul(self, scalar: &'b G::Scalar) -> HyraxCommitment<G> {
     let result = self.comm.iter().map(|c| c * scalar).collect();
@@ -108,7 +153,7 @@ impl<'a, 'b, G: Group> Mul<&'b G::Scalar> for &'a HyraxCommitment<G> {
   }
 }
 
-impl<G: Group> Mul<G::Scalar> for HyraxCommitment<G> {
+impl<G: PrimeGroup> Mul<G::Scalar> for HyraxCommitment<G> {
   type Output = HyraxCommitment<G>;
 
   fn mul(self, scalar: G::Scalar) -> HyraxCommitment<G> {
@@ -120,13 +165,11 @@ impl<G: Group> Mul<G::Scalar> for HyraxCommitment<G> {
   }
 }
 
-impl<'b, G: Group> AddAssign<&'b HyraxCommitment<G>> for HyraxCommitment<G> {
+impl<'b, G: PrimeGroup> AddAssign<&'b HyraxCommitment<G>> for HyraxCommitment<G> {
   fn add_assign(&mut self, other: &'b HyraxCommitment<G>) {
     if self.is_default {
       *self = other.clone();
-    } else if other.is_default {
-      return;
-    } else {
+    } else if !other.is_default {
       let result = (self as &HyraxCommitment<G>)
         .comm
         .iter()
@@ -145,7 +188,7 @@ impl<'b, G: Group> AddAssign<&'b HyraxCommitment<G>> for HyraxCommitment<G> {
   }
 }
 
-impl<'a, 'b, G: Group> Add<&'b HyraxCommitment<G>> for &'a HyraxCommitment<G> {
+impl<'b, G: PrimeGroup> Add<&'b HyraxCommitment<G>> for &HyraxCommitment<G> {
   type Output = HyraxCommitment<G>;
   fn add(self, other: &'b HyraxCommitment<G>) -> HyraxCommitment<G> {
     if self.is_default {
@@ -206,38 +249,76 @@ macro_rules! define_add_assign_variants {
   };
 }
 
-define_add_assign_variants!(G = Group, LHS = HyraxCommitment<G>, RHS = HyraxCommitment<G>);
-define_add_variants!(G = Group, LHS = HyraxCommitment<G>, RHS = HyraxCommitment<G>, Output = HyraxCommitment<G>);
+define_add_assign_variants!(G = PrimeGroup, LHS = HyraxCommitment<G>, RHS = HyraxCommitment<G>);
+define_add_variants!(G = PrimeGroup, LHS = HyraxCommitment<G>, RHS = HyraxCommitment<G>, Output = HyraxCommitment<G>);
 
 /// Provides a commitment engine
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct HyraxCommitmentEngine<G: Group> {
+pub struct HyraxCommitmentEngine<G: PrimeGroup> {
   _p: PhantomData<G>,
 }
 
-impl<G: Group> CommitmentEngineTrait<G> for HyraxCommitmentEngine<G> {
+impl<G: PrimeGroup> CommitmentEngineTrait<G> for HyraxCommitmentEngine<G> {
   type CommitmentKey = HyraxCommitmentKey<G>;
   type Commitment = HyraxCommitment<G>;
 
-  /// Derives generators for Hyrax PC, where num_vars is the number of variables in multilinear poly
-  fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey {
+  /// Derives generators for Hyrax PC, where num_vars is the number of variables in multilinear poly,
+  /// so the largest committed matrix is nearly square, and smaller ones keep its row width
+  /// (see `HyraxCommitmentKey::factored_lens`).
+  /// Since there are only about sqrt(n) generators, it also precomputes a fixed-base table over them
+  fn setup(label: &'static [u8], n: usize) -> Result<Self::CommitmentKey, SpartanError> {
+    Ok(Self::setup_near_square(label, n))
+  }
+
+  fn commit(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+    Self::commit_rows(ck, v, PedersenCommitmentEngine::commit)
+  }
+
+  fn commit_small(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+    Self::commit_rows(ck, v, PedersenCommitmentEngine::commit_small)
+  }
+}
+
+impl<G: PrimeGroup> HyraxCommitmentEngine<G> {
+  /// The infallible body of `setup`, for the keys that the evaluation engine derives for itself
+  pub(crate) fn setup_near_square(label: &'static [u8], n: usize) -> HyraxCommitmentKey<G> {
     let num_vars = n.next_power_of_two().log_2();
     let (_left, right) = EqPolynomial::<G::Scalar>::compute_factored_lens(num_vars);
+    let mut ck = Self::setup_with_split(label, right);
+    ck.precompute();
+    ck
+  }
+
+  /// Derives generators for rows of `2^num_vars_right` columns, so a polynomial in `ell` variables
+  /// is committed as a matrix with `2^(ell - num_vars_right)` rows.
+  /// Wider rows shrink commitments and the verifier's MSM over them, at the cost of more generators
+  /// and a larger inner product argument, and narrower rows do the opposite
+  pub fn setup_with_split(label: &'static [u8], num_vars_right: usize) -> HyraxCommitmentKey<G> {
     let span = tracing::span!(tracing::Level::INFO, "sampling generators");
     let _guard = span.enter();
-    let ck = PedersenCommitmentEngine::setup(label, (2usize).pow(right as u32));
-    drop(_guard); 
-    drop(span); 
-    HyraxCommitmentKey { ck }
+    let ck = PedersenCommitmentKey::from_label(label, (2usize).pow(num_vars_right as u32));
+    let h = PedersenCommitmentKey::from_label(b"hyrax_blinding", 1);
+    drop(_guard);
+    drop(span);
+    HyraxCommitmentKey {
+      ck,
+      h,
+      num_vars_right,
+    }
   }
 
-  fn commit(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+  /// Commits to every row of the matrix in which `v` is laid out with `commit_row`
+  fn commit_rows(
+    ck: &HyraxCommitmentKey<G>,
+    v: &[G::Scalar],
+    commit_row: impl Fn(&PedersenCommitmentKey<G>, &[G::Scalar]) -> PedersenCommitment<G> + Sync,
+  ) -> HyraxCommitment<G> {
     let poly = MultilinearPolynomial::new(v.to_vec());
     let n = poly.len();
     let ell = poly.get_num_vars();
     assert_eq!(n, (2usize).pow(ell as u32));
 
-    let (left_num_vars, right_num_vars) = EqPolynomial::<G::Scalar>::compute_factored_lens(ell);
+    let (left_num_vars, right_num_vars) = ck.factored_lens(ell);
     let L_size = (2usize).pow(left_num_vars as u32);
     let R_size = (2usize).pow(right_num_vars as u32);
     assert_eq!(L_size * R_size, n);
@@ -245,9 +326,7 @@ impl<G: Group> CommitmentEngineTrait<G> for HyraxCommitmentEngine<G> {
     let comm = (0..L_size)
       .collect::<Vec<usize>>()
       .into_par_iter()
-      .map(|i| {
-        PedersenCommitmentEngine::commit(&ck.ck, &poly.get_Z()[R_size * i..R_size * (i + 1)])
-      })
+      .map(|i| commit_row(&ck.ck, &poly.get_Z()[R_size * i..R_size * (i + 1)]))
       .collect();
 
     HyraxCommitment {
@@ -255,9 +334,46 @@ impl<G: Group> CommitmentEngineTrait<G> for HyraxCommitmentEngine<G> {
       is_default: false,
     }
   }
+
+  /// Commits to the provided vector, hiding every row commitment with a fresh random blinding factor.
+  /// The length of the vector must be a power of two
+  pub fn commit_hiding(
+    ck: &HyraxCommitmentKey<G>,
+    v: &[G::Scalar],
+  ) -> Result<(HyraxCommitment<G>, HyraxBlinds<G>), SpartanError> {
+    if !v.len().is_power_of_two() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    let ell = v.len().log_2();
+
+    let (left_num_vars, right_num_vars) = ck.factored_lens(ell);
+    let L_size = (2usize).pow(left_num_vars as u32);
+    let R_size = (2usize).pow(right_num_vars as u32);
+
+    let blinds = (0..L_size).map(|_| random_scalar::<G>()).collect::<Vec<_>>();
+    let comm = (0..L_size)
+      .into_par_iter()
+      .map(|i| {
+        PedersenCommitmentEngine::commit_with_blind(
+          &ck.ck,
+          &ck.h,
+          &v[R_size * i..R_size * (i + 1)],
+          &blinds[i],
+        )
+      })
+      .collect();
+
+    Ok((
+      HyraxCommitment {
+        comm,
+        is_default: false,
+      },
+      HyraxBlinds { blinds },
+    ))
+  }
 }
 
-impl<G: Group> TranscriptReprTrait<G> for HyraxCommitment<G> {
+impl<G: PrimeGroup> TranscriptReprTrait<G> for HyraxCommitment<G> {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     let mut v = Vec::new();
     v.append(&mut b"poly_commitment_begin".to_vec());
@@ -269,9 +385,17 @@ impl<G: Group> TranscriptReprTrait<G> for HyraxCommitment<G> {
     v.append(&mut b"poly_commitment_end".to_vec());
     v
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    let mut v = vec![G::Scalar::from(self.comm.len() as u64)];
+    for c in &self.comm {
+      v.extend(c.to_transcript_scalars());
+    }
+    v
+  }
 }
 
-impl<G: Group> TranscriptReprTrait<G> for HyraxCompressedCommitment<G> {
+impl<G: PrimeGroup> TranscriptReprTrait<G> for HyraxCompressedCommitment<G> {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     let mut v = Vec::new();
     v.append(&mut b"poly_commitment_begin".to_vec());
@@ -285,17 +409,25 @@ impl<G: Group> TranscriptReprTrait<G> for HyraxCompressedCommitment<G> {
   }
 }
 
+/// Returns the evaluations of the equality polynomials of the first `left_num_vars` variables
+/// of `point`, which index rows, and of the remaining ones, which index columns
+fn factored_evals<F: PrimeField>(point: &[F], left_num_vars: usize) -> (Vec<F>, Vec<F>) {
+  let L = EqPolynomial::new(point[..left_num_vars].to_vec()).evals();
+  let R = EqPolynomial::new(point[left_num_vars..].to_vec()).evals();
+  (L, R)
+}
+
 /// Provides an implementation of the hyrax key
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxProverKey<G: Group> {
+pub struct HyraxProverKey<G: PrimeGroup> {
   ck_s: CommitmentKey<G>,
 }
 
 /// Provides an implementation of the hyrax key
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxVerifierKey<G: Group> {
+pub struct HyraxVerifierKey<G: PrimeGroup> {
   ck_v: CommitmentKey<G>,
   ck_s: CommitmentKey<G>,
 }
@@ -303,20 +435,28 @@ pub struct HyraxVerifierKey<G: Group> {
 /// Provides an implementation of a polynomial evaluation argument
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxEvaluationArgument<G: Group> {
+pub struct HyraxEvaluationArgument<G: PrimeGroup> {
   ipa: InnerProductArgument<G>,
 }
 
+/// Provides a zero-knowledge polynomial evaluation argument, where the evaluation is
+/// only known to the verifier through a hiding commitment
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HyraxZKEvaluationArgument<G: PrimeGroup> {
+  proof: DotProductProof<G>,
+}
+
 /// Provides an implementation of a polynomial evaluation engine using Hyrax PC
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct HyraxEvaluationEngine<G: Group> {
+pub struct HyraxEvaluationEngine<G: PrimeGroup> {
   _p: PhantomData<G>,
 }
 
 impl<G> EvaluationEngineTrait<G> for HyraxEvaluationEngine<G>
 where
-  G: Group<CE = HyraxCommitmentEngine<G>>,
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
 {
   type CE = G::CE;
   type ProverKey = HyraxProverKey<G>;
@@ -330,7 +470,7 @@ where
     let span = tracing::span!(tracing::Level::INFO, "hyrax PK");
     let _guard = span.enter();
     let pk = HyraxProverKey::<G> {
-      ck_s: G::CE::setup(b"hyrax", 1),
+      ck_s: HyraxCommitmentEngine::setup_near_square(b"hyrax", 1),
     };
     drop(_guard); 
     drop(span); 
@@ -339,7 +479,7 @@ where
     let _guard = span.enter();
     let vk = HyraxVerifierKey::<G> {
       ck_v: ck.clone(),
-      ck_s: G::CE::setup(b"hyrax", 1),
+      ck_s: HyraxCommitmentEngine::setup_near_square(b"hyrax", 1),
     };
     drop(_guard); 
     drop(span); 
@@ -348,10 +488,10 @@ where
   }
 
   #[tracing::instrument(skip_all, name = "HyraxEvaluationEngine::prove")]
-  fn prove(
+  fn prove<TE: TranscriptEngineTrait<G>>(
     ck: &CommitmentKey<G>,
     pk: &Self::ProverKey,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
     comm: &Commitment<G>,
     poly: &[G::Scalar],
     point: &[G::Scalar],
@@ -368,14 +508,12 @@ where
     // assert vectors are of the right size
     assert_eq!(poly_m.get_num_vars(), point.len());
 
-    let (left_num_vars, right_num_vars) =
-      EqPolynomial::<G::Scalar>::compute_factored_lens(point.len());
+    let (left_num_vars, right_num_vars) = ck.factored_lens(point.len());
     let L_size = (2usize).pow(left_num_vars as u32);
     let R_size = (2usize).pow(right_num_vars as u32);
 
     // compute the L and R vectors (these depend only on the public challenge point so they are public)
-    let eq = EqPolynomial::new(point.to_vec());
-    let (L, R) = eq.compute_factored_evals();
+    let (L, R) = factored_evals(point, left_num_vars);
 
     assert_eq!(L.len(), L_size);
     assert_eq!(R.len(), R_size);
@@ -401,9 +539,9 @@ where
     Ok(HyraxEvaluationArgument { ipa })
   }
 
-  fn verify(
+  fn verify<TE: TranscriptEngineTrait<G>>(
     vk: &Self::VerifierKey,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
     comm: &Commitment<G>,
     point: &[G::Scalar],
     eval: &G::Scalar,
@@ -412,8 +550,8 @@ where
     transcript.absorb(b"poly_com", comm);
 
     // compute L and R
-    let eq = EqPolynomial::new(point.to_vec());
-    let (L, R) = eq.compute_factored_evals();
+    let (left_num_vars, _right_num_vars) = vk.ck_v.factored_lens(point.len());
+    let (L, R) = factored_evals(point, left_num_vars);
 
     // compute a weighted sum of commitments and L
     let ck = PedersenCommitmentEngine::reinterpret_commitments_as_ck(&comm.comm);
@@ -426,4 +564,388 @@ where
       .ipa
       .verify(&vk.ck_v.ck, &vk.ck_s.ck, R.len(), &ipa_instance, transcript)
   }
+
+  /// Binds the rows of every polynomial with the left half of its point, whose commitment the verifier
+  /// derives from the row commitments, so the sum-check only runs over the right half of the variables
+  #[tracing::instrument(skip_all, name = "HyraxEvaluationEngine::prove_batch")]
+  fn prove_batch<TE: TranscriptEngineTrait<G>>(
+    ck: &CommitmentKey<G>,
+    pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    polys: &[Vec<G::Scalar>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+  ) -> Result<BatchEvaluationArgument<G, Self>, SpartanError> {
+    let num_claims = comms.len();
+    if num_claims == 0
+      || polys.len() != num_claims
+      || points.len() != num_claims
+      || evals.len() != num_claims
+    {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    transcript.absorb(b"poly_com", &comms);
+
+    let ell = points.iter().map(|x| x.len()).max().unwrap();
+    let (left_num_vars, _right_num_vars) = ck.factored_lens(ell);
+
+    // bind the rows of every polynomial, padded to the largest size, with the left half of its padded point
+    let (LZ_vec, R_points) = polys
+      .par_iter()
+      .zip(points.par_iter())
+      .map(|(poly, point)| {
+        if poly.len() != 1 << point.len() {
+          return Err(SpartanError::InvalidInputLength);
+        }
+        let mut poly = poly.clone();
+        poly.resize(1 << ell, G::Scalar::ZERO);
+        let mut point_padded = vec![G::Scalar::ZERO; ell - point.len()];
+        point_padded.extend(point);
+
+        let L = EqPolynomial::new(point_padded[..left_num_vars].to_vec()).evals();
+        let LZ = MultilinearPolynomial::new(poly).bound(&L);
+        Ok((LZ, point_padded[left_num_vars..].to_vec()))
+      })
+      .collect::<Result<Vec<_>, _>>()?
+      .into_iter()
+      .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    let (sc_proof, r_z, evals_at_r_z) =
+      spartan::prove_batch_reduce::<G, _>(transcript, &LZ_vec, &R_points, evals)?;
+
+    // combine the claims about the vectors L*Z at r_z into one, which is proven with a single IPA
+    let gamma = transcript.squeeze(b"g")?;
+    let powers_of_gamma = spartan::powers::<G>(&gamma, num_claims);
+    let LZ_joint = spartan::combine_polys(&LZ_vec, &powers_of_gamma);
+    let eval_joint = evals_at_r_z
+      .iter()
+      .zip(powers_of_gamma.iter())
+      .map(|(e, g_i)| *e * g_i)
+      .sum();
+
+    let com_LZ = PedersenCommitmentEngine::commit(&ck.ck, &LZ_joint);
+    let R = EqPolynomial::new(r_z).evals();
+    let ipa_instance = InnerProductInstance::<G>::new(&com_LZ, &R, &eval_joint);
+    let ipa_witness = InnerProductWitness::<G>::new(&LZ_joint);
+    let ipa = InnerProductArgument::<G>::prove(
+      &ck.ck,
+      &pk.ck_s.ck,
+      &ipa_instance,
+      &ipa_witness,
+      transcript,
+    )?;
+
+    Ok(BatchEvaluationArgument {
+      sc_proof,
+      evals: evals_at_r_z,
+      arg: HyraxEvaluationArgument { ipa },
+    })
+  }
+
+  fn verify_batch<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+    arg: &BatchEvaluationArgument<G, Self>,
+  ) -> Result<(), SpartanError> {
+    let num_claims = comms.len();
+    if num_claims == 0 || points.len() != num_claims || evals.len() != num_claims {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    transcript.absorb(b"poly_com", &comms);
+
+    let ell = points.iter().map(|x| x.len()).max().unwrap();
+    let (left_num_vars, _right_num_vars) = vk.ck_v.factored_lens(ell);
+
+    // compute the commitments to L*Z from the row commitments; rows of the padding are zero
+    let (com_LZ_vec, R_points) = comms
+      .par_iter()
+      .zip(points.par_iter())
+      .map(|(comm, point)| {
+        let mut point_padded = vec![G::Scalar::ZERO; ell - point.len()];
+        point_padded.extend(point);
+
+        let L = EqPolynomial::new(point_padded[..left_num_vars].to_vec()).evals();
+        if comm.comm.len() > L.len() {
+          return Err(SpartanError::ProofVerifyError);
+        }
+        let ck = PedersenCommitmentEngine::reinterpret_commitments_as_ck(&comm.comm);
+        let com_LZ = PedersenCommitmentEngine::commit(&ck, &L[..comm.comm.len()]);
+        Ok((com_LZ, point_padded[left_num_vars..].to_vec()))
+      })
+      .collect::<Result<Vec<_>, _>>()?
+      .into_iter()
+      .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    let r_z = spartan::verify_batch_reduce::<G, _>(
+      transcript,
+      &arg.sc_proof,
+      &R_points,
+      evals,
+      &arg.evals,
+    )?;
+
+    let gamma = transcript.squeeze(b"g")?;
+    let powers_of_gamma = spartan::powers::<G>(&gamma, num_claims);
+    let com_LZ = com_LZ_vec
+      .iter()
+      .zip(powers_of_gamma.iter())
+      .fold(PedersenCommitment::<G>::default(), |acc, (c, g_i)| {
+        acc + c * g_i
+      });
+    let eval_joint = arg
+      .evals
+      .iter()
+      .zip(powers_of_gamma.iter())
+      .map(|(e, g_i)| *e * g_i)
+      .sum();
+
+    let R = EqPolynomial::new(r_z).evals();
+    let ipa_instance = InnerProductInstance::<G>::new(&com_LZ, &R, &eval_joint);
+
+    arg
+      .arg
+      .ipa
+      .verify(&vk.ck_v.ck, &vk.ck_s.ck, R.len(), &ipa_instance, transcript)
+  }
+}
+
+impl<G> HyraxEvaluationEngine<G>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+{
+  /// Proves the evaluation of a multilinear polynomial committed with `HyraxCommitmentEngine::commit_hiding`.
+  /// The evaluation is not revealed: the verifier only learns the hiding commitment
+  /// `eval * ck_eval + blind_eval * h`, and the argument is a hiding dot-product proof.
+  #[tracing::instrument(skip_all, name = "HyraxEvaluationEngine::prove_zk")]
+  pub fn prove_zk<TE: TranscriptEngineTrait<G>>(
+    ck: &HyraxCommitmentKey<G>,
+    ck_eval: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    comm: &HyraxCommitment<G>,
+    blinds: &HyraxBlinds<G>,
+    poly: &[G::Scalar],
+    point: &[G::Scalar],
+    eval: &G::Scalar,
+    blind_eval: &G::Scalar,
+  ) -> Result<HyraxZKEvaluationArgument<G>, SpartanError> {
+    if point.len() >= usize::BITS as usize || poly.len() != 1 << point.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    transcript.absorb(b"poly_com", comm);
+
+    let poly_m = MultilinearPolynomial::<G::Scalar>::new(poly.to_vec());
+
+    // compute the L and R vectors (these depend only on the public challenge point so they are public)
+    let (left_num_vars, _right_num_vars) = ck.factored_lens(point.len());
+    let (L, R) = factored_evals(point, left_num_vars);
+    if L.len() != blinds.blinds.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // the verifier computes a commitment to L*Z from the row commitments,
+    // whose blinding factor is the same linear combination of the row blinds
+    let LZ = poly_m.bound(&L);
+    let blind_LZ = L
+      .iter()
+      .zip(blinds.blinds.iter())
+      .map(|(l, b)| *l * b)
+      .sum::<G::Scalar>();
+
+    let (proof, _comm_LZ, _comm_eval) = DotProductProof::prove(
+      &ck.ck,
+      ck_eval,
+      &ck.h,
+      transcript,
+      &LZ,
+      &blind_LZ,
+      &R,
+      eval,
+      blind_eval,
+    )?;
+
+    Ok(HyraxZKEvaluationArgument { proof })
+  }
+
+  /// Verifies an evaluation argument produced by `prove_zk` against a hiding commitment to the evaluation
+  pub fn verify_zk<TE: TranscriptEngineTrait<G>>(
+    vk: &HyraxVerifierKey<G>,
+    ck_eval: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    comm: &HyraxCommitment<G>,
+    point: &[G::Scalar],
+    comm_eval: &PedersenCommitment<G>,
+    arg: &HyraxZKEvaluationArgument<G>,
+  ) -> Result<(), SpartanError> {
+    transcript.absorb(b"poly_com", comm);
+
+    // compute L and R
+    let (left_num_vars, _right_num_vars) = vk.ck_v.factored_lens(point.len());
+    let (L, R) = factored_evals(point, left_num_vars);
+    if L.len() != comm.comm.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // compute a weighted sum of commitments and L
+    let ck = PedersenCommitmentEngine::reinterpret_commitments_as_ck(&comm.comm);
+    let comm_LZ = PedersenCommitmentEngine::commit(&ck, &L);
+
+    arg.proof.verify(
+      &vk.ck_v.ck,
+      ck_eval,
+      &vk.ck_v.h,
+      transcript,
+      &R,
+      &comm_LZ,
+      comm_eval,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::provider::keccak::Keccak256Transcript;
+  use rand_core::OsRng;
+
+  type G = pasta_curves::pallas::Point;
+  type EE = HyraxEvaluationEngine<G>;
+
+  #[test]
+  fn test_hyrax_batch_eval() {
+    // polynomials in three and four variables have rows of the same size,
+    // so the shorter one can be padded to the size of the others
+    let ells = [4, 3, 4];
+    let polys = ells
+      .iter()
+      .map(|ell| {
+        (0..1 << ell)
+          .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    let points = ells
+      .iter()
+      .map(|ell| {
+        (0..*ell)
+          .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    let evals = polys
+      .iter()
+      .zip(points.iter())
+      .map(|(p, x)| MultilinearPolynomial::new(p.clone()).evaluate(x))
+      .collect::<Vec<_>>();
+
+    let ck = <G as Group>::CE::setup(b"test", 1 << 4).unwrap();
+    let comms = polys
+      .iter()
+      .map(|p| <G as Group>::CE::commit(&ck, p))
+      .collect::<Vec<_>>();
+    let (pk, vk) = EE::setup(&ck);
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let arg = EE::prove_batch(
+      &ck,
+      &pk,
+      &mut transcript,
+      &comms,
+      &polys,
+      &points,
+      &evals,
+    )
+    .unwrap();
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(EE::verify_batch(&vk, &mut transcript, &comms, &points, &evals, &arg).is_ok());
+
+    // a wrong evaluation is rejected
+    let mut wrong_evals = evals.clone();
+    wrong_evals[1] += <G as Group>::Scalar::ONE;
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(EE::verify_batch(&vk, &mut transcript, &comms, &points, &wrong_evals, &arg).is_err());
+  }
+
+  #[test]
+  fn test_hyrax_eval_with_split() {
+    let ell = 5;
+    let poly = (0..1 << ell)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let point = (0..ell)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let eval = MultilinearPolynomial::new(poly.clone()).evaluate(&point);
+
+    // from a single column to a single row
+    for num_vars_right in 0..=ell {
+      let ck = HyraxCommitmentEngine::<G>::setup_with_split(b"test", num_vars_right);
+      let comm = <G as Group>::CE::commit(&ck, &poly);
+      assert_eq!(comm.comm.len(), 1 << (ell - num_vars_right));
+      let (pk, vk) = EE::setup(&ck);
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let arg = EE::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval).unwrap();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).is_ok());
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let wrong_eval = eval + <G as Group>::Scalar::ONE;
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &wrong_eval, &arg).is_err());
+    }
+
+    // a key from plain setup lays out polynomials smaller than its size with its row width,
+    // so a polynomial padded with zeros starts with the same row commitments
+    let ck = <G as Group>::CE::setup(b"test", 1 << (ell + 2)).unwrap();
+    assert_eq!(ck.factored_lens(ell + 2), (3, 4));
+    assert_eq!(ck.factored_lens(ell), (1, 4));
+    assert_eq!(ck.factored_lens(2), (0, 2));
+    let comm = <G as Group>::CE::commit(&ck, &poly);
+    let mut padded = poly.clone();
+    padded.resize(1 << (ell + 2), <G as Group>::Scalar::ZERO);
+    let comm_padded = <G as Group>::CE::commit(&ck, &padded);
+    assert_eq!(comm_padded.comm[..comm.comm.len()], comm.comm[..]);
+  }
+
+  #[test]
+  fn test_hyrax_zk_invalid_lengths() {
+    let ck = <G as Group>::CE::setup(b"test", 1 << 3).unwrap();
+    let ck_eval = PedersenCommitmentEngine::setup(b"eval", 1).unwrap();
+    let poly = (0..1 << 3)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+
+    // a vector whose length is not a power of two is not committed
+    assert!(matches!(
+      HyraxCommitmentEngine::commit_hiding(&ck, &poly[..6]),
+      Err(SpartanError::InvalidInputLength)
+    ));
+
+    // the point must have a coordinate for each variable of the polynomial
+    let (comm, blinds) = HyraxCommitmentEngine::commit_hiding(&ck, &poly).unwrap();
+    let point = (0..2)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(matches!(
+      EE::prove_zk(
+        &ck,
+        &ck_eval,
+        &mut transcript,
+        &comm,
+        &blinds,
+        &poly,
+        &point,
+        &<G as Group>::Scalar::ZERO,
+        &<G as Group>::Scalar::ZERO,
+      ),
+      Err(SpartanError::InvalidInputLength)
+    ));
+  }
 }
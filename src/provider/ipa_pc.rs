@@ -7,11 +7,11 @@ use crate::{
     CommitmentEngineExtTrait, CommitmentKey as PedersenCommitmentKey,
     CompressedCommitment as PedersenCompressedCommitment,
   },
-  spartan::polys::eq::EqPolynomial,
+  spartan::{self, polys::eq::EqPolynomial, sumcheck::SumcheckProof},
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait},
-    evaluation::EvaluationEngineTrait,
-    Group, TranscriptEngineTrait, TranscriptReprTrait,
+    evaluation::{BatchEvaluationArgument, EvaluationEngineTrait},
+    Group, PrimeGroup, TranscriptEngineTrait, TranscriptReprTrait,
   },
   Commitment, CommitmentKey,
 };
@@ -24,27 +24,27 @@ use std::marker::PhantomData;
 /// Provides an implementation of the prover key
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct ProverKey<G: Group> {
+pub struct ProverKey<G: PrimeGroup> {
   ck_s: CommitmentKey<G>,
 }
 
 /// Provides an implementation of the verifier key
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct VerifierKey<G: Group> {
+pub struct VerifierKey<G: PrimeGroup> {
   ck_v: CommitmentKey<G>,
   ck_s: CommitmentKey<G>,
 }
 
 /// Provides an implementation of a polynomial evaluation engine using IPA
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct EvaluationEngine<G: Group> {
+pub struct EvaluationEngine<G: PrimeGroup> {
   _p: PhantomData<G>,
 }
 
 impl<G> EvaluationEngineTrait<G> for EvaluationEngine<G>
 where
-  G: Group<CE = PedersenCommitmentEngine<G>>,
+  G: PrimeGroup<CE = PedersenCommitmentEngine<G>>,
 {
   type CE = G::CE;
   type ProverKey = ProverKey<G>;
@@ -54,7 +54,7 @@ where
   fn setup(
     ck: &<<G as Group>::CE as CommitmentEngineTrait<G>>::CommitmentKey,
   ) -> (Self::ProverKey, Self::VerifierKey) {
-    let ck_c = G::CE::setup(b"ipa", 1);
+    let ck_c = PedersenCommitmentKey::from_label(b"ipa", 1);
 
     let pk = ProverKey { ck_s: ck_c.clone() };
     let vk = VerifierKey {
@@ -65,10 +65,10 @@ where
     (pk, vk)
   }
 
-  fn prove(
+  fn prove<TE: TranscriptEngineTrait<G>>(
     ck: &CommitmentKey<G>,
     pk: &Self::ProverKey,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
     comm: &Commitment<G>,
     poly: &[G::Scalar],
     point: &[G::Scalar],
@@ -81,9 +81,9 @@ where
   }
 
   /// A method to verify purported evaluations of a batch of polynomials
-  fn verify(
+  fn verify<TE: TranscriptEngineTrait<G>>(
     vk: &Self::VerifierKey,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
     comm: &Commitment<G>,
     point: &[G::Scalar],
     eval: &G::Scalar,
@@ -101,6 +101,104 @@ where
 
     Ok(())
   }
+
+  /// Claims at a common point are combined directly into a single inner product argument,
+  /// so the sum-check of the default implementation only runs for claims at different points
+  fn prove_batch<TE: TranscriptEngineTrait<G>>(
+    ck: &CommitmentKey<G>,
+    pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    polys: &[Vec<G::Scalar>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+  ) -> Result<BatchEvaluationArgument<G, Self>, SpartanError> {
+    if !points.windows(2).all(|x| x[0] == x[1]) {
+      return spartan::prove_batch::<G, Self, _>(ck, pk, transcript, comms, polys, points, evals);
+    }
+
+    let num_claims = comms.len();
+    if num_claims == 0
+      || polys.len() != num_claims
+      || points.len() != num_claims
+      || evals.len() != num_claims
+      || polys.iter().any(|p| p.len() != 1 << points[0].len())
+    {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    transcript.absorb(b"poly_com", &comms);
+    spartan::absorb_claims::<G, _>(transcript, points, evals);
+    let gamma = transcript.squeeze(b"g")?;
+    let powers_of_gamma = spartan::powers::<G>(&gamma, num_claims);
+    let comm_joint = Commitment::<G>::combine(comms, &powers_of_gamma)?;
+    let poly_joint = spartan::combine_polys(polys, &powers_of_gamma);
+    let eval_joint = evals
+      .iter()
+      .zip(powers_of_gamma.iter())
+      .map(|(e, g_i)| *e * g_i)
+      .sum();
+
+    let arg = Self::prove(
+      ck,
+      pk,
+      transcript,
+      &comm_joint,
+      &poly_joint,
+      &points[0],
+      &eval_joint,
+    )?;
+
+    Ok(BatchEvaluationArgument {
+      sc_proof: SumcheckProof::new(Vec::new()),
+      evals: evals.to_vec(),
+      arg,
+    })
+  }
+
+  fn verify_batch<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+    arg: &BatchEvaluationArgument<G, Self>,
+  ) -> Result<(), SpartanError> {
+    if !points.windows(2).all(|x| x[0] == x[1]) {
+      return spartan::verify_batch::<G, Self, _>(vk, transcript, comms, points, evals, arg);
+    }
+
+    let num_claims = comms.len();
+    if num_claims == 0 || points.len() != num_claims || evals.len() != num_claims {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // the argument holds no reduction, so its sum-check has no rounds
+    arg.sc_proof.verify(G::Scalar::ZERO, 0, 2, transcript)?;
+    if arg.evals != evals {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    transcript.absorb(b"poly_com", &comms);
+    spartan::absorb_claims::<G, _>(transcript, points, evals);
+    let gamma = transcript.squeeze(b"g")?;
+    let powers_of_gamma = spartan::powers::<G>(&gamma, num_claims);
+    let comm_joint = Commitment::<G>::combine(comms, &powers_of_gamma)?;
+    let eval_joint = evals
+      .iter()
+      .zip(powers_of_gamma.iter())
+      .map(|(e, g_i)| *e * g_i)
+      .sum();
+
+    Self::verify(
+      vk,
+      transcript,
+      &comm_joint,
+      &points[0],
+      &eval_joint,
+      &arg.arg,
+    )
+  }
 }
 
 fn inner_product<T>(a: &[T], b: &[T]) -> T
@@ -116,13 +214,13 @@ where
 
 /// An inner product instance consists of a commitment to a vector `a` and another vector `b`
 /// and the claim that c = <a, b>.
-pub struct InnerProductInstance<G: Group> {
+pub struct InnerProductInstance<G: PrimeGroup> {
   comm_a_vec: PedersenCommitment<G>,
   b_vec: Vec<G::Scalar>,
   c: G::Scalar,
 }
 
-impl<G: Group> InnerProductInstance<G> {
+impl<G: PrimeGroup> InnerProductInstance<G> {
   /// Creates a new inner product instance
   pub fn new(comm_a_vec: &PedersenCommitment<G>, b_vec: &[G::Scalar], c: &G::Scalar) -> Self {
     InnerProductInstance {
@@ -133,7 +231,7 @@ impl<G: Group> InnerProductInstance<G> {
   }
 }
 
-impl<G: Group> TranscriptReprTrait<G> for InnerProductInstance<G> {
+impl<G: PrimeGroup> TranscriptReprTrait<G> for InnerProductInstance<G> {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     // we do not need to include self.b_vec as in our context it is produced from the transcript
     [
@@ -142,14 +240,18 @@ impl<G: Group> TranscriptReprTrait<G> for InnerProductInstance<G> {
     ]
     .concat()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    [self.comm_a_vec.to_transcript_scalars(), vec![self.c]].concat()
+  }
 }
 
 /// An inner product witness consists the vector `a`.
-pub struct InnerProductWitness<G: Group> {
+pub struct InnerProductWitness<G: PrimeGroup> {
   a_vec: Vec<G::Scalar>,
 }
 
-impl<G: Group> InnerProductWitness<G> {
+impl<G: PrimeGroup> InnerProductWitness<G> {
   /// Creates a new inner product witness
   pub fn new(a_vec: &[G::Scalar]) -> Self {
     InnerProductWitness {
@@ -161,25 +263,25 @@ impl<G: Group> InnerProductWitness<G> {
 /// An inner product argument
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct InnerProductArgument<G: Group> {
+pub struct InnerProductArgument<G: PrimeGroup> {
   L_vec: Vec<PedersenCompressedCommitment<G>>,
   R_vec: Vec<PedersenCompressedCommitment<G>>,
   a_hat: G::Scalar,
 }
 
-impl<G: Group> InnerProductArgument<G> {
+impl<G: PrimeGroup> InnerProductArgument<G> {
   const fn protocol_name() -> &'static [u8] {
     b"IPA"
   }
 
   /// Proves an inner product relationship
   #[tracing::instrument(skip_all, name = "InnerProductArgument::prove")]
-  pub fn prove(
+  pub fn prove<TE: TranscriptEngineTrait<G>>(
     ck: &PedersenCommitmentKey<G>,
     ck_c: &PedersenCommitmentKey<G>,
     U: &InnerProductInstance<G>,
     W: &InnerProductWitness<G>,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<Self, SpartanError> {
     transcript.dom_sep(Self::protocol_name());
 
@@ -200,7 +302,7 @@ impl<G: Group> InnerProductArgument<G> {
     let prove_inner = |a_vec: &[G::Scalar],
                        b_vec: &[G::Scalar],
                        ck: &PedersenCommitmentKey<G>,
-                       transcript: &mut G::TE|
+                       transcript: &mut TE|
      -> Result<
       (
         PedersenCompressedCommitment<G>,
@@ -287,13 +389,13 @@ impl<G: Group> InnerProductArgument<G> {
   }
 
   /// Verifies an inner product relationship
-  pub fn verify(
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
     &self,
     ck: &PedersenCommitmentKey<G>,
     ck_c: &PedersenCommitmentKey<G>,
     n: usize,
     U: &InnerProductInstance<G>,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<(), SpartanError> {
     let (ck, _) = PedersenCommitmentEngine::split_at(ck, U.b_vec.len());
 
@@ -88,7 +88,7 @@ impl<G: Group> TranscriptEngineTrait<G> for Keccak256Transcript<G> {
   #[tracing::instrument(skip_all, name = "Keccak256Transcript::absorb")]
   fn absorb<T: TranscriptReprTrait<G>>(&mut self, label: &'static [u8], o: &T) {
     self.transcript.update(label);
-    self.transcript.update(&o.to_transcript_bytes());
+    self.transcript.update(o.to_transcript_bytes());
   }
 
   fn dom_sep(&mut self, bytes: &'static [u8]) {
@@ -0,0 +1,417 @@
+//! This module implements KZG commitments over a pairing-friendly group, along with the HyperKZG
+//! polynomial evaluation argument.
+//! A multilinear polynomial is committed to as the univariate polynomial whose coefficients are its
+//! evaluations over the boolean hypercube, so a commitment is a single group element.
+use crate::{
+  errors::SpartanError,
+  provider::pedersen::{
+    Commitment as PedersenCommitment, CommitmentEngine as PedersenCommitmentEngine,
+    CommitmentKey as PedersenCommitmentKey,
+  },
+  traits::{
+    commitment::CommitmentEngineTrait, evaluation::EvaluationEngineTrait, CompressedGroup,
+    PrimeGroup, TranscriptEngineTrait,
+  },
+  Commitment, CommitmentKey,
+};
+use core::{fmt::Debug, iter};
+use ff::Field;
+use rand_core::OsRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// A group that is the first source group of a pairing, over which KZG commitments are defined
+pub trait PairingGroup: PrimeGroup {
+  /// A type representing an element of the second source group of the pairing
+  type G2: Clone + Copy + Debug + Eq + Send + Sync + Serialize + for<'de> Deserialize<'de>;
+
+  /// Returns the generator of the second source group
+  fn g2_generator() -> Self::G2;
+
+  /// Multiplies an element of the second source group by a scalar
+  fn g2_mul(g: &Self::G2, s: &Self::Scalar) -> Self::G2;
+
+  /// Checks that the product of the pairings of the provided pairs is the identity of the target group
+  fn pairing_check(terms: &[(Self, Self::G2)]) -> bool;
+}
+
+/// A type that holds the structured reference string of KZG commitments
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct KZGCommitmentKey<G: PairingGroup> {
+  pub(crate) ck: PedersenCommitmentKey<G>, // powers of the trapdoor in the first source group
+  pub(crate) h: G::G2,                     // generator of the second source group
+  pub(crate) tau_h: G::G2,                 // trapdoor in the second source group
+}
+
+impl<G: PairingGroup> KZGCommitmentKey<G> {
+  /// Returns a commitment key from the powers `[tau^i] g` of a trapdoor `tau`, starting at the generator `g`
+  /// of the first source group, along with the generator `h` of the second source group and `[tau] h`,
+  /// as produced by a powers-of-tau ceremony. Returns an error unless the powers are consistent with `tau_h`
+  pub fn from_powers(
+    powers: Vec<G::PreprocessedGroupElement>,
+    h: G::G2,
+    tau_h: G::G2,
+  ) -> Result<Self, SpartanError> {
+    if powers.is_empty()
+      || G::vartime_multiscalar_mul(&[G::Scalar::ONE], &powers[..1]) != G::get_generator()
+      || h != G::g2_generator()
+    {
+      return Err(SpartanError::InvalidCommitmentKey);
+    }
+
+    // every power is `tau` times the previous one iff, for a random `r`,
+    // e(sum_i r^i [tau^(i+1)] g, h) = e(sum_i r^i [tau^i] g, [tau] h)
+    let r = G::Scalar::random(&mut OsRng);
+    let r_powers = iter::successors(Some(G::Scalar::ONE), |p| Some(*p * r))
+      .take(powers.len() - 1)
+      .collect::<Vec<_>>();
+    let L = G::vartime_multiscalar_mul(&r_powers, &powers[1..]);
+    let R = G::vartime_multiscalar_mul(&r_powers, &powers[..powers.len() - 1]);
+    if !G::pairing_check(&[(L, h), (G::zero() - R, tau_h)]) {
+      return Err(SpartanError::InvalidCommitmentKey);
+    }
+
+    Ok(KZGCommitmentKey {
+      ck: PedersenCommitmentKey::from_generators(powers),
+      h,
+      tau_h,
+    })
+  }
+
+  /// Produces the powers of a trapdoor sampled from the OS RNG, enough to commit to vectors of size `n`.
+  /// The trapdoor is discarded once its powers are computed, but whoever runs this setup could have kept it
+  /// and forge openings, so the key is only fit for tests and benchmarks
+  pub fn insecure_setup(n: usize) -> Self {
+    let tau = G::Scalar::random(&mut OsRng);
+
+    let powers = iter::successors(Some(G::Scalar::ONE), |p| Some(*p * tau))
+      .take(n.next_power_of_two())
+      .collect::<Vec<_>>();
+    let g = G::get_generator();
+    let ck = powers
+      .par_iter()
+      .map(|p| (g * p).preprocessed())
+      .collect::<Vec<_>>();
+
+    let h = G::g2_generator();
+    KZGCommitmentKey {
+      ck: PedersenCommitmentKey::from_generators(ck),
+      h,
+      tau_h: G::g2_mul(&h, &tau),
+    }
+  }
+}
+
+/// Provides a commitment engine for KZG commitments
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KZGCommitmentEngine<G: PairingGroup> {
+  _p: PhantomData<G>,
+}
+
+impl<G: PairingGroup> CommitmentEngineTrait<G> for KZGCommitmentEngine<G> {
+  type CommitmentKey = KZGCommitmentKey<G>;
+  type Commitment = PedersenCommitment<G>;
+
+  /// KZG keys must come from a powers-of-tau ceremony (see `KZGCommitmentKey::from_powers` and
+  /// `bn256_kzg::load_ptau`), so outside of tests this returns `SpartanError::MissingCommitmentKey`,
+  /// and the key is passed to `setup_precommitted` instead
+  #[cfg(not(test))]
+  fn setup(_label: &'static [u8], _n: usize) -> Result<Self::CommitmentKey, SpartanError> {
+    Err(SpartanError::MissingCommitmentKey)
+  }
+
+  #[cfg(test)]
+  fn setup(_label: &'static [u8], n: usize) -> Result<Self::CommitmentKey, SpartanError> {
+    Ok(KZGCommitmentKey::insecure_setup(n))
+  }
+
+  #[tracing::instrument(skip_all, name = "KZG::commit")]
+  fn commit(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+    PedersenCommitmentEngine::commit(&ck.ck, v)
+  }
+
+  fn commit_small(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+    PedersenCommitmentEngine::commit_small(&ck.ck, v)
+  }
+}
+
+/// Provides an implementation of the HyperKZG prover key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HyperKZGProverKey<G: PairingGroup> {
+  _p: PhantomData<G>,
+}
+
+/// Provides an implementation of the HyperKZG verifier key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HyperKZGVerifierKey<G: PairingGroup> {
+  g: G,
+  h: G::G2,
+  tau_h: G::G2,
+}
+
+/// Provides an evaluation argument for multilinear polynomials committed with KZG.
+/// Its size is logarithmic in the size of the polynomial, and the verifier computes two pairings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HyperKZGEvaluationArgument<G: PairingGroup> {
+  comms: Vec<G::CompressedGroupElement>, // commitments to the polynomials obtained by folding
+  evals: Vec<[G::Scalar; 3]>,            // evaluations of every polynomial at r, -r, and r^2
+  w: Vec<G::CompressedGroupElement>,     // witnesses of the batched openings at r, -r, and r^2
+}
+
+/// Provides an implementation of a polynomial evaluation engine using HyperKZG
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct HyperKZGEvaluationEngine<G: PairingGroup> {
+  _p: PhantomData<G>,
+}
+
+/// Evaluates the univariate polynomial with coefficients `p` at `u`
+fn eval_univariate<F: Field>(p: &[F], u: &F) -> F {
+  p.iter().rev().fold(F::ZERO, |acc, c| acc * u + c)
+}
+
+/// Returns the quotient of the division of the univariate polynomial with coefficients `p` by `X - u`
+pub(crate) fn divide_by_linear<F: Field>(p: &[F], u: &F) -> Vec<F> {
+  let mut q = vec![F::ZERO; p.len() - 1];
+  let mut acc = F::ZERO;
+  for k in (1..p.len()).rev() {
+    acc = acc * u + p[k];
+    q[k - 1] = acc;
+  }
+  q
+}
+
+/// Returns the first `n` powers of `s`, starting from one
+pub(crate) fn powers<F: Field>(s: &F, n: usize) -> Vec<F> {
+  iter::successors(Some(F::ONE), |p| Some(*p * s))
+    .take(n)
+    .collect()
+}
+
+impl<G> EvaluationEngineTrait<G> for HyperKZGEvaluationEngine<G>
+where
+  G: PairingGroup<CE = KZGCommitmentEngine<G>>,
+{
+  type CE = G::CE;
+  type ProverKey = HyperKZGProverKey<G>;
+  type VerifierKey = HyperKZGVerifierKey<G>;
+  type EvaluationArgument = HyperKZGEvaluationArgument<G>;
+
+  fn setup(ck: &CommitmentKey<G>) -> (Self::ProverKey, Self::VerifierKey) {
+    let pk = HyperKZGProverKey { _p: PhantomData };
+    let vk = HyperKZGVerifierKey {
+      g: G::get_generator(),
+      h: ck.h,
+      tau_h: ck.tau_h,
+    };
+
+    (pk, vk)
+  }
+
+  #[tracing::instrument(skip_all, name = "HyperKZGEvaluationEngine::prove")]
+  fn prove<TE: TranscriptEngineTrait<G>>(
+    ck: &CommitmentKey<G>,
+    _pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comm: &Commitment<G>,
+    poly: &[G::Scalar],
+    point: &[G::Scalar],
+    _eval: &G::Scalar,
+  ) -> Result<Self::EvaluationArgument, SpartanError> {
+    transcript.absorb(b"poly_com", comm);
+
+    let ell = point.len();
+    if ell == 0 || poly.len() != 1 << ell {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // fold the polynomial one variable at a time, starting from the last one,
+    // so the last folded polynomial is the constant `eval`
+    let mut polys = vec![poly.to_vec()];
+    for i in 0..ell - 1 {
+      let x = point[ell - 1 - i];
+      let folded = polys[i]
+        .par_chunks(2)
+        .map(|p| p[0] + x * (p[1] - p[0]))
+        .collect::<Vec<_>>();
+      polys.push(folded);
+    }
+
+    let comms = polys[1..]
+      .iter()
+      .map(|p| G::CE::commit(ck, p).to_group().compress())
+      .collect::<Vec<_>>();
+    transcript.absorb(b"c", &comms.as_slice());
+
+    // open every polynomial at r, -r, and r^2
+    let r = transcript.squeeze(b"r")?;
+    let u = [r, -r, r * r];
+    let evals = polys
+      .par_iter()
+      .map(|p| u.map(|u| eval_univariate(p, &u)))
+      .collect::<Vec<_>>();
+    transcript.absorb(b"v", &evals.concat().as_slice());
+
+    // batch the polynomials into a single one with powers of q, and open it at each point
+    let q = transcript.squeeze(b"q")?;
+    let q_powers = powers(&q, ell);
+    let batched = (0..poly.len())
+      .into_par_iter()
+      .map(|k| {
+        polys
+          .iter()
+          .zip(q_powers.iter())
+          .take_while(|(p, _)| k < p.len())
+          .map(|(p, q_i)| p[k] * q_i)
+          .sum::<G::Scalar>()
+      })
+      .collect::<Vec<_>>();
+    let w = u
+      .par_iter()
+      .map(|u| {
+        G::CE::commit(ck, &divide_by_linear(&batched, u))
+          .to_group()
+          .compress()
+      })
+      .collect::<Vec<_>>();
+    transcript.absorb(b"w", &w.as_slice());
+    let _d = transcript.squeeze(b"d")?;
+
+    Ok(HyperKZGEvaluationArgument { comms, evals, w })
+  }
+
+  fn verify<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comm: &Commitment<G>,
+    point: &[G::Scalar],
+    eval: &G::Scalar,
+    arg: &Self::EvaluationArgument,
+  ) -> Result<(), SpartanError> {
+    transcript.absorb(b"poly_com", comm);
+
+    let ell = point.len();
+    if ell == 0 || arg.comms.len() != ell - 1 || arg.evals.len() != ell || arg.w.len() != 3 {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    transcript.absorb(b"c", &arg.comms.as_slice());
+    let r = transcript.squeeze(b"r")?;
+    transcript.absorb(b"v", &arg.evals.concat().as_slice());
+    let q = transcript.squeeze(b"q")?;
+    transcript.absorb(b"w", &arg.w.as_slice());
+    let d = transcript.squeeze(b"d")?;
+
+    // every polynomial is the fold of the previous one, and the last fold is the claimed evaluation
+    let two_r_inv =
+      Option::<G::Scalar>::from((r + r).invert()).ok_or(SpartanError::ProofVerifyError)?;
+    let two_inv = Option::<G::Scalar>::from(G::Scalar::from(2u64).invert()).unwrap();
+    for i in 0..ell {
+      let x = point[ell - 1 - i];
+      let [p_r, p_minus_r, _] = arg.evals[i];
+      let even = (p_r + p_minus_r) * two_inv;
+      let odd = (p_r - p_minus_r) * two_r_inv;
+      let folded = if i + 1 < ell {
+        arg.evals[i + 1][2]
+      } else {
+        *eval
+      };
+      if even + x * (odd - even) != folded {
+        return Err(SpartanError::ProofVerifyError);
+      }
+    }
+
+    let decompress =
+      |c: &G::CompressedGroupElement| c.decompress().ok_or(SpartanError::DecompressionError);
+    let comms = iter::once(Ok(comm.to_group()))
+      .chain(arg.comms.iter().map(decompress))
+      .collect::<Result<Vec<_>, _>>()?;
+    let w = arg
+      .w
+      .iter()
+      .map(decompress)
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // the batched polynomial B = sum_i q^i P_i opens at u to B(u) = sum_i q^i P_i(u),
+    // that is, B(X) - B(u) = W(X) * (X - u) for the committed witness W
+    let q_powers = powers(&q, ell);
+    let comm_B = comms
+      .iter()
+      .zip(q_powers.iter())
+      .fold(G::zero(), |acc, (c, q_i)| acc + *c * q_i);
+
+    let u = [r, -r, r * r];
+    let d_powers = powers(&d, 3);
+    let (L, R) = (0..3).fold((G::zero(), G::zero()), |(L, R), j| {
+      let B_u = arg
+        .evals
+        .iter()
+        .zip(q_powers.iter())
+        .map(|(e, q_i)| e[j] * q_i)
+        .sum::<G::Scalar>();
+      (
+        L + (comm_B - vk.g * B_u + w[j] * u[j]) * d_powers[j],
+        R + w[j] * d_powers[j],
+      )
+    });
+
+    // e(L, h) = e(R, tau * h)
+    if !G::pairing_check(&[(L, vk.h), (G::zero() - R, vk.tau_h)]) {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{bn256_kzg, keccak::Keccak256Transcript},
+    spartan::polys::multilinear::MultilinearPolynomial,
+    traits::Group,
+  };
+
+  type G = bn256_kzg::Point;
+  type EE = HyperKZGEvaluationEngine<G>;
+
+  #[test]
+  fn test_hyperkzg_eval() {
+    for ell in 1..6 {
+      let n = 1 << ell;
+      let poly = (0..n)
+        .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+        .collect::<Vec<_>>();
+      let point = (0..ell)
+        .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+        .collect::<Vec<_>>();
+      let eval = MultilinearPolynomial::new(poly.clone()).evaluate(&point);
+
+      let ck = <G as Group>::CE::setup(b"test", n).unwrap();
+      let comm = <G as Group>::CE::commit(&ck, &poly);
+      let (pk, vk) = EE::setup(&ck);
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let arg = EE::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval).unwrap();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).is_ok());
+
+      // a wrong evaluation is rejected
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let wrong_eval = eval + <G as Group>::Scalar::ONE;
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &wrong_eval, &arg).is_err());
+
+      // so is an argument for another commitment
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let other_comm = comm + <G as Group>::CE::commit(&ck, &[<G as Group>::Scalar::ONE]);
+      assert!(EE::verify(&vk, &mut transcript, &other_comm, &point, &eval, &arg).is_err());
+    }
+  }
+}
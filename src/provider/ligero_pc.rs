@@ -0,0 +1,799 @@
+//! This module implements a hash-based polynomial commitment scheme in the style of Ligero.
+//! The evaluations of a multilinear polynomial are laid out as a matrix, every row is encoded with
+//! a Reed-Solomon code of rate 1/4, and a commitment is the root of a Keccak256 Merkle tree whose
+//! leaves are the columns of the encoded matrix.
+//! An evaluation argument sends a random combination of the rows, which shows that the committed
+//! rows are close to codewords, and the combination of the rows with the row variables of the point,
+//! and opens columns of the encoded matrix against which the verifier checks the encodings of both.
+//!
+//! The scheme is transparent and only relies on a hash function, but its commitments are not
+//! homomorphic, so batches of claims are opened side by side instead of being combined.
+use crate::{
+  errors::SpartanError,
+  spartan::{self, math::Math, polys::eq::EqPolynomial, sumcheck::SumcheckProof},
+  traits::{
+    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    evaluation::{BatchEvaluationArgument, EvaluationEngineTrait},
+    Group, TranscriptEngineTrait, TranscriptReprTrait,
+  },
+  Commitment,
+};
+use core::cmp::min;
+use ff::{Field, PrimeField};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::{marker::PhantomData, sync::Arc};
+
+/// The logarithm of the inverse of the rate of the Reed-Solomon code
+const LOG_BLOWUP: usize = 2;
+
+/// The number of columns opened by an evaluation argument. With a rate of 1/4, a prover that
+/// committed to rows far from the code passes all the queries with probability below 2^-100
+const NUM_QUERIES: usize = 256;
+
+/// A Keccak256 digest
+type Hash = [u8; 32];
+
+/// A type that holds the parameters of Ligero commitments, which need no generators
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LigeroCommitmentKey {
+  num_vars_right: usize, // number of variables indexing the columns of committed matrices
+}
+
+impl LigeroCommitmentKey {
+  /// Returns the numbers of variables indexing the rows and the columns of the matrix
+  /// in which a polynomial in `ell` variables is committed
+  pub fn factored_lens(&self, ell: usize) -> (usize, usize) {
+    let right_num_vars = min(ell, self.num_vars_right);
+    (ell - right_num_vars, right_num_vars)
+  }
+}
+
+/// Structure that holds commitments, which are Merkle roots of encoded matrices.
+/// The default commitment has no root and commits to the zero polynomial.
+/// A commitment produced by `commit` also keeps the encoded matrix, so that the prover does not
+/// encode it again to open it; the matrix is neither serialized nor compared
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LigeroCommitment<G: Group> {
+  root: Option<Hash>,
+  #[serde(skip)]
+  matrix: Option<Arc<EncodedMatrix<G::Scalar>>>,
+}
+
+/// A compressed commitment, which only holds the Merkle root
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LigeroCompressedCommitment {
+  root: Option<Hash>,
+}
+
+impl<G: Group> Default for LigeroCommitment<G> {
+  fn default() -> Self {
+    LigeroCommitment {
+      root: None,
+      matrix: None,
+    }
+  }
+}
+
+impl<G: Group> PartialEq for LigeroCommitment<G> {
+  fn eq(&self, other: &Self) -> bool {
+    self.root == other.root
+  }
+}
+
+impl<G: Group> Eq for LigeroCommitment<G> {}
+
+impl<G: Group> CommitmentTrait<G> for LigeroCommitment<G> {
+  type CompressedCommitment = LigeroCompressedCommitment;
+
+  fn compress(&self) -> Self::CompressedCommitment {
+    LigeroCompressedCommitment { root: self.root }
+  }
+
+  fn decompress(c: &Self::CompressedCommitment) -> Result<Self, SpartanError> {
+    Ok(LigeroCommitment {
+      root: c.root,
+      matrix: None,
+    })
+  }
+
+  fn combine(_comms: &[Self], _coeffs: &[G::Scalar]) -> Result<Self, SpartanError> {
+    Err(SpartanError::NonHomomorphicCommitment)
+  }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for LigeroCommitment<G> {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    <LigeroCompressedCommitment as TranscriptReprTrait<G>>::to_transcript_bytes(&self.compress())
+  }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for LigeroCompressedCommitment {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.append(&mut b"poly_commitment_begin".to_vec());
+
+    if let Some(root) = &self.root {
+      v.extend_from_slice(root);
+    }
+
+    v.append(&mut b"poly_commitment_end".to_vec());
+    v
+  }
+}
+
+/// Provides a commitment engine
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LigeroCommitmentEngine<G: Group> {
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> CommitmentEngineTrait<G> for LigeroCommitmentEngine<G> {
+  type CommitmentKey = LigeroCommitmentKey;
+  type Commitment = LigeroCommitment<G>;
+
+  /// Picks the split of the variables for a polynomial in `log n` variables, so that the
+  /// columns opened by an evaluation argument are about as large as the rows it sends
+  fn setup(_label: &'static [u8], n: usize) -> Result<Self::CommitmentKey, SpartanError> {
+    let num_vars = n.next_power_of_two().log_2();
+    let num_vars_right = min(num_vars, (num_vars + NUM_QUERIES.log_2()).div_ceil(2));
+    Ok(Self::setup_with_split(num_vars_right))
+  }
+
+  fn commit(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+    let ell = v.len().log_2();
+    assert_eq!(v.len(), (2usize).pow(ell as u32));
+
+    let (_left_num_vars, right_num_vars) = ck.factored_lens(ell);
+    let matrix = EncodedMatrix::new(v, (2usize).pow(right_num_vars as u32));
+
+    LigeroCommitment {
+      root: Some(matrix.tree.root()),
+      matrix: Some(Arc::new(matrix)),
+    }
+  }
+}
+
+impl<G: Group> LigeroCommitmentEngine<G> {
+  /// Returns parameters for rows of `2^num_vars_right` columns, so a polynomial in `ell` variables
+  /// is committed as a matrix with `2^(ell - num_vars_right)` rows.
+  /// Wider rows shrink the opened columns at the cost of larger combinations of rows in arguments
+  pub fn setup_with_split(num_vars_right: usize) -> LigeroCommitmentKey {
+    LigeroCommitmentKey { num_vars_right }
+  }
+}
+
+/// Computes in place the evaluations of the polynomial with coefficients `a` over the subgroup
+/// of `a.len()`-th roots of unity, where `a.len()` is a power of two
+fn fft<F: PrimeField>(a: &mut [F]) {
+  let n = a.len();
+  if n == 1 {
+    return;
+  }
+  let log_n = n.log_2();
+  assert!(log_n as u32 <= F::S);
+
+  for i in 0..n {
+    let j = i.reverse_bits() >> (usize::BITS - log_n as u32);
+    if i < j {
+      a.swap(i, j);
+    }
+  }
+
+  // the roots of unity of order 2, 4, ..., n
+  let mut omega = F::ROOT_OF_UNITY;
+  for _ in log_n..F::S as usize {
+    omega = omega.square();
+  }
+  let mut omegas = vec![omega];
+  for _ in 1..log_n {
+    let omega = omegas.last().unwrap().square();
+    omegas.push(omega);
+  }
+  omegas.reverse();
+
+  let mut half = 1;
+  for omega in omegas {
+    let twiddles = (0..half)
+      .scan(F::ONE, |w, _| {
+        let t = *w;
+        *w *= omega;
+        Some(t)
+      })
+      .collect::<Vec<_>>();
+    for chunk in a.chunks_mut(2 * half) {
+      let (lo, hi) = chunk.split_at_mut(half);
+      for ((u, v), w) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles.iter()) {
+        let t = *v * w;
+        *v = *u - t;
+        *u += t;
+      }
+    }
+    half *= 2;
+  }
+}
+
+/// Encodes a row, read as the coefficients of a univariate polynomial, into its evaluations
+/// over the roots of unity of order `row.len() << LOG_BLOWUP`
+fn encode<F: PrimeField>(row: &[F]) -> Vec<F> {
+  let mut codeword = row.to_vec();
+  codeword.resize(row.len() << LOG_BLOWUP, F::ZERO);
+  fft(&mut codeword);
+  codeword
+}
+
+/// Returns the combination of the rows of the matrix with `num_cols` columns laid out in `v`
+fn combine_rows<F: PrimeField>(v: &[F], num_cols: usize, coeffs: &[F]) -> Vec<F> {
+  (0..num_cols)
+    .into_par_iter()
+    .map(|j| {
+      coeffs
+        .iter()
+        .enumerate()
+        .map(|(i, c)| *c * v[i * num_cols + j])
+        .sum()
+    })
+    .collect()
+}
+
+fn hash_column<'a, F: PrimeField>(column: impl Iterator<Item = &'a F>) -> Hash {
+  let mut hasher = Keccak256::new();
+  hasher.update(b"ligero_column");
+  for x in column {
+    hasher.update(x.to_repr());
+  }
+  hasher.finalize().into()
+}
+
+fn hash_nodes(left: &Hash, right: &Hash) -> Hash {
+  let mut hasher = Keccak256::new();
+  hasher.update(b"ligero_node");
+  hasher.update(left);
+  hasher.update(right);
+  hasher.finalize().into()
+}
+
+/// A Merkle tree, stored level by level from the leaves to the root
+#[derive(Debug)]
+struct MerkleTree {
+  levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+  fn new(leaves: Vec<Hash>) -> Self {
+    assert!(leaves.len().is_power_of_two());
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+      let level = levels
+        .last()
+        .unwrap()
+        .par_chunks(2)
+        .map(|pair| hash_nodes(&pair[0], &pair[1]))
+        .collect();
+      levels.push(level);
+    }
+    MerkleTree { levels }
+  }
+
+  fn root(&self) -> Hash {
+    self.levels.last().unwrap()[0]
+  }
+
+  /// Returns the siblings of the nodes on the path from the leaf at `index` to the root
+  fn path(&self, index: usize) -> Vec<Hash> {
+    self.levels[..self.levels.len() - 1]
+      .iter()
+      .enumerate()
+      .map(|(i, level)| level[(index >> i) ^ 1])
+      .collect()
+  }
+
+  fn verify_path(root: &Hash, leaf: Hash, index: usize, path: &[Hash]) -> bool {
+    let node = path.iter().enumerate().fold(leaf, |node, (i, sibling)| {
+      if (index >> i) & 1 == 0 {
+        hash_nodes(&node, sibling)
+      } else {
+        hash_nodes(sibling, &node)
+      }
+    });
+    node == *root
+  }
+}
+
+/// The encodings of the rows of a matrix, with the Merkle tree over their columns
+#[derive(Debug)]
+struct EncodedMatrix<F: PrimeField> {
+  rows: Vec<Vec<F>>,
+  tree: MerkleTree,
+}
+
+impl<F: PrimeField> EncodedMatrix<F> {
+  fn new(v: &[F], num_cols: usize) -> Self {
+    let rows = v.par_chunks(num_cols).map(encode).collect::<Vec<_>>();
+    let leaves = (0..num_cols << LOG_BLOWUP)
+      .into_par_iter()
+      .map(|j| hash_column(rows.iter().map(|row| &row[j])))
+      .collect();
+    EncodedMatrix {
+      rows,
+      tree: MerkleTree::new(leaves),
+    }
+  }
+
+  fn column(&self, j: usize) -> Vec<F> {
+    self.rows.iter().map(|row| row[j]).collect()
+  }
+}
+
+/// Derives the indices of the opened columns, which are reduced modulo the width of each encoded matrix
+fn query_indices<G: Group, TE: TranscriptEngineTrait<G>>(
+  transcript: &mut TE,
+) -> Result<Vec<usize>, SpartanError> {
+  let seed = transcript.squeeze(b"q")?;
+  Ok(
+    (0..NUM_QUERIES as u64)
+      .map(|i| {
+        let mut hasher = Keccak256::new();
+        hasher.update(b"ligero_query");
+        hasher.update(seed.to_repr());
+        hasher.update(i.to_le_bytes());
+        let digest = hasher.finalize();
+        u64::from_le_bytes(digest[..8].try_into().unwrap()) as usize
+      })
+      .collect(),
+  )
+}
+
+/// Provides an implementation of the prover key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LigeroProverKey<G: Group> {
+  _p: PhantomData<G>,
+}
+
+/// Provides an implementation of the verifier key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LigeroVerifierKey<G: Group> {
+  ck: LigeroCommitmentKey,
+  _p: PhantomData<G>,
+}
+
+/// Opens a committed matrix at the row variables of a point
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct LigeroOpening<G: Group> {
+  u_test: Vec<G::Scalar>, // combination of the rows with powers of a challenge
+  u_eval: Vec<G::Scalar>, // combination of the rows with the evaluations of eq at the row variables
+  columns: Vec<Vec<G::Scalar>>, // opened columns of the encoded matrix
+  paths: Vec<Vec<Hash>>,  // Merkle paths of the opened columns
+}
+
+/// Provides an implementation of a polynomial evaluation argument, with an opening for every
+/// claim whose commitment is not the default one
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LigeroEvaluationArgument<G: Group> {
+  openings: Vec<LigeroOpening<G>>,
+}
+
+/// Provides an implementation of a polynomial evaluation engine using Ligero PC
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LigeroEvaluationEngine<G: Group> {
+  _p: PhantomData<G>,
+}
+
+impl<G> EvaluationEngineTrait<G> for LigeroEvaluationEngine<G>
+where
+  G: Group<CE = LigeroCommitmentEngine<G>>,
+{
+  type CE = G::CE;
+  type ProverKey = LigeroProverKey<G>;
+  type VerifierKey = LigeroVerifierKey<G>;
+  type EvaluationArgument = LigeroEvaluationArgument<G>;
+
+  fn setup(ck: &LigeroCommitmentKey) -> (Self::ProverKey, Self::VerifierKey) {
+    let pk = LigeroProverKey { _p: PhantomData };
+    let vk = LigeroVerifierKey {
+      ck: ck.clone(),
+      _p: PhantomData,
+    };
+    (pk, vk)
+  }
+
+  #[tracing::instrument(skip_all, name = "LigeroEvaluationEngine::prove")]
+  fn prove<TE: TranscriptEngineTrait<G>>(
+    ck: &LigeroCommitmentKey,
+    _pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comm: &Commitment<G>,
+    poly: &[G::Scalar],
+    point: &[G::Scalar],
+    _eval: &G::Scalar,
+  ) -> Result<Self::EvaluationArgument, SpartanError> {
+    Self::prove_openings(
+      ck,
+      transcript,
+      core::slice::from_ref(comm),
+      &[poly],
+      &[point],
+    )
+  }
+
+  fn verify<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comm: &Commitment<G>,
+    point: &[G::Scalar],
+    eval: &G::Scalar,
+    arg: &Self::EvaluationArgument,
+  ) -> Result<(), SpartanError> {
+    Self::verify_openings(
+      vk,
+      transcript,
+      core::slice::from_ref(comm),
+      &[point],
+      &[*eval],
+      arg,
+    )
+  }
+
+  /// Opens every claim at its own point, so no sum-check is needed to reduce them to a common point.
+  /// The openings share their challenges and the indices of the opened columns
+  #[tracing::instrument(skip_all, name = "LigeroEvaluationEngine::prove_batch")]
+  fn prove_batch<TE: TranscriptEngineTrait<G>>(
+    ck: &LigeroCommitmentKey,
+    _pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    polys: &[Vec<G::Scalar>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+  ) -> Result<BatchEvaluationArgument<G, Self>, SpartanError> {
+    if evals.len() != comms.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    let polys = polys.iter().map(|p| p.as_slice()).collect::<Vec<_>>();
+    let points = points.iter().map(|x| x.as_slice()).collect::<Vec<_>>();
+    let arg = Self::prove_openings(ck, transcript, comms, &polys, &points)?;
+
+    Ok(BatchEvaluationArgument {
+      sc_proof: SumcheckProof::new(Vec::new()),
+      evals: evals.to_vec(),
+      arg,
+    })
+  }
+
+  fn verify_batch<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+    arg: &BatchEvaluationArgument<G, Self>,
+  ) -> Result<(), SpartanError> {
+    // the argument holds no reduction, so its sum-check has no rounds
+    arg.sc_proof.verify(G::Scalar::ZERO, 0, 2, transcript)?;
+    if arg.evals != evals {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    let points = points.iter().map(|x| x.as_slice()).collect::<Vec<_>>();
+    Self::verify_openings(vk, transcript, comms, &points, evals, &arg.arg)
+  }
+}
+
+impl<G> LigeroEvaluationEngine<G>
+where
+  G: Group<CE = LigeroCommitmentEngine<G>>,
+{
+  /// Opens the matrices committed in `comms` at the row variables of the respective points.
+  /// The prover reuses the encoded matrices kept in the commitments, and only encodes again
+  /// the matrices of commitments that were decompressed or deserialized
+  fn prove_openings<TE: TranscriptEngineTrait<G>>(
+    ck: &LigeroCommitmentKey,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    polys: &[&[G::Scalar]],
+    points: &[&[G::Scalar]],
+  ) -> Result<LigeroEvaluationArgument<G>, SpartanError> {
+    let num_claims = comms.len();
+    if num_claims == 0
+      || polys.len() != num_claims
+      || points.len() != num_claims
+      || polys
+        .iter()
+        .zip(points.iter())
+        .any(|(p, x)| p.len() != (2usize).pow(x.len() as u32))
+    {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    transcript.absorb(b"poly_com", &comms);
+
+    // the default commitment commits to the zero polynomial and needs no opening
+    let claims = comms
+      .iter()
+      .zip(polys.iter())
+      .zip(points.iter())
+      .filter(|((comm, _poly), _point)| comm.root.is_some())
+      .map(|((comm, poly), point)| (comm, *poly, *point))
+      .collect::<Vec<_>>();
+
+    let matrices = claims
+      .iter()
+      .map(|(comm, poly, point)| {
+        let (left_num_vars, right_num_vars) = ck.factored_lens(point.len());
+        let num_cols = (2usize).pow(right_num_vars as u32);
+        let matrix = match &comm.matrix {
+          Some(matrix) if matrix.tree.levels[0].len() == num_cols << LOG_BLOWUP => matrix.clone(),
+          _ => Arc::new(EncodedMatrix::new(poly, num_cols)),
+        };
+        (left_num_vars, num_cols, matrix)
+      })
+      .collect::<Vec<_>>();
+
+    let r = transcript.squeeze(b"r")?;
+    let mut openings = claims
+      .iter()
+      .zip(matrices.iter())
+      .map(|((_, poly, point), (left_num_vars, num_cols, _matrix))| {
+        let powers_of_r = spartan::powers::<G>(&r, (2usize).pow(*left_num_vars as u32));
+        let L = EqPolynomial::new(point[..*left_num_vars].to_vec()).evals();
+        let u_test = combine_rows(poly, *num_cols, &powers_of_r);
+        let u_eval = combine_rows(poly, *num_cols, &L);
+        transcript.absorb(b"u_test", &u_test.as_slice());
+        transcript.absorb(b"u_eval", &u_eval.as_slice());
+        LigeroOpening {
+          u_test,
+          u_eval,
+          columns: Vec::new(),
+          paths: Vec::new(),
+        }
+      })
+      .collect::<Vec<_>>();
+
+    let indices = query_indices::<G, _>(transcript)?;
+    for (opening, (_left_num_vars, num_cols, matrix)) in openings.iter_mut().zip(matrices.iter()) {
+      let num_encoded_cols = num_cols << LOG_BLOWUP;
+      (opening.columns, opening.paths) = indices
+        .iter()
+        .map(|i| {
+          let j = i % num_encoded_cols;
+          (matrix.column(j), matrix.tree.path(j))
+        })
+        .unzip();
+    }
+
+    Ok(LigeroEvaluationArgument { openings })
+  }
+
+  fn verify_openings<TE: TranscriptEngineTrait<G>>(
+    vk: &LigeroVerifierKey<G>,
+    transcript: &mut TE,
+    comms: &[Commitment<G>],
+    points: &[&[G::Scalar]],
+    evals: &[G::Scalar],
+    arg: &LigeroEvaluationArgument<G>,
+  ) -> Result<(), SpartanError> {
+    let num_claims = comms.len();
+    if num_claims == 0 || points.len() != num_claims || evals.len() != num_claims {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    transcript.absorb(b"poly_com", &comms);
+
+    let claims = comms
+      .iter()
+      .zip(points.iter())
+      .zip(evals.iter())
+      .filter_map(|((comm, point), eval)| match &comm.root {
+        Some(root) => Some(Ok((root, *point, *eval))),
+        None if *eval == G::Scalar::ZERO => None,
+        None => Some(Err(SpartanError::ProofVerifyError)),
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    if claims.len() != arg.openings.len() {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    let r = transcript.squeeze(b"r")?;
+    for opening in &arg.openings {
+      transcript.absorb(b"u_test", &opening.u_test.as_slice());
+      transcript.absorb(b"u_eval", &opening.u_eval.as_slice());
+    }
+    let indices = query_indices::<G, _>(transcript)?;
+
+    claims
+      .par_iter()
+      .zip(arg.openings.par_iter())
+      .map(|((root, point, eval), opening)| {
+        let (left_num_vars, right_num_vars) = vk.ck.factored_lens(point.len());
+        let num_rows = (2usize).pow(left_num_vars as u32);
+        let num_cols = (2usize).pow(right_num_vars as u32);
+        let num_encoded_cols = num_cols << LOG_BLOWUP;
+        if opening.u_test.len() != num_cols
+          || opening.u_eval.len() != num_cols
+          || opening.columns.len() != NUM_QUERIES
+          || opening.paths.len() != NUM_QUERIES
+        {
+          return Err(SpartanError::ProofVerifyError);
+        }
+
+        // the combination with the row variables determines the evaluation
+        let L = EqPolynomial::new(point[..left_num_vars].to_vec()).evals();
+        let R = EqPolynomial::new(point[left_num_vars..].to_vec()).evals();
+        let eval_u = opening
+          .u_eval
+          .iter()
+          .zip(R.iter())
+          .map(|(u, r_j)| *u * r_j)
+          .sum::<G::Scalar>();
+        if eval_u != *eval {
+          return Err(SpartanError::ProofVerifyError);
+        }
+
+        // both combinations must agree with the opened columns of the encoded matrix
+        let powers_of_r = spartan::powers::<G>(&r, num_rows);
+        let codeword_test = encode(&opening.u_test);
+        let codeword_eval = encode(&opening.u_eval);
+        let inner_product = |a: &[G::Scalar], b: &[G::Scalar]| {
+          a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| *x * y)
+            .sum::<G::Scalar>()
+        };
+        for ((i, column), path) in indices
+          .iter()
+          .zip(opening.columns.iter())
+          .zip(opening.paths.iter())
+        {
+          let j = i % num_encoded_cols;
+          if column.len() != num_rows
+            || path.len() != num_encoded_cols.log_2()
+            || !MerkleTree::verify_path(root, hash_column(column.iter()), j, path)
+            || inner_product(&powers_of_r, column) != codeword_test[j]
+            || inner_product(&L, column) != codeword_eval[j]
+          {
+            return Err(SpartanError::ProofVerifyError);
+          }
+        }
+        Ok(())
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::keccak::Keccak256Transcript, spartan::polys::multilinear::MultilinearPolynomial,
+  };
+  use rand_core::OsRng;
+
+  type G = crate::provider::bn256_ligero::Engine;
+  type EE = LigeroEvaluationEngine<G>;
+
+  #[test]
+  fn test_fft() {
+    // evaluations of the polynomial 1 + 2X + 3X^2 + 4X^3 at the roots of unity of order 16
+    let coeffs = [1u64, 2, 3, 4]
+      .iter()
+      .map(|c| <G as Group>::Scalar::from(*c))
+      .collect::<Vec<_>>();
+    let codeword = encode(&coeffs);
+    let omega =
+      (0..<G as Group>::Scalar::S - 4).fold(<G as Group>::Scalar::ROOT_OF_UNITY, |w, _| w.square());
+    for (i, e) in codeword.iter().enumerate() {
+      let x = omega.pow_vartime([i as u64]);
+      let expected = coeffs
+        .iter()
+        .rev()
+        .fold(<G as Group>::Scalar::ZERO, |acc, c| acc * x + c);
+      assert_eq!(*e, expected);
+    }
+  }
+
+  #[test]
+  fn test_ligero_eval() {
+    let ell = 7;
+    let poly = (0..1 << ell)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let point = (0..ell)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let eval = MultilinearPolynomial::new(poly.clone()).evaluate(&point);
+
+    // from a single column to a single row
+    for num_vars_right in 0..=ell {
+      let ck = LigeroCommitmentEngine::<G>::setup_with_split(num_vars_right);
+      let comm = <G as Group>::CE::commit(&ck, &poly);
+      let (pk, vk) = EE::setup(&ck);
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let arg = EE::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval).unwrap();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).is_ok());
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let wrong_eval = eval + <G as Group>::Scalar::ONE;
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &wrong_eval, &arg).is_err());
+
+      // a decompressed commitment holds no encoded matrix, so the prover encodes it again
+      let comm = <Commitment<G> as CommitmentTrait<G>>::decompress(&comm.compress()).unwrap();
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let arg_encoded = EE::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval).unwrap();
+      assert_eq!(
+        bincode::serialize(&arg).unwrap(),
+        bincode::serialize(&arg_encoded).unwrap()
+      );
+    }
+  }
+
+  #[test]
+  fn test_ligero_batch_eval() {
+    // the default commitment, and polynomials of different sizes
+    let ells = [6, 3, 0, 6];
+    let mut polys = ells
+      .iter()
+      .map(|ell| {
+        (0..1 << ell)
+          .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    polys[2] = vec![<G as Group>::Scalar::ZERO];
+    let points = ells
+      .iter()
+      .map(|ell| {
+        (0..*ell)
+          .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+
+    let ck = <G as Group>::CE::setup(b"test", 1 << 6).unwrap();
+    let mut comms = polys
+      .iter()
+      .map(|p| <G as Group>::CE::commit(&ck, p))
+      .collect::<Vec<_>>();
+    comms[2] = Commitment::<G>::default();
+
+    let evals = polys
+      .iter()
+      .zip(points.iter())
+      .map(|(p, x)| MultilinearPolynomial::new(p.clone()).evaluate(x))
+      .collect::<Vec<_>>();
+    let (pk, vk) = EE::setup(&ck);
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let arg = EE::prove_batch(&ck, &pk, &mut transcript, &comms, &polys, &points, &evals).unwrap();
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(EE::verify_batch(&vk, &mut transcript, &comms, &points, &evals, &arg).is_ok());
+
+    // a wrong evaluation is rejected, including a nonzero evaluation of the zero polynomial
+    for i in 0..ells.len() {
+      let mut wrong_evals = evals.clone();
+      wrong_evals[i] += <G as Group>::Scalar::ONE;
+      let mut wrong_arg = arg.clone();
+      wrong_arg.evals = wrong_evals.clone();
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      assert!(EE::verify_batch(
+        &vk,
+        &mut transcript,
+        &comms,
+        &points,
+        &wrong_evals,
+        &wrong_arg
+      )
+      .is_err());
+    }
+  }
+}
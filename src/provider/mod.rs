@@ -1,16 +1,25 @@
 //! This module implements Spartan's traits using the following configuration:
-//! `CommitmentEngine` with Pedersen's commitments
-//! `Group` with pasta curves and BN256/Grumpkin
-//! `RO` traits with Poseidon
-//! `EvaluationEngine` with an IPA-based polynomial evaluation argument
+//! `CommitmentEngine` with Pedersen's commitments, KZG commitments, and hash-based Ligero commitments
+//! `Group` with pasta curves, BN256/Grumpkin, BLS12-381, and Ristretto
+//! `TranscriptEngine` with Keccak256 and Poseidon, and a recording wrapper for debugging
+//! `EvaluationEngine` with an IPA-based polynomial evaluation argument, HyperKZG, Zeromorph, and Ligero
 
+pub mod bls12_381;
 pub mod bn256_grumpkin;
+pub mod bn256_kzg;
+pub mod bn256_ligero;
 pub mod hyrax_pc;
 pub mod ipa_pc;
 pub mod keccak;
+pub mod kzg_pc;
+pub mod ligero_pc;
 pub mod pasta;
 pub mod pedersen;
+pub mod poseidon;
+pub mod recording;
+pub mod ristretto;
 pub mod secp_secq;
+pub mod zeromorph_pc;
 
 /// Curve ops
 /// This implementation behaves in ways specific to the halo2curves suite of curves in:
@@ -26,27 +35,49 @@ macro_rules! impl_traits {
     $name_curve_affine:ident,
     $order_str:literal
   ) => {
+    #[doc = concat!("A wrapper for compressed group elements of ", stringify!($name))]
+    #[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+    pub struct $name_compressed {
+      repr: <$name_curve as GroupEncoding>::Repr,
+    }
+
+    impl PartialEq for $name_compressed {
+      fn eq(&self, other: &Self) -> bool {
+        self.repr.as_ref() == other.repr.as_ref()
+      }
+    }
+
+    impl Eq for $name_compressed {}
+
     impl Group for $name::Point {
-      type Base = $name::Base;
       type Scalar = $name::Scalar;
+      type CE = HyraxCommitmentEngine<Self>;
+    }
+
+    impl PrimeGroup for $name::Point {
+      type Base = $name::Base;
       type CompressedGroupElement = $name_compressed;
       type PreprocessedGroupElement = $name::Affine;
-      type TE = Keccak256Transcript<Self>;
-      type CE = HyraxCommitmentEngine<Self>;
 
       fn vartime_multiscalar_mul(
         scalars: &[Self::Scalar],
         bases: &[Self::PreprocessedGroupElement],
       ) -> Self {
-        best_multiexp(scalars, bases)
+        msm_best(scalars, bases)
       }
 
       fn preprocessed(&self) -> Self::PreprocessedGroupElement {
         self.to_affine()
       }
 
+      fn from_preprocessed(p: &Self::PreprocessedGroupElement) -> Self {
+        (*p).into()
+      }
+
       fn compress(&self) -> Self::CompressedGroupElement {
-        self.to_bytes()
+        $name_compressed {
+          repr: self.to_bytes(),
+        }
       }
 
       fn from_label(label: &'static [u8], n: usize) -> Vec<Self::PreprocessedGroupElement> {
@@ -103,16 +134,16 @@ macro_rules! impl_traits {
         {
           (*coordinates.unwrap().x(), *coordinates.unwrap().y(), false)
         } else {
-          (Self::Base::zero(), Self::Base::zero(), true)
+          (Self::Base::ZERO, Self::Base::ZERO, true)
         }
       }
 
-      fn get_curve_params() -> (Self::Base, Self::Base, BigInt) {
+      fn get_curve_params() -> Option<(Self::Base, Self::Base, BigInt)> {
         let A = $name::Point::a();
         let B = $name::Point::b();
         let order = BigInt::from_str_radix($order_str, 16).unwrap();
 
-        (A, B, order)
+        Some((A, B, order))
       }
 
       fn zero() -> Self {
@@ -133,7 +164,7 @@ macro_rules! impl_traits {
 
     impl<G: Group> TranscriptReprTrait<G> for $name_compressed {
       fn to_transcript_bytes(&self) -> Vec<u8> {
-        self.as_ref().to_vec()
+        self.repr.as_ref().to_vec()
       }
     }
 
@@ -141,8 +172,170 @@ macro_rules! impl_traits {
       type GroupElement = $name::Point;
 
       fn decompress(&self) -> Option<$name::Point> {
-        Some($name_curve::from_bytes(&self).unwrap())
+        Some($name_curve::from_bytes(&self.repr).unwrap())
+      }
+    }
+  };
+}
+
+/// Defines `Point`, a wrapper of `bn256::Point` whose commitment engine is `$ce`, and `Compressed`,
+/// its compressed form, in the calling module, which provides the `Base`, `Scalar`, and `Affine` aliases.
+/// The wrapper shares the scalar field, the base field, and the generators of `bn256::Point`.
+macro_rules! impl_wrapped_bn256_point {
+  ($ce:ident, $doc:literal) => {
+    #[doc = $doc]
+    #[derive(
+      Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+    )]
+    #[serde(transparent)]
+    pub struct Point($crate::provider::bn256_grumpkin::bn256::Point);
+
+    /// A compressed point of the first source group of BN256
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(transparent)]
+    pub struct Compressed($crate::provider::bn256_grumpkin::bn256::Compressed);
+
+    impl From<$crate::provider::bn256_grumpkin::bn256::Point> for Point {
+      fn from(p: $crate::provider::bn256_grumpkin::bn256::Point) -> Self {
+        Point(p)
+      }
+    }
+
+    impl From<Point> for $crate::provider::bn256_grumpkin::bn256::Point {
+      fn from(p: Point) -> Self {
+        p.0
+      }
+    }
+
+    $crate::provider::impl_wrapped_point_ops!();
+
+    impl $crate::traits::Group for Point {
+      type Scalar = Scalar;
+      type CE = $ce<Self>;
+    }
+
+    impl $crate::traits::PrimeGroup for Point {
+      type Base = Base;
+      type CompressedGroupElement = Compressed;
+      type PreprocessedGroupElement = Affine;
+
+      fn vartime_multiscalar_mul(
+        scalars: &[Self::Scalar],
+        bases: &[Self::PreprocessedGroupElement],
+      ) -> Self {
+        Point(<$crate::provider::bn256_grumpkin::bn256::Point as $crate::traits::PrimeGroup>::vartime_multiscalar_mul(scalars, bases))
+      }
+
+      fn compress(&self) -> Self::CompressedGroupElement {
+        Compressed($crate::traits::PrimeGroup::compress(&self.0))
+      }
+
+      fn preprocessed(&self) -> Self::PreprocessedGroupElement {
+        $crate::traits::PrimeGroup::preprocessed(&self.0)
+      }
+
+      fn from_preprocessed(p: &Self::PreprocessedGroupElement) -> Self {
+        Point($crate::traits::PrimeGroup::from_preprocessed(p))
+      }
+
+      fn from_label(label: &'static [u8], n: usize) -> Vec<Self::PreprocessedGroupElement> {
+        <$crate::provider::bn256_grumpkin::bn256::Point as $crate::traits::PrimeGroup>::from_label(label, n)
+      }
+
+      fn to_coordinates(&self) -> (Self::Base, Self::Base, bool) {
+        $crate::traits::PrimeGroup::to_coordinates(&self.0)
+      }
+
+      fn zero() -> Self {
+        Point(<$crate::provider::bn256_grumpkin::bn256::Point as $crate::traits::PrimeGroup>::zero())
+      }
+
+      fn get_generator() -> Self {
+        Point(<$crate::provider::bn256_grumpkin::bn256::Point as $crate::traits::PrimeGroup>::get_generator())
+      }
+
+      fn get_curve_params() -> Option<(Self::Base, Self::Base, num_bigint::BigInt)> {
+        <$crate::provider::bn256_grumpkin::bn256::Point as $crate::traits::PrimeGroup>::get_curve_params()
+      }
+    }
+
+    impl<G: $crate::traits::Group> $crate::traits::TranscriptReprTrait<G> for Compressed {
+      fn to_transcript_bytes(&self) -> Vec<u8> {
+        $crate::traits::TranscriptReprTrait::<G>::to_transcript_bytes(&self.0)
+      }
+    }
+
+    impl $crate::traits::CompressedGroup for Compressed {
+      type GroupElement = Point;
+
+      fn decompress(&self) -> Option<Point> {
+        $crate::traits::CompressedGroup::decompress(&self.0).map(Point)
       }
     }
   };
 }
+
+/// Implements the group operations of `Point`, a wrapper of another group element in the calling
+/// module, and its multiplication by `Scalar`, by forwarding them to the wrapped element
+macro_rules! impl_wrapped_point_ops {
+  () => {
+    $crate::provider::impl_wrapped_point_ops!(@ops Add, add, AddAssign, add_assign);
+    $crate::provider::impl_wrapped_point_ops!(@ops Sub, sub, SubAssign, sub_assign);
+
+    impl core::ops::Mul<Scalar> for Point {
+      type Output = Point;
+      fn mul(self, rhs: Scalar) -> Point {
+        Point(self.0 * rhs)
+      }
+    }
+
+    impl<'r> core::ops::Mul<&'r Scalar> for Point {
+      type Output = Point;
+      fn mul(self, rhs: &'r Scalar) -> Point {
+        Point(self.0 * rhs)
+      }
+    }
+
+    impl core::ops::MulAssign<Scalar> for Point {
+      fn mul_assign(&mut self, rhs: Scalar) {
+        self.0 *= rhs;
+      }
+    }
+
+    impl<'r> core::ops::MulAssign<&'r Scalar> for Point {
+      fn mul_assign(&mut self, rhs: &'r Scalar) {
+        self.0 *= rhs;
+      }
+    }
+  };
+  (@ops $trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident) => {
+    impl core::ops::$trait<Point> for Point {
+      type Output = Point;
+      fn $fn(self, rhs: Point) -> Point {
+        Point(core::ops::$trait::$fn(self.0, rhs.0))
+      }
+    }
+
+    impl<'r> core::ops::$trait<&'r Point> for Point {
+      type Output = Point;
+      fn $fn(self, rhs: &'r Point) -> Point {
+        Point(core::ops::$trait::$fn(self.0, rhs.0))
+      }
+    }
+
+    impl core::ops::$assign_trait<Point> for Point {
+      fn $assign_fn(&mut self, rhs: Point) {
+        core::ops::$assign_trait::$assign_fn(&mut self.0, rhs.0);
+      }
+    }
+
+    impl<'r> core::ops::$assign_trait<&'r Point> for Point {
+      fn $assign_fn(&mut self, rhs: &'r Point) {
+        core::ops::$assign_trait::$assign_fn(&mut self.0, rhs.0);
+      }
+    }
+  };
+}
+
+pub(crate) use impl_wrapped_bn256_point;
+pub(crate) use impl_wrapped_point_ops;
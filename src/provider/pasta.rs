@@ -1,11 +1,14 @@
 //! This module implements the Spartan traits for `pallas::Point`, `pallas::Scalar`, `vesta::Point`, `vesta::Scalar`.
 use crate::{
-  provider::{hyrax_pc::HyraxCommitmentEngine, keccak::Keccak256Transcript},
-  traits::{CompressedGroup, Group, PrimeFieldExt, TranscriptReprTrait},
+  provider::hyrax_pc::HyraxCommitmentEngine,
+  traits::{
+    field_to_transcript_scalars, CompressedGroup, Group, PrimeFieldExt, PrimeGroup,
+    TranscriptReprTrait,
+  },
 };
 use digest::{ExtendableOutput, Update};
 use ff::{FromUniformBytes, PrimeField};
-use halo2curves::msm::best_multiexp;
+use halo2curves::msm::msm_best;
 use num_bigint::BigInt;
 use num_traits::Num;
 use pasta_curves::{
@@ -54,12 +57,14 @@ macro_rules! impl_traits {
     $order_str:literal
   ) => {
     impl Group for $name::Point {
-      type Base = $name::Base;
       type Scalar = $name::Scalar;
+      type CE = HyraxCommitmentEngine<Self>;
+    }
+
+    impl PrimeGroup for $name::Point {
+      type Base = $name::Base;
       type CompressedGroupElement = $name_compressed;
       type PreprocessedGroupElement = $name::Affine;
-      type TE = Keccak256Transcript<Self>;
-      type CE = HyraxCommitmentEngine<Self>;
 
       #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
       fn vartime_multiscalar_mul(
@@ -69,7 +74,7 @@ macro_rules! impl_traits {
         if scalars.len() >= 128 {
           pasta_msm::$name(bases, scalars)
         } else {
-          best_multiexp(scalars, bases)
+          msm_best(scalars, bases)
         }
       }
 
@@ -78,13 +83,17 @@ macro_rules! impl_traits {
         scalars: &[Self::Scalar],
         bases: &[Self::PreprocessedGroupElement],
       ) -> Self {
-        best_multiexp(scalars, bases)
+        msm_best(scalars, bases)
       }
 
       fn preprocessed(&self) -> Self::PreprocessedGroupElement {
         self.to_affine()
       }
 
+      fn from_preprocessed(p: &Self::PreprocessedGroupElement) -> Self {
+        (*p).into()
+      }
+
       fn compress(&self) -> Self::CompressedGroupElement {
         $name_compressed::new(self.to_bytes())
       }
@@ -144,12 +153,12 @@ macro_rules! impl_traits {
         }
       }
 
-      fn get_curve_params() -> (Self::Base, Self::Base, BigInt) {
+      fn get_curve_params() -> Option<(Self::Base, Self::Base, BigInt)> {
         let A = $name::Point::a();
         let B = $name::Point::b();
         let order = BigInt::from_str_radix($order_str, 16).unwrap();
 
-        (A, B, order)
+        Some((A, B, order))
       }
 
       fn zero() -> Self {
@@ -188,12 +197,20 @@ impl<G: Group> TranscriptReprTrait<G> for pallas::Base {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     self.to_repr().to_vec()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
+  }
 }
 
 impl<G: Group> TranscriptReprTrait<G> for pallas::Scalar {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     self.to_repr().to_vec()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
+  }
 }
 
 impl_traits!(
@@ -237,7 +254,7 @@ mod tests {
     for n in [
       1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 1021,
     ] {
-      let ck_par = <G as Group>::from_label(label, n);
+      let ck_par = <G as PrimeGroup>::from_label(label, n);
       let ck_ser = from_label_serial(label, n);
       assert_eq!(ck_par.len(), n);
       assert_eq!(ck_ser.len(), n);
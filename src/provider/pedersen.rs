@@ -3,38 +3,294 @@ use crate::{
   errors::SpartanError,
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait},
-    CompressedGroup, Group, TranscriptReprTrait,
+    CompressedGroup, PrimeGroup, TranscriptReprTrait,
   },
 };
+use bitvec::field::BitField;
 use core::{
+  cmp::{max, min, Ordering},
   fmt::Debug,
   marker::PhantomData,
   ops::{Add, AddAssign, Mul, MulAssign},
 };
+use ff::{PrimeField, PrimeFieldBits};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+/// Entries of a committed vector below this bound are added into buckets by value
+/// rather than multiplied with their generators
+const SMALL_SCALAR_BOUND: usize = 1 << 8;
+
+/// Returns the value of `s` if it is a small integer, i.e., below `SMALL_SCALAR_BOUND`
+fn small_value<F: PrimeFieldBits>(s: &F) -> Option<usize> {
+  // zeros and ones dominate the vectors committed with `commit_small`,
+  // so they are recognized without converting the scalar to bits
+  if s.is_zero_vartime() {
+    return Some(0);
+  }
+  if *s == F::ONE {
+    return Some(1);
+  }
+  let bits = s.to_le_bits();
+  let k = SMALL_SCALAR_BOUND.trailing_zeros() as usize;
+  match bits.last_one() {
+    Some(i) if i >= k => None,
+    _ => Some(bits[..k].load_le::<usize>()),
+  }
+}
 
 /// A type that holds commitment generators
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct CommitmentKey<G: Group> {
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CommitmentKey<G: PrimeGroup> {
   ck: Vec<G::PreprocessedGroupElement>,
+  precomputed: bool, // whether commitments use a fixed-base table over `ck`
+  #[serde(skip)]
+  table: OnceLock<Arc<FixedBaseTable<G>>>, // built on first use, so deserialized keys rebuild it
+}
+
+/// Keys are equal if they hold the same generators, whether or not they precompute a table
+impl<G: PrimeGroup> PartialEq for CommitmentKey<G>
+where
+  G::PreprocessedGroupElement: PartialEq,
+{
+  fn eq(&self, other: &Self) -> bool {
+    self.ck == other.ck
+  }
+}
+
+impl<G: PrimeGroup> Eq for CommitmentKey<G> where G::PreprocessedGroupElement: Eq {}
+
+impl<G: PrimeGroup> CommitmentKey<G> {
+  /// Creates a commitment key from the provided generators
+  pub(crate) fn from_generators(ck: Vec<G::PreprocessedGroupElement>) -> Self {
+    CommitmentKey {
+      ck,
+      precomputed: false,
+      table: OnceLock::new(),
+    }
+  }
+
+  /// Derives `n` generators, rounded up to a power of two, from the provided label
+  pub(crate) fn from_label(label: &'static [u8], n: usize) -> Self {
+    Self::from_generators(G::from_label(label, n.next_power_of_two()))
+  }
+
+  /// Returns the number of generators in the commitment key
+  pub(crate) fn len(&self) -> usize {
+    self.ck.len()
+  }
+
+  /// Precomputes a fixed-base table over the generators, which `commit` uses from then on.
+  /// The table holds `ceil(NUM_BITS / w)` multiples of every generator for a window `w`
+  /// chosen from the number of generators, so this trades memory for faster commitments
+  pub fn precompute(&mut self) {
+    self.precomputed = true;
+    self.table();
+  }
+
+  /// Returns true if the commitment key uses a fixed-base table. The table is not serialized,
+  /// so a deserialized key rebuilds it on its first commitment
+  pub fn is_precomputed(&self) -> bool {
+    self.precomputed
+  }
+
+  /// Returns the fixed-base table of a precomputed key, building it if needed
+  fn table(&self) -> Option<&FixedBaseTable<G>> {
+    self.precomputed.then(|| {
+      &**self.table.get_or_init(|| {
+        let span = tracing::span!(tracing::Level::INFO, "precompute fixed-base table");
+        let _guard = span.enter();
+        Arc::new(FixedBaseTable::new(&self.ck))
+      })
+    })
+  }
+
+  /// Computes `sum_i v_i * ck_i`, using the fixed-base table if one is present
+  fn msm(&self, v: &[G::Scalar]) -> G {
+    match self.table() {
+      Some(table) => table.msm(v),
+      None => G::vartime_multiscalar_mul(v, &self.ck[..v.len()]),
+    }
+  }
+
+  /// Computes `sum_i v_i * ck_i` for a vector whose entries are mostly zero or small integers.
+  /// Zero entries are skipped and the generators of small entries are added into buckets
+  /// by value, leaving scalar multiplications only for the other entries
+  fn msm_small(&self, v: &[G::Scalar]) -> G {
+    let table = self.table();
+    let chunk_size = max(v.len().div_ceil(rayon::current_num_threads()), 1);
+    v.par_chunks(chunk_size)
+      .enumerate()
+      .map(|(c, v)| self.msm_small_serial(table, v, c * chunk_size))
+      .reduce(G::zero, |a, b| a + b)
+  }
+
+  fn msm_small_serial(
+    &self,
+    table: Option<&FixedBaseTable<G>>,
+    v: &[G::Scalar],
+    offset: usize,
+  ) -> G {
+    let mut buckets = vec![G::zero(); SMALL_SCALAR_BOUND - 1];
+    let mut max_value = 0;
+    let mut large = Vec::new();
+    for (i, s) in v.iter().enumerate() {
+      match small_value(s) {
+        Some(0) => {}
+        Some(d) => {
+          buckets[d - 1] += match table {
+            Some(table) => table.base(offset + i),
+            None => G::from_preprocessed(&self.ck[offset + i]),
+          };
+          max_value = max(max_value, d);
+        }
+        None => large.push(offset + i),
+      }
+    }
+
+    // sum_d d * buckets[d - 1], computed with running sums
+    let mut acc = G::zero();
+    let mut sum = G::zero();
+    for bucket in buckets.into_iter().take(max_value).rev() {
+      acc += bucket;
+      sum += acc;
+    }
+
+    if !large.is_empty() {
+      sum += match table {
+        Some(table) => table.msm_serial(large.iter().map(|i| (*i, &v[i - offset]))),
+        None => {
+          let (scalars, bases): (Vec<_>, Vec<_>) = large
+            .iter()
+            .map(|i| (v[i - offset], self.ck[*i].clone()))
+            .unzip();
+          G::vartime_multiscalar_mul(&scalars, &bases)
+        }
+      };
+    }
+    sum
+  }
+}
+
+/// Multiples `2^(window * j) * g_i` of every generator `g_i`, so that a multiscalar
+/// multiplication over the generators is a single bucket pass without any doublings
+#[derive(Debug)]
+struct FixedBaseTable<G: PrimeGroup> {
+  window: usize,
+  num_windows: usize,
+  bases: Vec<G>, // bases[i * num_windows + j] = 2^(window * j) * g_i
+}
+
+impl<G: PrimeGroup> FixedBaseTable<G> {
+  fn new(ck: &[G::PreprocessedGroupElement]) -> Self {
+    let num_bits = G::Scalar::NUM_BITS as usize;
+    let n = max(ck.len().div_ceil(rayon::current_num_threads()), 1);
+
+    // with signed digits, a chunk of n generators costs n * (num_bits / w + 1) additions
+    // into the buckets and 2^w additions to sum up the buckets
+    let window = (2..=16)
+      .min_by_key(|w| n * (num_bits / w + 1) + (1 << w))
+      .unwrap();
+    let num_windows = num_bits / window + 1;
+
+    let bases = ck
+      .par_iter()
+      .flat_map_iter(|g| {
+        let mut base = G::from_preprocessed(g);
+        (0..num_windows).map(move |_| {
+          let out = base;
+          for _ in 0..window {
+            base = base + base;
+          }
+          out
+        })
+      })
+      .collect();
+
+    FixedBaseTable {
+      window,
+      num_windows,
+      bases,
+    }
+  }
+
+  fn msm(&self, v: &[G::Scalar]) -> G {
+    assert!(self.bases.len() >= v.len() * self.num_windows);
+    let chunk_size = max(v.len().div_ceil(rayon::current_num_threads()), 1);
+    v.par_chunks(chunk_size)
+      .enumerate()
+      .map(|(c, v)| {
+        let offset = c * chunk_size;
+        self.msm_serial(v.iter().enumerate().map(|(i, s)| (offset + i, s)))
+      })
+      .reduce(G::zero, |a, b| a + b)
+  }
+
+  /// Returns the generator `g_i`, which is the first of its multiples
+  fn base(&self, i: usize) -> G {
+    self.bases[i * self.num_windows]
+  }
+
+  /// Computes `sum_i s_i * g_i` over the provided pairs `(i, s_i)`
+  fn msm_serial<'a>(&self, terms: impl Iterator<Item = (usize, &'a G::Scalar)>) -> G {
+    let num_bits = G::Scalar::NUM_BITS as usize;
+    let half = 1i64 << (self.window - 1);
+    let mut buckets = vec![G::zero(); half as usize];
+    for (i, s) in terms {
+      let bits = s.to_le_bits();
+      let bases = &self.bases[i * self.num_windows..];
+      // recode the scalar into digits in [-2^(w-1), 2^(w-1)], so that negative digits
+      // subtract from the buckets and only half as many buckets are needed
+      let mut carry = 0i64;
+      for (j, base) in bases.iter().take(self.num_windows).enumerate() {
+        let lo = min(j * self.window, num_bits);
+        let hi = min(lo + self.window, num_bits);
+        let raw = bits[lo..hi]
+          .iter()
+          .rev()
+          .fold(0i64, |acc, b| (acc << 1) | (*b as i64));
+        let mut d = raw + carry;
+        carry = 0;
+        if d > half {
+          d -= 2 * half;
+          carry = 1;
+        }
+        match d.cmp(&0) {
+          Ordering::Greater => buckets[d as usize - 1] += base,
+          Ordering::Less => buckets[(-d) as usize - 1] -= base,
+          Ordering::Equal => {}
+        }
+      }
+    }
+
+    // sum_d d * buckets[d - 1], computed with running sums
+    let mut acc = G::zero();
+    let mut sum = G::zero();
+    for bucket in buckets.into_iter().rev() {
+      acc += bucket;
+      sum += acc;
+    }
+    sum
+  }
 }
 
 /// A type that holds a commitment
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct Commitment<G: Group> {
+pub struct Commitment<G: PrimeGroup> {
   comm: G,
 }
 
 /// A type that holds a compressed commitment
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct CompressedCommitment<G: Group> {
+pub struct CompressedCommitment<G: PrimeGroup> {
   comm: G::CompressedGroupElement,
 }
 
-impl<G: Group> CommitmentTrait<G> for Commitment<G> {
+impl<G: PrimeGroup> CommitmentTrait<G> for Commitment<G> {
   type CompressedCommitment = CompressedCommitment<G>;
 
   fn compress(&self) -> Self::CompressedCommitment {
@@ -52,15 +308,31 @@ impl<G: Group> CommitmentTrait<G> for Commitment<G> {
       comm: comm.unwrap(),
     })
   }
+
+  fn combine(comms: &[Self], coeffs: &[G::Scalar]) -> Result<Self, SpartanError> {
+    Ok(
+      comms
+        .iter()
+        .zip(coeffs.iter())
+        .fold(Self::default(), |acc, (c, s)| acc + c * s),
+    )
+  }
 }
 
-impl<G: Group> Default for Commitment<G> {
+impl<G: PrimeGroup> Commitment<G> {
+  /// Returns the group element that holds the commitment
+  pub(crate) fn to_group(self) -> G {
+    self.comm
+  }
+}
+
+impl<G: PrimeGroup> Default for Commitment<G> {
   fn default() -> Self {
     Commitment { comm: G::zero() }
   }
 }
 
-impl<G: Group> TranscriptReprTrait<G> for Commitment<G> {
+impl<G: PrimeGroup> TranscriptReprTrait<G> for Commitment<G> {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     let (x, y, is_infinity) = self.comm.to_coordinates();
     let is_infinity_byte = (!is_infinity).into();
@@ -71,22 +343,33 @@ impl<G: Group> TranscriptReprTrait<G> for Commitment<G> {
     ]
     .concat()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    let (x, y, is_infinity) = self.comm.to_coordinates();
+    let is_infinity_scalar = G::Scalar::from(u64::from(!is_infinity));
+    [
+      x.to_transcript_scalars(),
+      y.to_transcript_scalars(),
+      vec![is_infinity_scalar],
+    ]
+    .concat()
+  }
 }
 
-impl<G: Group> TranscriptReprTrait<G> for CompressedCommitment<G> {
+impl<G: PrimeGroup> TranscriptReprTrait<G> for CompressedCommitment<G> {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     self.comm.to_transcript_bytes()
   }
 }
 
-impl<G: Group> MulAssign<G::Scalar> for Commitment<G> {
+impl<G: PrimeGroup> MulAssign<G::Scalar> for Commitment<G> {
   fn mul_assign(&mut self, scalar: G::Scalar) {
     let result = (self as &Commitment<G>).comm * scalar;
     *self = Commitment { comm: result };
   }
 }
 
-impl<'a, 'b, G: Group> Mul<&'b G::Scalar> for &'a Commitment<G> {
+impl<'b, G: PrimeGroup> Mul<&'b G::Scalar> for &Commitment<G> {
   type Output = Commitment<G>;
   fn mul(self, scalar: &'b G::Scalar) -> Commitment<G> {
     Commitment {
@@ -95,7 +378,7 @@ impl<'a, 'b, G: Group> Mul<&'b G::Scalar> for &'a Commitment<G> {
   }
 }
 
-impl<G: Group> Mul<G::Scalar> for Commitment<G> {
+impl<G: PrimeGroup> Mul<G::Scalar> for Commitment<G> {
   type Output = Commitment<G>;
 
   fn mul(self, scalar: G::Scalar) -> Commitment<G> {
@@ -105,14 +388,14 @@ impl<G: Group> Mul<G::Scalar> for Commitment<G> {
   }
 }
 
-impl<'b, G: Group> AddAssign<&'b Commitment<G>> for Commitment<G> {
+impl<'b, G: PrimeGroup> AddAssign<&'b Commitment<G>> for Commitment<G> {
   fn add_assign(&mut self, other: &'b Commitment<G>) {
     let result = (self as &Commitment<G>).comm + other.comm;
     *self = Commitment { comm: result };
   }
 }
 
-impl<'a, 'b, G: Group> Add<&'b Commitment<G>> for &'a Commitment<G> {
+impl<'b, G: PrimeGroup> Add<&'b Commitment<G>> for &Commitment<G> {
   type Output = Commitment<G>;
   fn add(self, other: &'b Commitment<G>) -> Commitment<G> {
     Commitment {
@@ -156,23 +439,21 @@ macro_rules! define_add_assign_variants {
   };
 }
 
-define_add_assign_variants!(G = Group, LHS = Commitment<G>, RHS = Commitment<G>);
-define_add_variants!(G = Group, LHS = Commitment<G>, RHS = Commitment<G>, Output = Commitment<G>);
+define_add_assign_variants!(G = PrimeGroup, LHS = Commitment<G>, RHS = Commitment<G>);
+define_add_variants!(G = PrimeGroup, LHS = Commitment<G>, RHS = Commitment<G>, Output = Commitment<G>);
 
 /// Provides a commitment engine
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct CommitmentEngine<G: Group> {
+pub struct CommitmentEngine<G: PrimeGroup> {
   _p: PhantomData<G>,
 }
 
-impl<G: Group> CommitmentEngineTrait<G> for CommitmentEngine<G> {
+impl<G: PrimeGroup> CommitmentEngineTrait<G> for CommitmentEngine<G> {
   type CommitmentKey = CommitmentKey<G>;
   type Commitment = Commitment<G>;
 
-  fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey {
-    Self::CommitmentKey {
-      ck: G::from_label(label, n.next_power_of_two()),
-    }
+  fn setup(label: &'static [u8], n: usize) -> Result<Self::CommitmentKey, SpartanError> {
+    Ok(Self::CommitmentKey::from_label(label, n))
   }
 
   #[tracing::instrument(skip_all, name = "Pedersen::commit")]
@@ -181,15 +462,21 @@ impl<G: Group> CommitmentEngineTrait<G> for CommitmentEngine<G> {
       println!("commitment key length: {}", ck.ck.len());
       println!("v length: {}", v.len());
     }
+    assert!(ck.ck.len() >= v.len());
+    Commitment { comm: ck.msm(v) }
+  }
+
+  #[tracing::instrument(skip_all, name = "Pedersen::commit_small")]
+  fn commit_small(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
     assert!(ck.ck.len() >= v.len());
     Commitment {
-      comm: G::vartime_multiscalar_mul(v, &ck.ck[..v.len()]),
+      comm: ck.msm_small(v),
     }
   }
 }
 
 /// Additional extensions on the commitment engine
-pub trait CommitmentEngineExtTrait<G: Group>: CommitmentEngineTrait<G> {
+pub trait CommitmentEngineExtTrait<G: PrimeGroup>: CommitmentEngineTrait<G> {
   /// Splits the commitment key into two pieces at a specified point
   fn split_at(ck: &Self::CommitmentKey, n: usize) -> (Self::CommitmentKey, Self::CommitmentKey);
 
@@ -204,17 +491,22 @@ pub trait CommitmentEngineExtTrait<G: Group>: CommitmentEngineTrait<G> {
 
   /// Reinterprets the commitments as a commitment key
   fn reinterpret_commitments_as_ck(commitments: &[Self::Commitment]) -> Self::CommitmentKey;
+
+  /// Commits to the provided vector using the provided generators and hides it
+  /// with the blinding factor `r` over the first generator in `h`
+  fn commit_with_blind(
+    ck: &Self::CommitmentKey,
+    h: &Self::CommitmentKey,
+    v: &[G::Scalar],
+    r: &G::Scalar,
+  ) -> Self::Commitment;
 }
 
-impl<G: Group> CommitmentEngineExtTrait<G> for CommitmentEngine<G> {
+impl<G: PrimeGroup> CommitmentEngineExtTrait<G> for CommitmentEngine<G> {
   fn split_at(ck: &Self::CommitmentKey, n: usize) -> (Self::CommitmentKey, Self::CommitmentKey) {
     (
-      CommitmentKey {
-        ck: ck.ck[0..n].to_vec(),
-      },
-      CommitmentKey {
-        ck: ck.ck[n..].to_vec(),
-      },
+      CommitmentKey::from_generators(ck.ck[0..n].to_vec()),
+      CommitmentKey::from_generators(ck.ck[n..].to_vec()),
     )
   }
 
@@ -225,7 +517,7 @@ impl<G: Group> CommitmentEngineExtTrait<G> for CommitmentEngine<G> {
       c.extend(other.ck.clone());
       c
     };
-    Self::CommitmentKey { ck }
+    Self::CommitmentKey::from_generators(ck)
   }
 
   #[tracing::instrument(skip_all, name="CommitmentEngine::fold")]
@@ -241,7 +533,7 @@ impl<G: Group> CommitmentEngineExtTrait<G> for CommitmentEngine<G> {
       })
       .collect();
 
-    Self::CommitmentKey { ck }
+    Self::CommitmentKey::from_generators(ck)
   }
 
   fn scale(ck: &Self::CommitmentKey, r: &G::Scalar) -> Self::CommitmentKey {
@@ -252,12 +544,102 @@ impl<G: Group> CommitmentEngineExtTrait<G> for CommitmentEngine<G> {
       .map(|g| G::vartime_multiscalar_mul(&[*r], &[g]).preprocessed())
       .collect();
 
-    Self::CommitmentKey { ck: ck_scaled }
+    Self::CommitmentKey::from_generators(ck_scaled)
   }
 
   fn reinterpret_commitments_as_ck(commitments: &[Self::Commitment]) -> Self::CommitmentKey {
-    Self::CommitmentKey {
-      ck: commitments.iter().map(|c| c.comm.preprocessed()).collect(),
+    Self::CommitmentKey::from_generators(
+      commitments.iter().map(|c| c.comm.preprocessed()).collect(),
+    )
+  }
+
+  fn commit_with_blind(
+    ck: &Self::CommitmentKey,
+    h: &Self::CommitmentKey,
+    v: &[G::Scalar],
+    r: &G::Scalar,
+  ) -> Self::Commitment {
+    assert!(ck.ck.len() >= v.len());
+    assert!(!h.ck.is_empty());
+    Commitment {
+      comm: ck.msm(v) + G::vartime_multiscalar_mul(&[*r], &h.ck[..1]),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{provider::bn256_grumpkin::bn256, traits::Group};
+  use ff::Field;
+  use rand::rngs::OsRng;
+
+  type G = bn256::Point;
+
+  #[test]
+  fn test_fixed_base_commit() {
+    let mut ck = CommitmentEngine::<G>::setup(b"test", 64).unwrap();
+    let h = CommitmentEngine::<G>::setup(b"blind", 1).unwrap();
+    let v = (0..64)
+      .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let r = <G as Group>::Scalar::random(&mut OsRng);
+    let comms = [1, 7, 64]
+      .iter()
+      .map(|n| CommitmentEngine::commit(&ck, &v[..*n]))
+      .collect::<Vec<_>>();
+    let comm_blind = CommitmentEngine::commit_with_blind(&ck, &h, &v, &r);
+
+    ck.precompute();
+    assert!(ck.is_precomputed());
+    for (n, comm) in [1, 7, 64].iter().zip(comms.iter()) {
+      assert_eq!(CommitmentEngine::commit(&ck, &v[..*n]), *comm);
+    }
+    assert_eq!(
+      CommitmentEngine::commit_with_blind(&ck, &h, &v, &r),
+      comm_blind
+    );
+
+    // the table is not serialized, and a deserialized key rebuilds it
+    let ck_deserialized: CommitmentKey<G> =
+      bincode::deserialize(&bincode::serialize(&ck).unwrap()).unwrap();
+    assert_eq!(ck_deserialized, ck);
+    assert!(ck_deserialized.is_precomputed());
+    assert_eq!(CommitmentEngine::commit(&ck_deserialized, &v), comms[2]);
+    assert!(ck_deserialized.table.get().is_some());
+  }
+
+  #[test]
+  fn test_commit_small() {
+    let mut ck = CommitmentEngine::<G>::setup(b"test", 256).unwrap();
+    // zeros, ones, small integers, the largest small integer, and full-width scalars
+    let v = (0..256)
+      .map(|i| match i % 5 {
+        0 => <G as Group>::Scalar::ZERO,
+        1 => <G as Group>::Scalar::ONE,
+        2 => <G as Group>::Scalar::from(i as u64),
+        3 => <G as Group>::Scalar::from(SMALL_SCALAR_BOUND as u64 - 1),
+        _ => <G as Group>::Scalar::random(&mut OsRng),
+      })
+      .collect::<Vec<_>>();
+    let bits = v
+      .iter()
+      .map(|s| <G as Group>::Scalar::from(small_value(s).unwrap_or(1) as u64 % 2))
+      .collect::<Vec<_>>();
+    assert_eq!(small_value(&v[3]), Some(SMALL_SCALAR_BOUND - 1));
+    assert_eq!(
+      small_value(&<G as Group>::Scalar::from(SMALL_SCALAR_BOUND as u64)),
+      None
+    );
+
+    for _ in 0..2 {
+      for v in [&v, &bits] {
+        assert_eq!(
+          CommitmentEngine::commit_small(&ck, v),
+          CommitmentEngine::commit(&ck, v)
+        );
+      }
+      ck.precompute();
     }
   }
 }
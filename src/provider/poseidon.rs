@@ -0,0 +1,363 @@
+//! This module provides an implementation of `TranscriptEngineTrait` using the Poseidon permutation
+//! over the scalar field of the group. Unlike Keccak256, the transcript is cheap to recompute inside
+//! a circuit over the same field, which makes it the transcript of choice for proofs that are verified recursively.
+//!
+//! The instance has width 3 (rate 2, capacity 1), the S-box x^5, 8 full rounds, and 57 partial rounds.
+//! Its round constants and MDS matrix are derived with the Grain LFSR of the reference parameter script
+//! of Poseidon, so over BN256 the permutation is that of circomlib and of other implementations
+//! that use the reference parameters.
+use crate::{
+  errors::SpartanError,
+  traits::{bytes_to_transcript_scalars, Group, TranscriptEngineTrait, TranscriptReprTrait},
+};
+use core::{
+  any::{Any, TypeId},
+  marker::PhantomData,
+};
+use ff::{Field, PrimeField, PrimeFieldBits};
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, OnceLock},
+};
+
+const PERSONA_TAG: &[u8] = b"NoTR";
+const DOM_SEP_TAG: &[u8] = b"NoDS";
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+const ALPHA: u64 = 5;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 57;
+
+/// The parameters of the Poseidon permutation over `F`
+#[derive(Debug, Clone)]
+struct PoseidonConstants<F: PrimeField> {
+  round_constants: Vec<[F; WIDTH]>,
+  mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F: PrimeFieldBits> PoseidonConstants<F> {
+  fn new() -> Self {
+    assert_eq!(
+      gcd(ALPHA, modulus_minus_one_mod::<F>(ALPHA)),
+      1,
+      "x^{ALPHA} is not a permutation of the field"
+    );
+
+    let mut grain = GrainLFSR::new(F::NUM_BITS as usize, WIDTH, FULL_ROUNDS, PARTIAL_ROUNDS);
+    let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+      .map(|_| core::array::from_fn(|_| grain.next_field_element_rejection::<F>()))
+      .collect();
+
+    // a Cauchy matrix 1 / (x_i + y_j) for distinct x_i and y_j sampled with the LFSR
+    let mds = loop {
+      let xs: [F; 2 * WIDTH] = loop {
+        let xs = core::array::from_fn(|_| grain.next_field_element());
+        if (0..2 * WIDTH).all(|i| (0..i).all(|j| xs[i] != xs[j])) {
+          break xs;
+        }
+      };
+      let (xs, ys) = xs.split_at(WIDTH);
+      if xs
+        .iter()
+        .all(|x| ys.iter().all(|y| !(*x + y).is_zero_vartime()))
+      {
+        break core::array::from_fn(|i| {
+          core::array::from_fn(|j| (xs[i] + ys[j]).invert().unwrap())
+        });
+      }
+    };
+
+    Self {
+      round_constants,
+      mds,
+    }
+  }
+
+  fn permute(&self, state: &mut [F; WIDTH]) {
+    let half_full_rounds = FULL_ROUNDS / 2;
+    for (r, constants) in self.round_constants.iter().enumerate() {
+      for (s, c) in state.iter_mut().zip(constants) {
+        *s += c;
+      }
+
+      let is_full_round = r < half_full_rounds || r >= half_full_rounds + PARTIAL_ROUNDS;
+      let num_sboxes = if is_full_round { WIDTH } else { 1 };
+      for s in state.iter_mut().take(num_sboxes) {
+        *s = s.pow_vartime([ALPHA]);
+      }
+
+      *state = core::array::from_fn(|i| {
+        (0..WIDTH).fold(F::ZERO, |acc, j| acc + self.mds[i][j] * state[j])
+      });
+    }
+  }
+}
+
+/// Returns the constants of the permutation over `F`, which are derived once per field
+fn constants<F: PrimeFieldBits>() -> Arc<PoseidonConstants<F>> {
+  type Cache = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+  static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+  let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+  cache
+    .entry(TypeId::of::<F>())
+    .or_insert_with(|| Arc::new(PoseidonConstants::<F>::new()))
+    .clone()
+    .downcast()
+    .unwrap()
+}
+
+/// The Grain LFSR with which the reference parameter script of Poseidon samples
+/// the round constants and the MDS matrix of an instance
+struct GrainLFSR {
+  state: u128, // the 80 bits of the register, with the oldest in the least significant bit
+  num_bits: usize,
+}
+
+impl GrainLFSR {
+  fn new(num_bits: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+    // the register starts with the field type (1 for prime fields), the S-box type
+    // (0 for x^alpha), the field size, the width, and the numbers of rounds, followed by ones
+    let fields = [
+      (1, 2),
+      (0, 4),
+      (num_bits, 12),
+      (width, 12),
+      (full_rounds, 10),
+      (partial_rounds, 10),
+      ((1 << 30) - 1, 30),
+    ];
+    let mut state = 0u128;
+    let mut pos = 0;
+    for (value, len) in fields {
+      for i in (0..len).rev() {
+        state |= (((value >> i) & 1) as u128) << pos;
+        pos += 1;
+      }
+    }
+
+    let mut grain = GrainLFSR { state, num_bits };
+    for _ in 0..160 {
+      grain.step();
+    }
+    grain
+  }
+
+  fn step(&mut self) -> bool {
+    let s = self.state;
+    let bit = ((s >> 62) ^ (s >> 51) ^ (s >> 38) ^ (s >> 23) ^ (s >> 13) ^ s) & 1;
+    self.state = (s >> 1) | (bit << 79);
+    bit == 1
+  }
+
+  /// Outputs the second of each pair of bits whose first bit is set
+  fn next_bit(&mut self) -> bool {
+    while !self.step() {
+      self.step();
+    }
+    self.step()
+  }
+
+  /// Returns the next `num_bits` bits, most significant first
+  fn next_bits(&mut self) -> Vec<bool> {
+    (0..self.num_bits).map(|_| self.next_bit()).collect()
+  }
+
+  /// Samples a field element from the next bits, reduced modulo the characteristic
+  fn next_field_element<F: PrimeField>(&mut self) -> F {
+    self
+      .next_bits()
+      .into_iter()
+      .fold(F::ZERO, |acc, b| acc.double() + F::from(u64::from(b)))
+  }
+
+  /// Samples a field element from the next bits, skipping those that exceed the characteristic
+  fn next_field_element_rejection<F: PrimeFieldBits>(&mut self) -> F {
+    let modulus = F::char_le_bits()
+      .iter()
+      .by_vals()
+      .take(self.num_bits)
+      .collect::<Vec<_>>();
+    loop {
+      let bits = self.next_bits();
+      // compare with the modulus from the most significant bit down
+      if bits.iter().lt(modulus.iter().rev()) {
+        return bits
+          .into_iter()
+          .fold(F::ZERO, |acc, b| acc.double() + F::from(u64::from(b)));
+      }
+    }
+  }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+/// Computes (p - 1) mod `a`, where p is the modulus of `F`
+fn modulus_minus_one_mod<F: PrimeFieldBits>(a: u64) -> u64 {
+  let p_mod_a = F::char_le_bits()
+    .iter()
+    .by_vals()
+    .rev()
+    .fold(0, |acc, bit| (2 * acc + u64::from(bit)) % a);
+  (p_mod_a + a - 1) % a
+}
+
+/// Provides an implementation of `TranscriptEngine` with a Poseidon duplex sponge over `G::Scalar`
+#[derive(Debug, Clone)]
+pub struct PoseidonTranscript<G: Group> {
+  round: u16,
+  state: [G::Scalar; WIDTH],
+  pos: usize,
+  constants: Arc<PoseidonConstants<G::Scalar>>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> PoseidonTranscript<G> {
+  fn absorb_scalar(&mut self, s: G::Scalar) {
+    // the first element of the state is the capacity
+    if self.pos == RATE {
+      self.constants.permute(&mut self.state);
+      self.pos = 0;
+    }
+    self.state[1 + self.pos] += s;
+    self.pos += 1;
+  }
+
+  /// absorbs a length-prefixed sequence of scalars, so that consecutive inputs cannot be confused
+  fn absorb_scalars(&mut self, scalars: &[G::Scalar]) {
+    self.absorb_scalar(G::Scalar::from(scalars.len() as u64));
+    for s in scalars {
+      self.absorb_scalar(*s);
+    }
+  }
+
+  fn absorb_bytes(&mut self, bytes: &[u8]) {
+    self.absorb_scalars(&bytes_to_transcript_scalars::<G::Scalar>(bytes));
+  }
+}
+
+impl<G: Group> TranscriptEngineTrait<G> for PoseidonTranscript<G> {
+  fn new(label: &'static [u8]) -> Self {
+    let mut transcript = Self {
+      round: 0u16,
+      state: [G::Scalar::ZERO; WIDTH],
+      pos: 0,
+      constants: constants(),
+      _p: PhantomData,
+    };
+    transcript.absorb_bytes(&[PERSONA_TAG, label].concat());
+    transcript
+  }
+
+  fn squeeze(&mut self, label: &'static [u8]) -> Result<G::Scalar, SpartanError> {
+    self.absorb_bytes(&[DOM_SEP_TAG, self.round.to_le_bytes().as_ref(), label].concat());
+
+    // pad with a single one, permute, and read the first rate element
+    self.absorb_scalar(G::Scalar::ONE);
+    self.constants.permute(&mut self.state);
+    self.pos = 0;
+
+    // update state
+    self.round = {
+      if let Some(v) = self.round.checked_add(1) {
+        v
+      } else {
+        return Err(SpartanError::InternalTranscriptError);
+      }
+    };
+
+    Ok(self.state[1])
+  }
+
+  #[tracing::instrument(skip_all, name = "PoseidonTranscript::absorb")]
+  fn absorb<T: TranscriptReprTrait<G>>(&mut self, label: &'static [u8], o: &T) {
+    self.absorb_bytes(label);
+    self.absorb_scalars(&o.to_transcript_scalars());
+  }
+
+  fn dom_sep(&mut self, bytes: &'static [u8]) {
+    self.absorb_bytes(&[DOM_SEP_TAG, bytes].concat());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::provider::{bn256_grumpkin::bn256, ristretto, secp_secq::secp256k1};
+  use pasta_curves::pallas;
+  use rand::Rng;
+
+  fn test_poseidon_transcript_with<G: Group>() {
+    let mut rng = rand::thread_rng();
+    let scalars = std::iter::from_fn(|| Some(G::Scalar::from(rng.gen::<u64>())))
+      .take(5)
+      .collect::<Vec<_>>();
+
+    let run = |labels: [&'static [u8]; 2], scalars: &[G::Scalar]| {
+      let mut transcript: PoseidonTranscript<G> = PoseidonTranscript::new(b"test");
+      transcript.absorb(labels[0], &scalars);
+      let c1 = transcript.squeeze(b"c1").unwrap();
+      transcript.absorb(labels[1], &scalars[0]);
+      let c2 = transcript.squeeze(b"c2").unwrap();
+      (c1, c2)
+    };
+
+    // the transcript is deterministic
+    let (c1, c2) = run([b"s1", b"s2"], &scalars);
+    assert_eq!((c1, c2), run([b"s1", b"s2"], &scalars));
+    assert_ne!(c1, c2);
+
+    // and binds labels, values, and their lengths
+    assert_ne!(c2, run([b"s1", b"s3"], &scalars).1);
+    assert_ne!(c1, run([b"s3", b"s2"], &scalars).0);
+    assert_ne!(c1, run([b"s1", b"s2"], &scalars[..4]).0);
+    let mut other_scalars = scalars.clone();
+    other_scalars[4] += G::Scalar::ONE;
+    assert_ne!(c1, run([b"s1", b"s2"], &other_scalars).0);
+  }
+
+  #[test]
+  fn test_poseidon_transcript() {
+    test_poseidon_transcript_with::<pallas::Point>();
+    test_poseidon_transcript_with::<bn256::Point>();
+    test_poseidon_transcript_with::<secp256k1::Point>();
+    test_poseidon_transcript_with::<ristretto::Point>();
+  }
+
+  #[test]
+  fn test_poseidon_transcript_regression() {
+    let mut transcript: PoseidonTranscript<bn256::Point> = PoseidonTranscript::new(b"test");
+    transcript.absorb(b"s1", &bn256::Scalar::from(2u64));
+    transcript.absorb(b"s2", &bn256::Scalar::from(5u64));
+    let c1 = transcript.squeeze(b"c1").unwrap();
+    assert_eq!(
+      hex::encode(c1.to_repr().as_ref()),
+      "e524a35c78830b4ee569cfb316799fa10bbbd548ba3070eb27cc679a5eb66f27"
+    );
+  }
+
+  #[test]
+  fn test_poseidon_permutation() {
+    // the test vector of the reference implementation for x^5, BN256, and width 3
+    let bn256_constants = constants::<bn256::Scalar>();
+    let mut state = [0u64, 1, 2].map(bn256::Scalar::from);
+    bn256_constants.permute(&mut state);
+    let expected = [
+      "0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+      "0x0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29",
+      "0x0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c",
+    ];
+    for (s, e) in state.iter().zip(expected) {
+      assert_eq!(format!("{s:?}"), e);
+    }
+
+    // the constants are derived once per field
+    assert!(Arc::ptr_eq(&bn256_constants, &constants::<bn256::Scalar>()));
+  }
+}
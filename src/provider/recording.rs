@@ -0,0 +1,266 @@
+//! This module provides `RecordingTranscript`, a wrapper of any `TranscriptEngineTrait` that logs every
+//! operation on the transcript, and utilities to find where the transcripts of a prover and a verifier diverged.
+//!
+//! Since SNARKs create their transcripts internally, `record` gives each call to the prover or the
+//! verifier its own log. A transcript created inside `record` logs every operation to that log,
+//! even when it is used or cloned on other threads, such as rayon workers. Transcripts created
+//! outside of `record`, or on threads other than the one calling it, do not log anything:
+//! ```ignore
+//! type S = RelaxedR1CSSNARK<G, EE, RecordingTranscript<G>>;
+//! let (snark, prover_log) = record(|| SNARK::<G, S, C>::prove(&pk, circuit));
+//! let (res, verifier_log) = record(|| snark.unwrap().verify(&vk, &io));
+//! if let Some(d) = first_divergence(&prover_log, &verifier_log) {
+//!   println!("{d}");
+//! }
+//! ```
+use crate::{
+  errors::SpartanError,
+  provider::keccak::Keccak256Transcript,
+  traits::{Group, TranscriptEngineTrait, TranscriptReprTrait},
+};
+use core::{cell::RefCell, fmt, marker::PhantomData};
+use ff::PrimeField;
+use sha3::{Digest, Keccak256};
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<TranscriptEvent>>>;
+
+thread_local! {
+  // the logs of the calls to `record` in progress on this thread, innermost last
+  static RECORDERS: RefCell<Vec<Log>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An operation on a transcript
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEvent {
+  /// a transcript was initialized with the label
+  New(&'static [u8]),
+  /// an object was absorbed under the label; holds the Keccak256 digest of its transcript bytes
+  Absorb(&'static [u8], [u8; 32]),
+  /// a domain separator was added
+  DomSep(&'static [u8]),
+  /// a challenge was squeezed under the label; holds the canonical representation of the challenge
+  Squeeze(&'static [u8], Vec<u8>),
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl fmt::Display for TranscriptEvent {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let label = |l: &[u8]| String::from_utf8_lossy(l).into_owned();
+    match self {
+      TranscriptEvent::New(l) => write!(f, "new `{}`", label(l)),
+      TranscriptEvent::Absorb(l, d) => {
+        write!(f, "absorb `{}` (digest {})", label(l), to_hex(d))
+      }
+      TranscriptEvent::DomSep(l) => write!(f, "dom_sep `{}`", label(l)),
+      TranscriptEvent::Squeeze(l, c) => {
+        write!(f, "squeeze `{}` (challenge {})", label(l), to_hex(c))
+      }
+    }
+  }
+}
+
+/// The first operation at which two transcript logs differ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+  /// the position of the operation in the logs
+  pub index: usize,
+  /// the operation in the prover's log, if the log is long enough
+  pub prover: Option<TranscriptEvent>,
+  /// the operation in the verifier's log, if the log is long enough
+  pub verifier: Option<TranscriptEvent>,
+}
+
+impl fmt::Display for Divergence {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let event = |e: &Option<TranscriptEvent>| match e {
+      Some(e) => e.to_string(),
+      None => "nothing".to_string(),
+    };
+    write!(
+      f,
+      "transcripts diverge at operation {}: prover did {}, verifier did {}",
+      self.index,
+      event(&self.prover),
+      event(&self.verifier)
+    )
+  }
+}
+
+/// Returns the first operation at which the prover's and the verifier's logs differ, if any
+pub fn first_divergence(
+  prover: &[TranscriptEvent],
+  verifier: &[TranscriptEvent],
+) -> Option<Divergence> {
+  (0..prover.len().max(verifier.len()))
+    .find(|&i| prover.get(i) != verifier.get(i))
+    .map(|index| Divergence {
+      index,
+      prover: prover.get(index).cloned(),
+      verifier: verifier.get(index).cloned(),
+    })
+}
+
+/// Runs `f` and returns its result with the operations performed on the recording transcripts it created.
+/// Nested calls keep separate logs: a transcript logs only to the innermost call in progress when it is created
+pub fn record<R>(f: impl FnOnce() -> R) -> (R, Vec<TranscriptEvent>) {
+  // removes the log of this call even if `f` panics
+  struct Guard;
+  impl Drop for Guard {
+    fn drop(&mut self) {
+      RECORDERS.with(|r| r.borrow_mut().pop());
+    }
+  }
+
+  let log = Log::default();
+  RECORDERS.with(|r| r.borrow_mut().push(log.clone()));
+  let guard = Guard;
+  let res = f();
+  drop(guard);
+
+  let events = core::mem::take(&mut *log.lock().unwrap());
+  (res, events)
+}
+
+/// Provides an implementation of `TranscriptEngine` that forwards every operation to `TE` and logs it
+#[derive(Debug, Clone)]
+pub struct RecordingTranscript<G: Group, TE: TranscriptEngineTrait<G> = Keccak256Transcript<G>> {
+  inner: TE,
+  log: Option<Log>, // the log of the call to `record` that created the transcript, shared by its clones
+  _p: PhantomData<G>,
+}
+
+impl<G: Group, TE: TranscriptEngineTrait<G>> RecordingTranscript<G, TE> {
+  fn log(&self, event: TranscriptEvent) {
+    if let Some(log) = &self.log {
+      log.lock().unwrap().push(event);
+    }
+  }
+}
+
+impl<G: Group, TE: TranscriptEngineTrait<G>> TranscriptEngineTrait<G>
+  for RecordingTranscript<G, TE>
+{
+  fn new(label: &'static [u8]) -> Self {
+    let transcript = Self {
+      inner: TE::new(label),
+      log: RECORDERS.with(|r| r.borrow().last().cloned()),
+      _p: PhantomData,
+    };
+    transcript.log(TranscriptEvent::New(label));
+    transcript
+  }
+
+  fn squeeze(&mut self, label: &'static [u8]) -> Result<G::Scalar, SpartanError> {
+    let c = self.inner.squeeze(label)?;
+    self.log(TranscriptEvent::Squeeze(
+      label,
+      c.to_repr().as_ref().to_vec(),
+    ));
+    Ok(c)
+  }
+
+  fn absorb<T: TranscriptReprTrait<G>>(&mut self, label: &'static [u8], o: &T) {
+    let digest = Keccak256::digest(o.to_transcript_bytes()).into();
+    self.log(TranscriptEvent::Absorb(label, digest));
+    self.inner.absorb(label, o);
+  }
+
+  fn dom_sep(&mut self, bytes: &'static [u8]) {
+    self.log(TranscriptEvent::DomSep(bytes));
+    self.inner.dom_sep(bytes);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::provider::bn256_grumpkin::bn256;
+
+  type G = bn256::Point;
+
+  fn run(s: u64) -> Result<bn256::Scalar, SpartanError> {
+    let mut transcript = RecordingTranscript::<G>::new(b"test");
+    transcript.dom_sep(b"protocol");
+    transcript.absorb(b"s1", &bn256::Scalar::from(2u64));
+    transcript.absorb(b"s2", &bn256::Scalar::from(s));
+    transcript.squeeze(b"c")
+  }
+
+  #[test]
+  fn test_recording_transcript() {
+    // the recording transcript produces the same challenges as the wrapped one
+    let (c, log) = record(|| run(5).unwrap());
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    transcript.dom_sep(b"protocol");
+    transcript.absorb(b"s1", &bn256::Scalar::from(2u64));
+    transcript.absorb(b"s2", &bn256::Scalar::from(5u64));
+    assert_eq!(c, transcript.squeeze(b"c").unwrap());
+
+    assert_eq!(log.len(), 5);
+    assert_eq!(log[0], TranscriptEvent::New(b"test"));
+    assert_eq!(
+      log[4],
+      TranscriptEvent::Squeeze(b"c", c.to_repr().as_ref().to_vec())
+    );
+
+    // identical runs do not diverge
+    let (_, other_log) = record(|| run(5));
+    assert_eq!(first_divergence(&log, &other_log), None);
+
+    // a different payload is reported at its absorb
+    let (_, other_log) = record(|| run(6));
+    let d = first_divergence(&log, &other_log).unwrap();
+    assert_eq!(d.index, 3);
+    assert!(matches!(d.prover, Some(TranscriptEvent::Absorb(b"s2", _))));
+    assert!(matches!(
+      d.verifier,
+      Some(TranscriptEvent::Absorb(b"s2", _))
+    ));
+    assert!(d
+      .to_string()
+      .starts_with("transcripts diverge at operation 3"));
+
+    // a missing operation is reported as well
+    let d = first_divergence(&log, &log[..4]).unwrap();
+    assert_eq!((d.index, d.verifier), (4, None));
+  }
+
+  #[test]
+  fn test_record_scopes() {
+    // a transcript used on another thread still logs to the call that created it
+    let (_, log) = record(|| {
+      let mut transcript = RecordingTranscript::<G>::new(b"test");
+      std::thread::scope(|s| {
+        s.spawn(|| transcript.absorb(b"s1", &bn256::Scalar::from(2u64)));
+      });
+      transcript.squeeze(b"c").unwrap()
+    });
+    assert_eq!(log.len(), 3);
+    assert!(matches!(log[1], TranscriptEvent::Absorb(b"s1", _)));
+
+    // nested calls keep separate logs
+    let ((_, inner_log), outer_log) = record(|| {
+      let mut transcript = RecordingTranscript::<G>::new(b"outer");
+      let inner = record(|| run(5));
+      transcript.dom_sep(b"after");
+      inner
+    });
+    assert_eq!(inner_log.len(), 5);
+    assert_eq!(
+      outer_log,
+      vec![
+        TranscriptEvent::New(b"outer"),
+        TranscriptEvent::DomSep(b"after")
+      ]
+    );
+
+    // transcripts created outside of `record` do not log
+    let mut transcript = RecordingTranscript::<G>::new(b"test");
+    let (_, log) = record(|| transcript.dom_sep(b"protocol"));
+    assert!(log.is_empty());
+  }
+}
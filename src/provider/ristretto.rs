@@ -0,0 +1,342 @@
+//! This module implements the Spartan traits for `ristretto::Point` and `ristretto::Scalar`,
+//! the prime-order Ristretto group built from Curve25519 and its scalar field.
+//! It also provides `ristretto::ipa::Point`, a wrapper whose commitment engine is plain Pedersen,
+//! so that the group can be used with the IPA-based evaluation engine.
+use crate::{
+  errors::SpartanError,
+  provider::hyrax_pc::HyraxCommitmentEngine,
+  traits::{
+    field_to_transcript_scalars, CompressedGroup, Group, PrimeFieldExt, PrimeGroup,
+    TranscriptReprTrait,
+  },
+};
+use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
+use digest::{ExtendableOutput, Update};
+use ff::{Field, PrimeField};
+use num_bigint::BigInt;
+use rayon::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::Shake256;
+use std::io::Read;
+
+pub use curve25519_dalek::{
+  ristretto::{CompressedRistretto as Compressed, RistrettoPoint as Point},
+  scalar::Scalar,
+};
+
+mod base {
+  // the derive also defines `BaseRepr`, which cannot carry documentation
+  #![allow(missing_docs)]
+  use ff::PrimeField;
+
+  /// The base field of Curve25519, with modulus 2^255 - 19.
+  /// Ristretto elements have no canonical affine coordinates, so the base field only appears
+  /// through the canonical encoding of an element, which is itself a field element
+  #[derive(PrimeField)]
+  #[PrimeFieldModulus = "57896044618658097711785492504343953926634992332820282019728792003956564819949"]
+  #[PrimeFieldGenerator = "2"]
+  #[PrimeFieldReprEndianness = "little"]
+  pub struct Base([u64; 4]);
+}
+
+pub use base::Base;
+
+impl Base {
+  /// Interprets 32 little-endian bytes as a field element, if they encode one canonically
+  fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+    let mut repr = <Self as PrimeField>::Repr::default();
+    repr.as_mut().copy_from_slice(bytes);
+    Self::from_repr(repr).into()
+  }
+}
+
+impl Serialize for Base {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(self.to_repr().as_ref());
+    bytes.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for Base {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let bytes = <[u8; 32]>::deserialize(deserializer)?;
+    Base::from_bytes(&bytes)
+      .ok_or_else(|| serde::de::Error::custom(SpartanError::DecompressionError))
+  }
+}
+
+impl Group for Point {
+  type Scalar = Scalar;
+  type CE = HyraxCommitmentEngine<Self>;
+}
+
+impl PrimeGroup for Point {
+  type Base = Base;
+  type CompressedGroupElement = Compressed;
+  type PreprocessedGroupElement = Point;
+
+  fn vartime_multiscalar_mul(
+    scalars: &[Self::Scalar],
+    bases: &[Self::PreprocessedGroupElement],
+  ) -> Self {
+    <Point as VartimeMultiscalarMul>::vartime_multiscalar_mul(scalars, bases)
+  }
+
+  fn preprocessed(&self) -> Self::PreprocessedGroupElement {
+    *self
+  }
+
+  fn from_preprocessed(p: &Self::PreprocessedGroupElement) -> Self {
+    *p
+  }
+
+  fn compress(&self) -> Self::CompressedGroupElement {
+    Point::compress(self)
+  }
+
+  fn from_label(label: &'static [u8], n: usize) -> Vec<Self::PreprocessedGroupElement> {
+    let mut shake = Shake256::default();
+    shake.update(label);
+    let mut reader = shake.finalize_xof();
+    let mut uniform_bytes_vec = Vec::new();
+    for _ in 0..n {
+      let mut uniform_bytes = [0u8; 64];
+      reader.read_exact(&mut uniform_bytes).unwrap();
+      uniform_bytes_vec.push(uniform_bytes);
+    }
+    uniform_bytes_vec
+      .par_iter()
+      .map(Point::from_uniform_bytes)
+      .collect()
+  }
+
+  /// Ristretto elements have no affine coordinates, so this is unsupported in the sense of
+  /// the trait: it returns the canonical encoding of the element as x and zero as y, which
+  /// identifies the element in transcripts but is not a point on any curve
+  fn to_coordinates(&self) -> (Self::Base, Self::Base, bool) {
+    if *self == Point::identity() {
+      (Self::Base::ZERO, Self::Base::ZERO, true)
+    } else {
+      let x = Base::from_bytes(&Point::compress(self).to_bytes()).unwrap();
+      (x, Self::Base::ZERO, false)
+    }
+  }
+
+  /// Ristretto is a quotient of edwards25519 rather than a short Weierstrass curve,
+  /// so it has no coefficients A and B
+  fn get_curve_params() -> Option<(Self::Base, Self::Base, BigInt)> {
+    None
+  }
+
+  fn zero() -> Self {
+    Point::identity()
+  }
+
+  fn get_generator() -> Self {
+    curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT
+  }
+}
+
+impl PrimeFieldExt for Scalar {
+  fn from_uniform(bytes: &[u8]) -> Self {
+    let bytes_arr: [u8; 64] = bytes.try_into().unwrap();
+    Scalar::from_bytes_mod_order_wide(&bytes_arr)
+  }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for Base {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
+  }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for Scalar {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    self.to_bytes().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
+  }
+}
+
+impl<G: Group> TranscriptReprTrait<G> for Compressed {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    self.as_bytes().to_vec()
+  }
+}
+
+impl CompressedGroup for Compressed {
+  type GroupElement = Point;
+
+  fn decompress(&self) -> Option<Point> {
+    Compressed::decompress(self)
+  }
+}
+
+/// A wrapper of `ristretto::Point` whose commitment engine is Pedersen instead of Hyrax,
+/// as required by the IPA-based evaluation engine
+pub mod ipa {
+  use super::Scalar;
+  use crate::{
+    provider::pedersen::CommitmentEngine,
+    traits::{CompressedGroup, Group, PrimeGroup, TranscriptReprTrait},
+  };
+  use serde::{Deserialize, Serialize};
+
+  /// A point of the Ristretto group, whose scalars are committed to with Pedersen
+  #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+  #[serde(transparent)]
+  pub struct Point(super::Point);
+
+  /// A compressed point of the Ristretto group
+  #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+  #[serde(transparent)]
+  pub struct Compressed(super::Compressed);
+
+  impl From<super::Point> for Point {
+    fn from(p: super::Point) -> Self {
+      Point(p)
+    }
+  }
+
+  impl From<Point> for super::Point {
+    fn from(p: Point) -> Self {
+      p.0
+    }
+  }
+
+  crate::provider::impl_wrapped_point_ops!();
+
+  impl Group for Point {
+    type Scalar = Scalar;
+    type CE = CommitmentEngine<Self>;
+  }
+
+  impl PrimeGroup for Point {
+    type Base = super::Base;
+    type CompressedGroupElement = Compressed;
+    type PreprocessedGroupElement = super::Point;
+
+    fn vartime_multiscalar_mul(
+      scalars: &[Self::Scalar],
+      bases: &[Self::PreprocessedGroupElement],
+    ) -> Self {
+      Point(<super::Point as PrimeGroup>::vartime_multiscalar_mul(
+        scalars, bases,
+      ))
+    }
+
+    fn compress(&self) -> Self::CompressedGroupElement {
+      Compressed(PrimeGroup::compress(&self.0))
+    }
+
+    fn preprocessed(&self) -> Self::PreprocessedGroupElement {
+      self.0
+    }
+
+    fn from_preprocessed(p: &Self::PreprocessedGroupElement) -> Self {
+      Point(*p)
+    }
+
+    fn from_label(label: &'static [u8], n: usize) -> Vec<Self::PreprocessedGroupElement> {
+      <super::Point as PrimeGroup>::from_label(label, n)
+    }
+
+    fn to_coordinates(&self) -> (Self::Base, Self::Base, bool) {
+      PrimeGroup::to_coordinates(&self.0)
+    }
+
+    fn zero() -> Self {
+      Point(<super::Point as PrimeGroup>::zero())
+    }
+
+    fn get_generator() -> Self {
+      Point(<super::Point as PrimeGroup>::get_generator())
+    }
+
+    fn get_curve_params() -> Option<(Self::Base, Self::Base, num_bigint::BigInt)> {
+      <super::Point as PrimeGroup>::get_curve_params()
+    }
+  }
+
+  impl<G: Group> TranscriptReprTrait<G> for Compressed {
+    fn to_transcript_bytes(&self) -> Vec<u8> {
+      self.0.as_bytes().to_vec()
+    }
+  }
+
+  impl CompressedGroup for Compressed {
+    type GroupElement = Point;
+
+    fn decompress(&self) -> Option<Point> {
+      self.0.decompress().map(Point)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand_core::OsRng;
+  type G = Point;
+
+  fn from_label_serial(label: &'static [u8], n: usize) -> Vec<Point> {
+    let mut shake = Shake256::default();
+    shake.update(label);
+    let mut reader = shake.finalize_xof();
+    let mut ck = Vec::new();
+    for _ in 0..n {
+      let mut uniform_bytes = [0u8; 64];
+      reader.read_exact(&mut uniform_bytes).unwrap();
+      ck.push(Point::from_uniform_bytes(&uniform_bytes));
+    }
+    ck
+  }
+
+  #[test]
+  fn test_from_label() {
+    let label = b"test_from_label";
+    for n in [
+      1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 1021,
+    ] {
+      let ck_par = <G as PrimeGroup>::from_label(label, n);
+      let ck_ser = from_label_serial(label, n);
+      assert_eq!(ck_par.len(), n);
+      assert_eq!(ck_ser.len(), n);
+      assert_eq!(ck_par, ck_ser);
+    }
+  }
+
+  #[test]
+  fn test_group_ops() {
+    let g = <G as PrimeGroup>::get_generator();
+    let s = Scalar::random(&mut OsRng);
+    let t = Scalar::random(&mut OsRng);
+    let p = g * s;
+
+    // compression round-trips, and coordinates are well-defined for every element
+    assert_eq!(PrimeGroup::compress(&p).decompress(), Some(p));
+    assert!(PrimeGroup::to_coordinates(&<G as PrimeGroup>::zero()).2);
+    assert!(!PrimeGroup::to_coordinates(&p).2);
+
+    // multiscalar multiplication agrees with scalar multiplication
+    let bases = <G as PrimeGroup>::from_label(b"test_msm", 2);
+    assert_eq!(
+      <G as PrimeGroup>::vartime_multiscalar_mul(&[s, t], &bases),
+      bases[0] * s + bases[1] * t
+    );
+
+    // the generator of the base field is a non-residue: 2^((p - 1) / 2) = -1 for p = 2^255 - 19
+    let exp = [u64::MAX - 9, u64::MAX, u64::MAX, (1u64 << 62) - 1];
+    assert_eq!(Base::MULTIPLICATIVE_GENERATOR.pow_vartime(exp), -Base::ONE);
+
+    // Ristretto has no short Weierstrass coefficients
+    assert!(<ipa::Point as PrimeGroup>::get_curve_params().is_none());
+  }
+}
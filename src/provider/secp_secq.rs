@@ -1,15 +1,18 @@
 //! This module implements the Spartan traits for secp::Point, secp::Scalar, secq::Point, secq::Scalar.
 use crate::{
   impl_traits,
-  provider::{hyrax_pc::HyraxCommitmentEngine, keccak::Keccak256Transcript},
-  traits::{CompressedGroup, Group, PrimeFieldExt, TranscriptReprTrait},
+  provider::hyrax_pc::HyraxCommitmentEngine,
+  traits::{
+    field_to_transcript_scalars, CompressedGroup, Group, PrimeFieldExt, PrimeGroup,
+    TranscriptReprTrait,
+  },
 };
 use digest::{ExtendableOutput, Update};
-use ff::{FromUniformBytes, PrimeField};
+use ff::{Field, FromUniformBytes, PrimeField};
 use group::{cofactor::CofactorCurveAffine, Curve, Group as AnotherGroup, GroupEncoding};
-use halo2curves::msm::best_multiexp;
-use halo2curves::secp256k1::{Secp256k1, Secp256k1Affine, Secp256k1Compressed};
-use halo2curves::secq256k1::{Secq256k1, Secq256k1Affine, Secq256k1Compressed};
+use halo2curves::msm::msm_best;
+use halo2curves::secp256k1::{Secp256k1, Secp256k1Affine};
+use halo2curves::secq256k1::{Secq256k1, Secq256k1Affine};
 use num_bigint::BigInt;
 use num_traits::Num;
 use pasta_curves::arithmetic::{CurveAffine, CurveExt};
@@ -19,35 +22,43 @@ use std::io::Read;
 
 /// Re-exports that give access to the standard aliases used in the code base, for secp
 pub mod secp256k1 {
+  pub use super::Secp256k1CompressedElementWrapper as Compressed;
   pub use halo2curves::secp256k1::{
     Fp as Base, Fq as Scalar, Secp256k1 as Point, Secp256k1Affine as Affine,
-    Secp256k1Compressed as Compressed,
   };
 }
 
 /// Re-exports that give access to the standard aliases used in the code base, for secq
 pub mod secq256k1 {
+  pub use super::Secq256k1CompressedElementWrapper as Compressed;
   pub use halo2curves::secq256k1::{
     Fp as Base, Fq as Scalar, Secq256k1 as Point, Secq256k1Affine as Affine,
-    Secq256k1Compressed as Compressed,
   };
 }
 
 impl<G: Group> TranscriptReprTrait<G> for secp256k1::Base {
   fn to_transcript_bytes(&self) -> Vec<u8> {
-    self.to_repr().to_vec()
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
   }
 }
 
 impl<G: Group> TranscriptReprTrait<G> for secp256k1::Scalar {
   fn to_transcript_bytes(&self) -> Vec<u8> {
-    self.to_repr().to_vec()
+    self.to_repr().as_ref().to_vec()
+  }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    field_to_transcript_scalars::<G, _>(self)
   }
 }
 
 impl_traits!(
   secp256k1,
-  Secp256k1Compressed,
+  Secp256k1CompressedElementWrapper,
   Secp256k1,
   Secp256k1Affine,
   "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
@@ -55,7 +66,7 @@ impl_traits!(
 
 impl_traits!(
   secq256k1,
-  Secq256k1Compressed,
+  Secq256k1CompressedElementWrapper,
   Secq256k1,
   Secq256k1Affine,
   "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"
@@ -86,7 +97,7 @@ mod tests {
     for n in [
       1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 1021,
     ] {
-      let ck_par = <G as Group>::from_label(label, n);
+      let ck_par = <G as PrimeGroup>::from_label(label, n);
       let ck_ser = from_label_serial(label, n);
       assert_eq!(ck_par.len(), n);
       assert_eq!(ck_ser.len(), n);
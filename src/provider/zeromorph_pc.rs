@@ -0,0 +1,287 @@
+//! This module implements `EvaluationEngine` using Zeromorph, a multilinear polynomial evaluation
+//! argument built on top of univariate KZG commitments (see https://eprint.iacr.org/2023/917).
+//! The argument holds a commitment to each of the `log n` quotients of the polynomial, along with two
+//! other group elements, and the verifier computes two pairings.
+use crate::{
+  errors::SpartanError,
+  provider::kzg_pc::{divide_by_linear, powers, KZGCommitmentEngine, PairingGroup},
+  traits::{
+    commitment::CommitmentEngineTrait, evaluation::EvaluationEngineTrait, CompressedGroup,
+    TranscriptEngineTrait,
+  },
+  Commitment, CommitmentKey,
+};
+use core::iter;
+use ff::Field;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Provides an implementation of the Zeromorph prover key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZeromorphProverKey<G: PairingGroup> {
+  _p: PhantomData<G>,
+}
+
+/// Provides an implementation of the Zeromorph verifier key
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZeromorphVerifierKey<G: PairingGroup> {
+  n_max: usize, // number of powers in the commitment key, which bounds the degree of committed polynomials
+  g: G,
+  h: G::G2,
+  tau_h: G::G2,
+}
+
+/// Provides an evaluation argument for multilinear polynomials committed with KZG
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZeromorphEvaluationArgument<G: PairingGroup> {
+  comms_q: Vec<G::CompressedGroupElement>, // commitments to the quotients, one for each variable
+  comm_q_hat: G::CompressedGroupElement, // commitment to the quotients shifted to the maximal degree
+  comm_pi: G::CompressedGroupElement,    // witness of the opening of the combined polynomial at x
+}
+
+/// Provides an implementation of a polynomial evaluation engine using Zeromorph
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZeromorphEvaluationEngine<G: PairingGroup> {
+  _p: PhantomData<G>,
+}
+
+/// Evaluates `Phi_m(a) = sum_{i < 2^m} a^i = prod_{j < m} (1 + a^{2^j})`
+fn phi<F: Field>(a: &F, m: usize) -> F {
+  iter::successors(Some(*a), |a| Some(a.square()))
+    .take(m)
+    .map(|a_j| F::ONE + a_j)
+    .product()
+}
+
+/// Returns the scalars of the quotient commitments in the commitment to the polynomial that vanishes at `x`:
+/// `y^k x^{n_max - 2^k} + z (x^{2^k} Phi_{n-k-1}(x^{2^{k+1}}) - u_k Phi_{n-k}(x^{2^k}))` for the variable `u_k`
+/// of weight `2^k`
+fn quotient_scalars<F: Field>(point: &[F], n_max: usize, x: &F, y: &F, z: &F) -> Vec<F> {
+  let n = point.len();
+  let y_powers = powers(y, n);
+  (0..n)
+    .map(|k| {
+      let u_k = point[n - 1 - k];
+      let x_k = x.pow_vartime([(1u64 << k)]);
+      let degree_check = y_powers[k] * x.pow_vartime([(n_max - (1 << k)) as u64]);
+      let identity = x_k * phi(&x_k.square(), n - k - 1) - u_k * phi(&x_k, n - k);
+      degree_check + *z * identity
+    })
+    .collect()
+}
+
+impl<G> EvaluationEngineTrait<G> for ZeromorphEvaluationEngine<G>
+where
+  G: PairingGroup<CE = KZGCommitmentEngine<G>>,
+{
+  type CE = G::CE;
+  type ProverKey = ZeromorphProverKey<G>;
+  type VerifierKey = ZeromorphVerifierKey<G>;
+  type EvaluationArgument = ZeromorphEvaluationArgument<G>;
+
+  fn setup(ck: &CommitmentKey<G>) -> (Self::ProverKey, Self::VerifierKey) {
+    let pk = ZeromorphProverKey { _p: PhantomData };
+    let vk = ZeromorphVerifierKey {
+      n_max: ck.ck.len(),
+      g: G::get_generator(),
+      h: ck.h,
+      tau_h: ck.tau_h,
+    };
+
+    (pk, vk)
+  }
+
+  #[tracing::instrument(skip_all, name = "ZeromorphEvaluationEngine::prove")]
+  fn prove<TE: TranscriptEngineTrait<G>>(
+    ck: &CommitmentKey<G>,
+    _pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comm: &Commitment<G>,
+    poly: &[G::Scalar],
+    point: &[G::Scalar],
+    eval: &G::Scalar,
+  ) -> Result<Self::EvaluationArgument, SpartanError> {
+    transcript.absorb(b"poly_com", comm);
+
+    let n = point.len();
+    let n_max = ck.ck.len();
+    if poly.len() != 1 << n || poly.len() > n_max {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // f - eval = sum_k (X_k - u_k) q_k, where q_k depends on the variables of weight below 2^k:
+    // binding the variable of the highest weight leaves the quotient as the difference of the two halves
+    let mut quotients = vec![Vec::new(); n];
+    let mut f = poly.to_vec();
+    for k in (0..n).rev() {
+      let u_k = point[n - 1 - k];
+      let (lo, hi) = f.split_at(1 << k);
+      let q_k = lo
+        .par_iter()
+        .zip(hi.par_iter())
+        .map(|(lo, hi)| *hi - lo)
+        .collect::<Vec<_>>();
+      f = lo
+        .par_iter()
+        .zip(q_k.par_iter())
+        .map(|(lo, q)| *lo + u_k * q)
+        .collect();
+      quotients[k] = q_k;
+    }
+    debug_assert_eq!(f, vec![*eval]);
+
+    let comms_q = quotients
+      .iter()
+      .map(|q_k| G::CE::commit(ck, q_k).to_group().compress())
+      .collect::<Vec<_>>();
+    transcript.absorb(b"q", &comms_q.as_slice());
+
+    // shift every quotient to the maximal degree and batch them, which shows each q_k has degree below 2^k
+    let y = transcript.squeeze(b"y")?;
+    let y_powers = powers(&y, n);
+    let shifted = |j: usize| {
+      (0..n)
+        .filter(|k| j + (1 << k) >= n_max)
+        .map(|k| y_powers[k] * quotients[k][j + (1 << k) - n_max])
+        .sum::<G::Scalar>()
+    };
+    let q_hat = (0..n_max).into_par_iter().map(shifted).collect::<Vec<_>>();
+    let comm_q_hat = G::CE::commit(ck, &q_hat).to_group().compress();
+    transcript.absorb(b"q_hat", &comm_q_hat);
+
+    // both the degree check and the evaluation identity vanish at x, so their combination with z does too
+    let x = transcript.squeeze(b"x")?;
+    let z = transcript.squeeze(b"z")?;
+    let scalars = quotient_scalars(point, n_max, &x, &y, &z);
+    let mut combined = (0..n_max)
+      .into_par_iter()
+      .map(|j| {
+        let f_j = if j < poly.len() {
+          z * poly[j]
+        } else {
+          G::Scalar::ZERO
+        };
+        let q_j = (0..n)
+          .filter(|k| j < 1 << k)
+          .map(|k| scalars[k] * quotients[k][j])
+          .sum::<G::Scalar>();
+        q_hat[j] + f_j - q_j
+      })
+      .collect::<Vec<_>>();
+    combined[0] -= z * eval * phi(&x, n);
+
+    let pi = divide_by_linear(&combined, &x);
+    let comm_pi = G::CE::commit(ck, &pi).to_group().compress();
+    transcript.absorb(b"pi", &comm_pi);
+
+    Ok(ZeromorphEvaluationArgument {
+      comms_q,
+      comm_q_hat,
+      comm_pi,
+    })
+  }
+
+  fn verify<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comm: &Commitment<G>,
+    point: &[G::Scalar],
+    eval: &G::Scalar,
+    arg: &Self::EvaluationArgument,
+  ) -> Result<(), SpartanError> {
+    transcript.absorb(b"poly_com", comm);
+
+    let n = point.len();
+    if arg.comms_q.len() != n || n >= usize::BITS as usize || 1 << n > vk.n_max {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    transcript.absorb(b"q", &arg.comms_q.as_slice());
+    let y = transcript.squeeze(b"y")?;
+    transcript.absorb(b"q_hat", &arg.comm_q_hat);
+    let x = transcript.squeeze(b"x")?;
+    let z = transcript.squeeze(b"z")?;
+    transcript.absorb(b"pi", &arg.comm_pi);
+
+    let decompress =
+      |c: &G::CompressedGroupElement| c.decompress().ok_or(SpartanError::DecompressionError);
+    let comms_q = arg
+      .comms_q
+      .iter()
+      .map(decompress)
+      .collect::<Result<Vec<_>, _>>()?;
+    let comm_q_hat = decompress(&arg.comm_q_hat)?;
+    let comm_pi = decompress(&arg.comm_pi)?;
+
+    // the commitment to the combined polynomial, which must open to zero at x
+    let scalars = quotient_scalars(point, vk.n_max, &x, &y, &z);
+    let comm_combined = comms_q.iter().zip(scalars.iter()).fold(
+      comm_q_hat + comm.to_group() * z - vk.g * (z * eval * phi(&x, n)),
+      |acc, (c, s)| acc - *c * s,
+    );
+
+    // e(C + x * pi, h) = e(pi, tau * h)
+    if !G::pairing_check(&[
+      (comm_combined + comm_pi * x, vk.h),
+      (G::zero() - comm_pi, vk.tau_h),
+    ]) {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{bn256_kzg, keccak::Keccak256Transcript},
+    spartan::polys::multilinear::MultilinearPolynomial,
+    traits::Group,
+  };
+  use rand_core::OsRng;
+
+  type G = bn256_kzg::Point;
+  type EE = ZeromorphEvaluationEngine<G>;
+
+  #[test]
+  fn test_zeromorph_eval() {
+    for ell in 0..6 {
+      let n = 1 << ell;
+      let poly = (0..n)
+        .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+        .collect::<Vec<_>>();
+      let point = (0..ell)
+        .map(|_| <G as Group>::Scalar::random(&mut OsRng))
+        .collect::<Vec<_>>();
+      let eval = MultilinearPolynomial::new(poly.clone()).evaluate(&point);
+
+      // the commitment key may be larger than the polynomial
+      let ck = <G as Group>::CE::setup(b"test", 2 * n).unwrap();
+      let comm = <G as Group>::CE::commit(&ck, &poly);
+      let (pk, vk) = EE::setup(&ck);
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let arg = EE::prove(&ck, &pk, &mut transcript, &comm, &poly, &point, &eval).unwrap();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &eval, &arg).is_ok());
+
+      // a wrong evaluation is rejected
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let wrong_eval = eval + <G as Group>::Scalar::ONE;
+      assert!(EE::verify(&vk, &mut transcript, &comm, &point, &wrong_eval, &arg).is_err());
+
+      // so is an argument for another commitment
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let other_comm = comm + <G as Group>::CE::commit(&ck, &[<G as Group>::Scalar::ONE]);
+      assert!(EE::verify(&vk, &mut transcript, &other_comm, &point, &eval, &arg).is_err());
+    }
+  }
+}
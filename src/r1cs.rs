@@ -2,14 +2,18 @@
 #![allow(clippy::type_complexity)]
 use crate::{
   errors::SpartanError,
-  traits::{commitment::CommitmentEngineTrait, Group, TranscriptReprTrait},
+  spartan::polys::eq::EqPolynomial,
+  traits::{
+    commitment::{CommitmentEngineTrait, HomomorphicCommitmentTrait},
+    Group, TranscriptReprTrait,
+  },
   Commitment, CommitmentKey, CE,
 };
-use core::{cmp::max, marker::PhantomData};
+use core::{cmp::max, marker::PhantomData, ops::Range};
 use ff::Field;
 use itertools::concat;
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Public parameters for a given R1CS
 #[derive(Clone, Serialize, Deserialize)]
@@ -24,11 +28,43 @@ pub struct R1CSShape<G: Group> {
   pub(crate) num_cons: usize,
   pub(crate) num_vars: usize,
   pub(crate) num_io: usize,
+  #[serde(deserialize_with = "deserialize_sorted_by_row")]
   pub(crate) A: Vec<(usize, usize, G::Scalar)>,
+  #[serde(deserialize_with = "deserialize_sorted_by_row")]
   pub(crate) B: Vec<(usize, usize, G::Scalar)>,
+  #[serde(deserialize_with = "deserialize_sorted_by_row")]
   pub(crate) C: Vec<(usize, usize, G::Scalar)>,
 }
 
+/// Deserializes the entries of a matrix and orders them by row, as `R1CSShape::new` does,
+/// so a shape read back from a saved key satisfies the ordering the matrix-vector products rely on
+fn deserialize_sorted_by_row<'de, D, F>(deserializer: D) -> Result<Vec<(usize, usize, F)>, D::Error>
+where
+  D: Deserializer<'de>,
+  F: Deserialize<'de> + Send,
+{
+  let mut M = Vec::<(usize, usize, F)>::deserialize(deserializer)?;
+  M.par_sort_by_key(|(row, _, _)| *row);
+  Ok(M)
+}
+
+/// Wires the state of consecutive steps of a uniform computation together.
+/// Each step reads its state from the witness columns `state_in` and writes its next state to the
+/// witness columns `state_out` of a single step's shape. The repeated shape then enforces that the
+/// state read by step `s + 1` is the state written by step `s`, that the state read by the first step
+/// is the initial state `z0`, and that the state written by the last step is the final state `zN`.
+/// `z0` and `zN` are public and laid out as `X[io..io + arity]` and `X[io + arity..io + 2 * arity]`.
+/// The two checks for the `k`-th state element live in rows `row + k` and `row + arity + k` of a single
+/// step's shape, which hold the step-local part of the check. The terms that reach into the previous step
+/// or the IO are added on the fly by the uniform methods of `R1CSShape`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct R1CSStateWiring {
+  pub(crate) state_in: Vec<usize>,
+  pub(crate) state_out: Vec<usize>,
+  pub(crate) row: usize,
+  pub(crate) io: usize,
+}
+
 /// A type that holds a witness for a given R1CS instance
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct R1CSWitness<G: Group> {
@@ -62,7 +98,7 @@ pub struct RelaxedR1CSInstance<G: Group> {
 
 impl<G: Group> R1CS<G> {
   /// Samples public parameters for the specified number of constraints and variables in an R1CS
-  pub fn commitment_key(S: &R1CSShape<G>) -> CommitmentKey<G> {
+  pub fn commitment_key(S: &R1CSShape<G>) -> Result<CommitmentKey<G>, SpartanError> {
     let S = S.pad(); // pad the shape before computing the commitment key
     let num_cons = S.num_cons;
     let num_vars = S.num_vars;
@@ -70,6 +106,13 @@ impl<G: Group> R1CS<G> {
   }
 }
 
+impl R1CSStateWiring {
+  /// Returns the number of state elements passed from one step to the next
+  pub(crate) fn arity(&self) -> usize {
+    self.state_in.len()
+  }
+}
+
 impl<G: Group> R1CSShape<G> {
   /// Create an object of type `R1CSShape` from the explicitly specified R1CS matrices
   #[tracing::instrument(skip_all, name = "R1CSShape::new")]
@@ -112,13 +155,19 @@ impl<G: Group> R1CSShape<G> {
       return Err(SpartanError::InvalidIndex);
     }
 
+    // the entries are kept ordered by row, which the matrix-vector products rely on
+    let sorted = |M: &[(usize, usize, G::Scalar)]| {
+      let mut M = M.to_owned();
+      M.par_sort_by_key(|(row, _, _)| *row);
+      M
+    };
     let shape = R1CSShape {
       num_cons,
       num_vars,
       num_io,
-      A: A.to_owned(),
-      B: B.to_owned(),
-      C: C.to_owned(),
+      A: sorted(A),
+      B: sorted(B),
+      C: sorted(C),
     };
 
     // pad the shape
@@ -134,6 +183,443 @@ impl<G: Group> R1CSShape<G> {
     assert!(self.num_io < self.num_vars);
   }
 
+  /// Returns the number of public inputs of the shape obtained by repeating `self` for `num_steps` steps,
+  /// which either share the public inputs of `self` or each have their own copy of them (`io_per_step`)
+  pub(crate) fn num_io_uniform(&self, num_steps: usize, io_per_step: bool) -> usize {
+    if io_per_step {
+      self.num_io * num_steps
+    } else {
+      self.num_io
+    }
+  }
+
+  /// Returns the non-zero entries of `(u, X)` as laid out after the witness in `z = (W, u, X)` of the shape
+  /// obtained by repeating `self` for `num_steps` steps. With `io_per_step`, the inputs `X[s * num_io..(s + 1) * num_io]`
+  /// of step `s` sit in a block of their own at `num_vars * s + 1`, mirroring the witness blocks, so the
+  /// IO columns factor like the witness columns. The constant `u` is shared by all steps.
+  pub(crate) fn io_entries_uniform(
+    &self,
+    num_steps: usize,
+    io_per_step: bool,
+    u: &G::Scalar,
+    X: &[G::Scalar],
+  ) -> Vec<(usize, G::Scalar)> {
+    assert_eq!(X.len(), self.num_io_uniform(num_steps, io_per_step));
+
+    let mut entries = vec![(0, *u)];
+    if io_per_step {
+      entries.extend(X.chunks(self.num_io.max(1)).enumerate().flat_map(|(s, X_step)| {
+        (0..X_step.len()).map(move |i| (self.num_vars * s + 1 + i, X_step[i]))
+      }));
+    } else {
+      entries.extend((0..X.len()).map(|i| (i + 1, X[i])));
+    }
+    entries
+  }
+
+  /// Lays out the witness `W` of `num_steps` steps, each with `num_aux` variables, in the witness blocks of the
+  /// shape obtained by repeating `self` for `num_steps` steps (see `evaluate_uniform`). Every step is padded
+  /// to `num_vars` variables, and the padding steps up to the next power of two are zero.
+  pub(crate) fn pad_witness_uniform(
+    &self,
+    num_steps: usize,
+    num_aux: usize,
+    W: &[G::Scalar],
+  ) -> Result<Vec<G::Scalar>, SpartanError> {
+    if num_aux > self.num_vars || W.len() > num_aux * num_steps {
+      return Err(SpartanError::InvalidWitnessLength);
+    }
+
+    let mut W_padded = vec![G::Scalar::ZERO; self.num_vars * num_steps.next_power_of_two()];
+    W_padded
+      .par_chunks_mut(self.num_vars)
+      .zip(W.par_chunks(max(num_aux, 1)))
+      .for_each(|(W_padded_step, W_step)| {
+        W_padded_step[..W_step.len()].copy_from_slice(W_step);
+      });
+    Ok(W_padded)
+  }
+
+  /// Evaluates the multilinear extensions of the (A, B, C) matrices of the shape obtained by
+  /// repeating `self` for `num_steps` steps (see `ShapeCS::r1cs_shape_uniform`) at `(r_x, r_y)`.
+  /// With `N = num_steps.next_power_of_two()`, row `c * N + s` and witness column `j + num_vars * s`
+  /// of the repeated shape hold the entry `(c, j)` of step `s`, while the IO columns are shared by all steps,
+  /// unless `io_per_step` (see `io_entries_uniform`). The rows of the padding steps `num_steps <= s < N` are empty.
+  /// So the evaluation factors into an evaluation of `self` times an eq polynomial over the step bits
+  /// (summed over the first `num_steps` steps), and the repeated shape never needs to be materialized.
+  /// The cross-step terms of `wiring` are added to `A`.
+  pub(crate) fn evaluate_uniform(
+    &self,
+    num_steps: usize,
+    wiring: &R1CSStateWiring,
+    io_per_step: bool,
+    r_x: &[G::Scalar],
+    r_y: &[G::Scalar],
+  ) -> (G::Scalar, G::Scalar, G::Scalar) {
+    self.check_regular_shape();
+    assert!(num_steps > 0);
+
+    let num_rounds_steps = num_steps.next_power_of_two().ilog2() as usize;
+    let num_rounds_cons = self.num_cons.ilog2() as usize;
+    assert_eq!(r_x.len(), num_rounds_cons + num_rounds_steps);
+    assert_eq!(r_y.len(), 1 + num_rounds_steps + self.num_vars.ilog2() as usize);
+
+    // split the points into the bits of a step and the bits of a row/column within a step
+    let (r_x_cons, r_x_steps) = r_x.split_at(num_rounds_cons);
+    let (r_y_steps, r_y_vars) = r_y[1..].split_at(num_rounds_steps);
+
+    // witness columns of a step only interact with rows of the same step
+    let (eq_x_steps, eq_y_steps) = (
+      EqPolynomial::new(r_x_steps.to_vec()),
+      EqPolynomial::new(r_y_steps.to_vec()),
+    );
+    let eq_steps = eq_x_steps.evaluate_below(r_y_steps, num_steps);
+    let scale_W = (G::Scalar::ONE - r_y[0]) * eq_steps;
+    // the IO columns come right after the witness, so their step bits are zero,
+    // unless every step has its own block of inputs
+    let scale_X = r_y[0] * eq_y_steps.evaluate_index(0) * eq_x_steps.sum_evals_below(num_steps);
+    let scale_X_step = if io_per_step {
+      r_y[0] * eq_steps
+    } else {
+      scale_X
+    };
+
+    let (T_x, T_y) = rayon::join(
+      || EqPolynomial::new(r_x_cons.to_vec()).evals(),
+      || EqPolynomial::new(r_y_vars.to_vec()).evals(),
+    );
+
+    let evaluate_with_table = |M: &[(usize, usize, G::Scalar)]| -> G::Scalar {
+      M.par_iter()
+        .map(|(row, col, val)| {
+          let t_y = if *col < self.num_vars {
+            T_y[*col] * scale_W
+          } else if *col == self.num_vars {
+            T_y[0] * scale_X
+          } else {
+            T_y[*col - self.num_vars] * scale_X_step
+          };
+          T_x[*row] * t_y * val
+        })
+        .sum()
+    };
+
+    let (eval_A, (eval_B, eval_C)) = rayon::join(
+      || evaluate_with_table(&self.A),
+      || {
+        rayon::join(
+          || evaluate_with_table(&self.B),
+          || evaluate_with_table(&self.C),
+        )
+      },
+    );
+
+    // the wiring rows of step `s + 1` read the state written by step `s`, while those of the
+    // first and last steps read the IO instead; each such entry of `A` carries a -1 (or +1)
+    let arity = wiring.arity();
+    let eval_A = if arity > 0 {
+      let (first_x, last_x) = (
+        eq_x_steps.evaluate_index(0),
+        eq_x_steps.evaluate_index(num_steps - 1),
+      );
+      let (first_y, last_y) = (
+        eq_y_steps.evaluate_index(0),
+        eq_y_steps.evaluate_index(num_steps - 1),
+      );
+      let shift = eq_x_steps.evaluate_shifted(r_y_steps, num_steps);
+      let (r_W, r_X) = (G::Scalar::ONE - r_y[0], r_y[0]);
+
+      let eval_wiring = (0..arity)
+        .map(|k| {
+          let out_k = T_y[wiring.state_out[k]];
+          let z0_k = T_y[1 + wiring.io + k];
+          let zN_k = T_y[1 + wiring.io + arity + k];
+          let eval_in = -(r_W * shift * out_k + r_X * first_x * first_y * z0_k);
+          let eval_out = last_x * (r_W * last_y * out_k - r_X * first_y * zN_k);
+          T_x[wiring.row + k] * eval_in + T_x[wiring.row + arity + k] * eval_out
+        })
+        .sum::<G::Scalar>();
+
+      eval_A + eval_wiring
+    } else {
+      eval_A
+    };
+
+    (eval_A, eval_B, eval_C)
+  }
+
+  /// Computes the products of `z = (W, io)`, where `io = (u, X)`, with the (A, B, C) matrices of the shape
+  /// obtained by repeating `self` for `num_steps` steps (see `evaluate_uniform`), one step at a time.
+  /// Row `c * num_steps.next_power_of_two() + s` of each product is row `c` of step `s`.
+  #[tracing::instrument(skip_all, name = "R1CSShape::multiply_vec_uniform")]
+  pub(crate) fn multiply_vec_uniform(
+    &self,
+    num_steps: usize,
+    wiring: &R1CSStateWiring,
+    io_per_step: bool,
+    W: &[G::Scalar],
+    io: &[G::Scalar],
+  ) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError> {
+    let num_steps_padded = num_steps.next_power_of_two();
+    if W.len() != self.num_vars * num_steps_padded
+      || io.len() != 1 + self.num_io_uniform(num_steps, io_per_step)
+    {
+      return Err(SpartanError::InvalidWitnessLength);
+    }
+
+    // every step sees its own block of the witness and either the shared IO or its own inputs
+    let (mut Az_steps, (Bz_steps, Cz_steps)): (Vec<_>, (Vec<_>, Vec<_>)) = W
+      [..self.num_vars * num_steps]
+      .par_chunks(self.num_vars)
+      .enumerate()
+      .map(|(s, W_step)| {
+        if io_per_step {
+          let X_step = &io[1 + self.num_io * s..1 + self.num_io * (s + 1)];
+          self.multiply_vec(&[W_step, &io[..1], X_step].concat())
+        } else {
+          self.multiply_vec(&[W_step, io].concat())
+        }
+      })
+      .collect::<Result<Vec<_>, SpartanError>>()?
+      .into_iter()
+      .map(|(Az, Bz, Cz)| (Az, (Bz, Cz)))
+      .unzip();
+
+    // add the terms of the wiring rows that read the previous step's state or the IO
+    let arity = wiring.arity();
+    let X = &io[1..];
+    Az_steps
+      .par_iter_mut()
+      .enumerate()
+      .for_each(|(s, Az_step)| {
+        for k in 0..arity {
+          Az_step[wiring.row + k] -= if s == 0 {
+            X[wiring.io + k]
+          } else {
+            W[(s - 1) * self.num_vars + wiring.state_out[k]]
+          };
+          if s == num_steps - 1 {
+            Az_step[wiring.row + arity + k] +=
+              W[s * self.num_vars + wiring.state_out[k]] - X[wiring.io + arity + k];
+          }
+        }
+      });
+
+    // the rows of the padding steps are empty
+    let num_cons = self.num_cons * num_steps_padded;
+    let interleave = |M_steps: &[Vec<G::Scalar>]| -> Vec<G::Scalar> {
+      (0..num_cons)
+        .into_par_iter()
+        .map(|i| {
+          let s = i % num_steps_padded;
+          if s < num_steps {
+            M_steps[s][i / num_steps_padded]
+          } else {
+            G::Scalar::ZERO
+          }
+        })
+        .collect()
+    };
+
+    Ok((
+      interleave(&Az_steps),
+      interleave(&Bz_steps),
+      interleave(&Cz_steps),
+    ))
+  }
+
+  /// Computes the rows `rows` of the products of `z = (W, io)` with the (A, B, C) matrices of the shape
+  /// obtained by repeating `self` for `num_steps` steps, laid out as in `multiply_vec_uniform`.
+  /// Only the requested rows are materialized, so the products can be produced in chunks.
+  #[tracing::instrument(skip_all, name = "R1CSShape::multiply_rows_uniform")]
+  pub(crate) fn multiply_rows_uniform(
+    &self,
+    num_steps: usize,
+    wiring: &R1CSStateWiring,
+    io_per_step: bool,
+    W: &[G::Scalar],
+    io: &[G::Scalar],
+    rows: Range<usize>,
+  ) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError> {
+    let num_steps_padded = num_steps.next_power_of_two();
+    if W.len() != self.num_vars * num_steps_padded
+      || io.len() != 1 + self.num_io_uniform(num_steps, io_per_step)
+    {
+      return Err(SpartanError::InvalidWitnessLength);
+    }
+    if rows.end > self.num_cons * num_steps_padded {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    if rows.is_empty() {
+      return Ok((Vec::new(), Vec::new(), Vec::new()));
+    }
+
+    // the entries of the rows of a single step that the requested rows repeat
+    let row_first = rows.start / num_steps_padded;
+    let row_last = (rows.end - 1) / num_steps_padded;
+    let row_entries = |M: &[(usize, usize, G::Scalar)]| -> Vec<usize> {
+      (row_first..=row_last + 1)
+        .map(|row| M.partition_point(|(r, _, _)| *r < row))
+        .collect()
+    };
+
+    // column `col` of `z` as seen by step `s`
+    let X = &io[1..];
+    let z_step = |s: usize, col: usize| -> G::Scalar {
+      if col < self.num_vars {
+        W[s * self.num_vars + col]
+      } else if col == self.num_vars {
+        io[0]
+      } else if io_per_step {
+        X[self.num_io * s + col - self.num_vars - 1]
+      } else {
+        X[col - self.num_vars - 1]
+      }
+    };
+
+    let multiply = |M: &[(usize, usize, G::Scalar)]| -> Vec<G::Scalar> {
+      let offsets = row_entries(M);
+      rows
+        .clone()
+        .into_par_iter()
+        .map(|i| {
+          // the rows of the padding steps are empty
+          let (row, s) = (i / num_steps_padded, i % num_steps_padded);
+          if s >= num_steps {
+            return G::Scalar::ZERO;
+          }
+          let entries = &M[offsets[row - row_first]..offsets[row - row_first + 1]];
+          entries
+            .iter()
+            .map(|(_, col, val)| z_step(s, *col) * val)
+            .sum()
+        })
+        .collect()
+    };
+
+    let (mut Az, (Bz, Cz)) = rayon::join(
+      || multiply(&self.A),
+      || rayon::join(|| multiply(&self.B), || multiply(&self.C)),
+    );
+
+    // add the terms of the wiring rows that read the previous step's state or the IO
+    let arity = wiring.arity();
+    if arity > 0 {
+      Az.par_iter_mut()
+        .zip(rows.into_par_iter())
+        .for_each(|(Az_i, i)| {
+          let (row, s) = (i / num_steps_padded, i % num_steps_padded);
+          if s >= num_steps || row < wiring.row || row >= wiring.row + 2 * arity {
+            return;
+          }
+          let k = row - wiring.row;
+          if k < arity {
+            *Az_i -= if s == 0 {
+              X[wiring.io + k]
+            } else {
+              W[(s - 1) * self.num_vars + wiring.state_out[k]]
+            };
+          } else if s == num_steps - 1 {
+            let k = k - arity;
+            *Az_i += W[s * self.num_vars + wiring.state_out[k]] - X[wiring.io + arity + k];
+          }
+        });
+    }
+
+    Ok((Az, Bz, Cz))
+  }
+
+  /// Binds the "row" variables of the (A, B, C) matrices of the shape obtained by repeating `self`
+  /// for `num_steps` steps (see `evaluate_uniform`) to `r_x`. Returns the evaluation table of `A + r * B + r^2 * C`
+  /// over the columns of `z = (W, 1, X)`, padded to twice the number of witness variables.
+  /// The matrices are only bound for a single step and combined before being laid out for all steps.
+  #[tracing::instrument(skip_all, name = "R1CSShape::bind_row_vars_uniform")]
+  pub(crate) fn bind_row_vars_uniform(
+    &self,
+    num_steps: usize,
+    wiring: &R1CSStateWiring,
+    io_per_step: bool,
+    r_x: &[G::Scalar],
+    r: &G::Scalar,
+  ) -> Vec<G::Scalar> {
+    let num_steps_padded = num_steps.next_power_of_two();
+    let num_rounds_cons = self.num_cons.ilog2() as usize;
+    assert_eq!(r_x.len(), num_rounds_cons + num_steps_padded.ilog2() as usize);
+
+    let (r_x_cons, r_x_steps) = r_x.split_at(num_rounds_cons);
+    let (T_x, T_steps) = rayon::join(
+      || EqPolynomial::new(r_x_cons.to_vec()).evals(),
+      || EqPolynomial::new(r_x_steps.to_vec()).evals(),
+    );
+
+    // the padding steps have no rows, so their witness blocks stay zero and the
+    // shared IO columns are scaled by the sum of eq(r_x_steps, s) over the actual steps
+    let T_steps = &T_steps[..num_steps];
+    let T_io = T_steps.iter().sum::<G::Scalar>();
+
+    let bind_step = |M: &[(usize, usize, G::Scalar)]| -> Vec<G::Scalar> {
+      let mut M_evals_step = vec![G::Scalar::ZERO; self.num_vars + 1 + self.num_io];
+      for (row, col, val) in M {
+        M_evals_step[*col] += T_x[*row] * val;
+      }
+      M_evals_step
+    };
+    let (A_evals_step, (B_evals_step, C_evals_step)) = rayon::join(
+      || bind_step(&self.A),
+      || rayon::join(|| bind_step(&self.B), || bind_step(&self.C)),
+    );
+    let r_sq = *r * r;
+    let evals_step = (0..A_evals_step.len())
+      .map(|i| A_evals_step[i] + B_evals_step[i] * r + C_evals_step[i] * r_sq)
+      .collect::<Vec<_>>();
+
+    // the witness block of step `s` is scaled by eq(r_x_steps, s)
+    let num_vars = self.num_vars * num_steps_padded;
+    let mut evals = vec![G::Scalar::ZERO; 2 * num_vars];
+    let (evals_W, evals_io) = evals.split_at_mut(num_vars);
+    evals_W
+      .par_chunks_mut(self.num_vars)
+      .zip(T_steps.par_iter())
+      .for_each(|(evals_W_step, t)| {
+        for (e, e_step) in evals_W_step.iter_mut().zip(evals_step.iter()) {
+          *e = *t * e_step;
+        }
+      });
+    if io_per_step {
+      // the inputs of step `s` are scaled like its witness block
+      evals_io
+        .par_chunks_mut(self.num_vars)
+        .zip(T_steps.par_iter())
+        .for_each(|(evals_io_step, t)| {
+          for i in 1..1 + self.num_io {
+            evals_io_step[i] = *t * evals_step[self.num_vars + i];
+          }
+        });
+    } else {
+      for i in 1..1 + self.num_io {
+        evals_io[i] = T_io * evals_step[self.num_vars + i];
+      }
+    }
+    // the constant is shared in either case
+    evals_io[0] = T_io * evals_step[self.num_vars];
+
+    // the wiring rows of step `s + 1` read the state written by step `s`, while those of the
+    // first and last steps read the IO instead; they only have entries in `A`
+    let arity = wiring.arity();
+    for k in 0..arity {
+      let (t_in, t_out) = (T_x[wiring.row + k], T_x[wiring.row + arity + k]);
+      for s in 0..num_steps - 1 {
+        evals[s * self.num_vars + wiring.state_out[k]] -= T_steps[s + 1] * t_in;
+      }
+      evals[(num_steps - 1) * self.num_vars + wiring.state_out[k]] +=
+        T_steps[num_steps - 1] * t_out;
+      evals[num_vars + 1 + wiring.io + k] -= T_steps[0] * t_in;
+      evals[num_vars + 1 + wiring.io + arity + k] -= T_steps[num_steps - 1] * t_out;
+    }
+
+    evals
+  }
+
   #[tracing::instrument(skip_all, name = "R1CSShape::multiply_vec")]
   pub fn multiply_vec(
     &self,
@@ -155,21 +641,16 @@ impl<G: Group> R1CSShape<G> {
         // due to ordering.
 
         let num_threads = rayon::current_num_threads() * 4; // Enable work stealing incase of thread work imbalance
-        let thread_chunk_size = M.len() / num_threads;
-        let row_chunk_size = (num_rows as f64 / num_threads as f64).ceil() as usize;
+        let thread_chunk_size = std::cmp::max(1, M.len() / num_threads);
+        let row_chunk_size = std::cmp::max(1, (num_rows as f64 / num_threads as f64).ceil() as usize);
 
         let mut chunks: Vec<std::sync::Mutex<Vec<G::Scalar>>> = Vec::with_capacity(num_threads);
         let mut remaining_rows = num_rows;
-        (0..num_threads).for_each(|i| {
-          if i == num_threads - 1 { // the final chunk may be smaller
-            let inner = std::sync::Mutex::new(vec![G::Scalar::ZERO; remaining_rows]);
-            chunks.push(inner);
-          } else {
-            let inner = std::sync::Mutex::new(vec![G::Scalar::ZERO; row_chunk_size]);
-            chunks.push(inner);
-            remaining_rows -= row_chunk_size;
-          }
-        });
+        while remaining_rows > 0 { // the final chunk may be smaller
+          let chunk_rows = std::cmp::min(row_chunk_size, remaining_rows);
+          chunks.push(std::sync::Mutex::new(vec![G::Scalar::ZERO; chunk_rows]));
+          remaining_rows -= chunk_rows;
+        }
 
         let get_chunk = |row_index: usize| -> usize { row_index / row_chunk_size };
         let get_index = |row_index: usize| -> usize { row_index % row_chunk_size };
@@ -445,7 +926,7 @@ impl<G: Group> R1CSWitness<G> {
   /// Commits to the witness using the supplied generators
   #[tracing::instrument(skip_all, name = "R1CSWitness::commit")]
   pub fn commit(&self, ck: &CommitmentKey<G>) -> Commitment<G> {
-    CE::<G>::commit(ck, &self.W)
+    CE::<G>::commit_small(ck, &self.W)
   }
 }
 
@@ -465,6 +946,25 @@ impl<G: Group> R1CSInstance<G> {
       })
     }
   }
+
+  /// A method to create an instance of the shape obtained by repeating `S` for `num_steps` steps
+  /// (see `R1CSShape::num_io_uniform`)
+  pub fn new_uniform(
+    S: &R1CSShape<G>,
+    num_steps: usize,
+    io_per_step: bool,
+    comm_W: &Commitment<G>,
+    X: &[G::Scalar],
+  ) -> Result<R1CSInstance<G>, SpartanError> {
+    if S.num_io_uniform(num_steps, io_per_step) != X.len() {
+      Err(SpartanError::InvalidInputLength)
+    } else {
+      Ok(R1CSInstance {
+        comm_W: comm_W.clone(),
+        X: X.to_owned(),
+      })
+    }
+  }
 }
 
 impl<G: Group> TranscriptReprTrait<G> for R1CSInstance<G> {
@@ -475,6 +975,14 @@ impl<G: Group> TranscriptReprTrait<G> for R1CSInstance<G> {
     ]
     .concat()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    [
+      self.comm_W.to_transcript_scalars(),
+      self.X.as_slice().to_transcript_scalars(),
+    ]
+    .concat()
+  }
 }
 
 impl<G: Group> RelaxedR1CSWitness<G> {
@@ -590,7 +1098,10 @@ impl<G: Group> RelaxedR1CSInstance<G> {
     U2: &R1CSInstance<G>,
     comm_T: &Commitment<G>,
     r: &G::Scalar,
-  ) -> Result<RelaxedR1CSInstance<G>, SpartanError> {
+  ) -> Result<RelaxedR1CSInstance<G>, SpartanError>
+  where
+    Commitment<G>: HomomorphicCommitmentTrait<G>,
+  {
     let (X1, u1, comm_W_1, comm_E_1) =
       (&self.X, &self.u, &self.comm_W.clone(), &self.comm_E.clone());
     let (X2, comm_W_2) = (&U2.X, &U2.comm_W);
@@ -624,4 +1135,14 @@ impl<G: Group> TranscriptReprTrait<G> for RelaxedR1CSInstance<G> {
     ]
     .concat()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    [
+      self.comm_W.to_transcript_scalars(),
+      self.comm_E.to_transcript_scalars(),
+      vec![self.u],
+      self.X.as_slice().to_transcript_scalars(),
+    ]
+    .concat()
+  }
 }
@@ -6,19 +6,33 @@
 //!
 //! In polynomial.rs we also provide foundational types and functions for manipulating multilinear polynomials.
 pub(crate) mod math;
+pub mod nizk;
 pub mod polys;
 pub mod ppsnark;
 pub mod snark;
+pub mod sumcheck;
 pub mod upsnark;
-mod sumcheck;
+pub mod zksnark;
 
-use crate::{traits::Group, Commitment};
+use crate::{
+  errors::SpartanError,
+  traits::{
+    commitment::CommitmentTrait,
+    evaluation::{BatchEvaluationArgument, EvaluationEngineTrait},
+    Group, TranscriptEngineTrait,
+  },
+  Commitment, CommitmentKey,
+};
 use ff::Field;
-use polys::multilinear::SparsePolynomial;
+use polys::{
+  eq::EqPolynomial,
+  multilinear::{MultilinearPolynomial, SparsePolynomial},
+};
+use sumcheck::SumcheckProof;
 
 use rayon::prelude::*;
 
-fn powers<G: Group>(s: &G::Scalar, n: usize) -> Vec<G::Scalar> {
+pub(crate) fn powers<G: Group>(s: &G::Scalar, n: usize) -> Vec<G::Scalar> {
   assert!(n >= 1);
   let mut powers = Vec::new();
   powers.push(G::Scalar::ONE);
@@ -28,45 +42,25 @@ fn powers<G: Group>(s: &G::Scalar, n: usize) -> Vec<G::Scalar> {
   powers
 }
 
+/// Absorbs the points and the claimed evaluations of a batch of evaluation claims,
+/// so that challenges squeezed afterwards depend on the claims they combine
+pub(crate) fn absorb_claims<G: Group, TE: TranscriptEngineTrait<G>>(
+  transcript: &mut TE,
+  points: &[Vec<G::Scalar>],
+  evals: &[G::Scalar],
+) {
+  for x in points {
+    transcript.absorb(b"x", &x.as_slice());
+  }
+  transcript.absorb(b"e", &evals);
+}
+
 /// A type that holds a witness to a polynomial evaluation instance
 pub struct PolyEvalWitness<G: Group> {
   p: Vec<G::Scalar>, // polynomial
 }
 
 impl<G: Group> PolyEvalWitness<G> {
-  fn pad(W: &[PolyEvalWitness<G>]) -> Vec<PolyEvalWitness<G>> {
-    // determine the maximum size
-    if let Some(n) = W.iter().map(|w| w.p.len()).max() {
-      W.par_iter()
-        .map(|w| {
-          let mut p = vec![G::Scalar::ZERO; n];
-          p[..w.p.len()].copy_from_slice(&w.p);
-          PolyEvalWitness { p }
-        })
-        .collect()
-    } else {
-      Vec::new()
-    }
-  }
-
-  #[tracing::instrument(skip_all, name = "PolyEvalWitness::weighted_sum")]
-  fn weighted_sum(W: &[PolyEvalWitness<G>], s: &[G::Scalar]) -> PolyEvalWitness<G> {
-    assert_eq!(W.len(), s.len());
-    let p = W.par_iter()
-      .zip(s.par_iter())
-      .map(|(w, s_i)| {
-        w.p.iter().map(|w_p_j| *w_p_j * s_i).collect::<Vec<G::Scalar>>()
-      })
-      .reduce_with(|mut acc, item| {
-        for (j, item_j) in item.iter().enumerate() {
-          acc[j] += *item_j;
-        }
-        acc
-      })
-      .unwrap();
-    PolyEvalWitness { p }
-  }
-
   fn batch(p_vec: &[&Vec<G::Scalar>], s: &G::Scalar) -> PolyEvalWitness<G> {
     let powers_of_s = powers::<G>(s, p_vec.len());
     let mut p = vec![G::Scalar::ZERO; p_vec[0].len()];
@@ -87,47 +81,300 @@ pub struct PolyEvalInstance<G: Group> {
 }
 
 impl<G: Group> PolyEvalInstance<G> {
-  fn pad(U: &[PolyEvalInstance<G>]) -> Vec<PolyEvalInstance<G>> {
-    // determine the maximum size
-    if let Some(ell) = U.iter().map(|u| u.x.len()).max() {
-      U.iter()
-        .map(|u| {
-          let mut x = vec![G::Scalar::ZERO; ell - u.x.len()];
-          x.extend(u.x.clone());
-          PolyEvalInstance {
-            c: u.c.clone(),
-            x,
-            e: u.e,
-          }
-        })
-        .collect()
-    } else {
-      Vec::new()
-    }
-  }
-
   fn batch(
     c_vec: &[Commitment<G>],
     x: &[G::Scalar],
     e_vec: &[G::Scalar],
     s: &G::Scalar,
-  ) -> PolyEvalInstance<G> {
+  ) -> Result<PolyEvalInstance<G>, SpartanError> {
     let powers_of_s = powers::<G>(s, c_vec.len());
     let e = e_vec
       .iter()
       .zip(powers_of_s.iter())
       .map(|(e, p)| *e * p)
       .sum();
-    let c = c_vec
-      .iter()
-      .zip(powers_of_s.iter())
-      .map(|(c, p)| c.clone() * *p)
-      .fold(Commitment::<G>::default(), |acc, item| acc + item);
+    let c = Commitment::<G>::combine(c_vec, &powers_of_s)?;
 
-    PolyEvalInstance {
+    Ok(PolyEvalInstance {
       c,
       x: x.to_vec(),
       e,
+    })
+  }
+}
+
+/// Pads the polynomials with zeros to the size of the largest one
+fn pad_polys<F: Field>(polys: &[Vec<F>]) -> Vec<Vec<F>> {
+  let n = polys.iter().map(|p| p.len()).max().unwrap_or(0);
+  polys
+    .par_iter()
+    .map(|p| {
+      let mut padded = vec![F::ZERO; n];
+      padded[..p.len()].copy_from_slice(p);
+      padded
+    })
+    .collect()
+}
+
+/// Pads the points with leading zeros to the length of the longest one,
+/// which matches the evaluations of polynomials padded with `pad_polys`
+fn pad_points<F: Field>(points: &[Vec<F>]) -> Vec<Vec<F>> {
+  let ell = points.iter().map(|x| x.len()).max().unwrap_or(0);
+  points
+    .iter()
+    .map(|x| {
+      let mut padded = vec![F::ZERO; ell - x.len()];
+      padded.extend(x);
+      padded
+    })
+    .collect()
+}
+
+/// Reduces claims `evals[i] = polys[i](points[i])` about polynomials of the same size to claims about
+/// their evaluations at a common point with a sum-check.
+/// Returns the sum-check proof, the common point, and the evaluations at the common point
+pub(crate) fn prove_batch_reduce<G: Group, TE: TranscriptEngineTrait<G>>(
+  transcript: &mut TE,
+  polys: &[Vec<G::Scalar>],
+  points: &[Vec<G::Scalar>],
+  evals: &[G::Scalar],
+) -> Result<(SumcheckProof<G>, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError> {
+  // generate a challenge
+  absorb_claims::<G, _>(transcript, points, evals);
+  let rho = transcript.squeeze(b"r")?;
+  let num_claims = polys.len();
+  let powers_of_rho = powers::<G>(&rho, num_claims);
+  let claim_batch_joint = evals
+    .iter()
+    .zip(powers_of_rho.iter())
+    .map(|(e, p)| *e * p)
+    .sum();
+
+  let mut polys_left: Vec<MultilinearPolynomial<G::Scalar>> = polys
+    .iter()
+    .map(|p| MultilinearPolynomial::new(p.clone()))
+    .collect();
+  let mut polys_right: Vec<MultilinearPolynomial<G::Scalar>> = points
+    .iter()
+    .map(|x| MultilinearPolynomial::new(EqPolynomial::new(x.clone()).evals()))
+    .collect();
+
+  let num_rounds_z = points[0].len();
+  let comb_func = |poly_A_comp: &G::Scalar, poly_B_comp: &G::Scalar| -> G::Scalar {
+    *poly_A_comp * *poly_B_comp
+  };
+  let (sc_proof, r_z, (evals_at_r_z, _)) = SumcheckProof::prove_quad_batch(
+    &claim_batch_joint,
+    num_rounds_z,
+    &mut polys_left,
+    &mut polys_right,
+    &powers_of_rho,
+    comb_func,
+    transcript,
+  )?;
+
+  transcript.absorb(b"l", &evals_at_r_z.as_slice());
+
+  Ok((sc_proof, r_z, evals_at_r_z))
+}
+
+/// Verifies a reduction produced by `prove_batch_reduce` and returns the common point
+pub(crate) fn verify_batch_reduce<G: Group, TE: TranscriptEngineTrait<G>>(
+  transcript: &mut TE,
+  sc_proof: &SumcheckProof<G>,
+  points: &[Vec<G::Scalar>],
+  evals: &[G::Scalar],
+  evals_at_r_z: &[G::Scalar],
+) -> Result<Vec<G::Scalar>, SpartanError> {
+  if evals_at_r_z.len() != points.len() {
+    return Err(SpartanError::ProofVerifyError);
+  }
+
+  // generate a challenge
+  absorb_claims::<G, _>(transcript, points, evals);
+  let rho = transcript.squeeze(b"r")?;
+  let num_claims = points.len();
+  let powers_of_rho = powers::<G>(&rho, num_claims);
+  let claim_batch_joint = evals
+    .iter()
+    .zip(powers_of_rho.iter())
+    .map(|(e, p)| *e * p)
+    .sum();
+
+  let num_rounds_z = points[0].len();
+  let (claim_batch_final, r_z) = sc_proof.verify(claim_batch_joint, num_rounds_z, 2, transcript)?;
+
+  let claim_batch_final_expected = {
+    let poly_rz = EqPolynomial::new(r_z.clone());
+    points
+      .iter()
+      .zip(evals_at_r_z.iter())
+      .zip(powers_of_rho.iter())
+      .map(|((x, e_i), rho_i)| poly_rz.evaluate(x) * e_i * rho_i)
+      .sum()
+  };
+
+  if claim_batch_final != claim_batch_final_expected {
+    return Err(SpartanError::InvalidSumcheckProof);
+  }
+
+  transcript.absorb(b"l", &evals_at_r_z);
+
+  Ok(r_z)
+}
+
+/// Returns the random linear combination of the polynomials, which have the same size, with the provided coefficients
+pub(crate) fn combine_polys<F: Field>(polys: &[Vec<F>], coeffs: &[F]) -> Vec<F> {
+  (0..polys[0].len())
+    .into_par_iter()
+    .map(|j| polys.iter().zip(coeffs.iter()).map(|(p, c)| p[j] * c).sum())
+    .collect()
+}
+
+/// Returns the indices of the claims at each distinct point, in the order of their first claims
+fn group_by_point<F: Field>(points: &[Vec<F>]) -> Vec<Vec<usize>> {
+  let mut groups: Vec<Vec<usize>> = Vec::new();
+  for (i, x) in points.iter().enumerate() {
+    match groups.iter_mut().find(|g| points[g[0]] == *x) {
+      Some(g) => g.push(i),
+      None => groups.push(vec![i]),
     }
   }
+  groups
+}
+
+/// Implements the default `EvaluationEngineTrait::prove_batch`, which requires homomorphic commitments.
+/// Claims at the same point are first combined into one, and a sum-check then reduces the claims
+/// at distinct points to claims at a common point, where a random linear combination of the
+/// polynomials is opened with `EE::prove`
+#[tracing::instrument(skip_all, name = "Spartan2::prove_batch")]
+pub fn prove_batch<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>>(
+  ck: &CommitmentKey<G>,
+  pk: &EE::ProverKey,
+  transcript: &mut TE,
+  comms: &[Commitment<G>],
+  polys: &[Vec<G::Scalar>],
+  points: &[Vec<G::Scalar>],
+  evals: &[G::Scalar],
+) -> Result<BatchEvaluationArgument<G, EE>, SpartanError> {
+  let num_claims = comms.len();
+  if num_claims == 0
+    || polys.len() != num_claims
+    || points.len() != num_claims
+    || evals.len() != num_claims
+    || polys
+      .iter()
+      .zip(points.iter())
+      .any(|(p, x)| p.len() != 1 << x.len())
+  {
+    return Err(SpartanError::InvalidInputLength);
+  }
+
+  // combine the claims at the same point with powers of a challenge
+  transcript.absorb(b"poly_com", &comms);
+  absorb_claims::<G, _>(transcript, points, evals);
+  let c = transcript.squeeze(b"c")?;
+  let (w_vec, u_vec): (Vec<PolyEvalWitness<G>>, Vec<PolyEvalInstance<G>>) = group_by_point(points)
+    .iter()
+    .map(|g| {
+      let p_vec = g.iter().map(|i| &polys[*i]).collect::<Vec<_>>();
+      let c_vec = g.iter().map(|i| comms[*i].clone()).collect::<Vec<_>>();
+      let e_vec = g.iter().map(|i| evals[*i]).collect::<Vec<_>>();
+      Ok((
+        PolyEvalWitness::batch(&p_vec, &c),
+        PolyEvalInstance::batch(&c_vec, &points[g[0]], &e_vec, &c)?,
+      ))
+    })
+    .collect::<Result<Vec<_>, SpartanError>>()?
+    .into_iter()
+    .unzip();
+  let num_claims = u_vec.len();
+  let comms = u_vec.iter().map(|u| u.c.clone()).collect::<Vec<_>>();
+  let evals = u_vec.iter().map(|u| u.e).collect::<Vec<_>>();
+
+  let polys = pad_polys(&w_vec.into_iter().map(|w| w.p).collect::<Vec<_>>()); // pad the polynomials to be of the same size
+  let points = pad_points(&u_vec.into_iter().map(|u| u.x).collect::<Vec<_>>()); // pad the evaluation points
+  // We will now reduce a vector of claims of evaluations at different points into claims about them at the same point.
+  // For example, eval_W =? W(r_y[1..]) and eval_E =? E(r_x) into
+  // two claims: eval_W_prime =? W(rz) and eval_E_prime =? E(rz)
+  // We can then combine the two into one: eval_W_prime + gamma * eval_E_prime =? (W + gamma*E)(rz),
+  // where gamma is a public challenge
+  // Since commitments to W and E are homomorphic, the verifier can compute a commitment
+  // to the batched polynomial.
+  let (sc_proof, r_z, evals_at_r_z) =
+    prove_batch_reduce::<G, _>(transcript, &polys, &points, &evals)?;
+
+  // we now combine evaluation claims at the same point rz into one
+  let gamma = transcript.squeeze(b"g")?;
+  let powers_of_gamma: Vec<G::Scalar> = powers::<G>(&gamma, num_claims);
+  let comm_joint = Commitment::<G>::combine(&comms, &powers_of_gamma)?;
+  let poly_joint = combine_polys(&polys, &powers_of_gamma);
+  let eval_joint = evals_at_r_z
+    .iter()
+    .zip(powers_of_gamma.iter())
+    .map(|(e, g_i)| *e * *g_i)
+    .sum();
+
+  let arg = EE::prove(
+    ck,
+    pk,
+    transcript,
+    &comm_joint,
+    &poly_joint,
+    &r_z,
+    &eval_joint,
+  )?;
+
+  Ok(BatchEvaluationArgument {
+    sc_proof,
+    evals: evals_at_r_z,
+    arg,
+  })
+}
+
+/// Implements the default `EvaluationEngineTrait::verify_batch`, which requires homomorphic commitments
+pub fn verify_batch<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>>(
+  vk: &EE::VerifierKey,
+  transcript: &mut TE,
+  comms: &[Commitment<G>],
+  points: &[Vec<G::Scalar>],
+  evals: &[G::Scalar],
+  arg: &BatchEvaluationArgument<G, EE>,
+) -> Result<(), SpartanError> {
+  let num_claims = comms.len();
+  if num_claims == 0 || points.len() != num_claims || evals.len() != num_claims {
+    return Err(SpartanError::InvalidInputLength);
+  }
+
+  // combine the claims at the same point with powers of a challenge
+  transcript.absorb(b"poly_com", &comms);
+  absorb_claims::<G, _>(transcript, points, evals);
+  let c = transcript.squeeze(b"c")?;
+  let u_vec = group_by_point(points)
+    .iter()
+    .map(|g| {
+      let c_vec = g.iter().map(|i| comms[*i].clone()).collect::<Vec<_>>();
+      let e_vec = g.iter().map(|i| evals[*i]).collect::<Vec<_>>();
+      PolyEvalInstance::batch(&c_vec, &points[g[0]], &e_vec, &c)
+    })
+    .collect::<Result<Vec<PolyEvalInstance<G>>, SpartanError>>()?;
+  let num_claims = u_vec.len();
+  let comms = u_vec.iter().map(|u| u.c.clone()).collect::<Vec<_>>();
+  let evals = u_vec.iter().map(|u| u.e).collect::<Vec<_>>();
+
+  let points = pad_points(&u_vec.into_iter().map(|u| u.x).collect::<Vec<_>>()); // pad the evaluation points
+  let r_z = verify_batch_reduce::<G, _>(transcript, &arg.sc_proof, &points, &evals, &arg.evals)?;
+
+  // we now combine evaluation claims at the same point rz into one
+  let gamma = transcript.squeeze(b"g")?;
+  let powers_of_gamma: Vec<G::Scalar> = powers::<G>(&gamma, num_claims);
+  let comm_joint = Commitment::<G>::combine(&comms, &powers_of_gamma)?;
+  let eval_joint = arg
+    .evals
+    .iter()
+    .zip(powers_of_gamma.iter())
+    .map(|(e, g_i)| *e * *g_i)
+    .sum();
+
+  EE::verify(vk, transcript, &comm_joint, &r_z, &eval_joint, &arg.arg)
 }
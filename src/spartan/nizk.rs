@@ -0,0 +1,646 @@
+//! This module implements non-interactive zero-knowledge proofs (sigma protocols) over
+//! hiding Pedersen commitments of the form `C = <v, G> + r * h`.
+//! They are the building blocks of the zero-knowledge mode of Spartan:
+//! - `KnowledgeProof`: knowledge of an opening of a commitment to a scalar
+//! - `EqualityProof`: two commitments to scalars hide the same value
+//! - `ProductProof`: three commitments to scalars hide `x`, `y`, and `x * y`
+//! - `DotProductProof`: a commitment to a vector `x` and a commitment to a scalar `y` satisfy `y = <x, a>` for a public `a`
+#![allow(clippy::too_many_arguments)]
+use crate::{
+  errors::SpartanError,
+  provider::pedersen::{
+    Commitment as PedersenCommitment, CommitmentEngine as PedersenCommitmentEngine,
+    CommitmentEngineExtTrait, CommitmentKey as PedersenCommitmentKey,
+    CompressedCommitment as PedersenCompressedCommitment,
+  },
+  traits::{
+    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    PrimeGroup, TranscriptEngineTrait,
+  },
+};
+use ff::Field;
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+
+/// Commits to `v` using the generators in `ck` and the blinding factor `r` over `h`
+pub(crate) fn commit<G: PrimeGroup>(
+  ck: &PedersenCommitmentKey<G>,
+  h: &PedersenCommitmentKey<G>,
+  v: &[G::Scalar],
+  r: &G::Scalar,
+) -> PedersenCommitment<G> {
+  PedersenCommitmentEngine::<G>::commit_with_blind(ck, h, v, r)
+}
+
+/// Computes `r * h`, i.e., a commitment to the empty vector with blinding factor `r`
+fn blind<G: PrimeGroup>(h: &PedersenCommitmentKey<G>, r: &G::Scalar) -> PedersenCommitment<G> {
+  PedersenCommitmentEngine::<G>::commit(h, &[*r])
+}
+
+/// Samples a uniformly random scalar to be used as a blinding factor
+pub(crate) fn random_scalar<G: PrimeGroup>() -> G::Scalar {
+  G::Scalar::random(&mut OsRng)
+}
+
+fn decompress<G: PrimeGroup>(
+  c: &PedersenCompressedCommitment<G>,
+) -> Result<PedersenCommitment<G>, SpartanError> {
+  <PedersenCommitment<G> as CommitmentTrait<G>>::decompress(c)
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+  assert_eq!(a.len(), b.len());
+  a.iter().zip(b.iter()).map(|(a_i, b_i)| *a_i * b_i).sum()
+}
+
+/// A proof of knowledge of `(x, r)` such that `C = x * g + r * h`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct KnowledgeProof<G: PrimeGroup> {
+  alpha: PedersenCompressedCommitment<G>,
+  z1: G::Scalar,
+  z2: G::Scalar,
+}
+
+impl<G: PrimeGroup> KnowledgeProof<G> {
+  const fn protocol_name() -> &'static [u8] {
+    b"knowledge proof"
+  }
+
+  /// Proves knowledge of an opening `(x, r)` of `C`
+  pub fn prove<TE: TranscriptEngineTrait<G>>(
+    ck: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    C: &PedersenCommitment<G>,
+    x: &G::Scalar,
+    r: &G::Scalar,
+  ) -> Result<Self, SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+
+    let (t1, t2) = (random_scalar::<G>(), random_scalar::<G>());
+    let alpha = commit(ck, h, &[t1], &t2).compress();
+
+    transcript.absorb(b"C", C);
+    transcript.absorb(b"alpha", &alpha);
+    let c = transcript.squeeze(b"c")?;
+
+    Ok(KnowledgeProof {
+      alpha,
+      z1: *x * c + t1,
+      z2: *r * c + t2,
+    })
+  }
+
+  /// Verifies a proof of knowledge of an opening of `C`
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
+    &self,
+    ck: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    C: &PedersenCommitment<G>,
+  ) -> Result<(), SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+    transcript.absorb(b"C", C);
+    transcript.absorb(b"alpha", &self.alpha);
+    let c = transcript.squeeze(b"c")?;
+
+    self.check(ck, h, C, &c)
+  }
+
+  /// Checks the verification equation for a given challenge
+  fn check(
+    &self,
+    ck: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    C: &PedersenCommitment<G>,
+    c: &G::Scalar,
+  ) -> Result<(), SpartanError> {
+    let lhs = commit(ck, h, &[self.z1], &self.z2);
+    let rhs = *C * *c + decompress(&self.alpha)?;
+    if lhs == rhs {
+      Ok(())
+    } else {
+      Err(SpartanError::ProofVerifyError)
+    }
+  }
+}
+
+/// A proof that two commitments `C1 = v * g + r1 * h` and `C2 = v * g + r2 * h` hide the same value
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EqualityProof<G: PrimeGroup> {
+  alpha: PedersenCompressedCommitment<G>,
+  z: G::Scalar,
+}
+
+impl<G: PrimeGroup> EqualityProof<G> {
+  const fn protocol_name() -> &'static [u8] {
+    b"equality proof"
+  }
+
+  /// Proves that `C1` and `C2` hide the same value given their blinding factors
+  pub fn prove<TE: TranscriptEngineTrait<G>>(
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    C1: &PedersenCommitment<G>,
+    C2: &PedersenCommitment<G>,
+    r1: &G::Scalar,
+    r2: &G::Scalar,
+  ) -> Result<Self, SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+
+    // C1 - C2 = (r1 - r2) * h, so this is a proof of knowledge of a discrete log base h
+    let t = random_scalar::<G>();
+    let alpha = blind::<G>(h, &t).compress();
+
+    transcript.absorb(b"C1", C1);
+    transcript.absorb(b"C2", C2);
+    transcript.absorb(b"alpha", &alpha);
+    let c = transcript.squeeze(b"c")?;
+
+    Ok(EqualityProof {
+      alpha,
+      z: c * (*r1 - *r2) + t,
+    })
+  }
+
+  /// Verifies that `C1` and `C2` hide the same value
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
+    &self,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    C1: &PedersenCommitment<G>,
+    C2: &PedersenCommitment<G>,
+  ) -> Result<(), SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+    transcript.absorb(b"C1", C1);
+    transcript.absorb(b"C2", C2);
+    transcript.absorb(b"alpha", &self.alpha);
+    let c = transcript.squeeze(b"c")?;
+
+    self.check(h, C1, C2, &c)
+  }
+
+  /// Checks the verification equation for a given challenge
+  fn check(
+    &self,
+    h: &PedersenCommitmentKey<G>,
+    C1: &PedersenCommitment<G>,
+    C2: &PedersenCommitment<G>,
+    c: &G::Scalar,
+  ) -> Result<(), SpartanError> {
+    let lhs = blind::<G>(h, &self.z);
+    let rhs = (*C1 + *C2 * (-G::Scalar::ONE)) * *c + decompress(&self.alpha)?;
+    if lhs == rhs {
+      Ok(())
+    } else {
+      Err(SpartanError::ProofVerifyError)
+    }
+  }
+}
+
+/// A proof that commitments `X`, `Y`, and `Z` hide scalars `x`, `y`, and `z` with `z = x * y`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProductProof<G: PrimeGroup> {
+  alpha: PedersenCompressedCommitment<G>,
+  beta: PedersenCompressedCommitment<G>,
+  delta: PedersenCompressedCommitment<G>,
+  z: [G::Scalar; 5],
+}
+
+impl<G: PrimeGroup> ProductProof<G> {
+  const fn protocol_name() -> &'static [u8] {
+    b"product proof"
+  }
+
+  /// Proves that `Z` hides the product of the values hidden in `X` and `Y`
+  pub fn prove<TE: TranscriptEngineTrait<G>>(
+    ck: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    X: &PedersenCommitment<G>,
+    Y: &PedersenCommitment<G>,
+    Z: &PedersenCommitment<G>,
+    x: &G::Scalar,
+    r_x: &G::Scalar,
+    y: &G::Scalar,
+    r_y: &G::Scalar,
+    r_z: &G::Scalar,
+  ) -> Result<Self, SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+
+    let b = (0..5).map(|_| random_scalar::<G>()).collect::<Vec<_>>();
+
+    let alpha = commit(ck, h, &[b[0]], &b[1]).compress();
+    let beta = commit(ck, h, &[b[2]], &b[3]).compress();
+    // delta commits to b[2] * x using X as the base for the message
+    let delta = (*X * b[2] + blind::<G>(h, &b[4])).compress();
+
+    transcript.absorb(b"X", X);
+    transcript.absorb(b"Y", Y);
+    transcript.absorb(b"Z", Z);
+    transcript.absorb(b"alpha", &alpha);
+    transcript.absorb(b"beta", &beta);
+    transcript.absorb(b"delta", &delta);
+    let c = transcript.squeeze(b"c")?;
+
+    let z = [
+      b[0] + c * x,
+      b[1] + c * r_x,
+      b[2] + c * y,
+      b[3] + c * r_y,
+      b[4] + c * (*r_z - *r_x * y),
+    ];
+
+    Ok(ProductProof {
+      alpha,
+      beta,
+      delta,
+      z,
+    })
+  }
+
+  /// Verifies that `Z` hides the product of the values hidden in `X` and `Y`
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
+    &self,
+    ck: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    X: &PedersenCommitment<G>,
+    Y: &PedersenCommitment<G>,
+    Z: &PedersenCommitment<G>,
+  ) -> Result<(), SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+    transcript.absorb(b"X", X);
+    transcript.absorb(b"Y", Y);
+    transcript.absorb(b"Z", Z);
+    transcript.absorb(b"alpha", &self.alpha);
+    transcript.absorb(b"beta", &self.beta);
+    transcript.absorb(b"delta", &self.delta);
+    let c = transcript.squeeze(b"c")?;
+
+    self.check(ck, h, X, Y, Z, &c)
+  }
+
+  /// Checks the verification equations for a given challenge
+  fn check(
+    &self,
+    ck: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    X: &PedersenCommitment<G>,
+    Y: &PedersenCommitment<G>,
+    Z: &PedersenCommitment<G>,
+    c: &G::Scalar,
+  ) -> Result<(), SpartanError> {
+    let z = &self.z;
+    let check_alpha = *X * *c + decompress(&self.alpha)? == commit(ck, h, &[z[0]], &z[1]);
+    let check_beta = *Y * *c + decompress(&self.beta)? == commit(ck, h, &[z[2]], &z[3]);
+    let check_delta =
+      *Z * *c + decompress(&self.delta)? == *X * z[2] + blind::<G>(h, &z[4]);
+
+    if check_alpha && check_beta && check_delta {
+      Ok(())
+    } else {
+      Err(SpartanError::InvalidProductProof)
+    }
+  }
+}
+
+/// A proof that a commitment `Cx` to a vector `x` and a commitment `Cy` to a scalar `y`
+/// satisfy `y = <x, a>` for a public vector `a`. The proof reveals nothing else about `x`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct DotProductProof<G: PrimeGroup> {
+  delta: PedersenCompressedCommitment<G>,
+  beta: PedersenCompressedCommitment<G>,
+  z: Vec<G::Scalar>,
+  z_delta: G::Scalar,
+  z_beta: G::Scalar,
+}
+
+impl<G: PrimeGroup> DotProductProof<G> {
+  const fn protocol_name() -> &'static [u8] {
+    b"dot product proof"
+  }
+
+  /// Proves that `y = <x, a>`, where `x` is committed with `ck_n` and `y` with `ck_1`
+  pub fn prove<TE: TranscriptEngineTrait<G>>(
+    ck_n: &PedersenCommitmentKey<G>,
+    ck_1: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    x_vec: &[G::Scalar],
+    r_x: &G::Scalar,
+    a_vec: &[G::Scalar],
+    y: &G::Scalar,
+    r_y: &G::Scalar,
+  ) -> Result<(Self, PedersenCommitment<G>, PedersenCommitment<G>), SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+
+    if x_vec.len() != a_vec.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // sample a random mask for the witness vector and its inner product with a
+    let d_vec = (0..x_vec.len())
+      .map(|_| random_scalar::<G>())
+      .collect::<Vec<_>>();
+    let (r_delta, r_beta) = (random_scalar::<G>(), random_scalar::<G>());
+
+    let Cx = commit(ck_n, h, x_vec, r_x);
+    let Cy = commit(ck_1, h, &[*y], r_y);
+    let delta = commit(ck_n, h, &d_vec, &r_delta).compress();
+    let beta = commit(ck_1, h, &[inner_product(a_vec, &d_vec)], &r_beta).compress();
+
+    transcript.absorb(b"Cx", &Cx);
+    transcript.absorb(b"Cy", &Cy);
+    transcript.absorb(b"a", &a_vec);
+    transcript.absorb(b"delta", &delta);
+    transcript.absorb(b"beta", &beta);
+    let c = transcript.squeeze(b"c")?;
+
+    let z = x_vec
+      .iter()
+      .zip(d_vec.iter())
+      .map(|(x_i, d_i)| c * x_i + d_i)
+      .collect::<Vec<_>>();
+
+    Ok((
+      DotProductProof {
+        delta,
+        beta,
+        z,
+        z_delta: c * r_x + r_delta,
+        z_beta: c * r_y + r_beta,
+      },
+      Cx,
+      Cy,
+    ))
+  }
+
+  /// Verifies that `Cx` and `Cy` hide `x` and `y` such that `y = <x, a>`
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
+    &self,
+    ck_n: &PedersenCommitmentKey<G>,
+    ck_1: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+    a_vec: &[G::Scalar],
+    Cx: &PedersenCommitment<G>,
+    Cy: &PedersenCommitment<G>,
+  ) -> Result<(), SpartanError> {
+    transcript.dom_sep(Self::protocol_name());
+    transcript.absorb(b"Cx", Cx);
+    transcript.absorb(b"Cy", Cy);
+    transcript.absorb(b"a", &a_vec);
+    transcript.absorb(b"delta", &self.delta);
+    transcript.absorb(b"beta", &self.beta);
+    let c = transcript.squeeze(b"c")?;
+
+    self.check(ck_n, ck_1, h, a_vec, Cx, Cy, &c)
+  }
+
+  /// Checks the verification equations for a given challenge
+  fn check(
+    &self,
+    ck_n: &PedersenCommitmentKey<G>,
+    ck_1: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    a_vec: &[G::Scalar],
+    Cx: &PedersenCommitment<G>,
+    Cy: &PedersenCommitment<G>,
+    c: &G::Scalar,
+  ) -> Result<(), SpartanError> {
+    if self.z.len() != a_vec.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    let check_x = *Cx * *c + decompress(&self.delta)? == commit(ck_n, h, &self.z, &self.z_delta);
+    let check_y = *Cy * *c + decompress(&self.beta)?
+      == commit(ck_1, h, &[inner_product(&self.z, a_vec)], &self.z_beta);
+
+    if check_x && check_y {
+      Ok(())
+    } else {
+      Err(SpartanError::ProofVerifyError)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::provider::{bn256_grumpkin::bn256, keccak::Keccak256Transcript};
+
+  fn keys<G: PrimeGroup>(n: usize) -> (
+    PedersenCommitmentKey<G>,
+    PedersenCommitmentKey<G>,
+    PedersenCommitmentKey<G>,
+  ) {
+    (
+      PedersenCommitmentEngine::<G>::setup(b"test_ck_n", n).unwrap(),
+      PedersenCommitmentEngine::<G>::setup(b"test_ck_1", 1).unwrap(),
+      PedersenCommitmentEngine::<G>::setup(b"test_h", 1).unwrap(),
+    )
+  }
+
+  fn random_vec<G: PrimeGroup>(n: usize) -> Vec<G::Scalar> {
+    (0..n).map(|_| random_scalar::<G>()).collect()
+  }
+
+  // Each simulator below is given the statement and a challenge, but no witness. It samples the
+  // responses uniformly at random and solves the verification equations for the prover's first messages.
+  // For every challenge, a real transcript also has uniformly random responses, and its first
+  // messages are the unique values that make the verification equations hold. So the two
+  // transcript distributions are identical, which the tests check by running both through the same verifier.
+
+  fn test_knowledge_proof_with<G: PrimeGroup>() {
+    let (_, ck_1, h) = keys::<G>(1);
+    let (x, r) = (random_scalar::<G>(), random_scalar::<G>());
+    let C = commit(&ck_1, &h, &[x], &r);
+
+    // real transcript
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let proof = KnowledgeProof::prove(&ck_1, &h, &mut transcript, &C, &x, &r).unwrap();
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof.verify(&ck_1, &h, &mut transcript, &C).is_ok());
+
+    // a wrong statement is rejected
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let C_bad = commit(&ck_1, &h, &[x + G::Scalar::ONE], &r);
+    assert!(proof.verify(&ck_1, &h, &mut transcript, &C_bad).is_err());
+
+    // simulated transcript
+    let c = random_scalar::<G>();
+    let (z1, z2) = (random_scalar::<G>(), random_scalar::<G>());
+    let alpha = (commit(&ck_1, &h, &[z1], &z2) + C * (-c)).compress();
+    let simulated = KnowledgeProof { alpha, z1, z2 };
+    assert!(simulated.check(&ck_1, &h, &C, &c).is_ok());
+  }
+
+  fn test_equality_proof_with<G: PrimeGroup>() {
+    let (_, ck_1, h) = keys::<G>(1);
+    let (v, r1, r2) = (
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+    );
+    let C1 = commit(&ck_1, &h, &[v], &r1);
+    let C2 = commit(&ck_1, &h, &[v], &r2);
+
+    // real transcript
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let proof = EqualityProof::prove(&h, &mut transcript, &C1, &C2, &r1, &r2).unwrap();
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof.verify(&h, &mut transcript, &C1, &C2).is_ok());
+
+    // commitments to different values are rejected
+    let C3 = commit(&ck_1, &h, &[v + G::Scalar::ONE], &r2);
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let proof = EqualityProof::prove(&h, &mut transcript, &C1, &C3, &r1, &r2).unwrap();
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof.verify(&h, &mut transcript, &C1, &C3).is_err());
+
+    // simulated transcript
+    let (c, z) = (random_scalar::<G>(), random_scalar::<G>());
+    let alpha = (blind::<G>(&h, &z) + (C1 + C2 * (-G::Scalar::ONE)) * (-c)).compress();
+    let simulated = EqualityProof { alpha, z };
+    assert!(simulated.check(&h, &C1, &C2, &c).is_ok());
+  }
+
+  fn test_product_proof_with<G: PrimeGroup>() {
+    let (_, ck_1, h) = keys::<G>(1);
+    let (x, y) = (random_scalar::<G>(), random_scalar::<G>());
+    let (r_x, r_y, r_z) = (
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+    );
+    let X = commit(&ck_1, &h, &[x], &r_x);
+    let Y = commit(&ck_1, &h, &[y], &r_y);
+    let Z = commit(&ck_1, &h, &[x * y], &r_z);
+
+    // real transcript
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let proof =
+      ProductProof::prove(&ck_1, &h, &mut transcript, &X, &Y, &Z, &x, &r_x, &y, &r_y, &r_z)
+        .unwrap();
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof.verify(&ck_1, &h, &mut transcript, &X, &Y, &Z).is_ok());
+
+    // a commitment to a different product is rejected
+    let Z_bad = commit(&ck_1, &h, &[x * y + G::Scalar::ONE], &r_z);
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof.verify(&ck_1, &h, &mut transcript, &X, &Y, &Z_bad).is_err());
+
+    // simulated transcript
+    let c = random_scalar::<G>();
+    let z = [
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+    ];
+    let simulated = ProductProof {
+      alpha: (commit(&ck_1, &h, &[z[0]], &z[1]) + X * (-c)).compress(),
+      beta: (commit(&ck_1, &h, &[z[2]], &z[3]) + Y * (-c)).compress(),
+      delta: (X * z[2] + blind::<G>(&h, &z[4]) + Z * (-c)).compress(),
+      z,
+    };
+    assert!(simulated.check(&ck_1, &h, &X, &Y, &Z, &c).is_ok());
+  }
+
+  fn test_dot_product_proof_with<G: PrimeGroup>() {
+    let n = 8;
+    let (ck_n, ck_1, h) = keys::<G>(n);
+    let x_vec = random_vec::<G>(n);
+    let a_vec = random_vec::<G>(n);
+    let y = inner_product(&x_vec, &a_vec);
+    let (r_x, r_y) = (random_scalar::<G>(), random_scalar::<G>());
+
+    // real transcript
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let (proof, Cx, Cy) = DotProductProof::prove(
+      &ck_n,
+      &ck_1,
+      &h,
+      &mut transcript,
+      &x_vec,
+      &r_x,
+      &a_vec,
+      &y,
+      &r_y,
+    )
+    .unwrap();
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof
+      .verify(&ck_n, &ck_1, &h, &mut transcript, &a_vec, &Cx, &Cy)
+      .is_ok());
+
+    // a commitment to a wrong inner product is rejected
+    let Cy_bad = commit(&ck_1, &h, &[y + G::Scalar::ONE], &r_y);
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof
+      .verify(&ck_n, &ck_1, &h, &mut transcript, &a_vec, &Cx, &Cy_bad)
+      .is_err());
+
+    // proving the same statement twice yields unrelated transcripts
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let (proof2, _, _) = DotProductProof::prove(
+      &ck_n,
+      &ck_1,
+      &h,
+      &mut transcript,
+      &x_vec,
+      &r_x,
+      &a_vec,
+      &y,
+      &r_y,
+    )
+    .unwrap();
+    assert_ne!(proof.z, proof2.z);
+    assert_ne!(proof.delta, proof2.delta);
+
+    // simulated transcript
+    let c = random_scalar::<G>();
+    let z = random_vec::<G>(n);
+    let (z_delta, z_beta) = (random_scalar::<G>(), random_scalar::<G>());
+    let simulated = DotProductProof {
+      delta: (commit(&ck_n, &h, &z, &z_delta) + Cx * (-c)).compress(),
+      beta: (commit(&ck_1, &h, &[inner_product(&z, &a_vec)], &z_beta) + Cy * (-c)).compress(),
+      z,
+      z_delta,
+      z_beta,
+    };
+    assert!(simulated
+      .check(&ck_n, &ck_1, &h, &a_vec, &Cx, &Cy, &c)
+      .is_ok());
+  }
+
+  #[test]
+  fn test_knowledge_proof() {
+    test_knowledge_proof_with::<pasta_curves::pallas::Point>();
+    test_knowledge_proof_with::<bn256::Point>();
+  }
+
+  #[test]
+  fn test_equality_proof() {
+    test_equality_proof_with::<pasta_curves::pallas::Point>();
+    test_equality_proof_with::<bn256::Point>();
+  }
+
+  #[test]
+  fn test_product_proof() {
+    test_product_proof_with::<pasta_curves::pallas::Point>();
+    test_product_proof_with::<bn256::Point>();
+  }
+
+  #[test]
+  fn test_dot_product_proof() {
+    test_dot_product_proof_with::<pasta_curves::pallas::Point>();
+    test_dot_product_proof_with::<bn256::Point>();
+  }
+}
@@ -1,7 +1,10 @@
 //! `EqPolynomial`: Represents multilinear extension of equality polynomials, evaluated based on binary input values.
+//! `SplitEqPolynomial`: Holds an `EqPolynomial` in factored form while a sum-check binds its variables.
 
 use ff::PrimeField;
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::prelude::{
+  IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
 
 /// Represents the multilinear extension polynomial (MLE) of the equality polynomial $eq(x,e)$, denoted as $\tilde{eq}(x, e)$.
 ///
@@ -40,6 +43,71 @@ impl<Scalar: PrimeField> EqPolynomial<Scalar> {
       .fold(Scalar::ONE, |acc, item| acc * item)
   }
 
+  /// Evaluates the `EqPolynomial` at the point of the boolean hypercube whose bits are those of `e`.
+  ///
+  /// Panics if `e` does not fit in `|r|` bits.
+  pub fn evaluate_index(&self, e: usize) -> Scalar {
+    let ell = self.r.len();
+    assert!(e < 1 << ell);
+    (0..ell)
+      .map(|i| {
+        if (e >> (ell - 1 - i)) & 1 == 1 {
+          self.r[i]
+        } else {
+          Scalar::ONE - self.r[i]
+        }
+      })
+      .fold(Scalar::ONE, |acc, item| acc * item)
+  }
+
+  /// Computes $\sum_{e < n} \tilde{eq}(r, e)$, i.e., the sum of the first `n` entries of `evals`.
+  ///
+  /// Panics if `n > 2^|r|`.
+  pub fn sum_evals_below(&self, n: usize) -> Scalar {
+    let factors = self
+      .r
+      .iter()
+      .map(|r| (Scalar::ONE - r, *r))
+      .collect::<Vec<_>>();
+    sum_below(n, &factors)
+  }
+
+  /// Computes $\sum_{e < n} \tilde{eq}(r, e) \cdot \tilde{eq}(rx, e)$, which for `n = 2^|r|` is `evaluate(rx)`.
+  ///
+  /// Panics if `rx` and `r` have different lengths, or if `n > 2^|r|`.
+  pub fn evaluate_below(&self, rx: &[Scalar], n: usize) -> Scalar {
+    assert_eq!(self.r.len(), rx.len());
+    sum_below(n, &eq_factors(&self.r, rx))
+  }
+
+  /// Evaluates the multilinear extension of the "shift by one" relation restricted to `[0, n)` at `(r, rx)`, i.e.,
+  /// $\sum_{e} \tilde{eq}(r, e) \cdot \tilde{eq}(rx, e - 1)$ over all $1 \le e < n$.
+  ///
+  /// Over the boolean hypercube, $e - 1$ agrees with $e$ on the bits above the lowest one bit of $e$,
+  /// clears that bit, and sets the zeros below it. The sum ranges over the position of that bit.
+  ///
+  /// Panics if `rx` and `r` have different lengths, or if `n > 2^|r|`.
+  pub fn evaluate_shifted(&self, rx: &[Scalar], n: usize) -> Scalar {
+    assert_eq!(self.r.len(), rx.len());
+    let ell = rx.len();
+    assert!(n <= 1 << ell);
+    let factors = eq_factors(&self.r, rx);
+
+    let mut eval = Scalar::ZERO;
+    let mut suffix = Scalar::ONE; // product over the bits below the current position
+    for i in (0..ell).rev() {
+      // `e` is `h * 2^(k + 1) + 2^k` for the bits `h` above the lowest one bit `k`, so `e < n` bounds `h`
+      let k = ell - 1 - i;
+      if n > 1 << k {
+        let n_above = (n - (1 << k)).div_ceil(1 << (k + 1));
+        eval += sum_below(n_above, &factors[..i]) * self.r[i] * (Scalar::ONE - rx[i]) * suffix;
+      }
+      suffix *= (Scalar::ONE - self.r[i]) * rx[i];
+    }
+
+    eval
+  }
+
   /// Evaluates the `EqPolynomial` at all the `2^|r|` points in its domain.
   ///
   /// Returns a vector of Scalars, each corresponding to the polynomial evaluation at a specific point.
@@ -85,11 +153,168 @@ impl<Scalar: PrimeField> EqPolynomial<Scalar> {
   }
 }
 
+/// The `EqPolynomial` $\tilde{eq}(\tau, x)$ as a sum-check binds the variables of $x$ from the top, in factored form.
+///
+/// Instead of the $2^{|\tau|}$ evaluations from `EqPolynomial::evals`, it holds the product of the factors of the
+/// variables bound so far and the halves from `compute_factored_evals` over the variables after the current one,
+/// which take $O(2^{|\tau| / 2})$ space. Since $\tilde{eq}(\tau, x)$ is linear in the current variable with known
+/// coefficients, a sum-check prover for $\tilde{eq}(\tau, x) \cdot g(x)$ only needs the round polynomials of $g$,
+/// which have one degree less (Gruen's optimization).
+pub struct SplitEqPolynomial<Scalar: PrimeField> {
+  tau: Vec<Scalar>,
+  num_bound: usize,
+  scalar: Scalar, // eq(tau_i, r_i) over the bound variables
+  E_out: Vec<Scalar>,
+  E_in: Vec<Scalar>,
+}
+
+impl<Scalar: PrimeField> SplitEqPolynomial<Scalar> {
+  /// Creates a new `SplitEqPolynomial` with no variable bound.
+  pub fn new(tau: Vec<Scalar>) -> Self {
+    let (E_out, E_in) = match tau.split_first() {
+      Some((_, rest)) => EqPolynomial::new(rest.to_vec()).compute_factored_evals(),
+      None => (vec![Scalar::ONE], vec![Scalar::ONE]),
+    };
+    Self {
+      tau,
+      num_bound: 0,
+      scalar: Scalar::ONE,
+      E_out,
+      E_in,
+    }
+  }
+
+  /// Returns the number of variables that are not bound yet.
+  pub fn get_num_vars(&self) -> usize {
+    self.tau.len() - self.num_bound
+  }
+
+  /// Returns the number of evaluations over the Boolean hypercube of the unbound variables.
+  pub fn len(&self) -> usize {
+    1 << self.get_num_vars()
+  }
+
+  /// Returns true if all the variables are bound.
+  pub fn is_empty(&self) -> bool {
+    self.get_num_vars() == 0
+  }
+
+  /// Returns the evaluations of $\tilde{eq}$ over the first half of the variables after the current one, where the
+  /// evaluation at the point with index `i * E_in().len() + j` is `E_out()[i] * E_in()[j]` up to the linear factor.
+  pub fn E_out(&self) -> &[Scalar] {
+    &self.E_out
+  }
+
+  /// Returns the evaluations of $\tilde{eq}$ over the second half of the variables after the current one.
+  pub fn E_in(&self) -> &[Scalar] {
+    &self.E_in
+  }
+
+  /// Returns the evaluations at 0 and 1 of the factor of $\tilde{eq}$ that is linear in the current variable,
+  /// including the factors of the bound variables.
+  ///
+  /// Panics if all the variables are bound.
+  pub fn linear_factor(&self) -> (Scalar, Scalar) {
+    let tau = self.tau[self.num_bound];
+    let eval_1 = self.scalar * tau;
+    (self.scalar - eval_1, eval_1)
+  }
+
+  /// Returns the evaluation at 1 of a quadratic $t$, given $t(0)$ and the claim $s(0) + s(1)$ for the round polynomial
+  /// $s(X) = l(X) \cdot t(X)$, where $l$ is the linear factor. Returns `None` if $l(1)$ is zero.
+  pub fn quadratic_eval_1(&self, claim: &Scalar, eval_0: &Scalar) -> Option<Scalar> {
+    let (l_0, l_1) = self.linear_factor();
+    Option::<Scalar>::from(l_1.invert()).map(|inv| (*claim - l_0 * eval_0) * inv)
+  }
+
+  /// Returns the evaluations at 0, 1, 2, and 3 of the round polynomial $s(X) = l(X) \cdot t(X)$, where $l$ is
+  /// the linear factor and $t$ is the quadratic with `evals` at 0, 1, and 2. The evaluation at 1 is derived
+  /// from the claim $s(0) + s(1)$, as sum-check provers do.
+  pub fn round_evals(&self, claim: &Scalar, evals: &[Scalar; 3]) -> Vec<Scalar> {
+    let (l_0, l_1) = self.linear_factor();
+    let l_2 = l_1.double() - l_0;
+    let l_3 = l_2 + l_1 - l_0;
+    let eval_3 = evals[0] + (evals[2] - evals[1]).double() + (evals[2] - evals[1]);
+
+    let eval_0 = l_0 * evals[0];
+    vec![eval_0, *claim - eval_0, l_2 * evals[2], l_3 * eval_3]
+  }
+
+  /// Returns the value of $\tilde{eq}(\tau, r)$ once all the variables are bound to `r`.
+  ///
+  /// Panics if a variable is not bound.
+  pub fn eval(&self) -> Scalar {
+    assert!(self.is_empty());
+    self.scalar
+  }
+
+  /// Binds the current variable to `r`.
+  pub fn bound_poly_var_top(&mut self, r: &Scalar) {
+    let (eval_0, eval_1) = self.linear_factor();
+    self.scalar = eval_0 + (eval_1 - eval_0) * r;
+    self.num_bound += 1;
+
+    // the next variable leaves the tables; summing over it is free, since its factors add up to one
+    let shrink = |E: &mut Vec<Scalar>| {
+      let n = E.len() / 2;
+      let (left, right) = E.split_at_mut(n);
+      left
+        .par_iter_mut()
+        .zip(right.par_iter())
+        .for_each(|(a, b)| *a += *b);
+      E.truncate(n);
+    };
+    if self.E_out.len() > 1 {
+      shrink(&mut self.E_out);
+    } else if self.E_in.len() > 1 {
+      shrink(&mut self.E_in);
+    }
+  }
+}
+
+/// Returns the factors `(eq(r_i, 0) * eq(rx_i, 0), eq(r_i, 1) * eq(rx_i, 1))` of `eq(r, e) * eq(rx, e)` for every bit of `e`
+fn eq_factors<Scalar: PrimeField>(r: &[Scalar], rx: &[Scalar]) -> Vec<(Scalar, Scalar)> {
+  r.iter()
+    .zip(rx.iter())
+    .map(|(r, rx)| ((Scalar::ONE - r) * (Scalar::ONE - rx), *r * rx))
+    .collect()
+}
+
+/// Computes $\sum_{e < n} \prod_i f_i(e_i)$ over the big-endian bits $e_i$ of `e`,
+/// where `factors[i] = (f_i(0), f_i(1))`, in time linear in the number of bits.
+fn sum_below<Scalar: PrimeField>(n: usize, factors: &[(Scalar, Scalar)]) -> Scalar {
+  let ell = factors.len();
+  assert!(n <= 1 << ell);
+
+  // suffix[i] is the sum over all the bits from position i onwards
+  let mut suffix = vec![Scalar::ONE; ell + 1];
+  for i in (0..ell).rev() {
+    suffix[i] = suffix[i + 1] * (factors[i].0 + factors[i].1);
+  }
+  if n == 1 << ell {
+    return suffix[0];
+  }
+
+  // every `e < n` agrees with `n` on the bits above some one bit of `n`, where `e` has a zero bit
+  let mut sum = Scalar::ZERO;
+  let mut prefix = Scalar::ONE;
+  for (i, (f_0, f_1)) in factors.iter().enumerate() {
+    if (n >> (ell - 1 - i)) & 1 == 1 {
+      sum += prefix * f_0 * suffix[i + 1];
+      prefix *= f_1;
+    } else {
+      prefix *= f_0;
+    }
+  }
+  sum
+}
+
 #[cfg(test)]
 mod tests {
   use crate::provider;
 
   use super::*;
+  use crate::spartan::polys::multilinear::MultilinearPolynomial;
   use pasta_curves::Fp;
 
   fn test_eq_polynomial_with<F: PrimeField>() {
@@ -110,6 +335,71 @@ mod tests {
     }
   }
 
+  fn test_evaluate_below_with<F: PrimeField>() {
+    let mut rng = rand::thread_rng();
+    for ell in 0..4 {
+      let r = (0..ell).map(|_| F::random(&mut rng)).collect::<Vec<_>>();
+      let rx = (0..ell).map(|_| F::random(&mut rng)).collect::<Vec<_>>();
+
+      let (eq_r, eq_rx) = (EqPolynomial::new(r.clone()), EqPolynomial::new(rx.clone()));
+      let (evals_r, evals_rx) = (eq_r.evals(), eq_rx.evals());
+      for n in 0..=evals_r.len() {
+        let expected_sum = (0..n).map(|i| evals_r[i]).fold(F::ZERO, |acc, item| acc + item);
+        let expected_below = (0..n)
+          .map(|i| evals_r[i] * evals_rx[i])
+          .fold(F::ZERO, |acc, item| acc + item);
+        let expected_shifted = (1..n)
+          .map(|i| evals_r[i] * evals_rx[i - 1])
+          .fold(F::ZERO, |acc, item| acc + item);
+
+        assert_eq!(eq_r.sum_evals_below(n), expected_sum);
+        assert_eq!(eq_r.evaluate_below(&rx, n), expected_below);
+        assert_eq!(eq_r.evaluate_shifted(&rx, n), expected_shifted);
+        if n < evals_r.len() {
+          assert_eq!(eq_r.evaluate_index(n), evals_r[n]);
+        }
+      }
+    }
+  }
+
+  fn test_split_eq_polynomial_with<F: PrimeField>() {
+    let mut rng = rand::thread_rng();
+    for ell in 0..6 {
+      let tau = (0..ell).map(|_| F::random(&mut rng)).collect::<Vec<_>>();
+      let r = (0..ell).map(|_| F::random(&mut rng)).collect::<Vec<_>>();
+      let mut evals = MultilinearPolynomial::new(EqPolynomial::new(tau.clone()).evals());
+      let mut eq = SplitEqPolynomial::new(tau.clone());
+
+      for r_i in r.iter() {
+        // the split form agrees with the bound table at every point
+        let (l_0, l_1) = eq.linear_factor();
+        let half = eq.len() / 2;
+        assert_eq!(eq.E_out().len() * eq.E_in().len(), half);
+        for i in 0..half {
+          let e = eq.E_out()[i / eq.E_in().len()] * eq.E_in()[i % eq.E_in().len()];
+          assert_eq!(evals[i], l_0 * e);
+          assert_eq!(evals[half + i], l_1 * e);
+        }
+
+        evals.bound_poly_var_top(r_i);
+        eq.bound_poly_var_top(r_i);
+      }
+      assert_eq!(eq.eval(), EqPolynomial::new(tau).evaluate(&r));
+    }
+  }
+
+  #[test]
+  fn test_split_eq_polynomial() {
+    test_split_eq_polynomial_with::<Fp>();
+    test_split_eq_polynomial_with::<provider::bn256_grumpkin::bn256::Scalar>();
+  }
+
+  #[test]
+  fn test_evaluate_below() {
+    test_evaluate_below_with::<Fp>();
+    test_evaluate_below_with::<provider::bn256_grumpkin::bn256::Scalar>();
+  }
+
   #[test]
   fn test_eq_polynomial() {
     test_eq_polynomial_with::<Fp>();
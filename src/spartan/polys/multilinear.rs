@@ -134,13 +134,11 @@ impl<Scalar: PrimeField> MultilinearPolynomial<Scalar> {
     &self.Z
   }
 
-  /// Bounds the polynomial's top variables using the given scalars.
+  /// Bounds the polynomial's top variables using the given scalars, one for each assignment of them.
   #[tracing::instrument(skip_all, name = "MultilinearPolynomial::bound")]
   pub fn bound(&self, L: &[Scalar]) -> Vec<Scalar> {
-    let (left_num_vars, right_num_vars) =
-      EqPolynomial::<Scalar>::compute_factored_lens(self.num_vars);
-    let L_size = (2_usize).pow(left_num_vars as u32);
-    let R_size = (2_usize).pow(right_num_vars as u32);
+    let L_size = L.len();
+    let R_size = self.Z.len() / L_size;
 
     (0..R_size)
       .into_par_iter()
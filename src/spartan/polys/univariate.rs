@@ -23,8 +23,11 @@ pub struct CompressedUniPoly<Scalar: PrimeField> {
 
 impl<Scalar: PrimeField> UniPoly<Scalar> {
   pub fn from_evals(evals: &[Scalar]) -> Self {
-    // we only support degree-2 or degree-3 univariate polynomials
-    assert!(evals.len() == 3 || evals.len() == 4);
+    // degree-2 and degree-3 polynomials, the common case, are interpolated in closed form
+    assert!(evals.len() >= 2);
+    if evals.len() != 3 && evals.len() != 4 {
+      return Self::from_evals_newton(evals);
+    }
     let two_inv = Scalar::from(2).invert().unwrap();
     let coeffs = if evals.len() == 3 {
       // ax^2 + bx + c
@@ -53,6 +56,39 @@ impl<Scalar: PrimeField> UniPoly<Scalar> {
     UniPoly { coeffs }
   }
 
+  /// interpolates the polynomial of degree `evals.len() - 1` with `evals[i]` at `i`, using Newton's forward differences
+  fn from_evals_newton(evals: &[Scalar]) -> Self {
+    let n = evals.len();
+    let mut diffs = evals.to_vec();
+    let mut coeffs = vec![Scalar::ZERO; n];
+    let mut basis = vec![Scalar::ONE]; // x (x - 1) ... (x - k + 1)
+    let mut k_factorial = Scalar::ONE;
+    for k in 0..n {
+      // diffs[0] is the k-th forward difference at 0
+      let c = diffs[0] * k_factorial.invert().unwrap();
+      for (coeff, b) in coeffs.iter_mut().zip(basis.iter()) {
+        *coeff += c * b;
+      }
+
+      for i in 0..n - k - 1 {
+        diffs[i] = diffs[i + 1] - diffs[i];
+      }
+      let k_scalar = Scalar::from(k as u64);
+      basis.push(Scalar::ZERO);
+      for i in (0..basis.len()).rev() {
+        let shifted = if i > 0 { basis[i - 1] } else { Scalar::ZERO };
+        basis[i] = shifted - k_scalar * basis[i];
+      }
+      k_factorial *= Scalar::from(k as u64 + 1);
+    }
+
+    UniPoly { coeffs }
+  }
+
+  pub fn coeffs(&self) -> &[Scalar] {
+    &self.coeffs
+  }
+
   pub fn degree(&self) -> usize {
     self.coeffs.len() - 1
   }
@@ -85,6 +121,22 @@ impl<Scalar: PrimeField> UniPoly<Scalar> {
       coeffs_except_linear_term,
     }
   }
+
+  /// Compresses a polynomial `t` whose weighted sum `l(0) * t(0) + l(1) * t(1)` is known, for a linear `l`
+  /// with evaluations `l_evals` at 0 and 1. Omits the linear term as `compress` does, unless `l(1)` is zero,
+  /// in which case the constant term is omitted
+  pub fn compress_with_linear_factor(
+    &self,
+    l_evals: &(Scalar, Scalar),
+  ) -> CompressedUniPoly<Scalar> {
+    if l_evals.1.is_zero_vartime() {
+      CompressedUniPoly {
+        coeffs_except_linear_term: self.coeffs[1..].to_vec(),
+      }
+    } else {
+      self.compress()
+    }
+  }
 }
 
 impl<Scalar: PrimeField> CompressedUniPoly<Scalar> {
@@ -104,6 +156,34 @@ impl<Scalar: PrimeField> CompressedUniPoly<Scalar> {
     assert_eq!(self.coeffs_except_linear_term.len() + 1, coeffs.len());
     UniPoly { coeffs }
   }
+
+  /// Inverts `compress_with_linear_factor`, given the weighted sum `hint = l(0) * t(0) + l(1) * t(1)`.
+  /// Returns `None` if the polynomial is empty or `l` is zero
+  pub fn decompress_with_linear_factor(
+    &self,
+    hint: &Scalar,
+    l_evals: &(Scalar, Scalar),
+  ) -> Option<UniPoly<Scalar>> {
+    let (l_0, l_1) = l_evals;
+    let coeffs = &self.coeffs_except_linear_term;
+    if coeffs.is_empty() {
+      return None;
+    }
+
+    let coeffs = match Option::<Scalar>::from(l_1.invert()) {
+      // t(1), the sum of the coefficients, is (hint - l(0) * t(0)) / l(1)
+      Some(l_1_inv) => {
+        let linear_term = (*hint - *l_0 * coeffs[0]) * l_1_inv - coeffs.iter().sum::<Scalar>();
+        [&coeffs[0..1], &[linear_term], &coeffs[1..]].concat()
+      }
+      // t(0) is hint / l(0)
+      None => {
+        let constant_term = *hint * Option::<Scalar>::from(l_0.invert())?;
+        [&[constant_term], &coeffs[..]].concat()
+      }
+    };
+    Some(UniPoly { coeffs })
+  }
 }
 
 impl<G: Group> TranscriptReprTrait<G> for UniPoly<G::Scalar> {
@@ -111,6 +191,10 @@ impl<G: Group> TranscriptReprTrait<G> for UniPoly<G::Scalar> {
     let coeffs = self.compress().coeffs_except_linear_term;
     coeffs.as_slice().to_transcript_bytes()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    self.compress().coeffs_except_linear_term
+  }
 }
 #[cfg(test)]
 mod tests {
@@ -185,4 +269,29 @@ mod tests {
     test_from_evals_cubic_with::<bn256_grumpkin::bn256::Scalar>();
     test_from_evals_cubic_with::<secp256k1::Scalar>()
   }
+
+  fn test_from_evals_newton_with<F: PrimeField>() {
+    // polynomials of every degree up to 6, with coefficients 1, 2, 3, ...
+    for degree in 1..=6 {
+      let coeffs = (1..=degree as u64 + 1).map(F::from).collect::<Vec<_>>();
+      let poly = UniPoly {
+        coeffs: coeffs.clone(),
+      };
+      let evals = (0..=degree as u64)
+        .map(|i| poly.evaluate(&F::from(i)))
+        .collect::<Vec<_>>();
+      assert_eq!(UniPoly::from_evals(&evals).coeffs, coeffs);
+      assert_eq!(UniPoly::from_evals_newton(&evals).coeffs, coeffs);
+
+      let hint = evals[0] + evals[1];
+      assert_eq!(poly.compress().decompress(&hint).coeffs, coeffs);
+    }
+  }
+
+  #[test]
+  fn test_from_evals_newton() {
+    test_from_evals_newton_with::<pasta_curves::pallas::Scalar>();
+    test_from_evals_newton_with::<bn256_grumpkin::bn256::Scalar>();
+    test_from_evals_newton_with::<secp256k1::Scalar>()
+  }
 }
@@ -10,21 +10,21 @@ use crate::{
   },
   digest::{DigestComputer, SimpleDigestible},
   errors::SpartanError,
+  provider::keccak::Keccak256Transcript,
   r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
   spartan::{
     math::Math,
     polys::{
-      eq::EqPolynomial,
+      eq::{EqPolynomial, SplitEqPolynomial},
       multilinear::MultilinearPolynomial,
       univariate::{CompressedUniPoly, UniPoly},
     },
-    powers,
     sumcheck::SumcheckProof,
     PolyEvalInstance, PolyEvalWitness, SparsePolynomial,
   },
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait},
-    evaluation::EvaluationEngineTrait,
+    evaluation::{BatchEvaluationArgument, EvaluationEngineTrait},
     snark::RelaxedR1CSSNARKTrait,
     Group, TranscriptEngineTrait, TranscriptReprTrait,
   },
@@ -42,6 +42,37 @@ fn vec_to_arr<T, const N: usize>(v: Vec<T>) -> [T; N] {
     .unwrap_or_else(|v: Vec<T>| panic!("Expected a Vec of length {} but it was {}", N, v.len()))
 }
 
+/// Returns the claims that the committed polynomials evaluate to `evals` at the same point `x`
+fn instances_at_point<G: Group>(
+  comms: &[Commitment<G>],
+  x: &[G::Scalar],
+  evals: &[G::Scalar],
+) -> Vec<PolyEvalInstance<G>> {
+  comms
+    .iter()
+    .zip(evals.iter())
+    .map(|(c, e)| PolyEvalInstance {
+      c: c.clone(),
+      x: x.to_vec(),
+      e: *e,
+    })
+    .collect()
+}
+
+/// Returns the claims of `instances_at_point`, along with the polynomials as witnesses
+fn claims_at_point<G: Group>(
+  polys: &[&Vec<G::Scalar>],
+  comms: &[Commitment<G>],
+  x: &[G::Scalar],
+  evals: &[G::Scalar],
+) -> Vec<(PolyEvalWitness<G>, PolyEvalInstance<G>)> {
+  polys
+    .iter()
+    .map(|p| PolyEvalWitness { p: p.to_vec() })
+    .zip(instances_at_point(comms, x, evals))
+    .collect()
+}
+
 struct IdentityPolynomial<Scalar: PrimeField> {
   ell: usize,
   _p: PhantomData<Scalar>,
@@ -125,6 +156,23 @@ impl<G: Group> TranscriptReprTrait<G> for R1CSShapeSparkCommitment<G> {
     .as_slice()
     .to_transcript_bytes()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    [
+      &self.comm_row,
+      &self.comm_col,
+      &self.comm_val_A,
+      &self.comm_val_B,
+      &self.comm_val_C,
+      &self.comm_row_read_ts,
+      &self.comm_row_audit_ts,
+      &self.comm_col_read_ts,
+      &self.comm_col_audit_ts,
+    ]
+    .iter()
+    .flat_map(|c| c.to_transcript_scalars())
+    .collect()
+  }
 }
 
 impl<G: Group> R1CSShapeSparkRepr<G> {
@@ -304,8 +352,9 @@ pub trait SumcheckEngine<G: Group> {
   /// the size of the polynomials
   fn size(&self) -> usize;
 
-  /// returns evaluation points at 0, 2, d-1 (where d is the degree of the sum-check polynomial)
-  fn evaluation_points(&self) -> Vec<Vec<G::Scalar>>;
+  /// returns evaluation points at 0, 2, d-1 (where d is the degree of the sum-check polynomial),
+  /// given the claims of the current round in the order of `initial_claims`
+  fn evaluation_points(&self, claims: &[G::Scalar]) -> Vec<Vec<G::Scalar>>;
 
   /// bounds a variable in the constituent polynomials
   fn bound(&mut self, r: &G::Scalar);
@@ -314,24 +363,28 @@ pub trait SumcheckEngine<G: Group> {
   fn final_claims(&self) -> Vec<Vec<G::Scalar>>;
 }
 
-struct ProductSumcheckInstance<G: Group> {
-  pub(crate) claims: Vec<G::Scalar>, // claimed products
-  pub(crate) comm_output_vec: Vec<Commitment<G>>,
+/// A sum-check instance proving that the outputs of a layered product circuit are well formed,
+/// which the memory-checking argument uses to prove the products of its input vectors
+pub struct ProductSumcheckInstance<G: Group> {
+  claims: Vec<G::Scalar>, // claimed products
+  comm_output_vec: Vec<Commitment<G>>,
 
   input_vec: Vec<Vec<G::Scalar>>,
   output_vec: Vec<Vec<G::Scalar>>,
 
-  poly_A: MultilinearPolynomial<G::Scalar>,
+  eq: SplitEqPolynomial<G::Scalar>,
   poly_B_vec: Vec<MultilinearPolynomial<G::Scalar>>,
   poly_C_vec: Vec<MultilinearPolynomial<G::Scalar>>,
   poly_D_vec: Vec<MultilinearPolynomial<G::Scalar>>,
 }
 
 impl<G: Group> ProductSumcheckInstance<G> {
-  pub fn new(
+  /// Commits to the product circuit of each input vector, absorbs the commitments and the claimed products,
+  /// and squeezes the point of the `eq` polynomial from `transcript`
+  pub fn new<TE: TranscriptEngineTrait<G>>(
     ck: &CommitmentKey<G>,
     input_vec: Vec<Vec<G::Scalar>>, // list of input vectors
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<Self, SpartanError> {
     let compute_layer = |input: &[G::Scalar]| -> (Vec<G::Scalar>, Vec<G::Scalar>, Vec<G::Scalar>) {
       let left = (0..input.len() / 2)
@@ -409,7 +462,7 @@ impl<G: Group> ProductSumcheckInstance<G> {
       .map(|_i| transcript.squeeze(b"e"))
       .collect::<Result<Vec<G::Scalar>, SpartanError>>()?;
 
-    let poly_A = MultilinearPolynomial::new(EqPolynomial::new(rand_eq).evals());
+    let eq = SplitEqPolynomial::new(rand_eq);
     let poly_B_vec = left_vec
       .into_par_iter()
       .map(MultilinearPolynomial::new)
@@ -429,12 +482,22 @@ impl<G: Group> ProductSumcheckInstance<G> {
       comm_output_vec,
       input_vec,
       output_vec,
-      poly_A,
+      eq,
       poly_B_vec,
       poly_C_vec,
       poly_D_vec,
     })
   }
+
+  /// Returns the claimed product of each input vector
+  pub fn claims(&self) -> &[G::Scalar] {
+    &self.claims
+  }
+
+  /// Returns the commitments to the outputs of each product circuit
+  pub fn comm_output_vec(&self) -> &[Commitment<G>] {
+    &self.comm_output_vec
+  }
 }
 
 impl<G: Group> SumcheckEngine<G> for ProductSumcheckInstance<G> {
@@ -448,36 +511,35 @@ impl<G: Group> SumcheckEngine<G> for ProductSumcheckInstance<G> {
 
   fn size(&self) -> usize {
     for poly_B in &self.poly_B_vec {
-      assert_eq!(poly_B.len(), self.poly_A.len());
+      assert_eq!(poly_B.len(), self.eq.len());
     }
     for poly_C in &self.poly_C_vec {
-      assert_eq!(poly_C.len(), self.poly_A.len());
+      assert_eq!(poly_C.len(), self.eq.len());
     }
     for poly_D in &self.poly_D_vec {
-      assert_eq!(poly_D.len(), self.poly_A.len());
+      assert_eq!(poly_D.len(), self.eq.len());
     }
-    self.poly_A.len()
+    self.eq.len()
   }
 
-  fn evaluation_points(&self) -> Vec<Vec<G::Scalar>> {
-    let poly_A = &self.poly_A;
-
-    let comb_func =
-      |poly_A_comp: &G::Scalar,
-       poly_B_comp: &G::Scalar,
-       poly_C_comp: &G::Scalar,
-       poly_D_comp: &G::Scalar|
-       -> G::Scalar { *poly_A_comp * (*poly_B_comp * *poly_C_comp - *poly_D_comp) };
+  fn evaluation_points(&self, claims: &[G::Scalar]) -> Vec<Vec<G::Scalar>> {
+    let comb_func = |poly_B_comp: &G::Scalar,
+                     poly_C_comp: &G::Scalar,
+                     poly_D_comp: &G::Scalar|
+     -> G::Scalar { *poly_B_comp * *poly_C_comp - *poly_D_comp };
 
     self
       .poly_B_vec
       .iter()
       .zip(self.poly_C_vec.iter())
       .zip(self.poly_D_vec.iter())
-      .map(|((poly_B, poly_C), poly_D)| {
+      .zip(claims.iter())
+      .map(|(((poly_B, poly_C), poly_D), claim)| {
         // Make an iterator returning the contributions to the evaluations
         let (eval_point_0, eval_point_2, eval_point_3) =
-          SumcheckProof::<G>::compute_eval_points_cubic(poly_A, poly_B, poly_C, poly_D, &comb_func);
+          SumcheckProof::<G>::compute_eval_points_cubic_with_eq(
+            claim, &self.eq, poly_B, poly_C, poly_D, &comb_func,
+          );
 
         vec![eval_point_0, eval_point_2, eval_point_3]
       })
@@ -485,7 +547,7 @@ impl<G: Group> SumcheckEngine<G> for ProductSumcheckInstance<G> {
   }
 
   fn bound(&mut self, r: &G::Scalar) {
-    self.poly_A.bound_poly_var_top(r);
+    self.eq.bound_poly_var_top(r);
     for ((poly_B, poly_C), poly_D) in self
       .poly_B_vec
       .iter_mut()
@@ -498,7 +560,7 @@ impl<G: Group> SumcheckEngine<G> for ProductSumcheckInstance<G> {
     }
   }
   fn final_claims(&self) -> Vec<Vec<G::Scalar>> {
-    let poly_A_final = vec![self.poly_A[0]];
+    let poly_A_final = vec![self.eq.eval()];
     let poly_B_final = (0..self.poly_B_vec.len())
       .map(|i| self.poly_B_vec[i][0])
       .collect();
@@ -513,13 +575,31 @@ impl<G: Group> SumcheckEngine<G> for ProductSumcheckInstance<G> {
   }
 }
 
-struct OuterSumcheckInstance<G: Group> {
-  poly_tau: MultilinearPolynomial<G::Scalar>,
+/// A sum-check instance proving `0 = \sum_x eq(tau, x) * (Az(x) * Bz(x) - uCz_E(x))`
+pub struct OuterSumcheckInstance<G: Group> {
+  eq_tau: SplitEqPolynomial<G::Scalar>,
   poly_Az: MultilinearPolynomial<G::Scalar>,
   poly_Bz: MultilinearPolynomial<G::Scalar>,
   poly_uCz_E: MultilinearPolynomial<G::Scalar>,
 }
 
+impl<G: Group> OuterSumcheckInstance<G> {
+  /// Creates an instance from the point `tau` and the evaluations of `Az`, `Bz`, and `uCz_E`
+  pub fn new(
+    tau: Vec<G::Scalar>,
+    Az: Vec<G::Scalar>,
+    Bz: Vec<G::Scalar>,
+    uCz_E: Vec<G::Scalar>,
+  ) -> Self {
+    Self {
+      eq_tau: SplitEqPolynomial::new(tau),
+      poly_Az: MultilinearPolynomial::new(Az),
+      poly_Bz: MultilinearPolynomial::new(Bz),
+      poly_uCz_E: MultilinearPolynomial::new(uCz_E),
+    }
+  }
+}
+
 impl<G: Group> SumcheckEngine<G> for OuterSumcheckInstance<G> {
   fn initial_claims(&self) -> Vec<G::Scalar> {
     vec![G::Scalar::ZERO]
@@ -530,35 +610,35 @@ impl<G: Group> SumcheckEngine<G> for OuterSumcheckInstance<G> {
   }
 
   fn size(&self) -> usize {
-    assert_eq!(self.poly_tau.len(), self.poly_Az.len());
-    assert_eq!(self.poly_tau.len(), self.poly_Bz.len());
-    assert_eq!(self.poly_tau.len(), self.poly_uCz_E.len());
-    self.poly_tau.len()
+    assert_eq!(self.eq_tau.len(), self.poly_Az.len());
+    assert_eq!(self.eq_tau.len(), self.poly_Bz.len());
+    assert_eq!(self.eq_tau.len(), self.poly_uCz_E.len());
+    self.eq_tau.len()
   }
 
-  fn evaluation_points(&self) -> Vec<Vec<G::Scalar>> {
-    let (poly_A, poly_B, poly_C, poly_D) = (
-      &self.poly_tau,
-      &self.poly_Az,
-      &self.poly_Bz,
-      &self.poly_uCz_E,
-    );
-    let comb_func =
-      |poly_A_comp: &G::Scalar,
-       poly_B_comp: &G::Scalar,
-       poly_C_comp: &G::Scalar,
-       poly_D_comp: &G::Scalar|
-       -> G::Scalar { *poly_A_comp * (*poly_B_comp * *poly_C_comp - *poly_D_comp) };
+  fn evaluation_points(&self, claims: &[G::Scalar]) -> Vec<Vec<G::Scalar>> {
+    let (poly_B, poly_C, poly_D) = (&self.poly_Az, &self.poly_Bz, &self.poly_uCz_E);
+    let comb_func = |poly_B_comp: &G::Scalar,
+                     poly_C_comp: &G::Scalar,
+                     poly_D_comp: &G::Scalar|
+     -> G::Scalar { *poly_B_comp * *poly_C_comp - *poly_D_comp };
 
     // Make an iterator returning the contributions to the evaluations
     let (eval_point_0, eval_point_2, eval_point_3) =
-      SumcheckProof::<G>::compute_eval_points_cubic(poly_A, poly_B, poly_C, poly_D, &comb_func);
+      SumcheckProof::<G>::compute_eval_points_cubic_with_eq(
+        &claims[0],
+        &self.eq_tau,
+        poly_B,
+        poly_C,
+        poly_D,
+        &comb_func,
+      );
 
     vec![vec![eval_point_0, eval_point_2, eval_point_3]]
   }
 
   fn bound(&mut self, r: &G::Scalar) {
-    self.poly_tau.bound_poly_var_top(r);
+    self.eq_tau.bound_poly_var_top(r);
     self.poly_Az.bound_poly_var_top(r);
     self.poly_Bz.bound_poly_var_top(r);
     self.poly_uCz_E.bound_poly_var_top(r);
@@ -566,7 +646,7 @@ impl<G: Group> SumcheckEngine<G> for OuterSumcheckInstance<G> {
 
   fn final_claims(&self) -> Vec<Vec<G::Scalar>> {
     vec![vec![
-      self.poly_tau[0],
+      self.eq_tau.eval(),
       self.poly_Az[0],
       self.poly_Bz[0],
       self.poly_uCz_E[0],
@@ -574,13 +654,31 @@ impl<G: Group> SumcheckEngine<G> for OuterSumcheckInstance<G> {
   }
 }
 
-struct InnerSumcheckInstance<G: Group> {
+/// A sum-check instance proving `claim = \sum_y E_row(y) * E_col(y) * val(y)`
+pub struct InnerSumcheckInstance<G: Group> {
   claim: G::Scalar,
   poly_E_row: MultilinearPolynomial<G::Scalar>,
   poly_E_col: MultilinearPolynomial<G::Scalar>,
   poly_val: MultilinearPolynomial<G::Scalar>,
 }
 
+impl<G: Group> InnerSumcheckInstance<G> {
+  /// Creates an instance from the claimed sum and the evaluations of `E_row`, `E_col`, and `val`
+  pub fn new(
+    claim: G::Scalar,
+    E_row: Vec<G::Scalar>,
+    E_col: Vec<G::Scalar>,
+    val: Vec<G::Scalar>,
+  ) -> Self {
+    Self {
+      claim,
+      poly_E_row: MultilinearPolynomial::new(E_row),
+      poly_E_col: MultilinearPolynomial::new(E_col),
+      poly_val: MultilinearPolynomial::new(val),
+    }
+  }
+}
+
 impl<G: Group> SumcheckEngine<G> for InnerSumcheckInstance<G> {
   fn initial_claims(&self) -> Vec<G::Scalar> {
     vec![self.claim]
@@ -596,7 +694,7 @@ impl<G: Group> SumcheckEngine<G> for InnerSumcheckInstance<G> {
     self.poly_E_row.len()
   }
 
-  fn evaluation_points(&self) -> Vec<Vec<G::Scalar>> {
+  fn evaluation_points(&self, _claims: &[G::Scalar]) -> Vec<Vec<G::Scalar>> {
     let (poly_A, poly_B, poly_C) = (&self.poly_E_row, &self.poly_E_col, &self.poly_val);
     let comb_func = |poly_A_comp: &G::Scalar,
                      poly_B_comp: &G::Scalar,
@@ -688,7 +786,11 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> SimpleDigestible for VerifierKey<G,
 /// the commitment to a vector viewed as a polynomial commitment
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct RelaxedR1CSSNARK<G: Group, EE: EvaluationEngineTrait<G>> {
+pub struct RelaxedR1CSSNARK<
+  G: Group,
+  EE: EvaluationEngineTrait<G>,
+  TE: TranscriptEngineTrait<G> = Keccak256Transcript<G>,
+> {
   // commitment to witness
   comm_W: CompressedCommitment<G>,
 
@@ -739,17 +841,18 @@ pub struct RelaxedR1CSSNARK<G: Group, EE: EvaluationEngineTrait<G>> {
   eval_W: G::Scalar,
 
   // batch openings of all multilinear polynomials
-  sc_proof_batch: SumcheckProof<G>,
-  evals_batch_arr: [G::Scalar; 7],
-  eval_arg: EE::EvaluationArgument,
+  eval_arg: BatchEvaluationArgument<G, EE>,
+  _p: PhantomData<TE>,
 }
 
-impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARK<G, EE> {
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>>
+  RelaxedR1CSSNARK<G, EE, TE>
+{
   fn prove_inner<T1, T2, T3>(
     mem: &mut T1,
     outer: &mut T2,
     inner: &mut T3,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<
     (
       SumcheckProof<G>,
@@ -804,24 +907,32 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARK<G, EE> {
     let mut r: Vec<G::Scalar> = Vec::new();
     let mut cubic_polys: Vec<CompressedUniPoly<G::Scalar>> = Vec::new();
     let num_rounds = mem.size().log_2();
+
+    // the claim of each instance in the current round
+    let mut claims = claims;
+    let (num_claims_mem, num_claims_outer) =
+      (mem.initial_claims().len(), outer.initial_claims().len());
     for _i in 0..num_rounds {
       let mut evals: Vec<Vec<G::Scalar>> = Vec::new();
-      evals.extend(mem.evaluation_points());
-      evals.extend(outer.evaluation_points());
-      evals.extend(inner.evaluation_points());
+      {
+        let (claims_mem, rest) = claims.split_at(num_claims_mem);
+        let (claims_outer, claims_inner) = rest.split_at(num_claims_outer);
+        evals.extend(mem.evaluation_points(claims_mem));
+        evals.extend(outer.evaluation_points(claims_outer));
+        evals.extend(inner.evaluation_points(claims_inner));
+      }
       assert_eq!(evals.len(), num_claims);
 
       let evals_combined_0 = (0..evals.len()).map(|i| evals[i][0] * coeffs[i]).sum();
       let evals_combined_2 = (0..evals.len()).map(|i| evals[i][1] * coeffs[i]).sum();
       let evals_combined_3 = (0..evals.len()).map(|i| evals[i][2] * coeffs[i]).sum();
 
-      let evals = vec![
+      let poly = UniPoly::from_evals(&[
         evals_combined_0,
         e - evals_combined_0,
         evals_combined_2,
         evals_combined_3,
-      ];
-      let poly = UniPoly::from_evals(&evals);
+      ]);
 
       // append the prover's message to the transcript
       transcript.absorb(b"p", &poly);
@@ -830,6 +941,11 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARK<G, EE> {
       let r_i = transcript.squeeze(b"c")?;
       r.push(r_i);
 
+      for (claim, evals) in claims.iter_mut().zip(evals.iter()) {
+        *claim =
+          UniPoly::from_evals(&[evals[0], *claim - evals[0], evals[1], evals[2]]).evaluate(&r_i);
+      }
+
       mem.bound(&r_i);
       outer.bound(&r_i);
       inner.bound(&r_i);
@@ -881,7 +997,9 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> VerifierKey<G, EE> {
   }
 }
 
-impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for RelaxedR1CSSNARK<G, EE> {
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>> RelaxedR1CSSNARKTrait<G>
+  for RelaxedR1CSSNARK<G, EE, TE>
+{
   type ProverKey = ProverKey<G, EE>;
   type VerifierKey = VerifierKey<G, EE>;
 
@@ -890,11 +1008,13 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
   ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError> {
     let mut cs: ShapeCS<G> = ShapeCS::new();
     let _ = circuit.synthesize(&mut cs);
-    let (S, ck) = cs.r1cs_shape();
-
-    let (pk_ee, vk_ee) = EE::setup(&ck);
+    let (S, _ck) = cs.r1cs_shape()?;
 
+    // the commitment key must also cover the sparse representation of the shape
     let S_repr = R1CSShapeSparkRepr::new(&S);
+    let ck = G::CE::setup(b"ck", max(S_repr.N, max(S.num_cons, S.num_vars)))?;
+
+    let (pk_ee, vk_ee) = EE::setup(&ck);
     let S_comm = S_repr.commit(&ck);
 
     let vk = VerifierKey::new(S.num_cons, S.num_vars, S_comm.clone(), vk_ee);
@@ -927,7 +1047,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
     );
 
     let W = W.pad(&pk.S); // pad the witness
-    let mut transcript = G::TE::new(b"RelaxedR1CSSNARK");
+    let mut transcript = TE::new(b"RelaxedR1CSSNARK");
 
     // a list of polynomial evaluation claims that will be batched
     let mut w_u_vec = Vec::new();
@@ -942,8 +1062,12 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
     // compute the full satisfying assignment by concatenating W.W, U.u, and U.X
     let z = [W.W.clone(), vec![U.u], U.X.clone()].concat();
 
-    // compute Az, Bz, Cz
+    // compute Az, Bz, Cz, padded to the size of the sum-check so that the committed
+    // vectors are exactly the ones opened later
     let (mut Az, mut Bz, mut Cz) = pk.S.multiply_vec(&z)?;
+    Az.resize(pk.S_repr.N, G::Scalar::ZERO);
+    Bz.resize(pk.S_repr.N, G::Scalar::ZERO);
+    Cz.resize(pk.S_repr.N, G::Scalar::ZERO);
 
     // commit to Az, Bz, Cz
     let (comm_Az, (comm_Bz, comm_Cz)) = rayon::join(
@@ -963,20 +1087,12 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       .collect::<Result<Vec<G::Scalar>, SpartanError>>()?;
 
     // (1) send commitments to Az, Bz, and Cz along with their evaluations at tau
-    let (Az, Bz, Cz, E) = {
-      Az.resize(pk.S_repr.N, G::Scalar::ZERO);
-      Bz.resize(pk.S_repr.N, G::Scalar::ZERO);
-      Cz.resize(pk.S_repr.N, G::Scalar::ZERO);
-
-      let E = {
-        let mut val = vec![G::Scalar::ZERO; pk.S_repr.N];
-        for (i, w_e) in W.E.iter().enumerate() {
-          val[i] = *w_e;
-        }
-        val
-      };
-
-      (Az, Bz, Cz, E)
+    let E = {
+      let mut val = vec![G::Scalar::ZERO; pk.S_repr.N];
+      for (i, w_e) in W.E.iter().enumerate() {
+        val[i] = *w_e;
+      }
+      val
     };
     let (eval_Az_at_tau, eval_Bz_at_tau, eval_Cz_at_tau) = {
       let evals_at_tau = [&Az, &Bz, &Cz]
@@ -1006,17 +1122,14 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       &vec![comm_E_row.clone(), comm_E_col.clone()].as_slice(),
     );
 
-    // add claims about Az, Bz, and Cz to be checked later
-    // since all the three polynomials are opened at tau,
-    // we can combine them into a single polynomial opened at tau
+    // add claims about Az, Bz, and Cz at tau to be checked later;
+    // the evaluation engine combines claims at the same point if its commitments allow it
     let eval_vec = vec![eval_Az_at_tau, eval_Bz_at_tau, eval_Cz_at_tau];
     let comm_vec = vec![comm_Az.clone(), comm_Bz.clone(), comm_Cz.clone()];
     let poly_vec = vec![&Az, &Bz, &Cz];
     transcript.absorb(b"e", &eval_vec.as_slice()); // c_vec is already in the transcript
     let c = transcript.squeeze(b"c")?;
-    let w = PolyEvalWitness::batch(&poly_vec, &c);
-    let u = PolyEvalInstance::batch(&comm_vec, &tau, &eval_vec, &c);
-    w_u_vec.push((w, u));
+    w_u_vec.extend(claims_at_point(&poly_vec, &comm_vec, &tau, &eval_vec));
 
     let c_inner = c;
 
@@ -1026,17 +1139,14 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
     // (3) E_row(i) = eq(tau, row(i)) and E_col(i) = z(col(i))
 
     // a sum-check instance to prove the first claim
-    let mut outer_sc_inst = OuterSumcheckInstance {
-      poly_tau: MultilinearPolynomial::new(EqPolynomial::new(tau).evals()),
-      poly_Az: MultilinearPolynomial::new(Az.clone()),
-      poly_Bz: MultilinearPolynomial::new(Bz.clone()),
-      poly_uCz_E: {
-        let uCz_E = (0..Cz.len())
-          .map(|i| U.u * Cz[i] + E[i])
-          .collect::<Vec<G::Scalar>>();
-        MultilinearPolynomial::new(uCz_E)
-      },
-    };
+    let mut outer_sc_inst = OuterSumcheckInstance::new(
+      tau,
+      Az.clone(),
+      Bz.clone(),
+      (0..Cz.len())
+        .map(|i| U.u * Cz[i] + E[i])
+        .collect::<Vec<G::Scalar>>(),
+    );
 
     // a sum-check instance to prove the second claim
     let val = pk
@@ -1047,12 +1157,12 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       .zip(pk.S_repr.val_C.iter())
       .map(|((v_a, v_b), v_c)| *v_a + c_inner * *v_b + c_inner * c_inner * *v_c)
       .collect::<Vec<G::Scalar>>();
-    let mut inner_sc_inst = InnerSumcheckInstance {
-      claim: eval_Az_at_tau + c_inner * eval_Bz_at_tau + c_inner * c_inner * eval_Cz_at_tau,
-      poly_E_row: MultilinearPolynomial::new(E_row.clone()),
-      poly_E_col: MultilinearPolynomial::new(E_col.clone()),
-      poly_val: MultilinearPolynomial::new(val),
-    };
+    let mut inner_sc_inst = InnerSumcheckInstance::new(
+      eval_Az_at_tau + c_inner * eval_Bz_at_tau + c_inner * c_inner * eval_Cz_at_tau,
+      E_row.clone(),
+      E_col.clone(),
+      val,
+    );
 
     // a third sum-check instance to prove the memory-related claim
     // we now need to prove that E_row and E_col are well-formed
@@ -1191,93 +1301,31 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       .collect::<Vec<G::Scalar>>();
     transcript.absorb(b"e", &evals.as_slice());
 
-    // squeeze a challenge to combine multiple claims into one
-    let powers_of_rho = {
-      let s = transcript.squeeze(b"r")?;
-      let mut s_vec = vec![s];
-      for i in 1..mem_sc_inst.initial_claims().len() {
-        s_vec.push(s_vec[i - 1] * s);
-      }
-      s_vec
-    };
-
-    // take weighted sum of input, output, and their commitments
-    let product = mem_sc_inst
-      .claims
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(e, p)| *e * p)
-      .sum();
-
-    let eval_output = eval_output_vec
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(e, p)| *e * p)
-      .sum();
-
-    let comm_output = mem_sc_inst
-      .comm_output_vec
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(c, r_i)| c.clone() * *r_i)
-      .fold(Commitment::<G>::default(), |acc, item| acc + item);
-
-    let weighted_sum = |W: &[Vec<G::Scalar>], s: &[G::Scalar]| -> Vec<G::Scalar> {
-      assert_eq!(W.len(), s.len());
-      let mut p = vec![G::Scalar::ZERO; W[0].len()];
-      for i in 0..W.len() {
-        for (j, item) in W[i].iter().enumerate().take(W[i].len()) {
-          p[j] += *item * s[i]
-        }
-      }
-      p
-    };
-
-    let poly_output = weighted_sum(&mem_sc_inst.output_vec, &powers_of_rho);
-
-    let eval_output2 = eval_output2_vec
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(e, p)| *e * p)
-      .sum();
-
-    // eval_output = output(r_sat)
-    w_u_vec.push((
-      PolyEvalWitness {
-        p: poly_output.clone(),
-      },
-      PolyEvalInstance {
-        c: comm_output.clone(),
-        x: r_sat.clone(),
-        e: eval_output,
-      },
-    ));
-
-    // claimed_product = output(1, ..., 1, 0)
+    // each output opens to its evaluation at r_sat, to its claimed product at (1, ..., 1, 0),
+    // and to its evaluation at rand_ext[1..]
     let x = {
       let mut x = vec![G::Scalar::ONE; r_sat.len()];
       x[r_sat.len() - 1] = G::Scalar::ZERO;
       x
     };
-    w_u_vec.push((
-      PolyEvalWitness {
-        p: poly_output.clone(),
-      },
-      PolyEvalInstance {
-        c: comm_output.clone(),
-        x,
-        e: product,
-      },
+    let output_vec = mem_sc_inst.output_vec.iter().collect::<Vec<_>>();
+    w_u_vec.extend(claims_at_point(
+      &output_vec,
+      mem_sc_inst.comm_output_vec(),
+      &r_sat,
+      &eval_output_vec,
     ));
-
-    // eval_output2 = output(rand_ext[1..])
-    w_u_vec.push((
-      PolyEvalWitness { p: poly_output },
-      PolyEvalInstance {
-        c: comm_output,
-        x: rand_ext[1..].to_vec(),
-        e: eval_output2,
-      },
+    w_u_vec.extend(claims_at_point(
+      &output_vec,
+      mem_sc_inst.comm_output_vec(),
+      &x,
+      mem_sc_inst.claims(),
+    ));
+    w_u_vec.extend(claims_at_point(
+      &output_vec,
+      mem_sc_inst.comm_output_vec(),
+      &rand_ext[1..],
+      &eval_output2_vec,
     ));
 
     let r_prod = rand_ext[1..].to_vec();
@@ -1326,12 +1374,11 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
         eval_col_read_ts,
         eval_E_col_at_r_prod,
         eval_col_audit_ts,
-        eval_W, // this will not be batched below as it is evaluated at r_prod[1..]
+        eval_W, // this is evaluated at r_prod[1..]
       ]
       .as_slice(),
     );
 
-    let c = transcript.squeeze(b"c")?;
     let eval_vec = [
       eval_row,
       eval_row_read_ts,
@@ -1362,11 +1409,8 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       &E_col,
       &pk.S_repr.col_audit_ts,
     ];
-    let w = PolyEvalWitness::batch(&poly_vec, &c);
-    let u = PolyEvalInstance::batch(&comm_vec, &r_prod, &eval_vec, &c);
-
-    // add the claim to prove for later
-    w_u_vec.push((w, u));
+    // add the claims to prove for later
+    w_u_vec.extend(claims_at_point(&poly_vec, &comm_vec, &r_prod, &eval_vec));
 
     w_u_vec.push((
       PolyEvalWitness { p: W.W },
@@ -1377,7 +1421,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       },
     ));
 
-    // all nine evaluations are at r_sat, we can fold them into one; they were added to the transcript earlier
+    // all nine evaluations are at r_sat; they were added to the transcript earlier
     let eval_vec = [
       eval_Az, eval_Bz, eval_Cz, eval_E, eval_E_row, eval_E_col, eval_val_A, eval_val_B, eval_val_C,
     ];
@@ -1404,85 +1448,19 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       &pk.S_repr.val_C,
     ];
     transcript.absorb(b"e", &eval_vec.as_slice()); // c_vec is already in the transcript
-    let c = transcript.squeeze(b"c")?;
-    let w = PolyEvalWitness::batch(&poly_vec, &c);
-    let u = PolyEvalInstance::batch(&comm_vec, &r_sat, &eval_vec, &c);
-    w_u_vec.push((w, u));
-
-    // We will now reduce a vector of claims of evaluations at different points into claims about them at the same point.
-    // For example, eval_W =? W(r_y[1..]) and eval_W =? E(r_x) into
-    // two claims: eval_W_prime =? W(rz) and eval_E_prime =? E(rz)
-    // We can them combine the two into one: eval_W_prime + gamma * eval_E_prime =? (W + gamma*E)(rz),
-    // where gamma is a public challenge
-    // Since commitments to W and E are homomorphic, the verifier can compute a commitment
-    // to the batched polynomial.
-    assert!(w_u_vec.len() >= 2);
+    w_u_vec.extend(claims_at_point(&poly_vec, &comm_vec, &r_sat, &eval_vec));
 
+    // prove all the claims, which are at different points, in a batch
     let (w_vec, u_vec): (Vec<PolyEvalWitness<G>>, Vec<PolyEvalInstance<G>>) =
       w_u_vec.into_iter().unzip();
-    let w_vec_padded = PolyEvalWitness::pad(&w_vec); // pad the polynomials to be of the same size
-    let u_vec_padded = PolyEvalInstance::pad(&u_vec); // pad the evaluation points
-
-    // generate a challenge
-    let rho = transcript.squeeze(b"r")?;
-    let num_claims = w_vec_padded.len();
-    let powers_of_rho = powers::<G>(&rho, num_claims);
-    let claim_batch_joint = u_vec_padded
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(u, p)| u.e * p)
-      .sum();
-
-    let mut polys_left: Vec<MultilinearPolynomial<G::Scalar>> = w_vec_padded
-      .iter()
-      .map(|w| MultilinearPolynomial::new(w.p.clone()))
-      .collect();
-    let mut polys_right: Vec<MultilinearPolynomial<G::Scalar>> = u_vec_padded
-      .iter()
-      .map(|u| MultilinearPolynomial::new(EqPolynomial::new(u.x.clone()).evals()))
-      .collect();
-
-    let num_rounds_z = u_vec_padded[0].x.len();
-    let comb_func = |poly_A_comp: &G::Scalar, poly_B_comp: &G::Scalar| -> G::Scalar {
-      *poly_A_comp * *poly_B_comp
-    };
-    let (sc_proof_batch, r_z, claims_batch) = SumcheckProof::prove_quad_batch(
-      &claim_batch_joint,
-      num_rounds_z,
-      &mut polys_left,
-      &mut polys_right,
-      &powers_of_rho,
-      comb_func,
-      &mut transcript,
-    )?;
-
-    let (claims_batch_left, _): (Vec<G::Scalar>, Vec<G::Scalar>) = claims_batch;
-
-    transcript.absorb(b"l", &claims_batch_left.as_slice());
-
-    // we now combine evaluation claims at the same point rz into one
-    let gamma = transcript.squeeze(b"g")?;
-    let powers_of_gamma: Vec<G::Scalar> = powers::<G>(&gamma, num_claims);
-    let comm_joint = u_vec_padded
-      .iter()
-      .zip(powers_of_gamma.iter())
-      .map(|(u, g_i)| u.c.clone() * *g_i)
-      .fold(Commitment::<G>::default(), |acc, item| acc + item);
-    let poly_joint = PolyEvalWitness::weighted_sum(&w_vec_padded, &powers_of_gamma);
-    let eval_joint = claims_batch_left
-      .iter()
-      .zip(powers_of_gamma.iter())
-      .map(|(e, g_i)| *e * *g_i)
-      .sum();
-
-    let eval_arg = EE::prove(
+    let eval_arg = EE::prove_batch(
       &pk.ck,
       &pk.pk_ee,
       &mut transcript,
-      &comm_joint,
-      &poly_joint.p,
-      &r_z,
-      &eval_joint,
+      &u_vec.iter().map(|u| u.c.clone()).collect::<Vec<_>>(),
+      &w_vec.into_iter().map(|w| w.p).collect::<Vec<_>>(),
+      &u_vec.iter().map(|u| u.x.clone()).collect::<Vec<_>>(),
+      &u_vec.iter().map(|u| u.e).collect::<Vec<_>>(),
     )?;
 
     Ok(RelaxedR1CSSNARK {
@@ -1497,12 +1475,12 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       eval_Cz_at_tau,
       comm_output_arr: vec_to_arr(
         mem_sc_inst
-          .comm_output_vec
+          .comm_output_vec()
           .iter()
           .map(|c| c.compress())
           .collect::<Vec<CompressedCommitment<G>>>(),
       ),
-      claims_product_arr: vec_to_arr(mem_sc_inst.claims.clone()),
+      claims_product_arr: vec_to_arr(mem_sc_inst.claims().to_vec()),
 
       sc_sat,
 
@@ -1532,9 +1510,8 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       eval_col_audit_ts,
       eval_W,
 
-      sc_proof_batch,
-      evals_batch_arr: vec_to_arr(claims_batch_left),
       eval_arg,
+      _p: PhantomData,
     })
   }
 
@@ -1544,7 +1521,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
     let comm_W = Commitment::<G>::decompress(&self.comm_W)?;
     let U = RelaxedR1CSInstance::from_r1cs_instance_unchecked(&comm_W, io);
 
-    let mut transcript = G::TE::new(b"RelaxedR1CSSNARK");
+    let mut transcript = TE::new(b"RelaxedR1CSSNARK");
     let mut u_vec: Vec<PolyEvalInstance<G>> = Vec::new();
 
     // append the verifier key (including commitment to R1CS matrices) and the RelaxedR1CSInstance to the transcript
@@ -1582,9 +1559,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       &vec![comm_E_row.clone(), comm_E_col.clone()].as_slice(),
     );
 
-    // add claims about Az, Bz, and Cz to be checked later
-    // since all the three polynomials are opened at tau,
-    // we can combine them into a single polynomial opened at tau
+    // add claims about Az, Bz, and Cz at tau to be checked later
     let eval_vec = vec![
       self.eval_Az_at_tau,
       self.eval_Bz_at_tau,
@@ -1593,10 +1568,9 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
     let comm_vec = vec![comm_Az.clone(), comm_Bz.clone(), comm_Cz.clone()];
     transcript.absorb(b"e", &eval_vec.as_slice()); // c_vec is already in the transcript
     let c = transcript.squeeze(b"c")?;
-    let u = PolyEvalInstance::batch(&comm_vec, &tau, &eval_vec, &c);
-    let claim_inner = u.e;
+    u_vec.extend(instances_at_point(&comm_vec, &tau, &eval_vec));
+    let claim_inner = self.eval_Az_at_tau + c * self.eval_Bz_at_tau + c * c * self.eval_Cz_at_tau;
     let c_inner = c;
-    u_vec.push(u);
 
     let gamma_1 = transcript.squeeze(b"g1")?;
     let gamma_2 = transcript.squeeze(b"g2")?;
@@ -1713,73 +1687,31 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       .collect::<Vec<G::Scalar>>();
     transcript.absorb(b"e", &evals.as_slice());
 
-    // squeeze a challenge to combine multiple claims into one
-    let powers_of_rho = {
-      let s = transcript.squeeze(b"r")?;
-      let mut s_vec = vec![s];
-      for i in 1..num_claims {
-        s_vec.push(s_vec[i - 1] * s);
-      }
-      s_vec
-    };
-
-    // take weighted sum of input, output, and their commitments
-    let product = self
-      .claims_product_arr
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(e, p)| *e * p)
-      .sum();
-
-    let eval_output = self
-      .eval_output_arr
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(e, p)| *e * p)
-      .sum();
-
-    let comm_output = comm_output_vec
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(c, r_i)| c.clone() * *r_i)
-      .fold(Commitment::<G>::default(), |acc, item| acc + item);
-
-    let eval_output2 = self
-      .eval_output2_arr
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(e, p)| *e * p)
-      .sum();
-
-    // eval_output = output(r_sat)
-    u_vec.push(PolyEvalInstance {
-      c: comm_output.clone(),
-      x: r_sat.clone(),
-      e: eval_output,
-    });
-
-    // claimed_product = output(1, ..., 1, 0)
+    // each output opens to its evaluation at r_sat, to its claimed product at (1, ..., 1, 0),
+    // and to its evaluation at rand_ext[1..]
     let x = {
       let mut x = vec![G::Scalar::ONE; r_sat.len()];
       x[r_sat.len() - 1] = G::Scalar::ZERO;
       x
     };
-    u_vec.push(PolyEvalInstance {
-      c: comm_output.clone(),
-      x,
-      e: product,
-    });
-
-    // eval_output2 = output(rand_ext[1..])
-    u_vec.push(PolyEvalInstance {
-      c: comm_output,
-      x: rand_ext[1..].to_vec(),
-      e: eval_output2,
-    });
+    u_vec.extend(instances_at_point(
+      &comm_output_vec,
+      &r_sat,
+      &self.eval_output_arr,
+    ));
+    u_vec.extend(instances_at_point(
+      &comm_output_vec,
+      &x,
+      &self.claims_product_arr,
+    ));
+    u_vec.extend(instances_at_point(
+      &comm_output_vec,
+      &rand_ext[1..],
+      &self.eval_output2_arr,
+    ));
 
     let r_prod = rand_ext[1..].to_vec();
     // row-related and col-related claims of polynomial evaluations to aid the final check of the sum-check
-    // we can batch all the claims
     transcript.absorb(
       b"e",
       &[
@@ -1795,7 +1727,6 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       ]
       .as_slice(),
     );
-    let c = transcript.squeeze(b"c")?;
     let eval_vec = [
       self.eval_row,
       self.eval_row_read_ts,
@@ -1816,10 +1747,8 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       comm_E_col.clone(),
       vk.S_comm.comm_col_audit_ts.clone(),
     ];
-    let u = PolyEvalInstance::batch(&comm_vec, &r_prod, &eval_vec, &c);
-
-    // add the claim to prove for later
-    u_vec.push(u);
+    // add the claims to prove for later
+    u_vec.extend(instances_at_point(&comm_vec, &r_prod, &eval_vec));
 
     // compute eval_Z
     let (eval_Z, r_prod_unpad) = {
@@ -1931,8 +1860,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       return Err(SpartanError::InvalidSumcheckProof);
     }
 
-    // since all the nine polynomials are opened at r_sat,
-    // we can combine them into a single polynomial opened at r_sat
+    // all the nine polynomials are opened at r_sat
     let eval_vec = [
       self.eval_Az,
       self.eval_Bz,
@@ -1956,71 +1884,15 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
       vk.S_comm.comm_val_C.clone(),
     ];
     transcript.absorb(b"e", &eval_vec.as_slice()); // c_vec is already in the transcript
-    let c = transcript.squeeze(b"c")?;
-    let u = PolyEvalInstance::batch(&comm_vec, &r_sat, &eval_vec, &c);
-    u_vec.push(u);
-
-    let u_vec_padded = PolyEvalInstance::pad(&u_vec); // pad the evaluation points
-
-    // generate a challenge
-    let rho = transcript.squeeze(b"r")?;
-    let num_claims = u_vec.len();
-    let powers_of_rho = powers::<G>(&rho, num_claims);
-    let claim_batch_joint = u_vec_padded
-      .iter()
-      .zip(powers_of_rho.iter())
-      .map(|(u, p)| u.e * p)
-      .sum();
-
-    let num_rounds_z = u_vec_padded[0].x.len();
-    let (claim_batch_final, r_z) =
-      self
-        .sc_proof_batch
-        .verify(claim_batch_joint, num_rounds_z, 2, &mut transcript)?;
-
-    let claim_batch_final_expected = {
-      let poly_rz = EqPolynomial::new(r_z.clone());
-      let evals = u_vec_padded
-        .iter()
-        .map(|u| poly_rz.evaluate(&u.x))
-        .collect::<Vec<G::Scalar>>();
-
-      evals
-        .iter()
-        .zip(self.evals_batch_arr.iter())
-        .zip(powers_of_rho.iter())
-        .map(|((e_i, p_i), rho_i)| *e_i * *p_i * rho_i)
-        .sum()
-    };
-
-    if claim_batch_final != claim_batch_final_expected {
-      return Err(SpartanError::InvalidSumcheckProof);
-    }
-
-    transcript.absorb(b"l", &self.evals_batch_arr.as_slice());
-
-    // we now combine evaluation claims at the same point rz into one
-    let gamma = transcript.squeeze(b"g")?;
-    let powers_of_gamma: Vec<G::Scalar> = powers::<G>(&gamma, num_claims);
-    let comm_joint = u_vec_padded
-      .iter()
-      .zip(powers_of_gamma.iter())
-      .map(|(u, g_i)| u.c.clone() * *g_i)
-      .fold(Commitment::<G>::default(), |acc, item| acc + item);
-    let eval_joint = self
-      .evals_batch_arr
-      .iter()
-      .zip(powers_of_gamma.iter())
-      .map(|(e, g_i)| *e * *g_i)
-      .sum();
+    u_vec.extend(instances_at_point(&comm_vec, &r_sat, &eval_vec));
 
-    // verify
-    EE::verify(
+    // verify all the claims, which are at different points, in a batch
+    EE::verify_batch(
       &vk.vk_ee,
       &mut transcript,
-      &comm_joint,
-      &r_z,
-      &eval_joint,
+      &u_vec.iter().map(|u| u.c.clone()).collect::<Vec<_>>(),
+      &u_vec.iter().map(|u| u.x.clone()).collect::<Vec<_>>(),
+      &u_vec.iter().map(|u| u.e).collect::<Vec<_>>(),
       &self.eval_arg,
     )?;
 
@@ -12,22 +12,23 @@ use crate::{
     },
     digest::{DigestComputer, SimpleDigestible},
     errors::SpartanError,
-    r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
+    provider::keccak::Keccak256Transcript,
+    r1cs::{R1CSShape, R1CSStateWiring, RelaxedR1CSInstance, RelaxedR1CSWitness},
     spartan::{
         polys::{
             eq::EqPolynomial, multilinear::MultilinearPolynomial, multilinear::SparsePolynomial,
         },
-        powers,
         sumcheck::SumcheckProof,
-        PolyEvalInstance, PolyEvalWitness,
     },
     traits::{
-        commitment::CommitmentTrait, evaluation::EvaluationEngineTrait,
+        commitment::CommitmentTrait,
+        evaluation::{BatchEvaluationArgument, EvaluationEngineTrait},
         snark::RelaxedR1CSSNARKTrait, Group, TranscriptEngineTrait,
     },
     Commitment, CommitmentKey, CompressedCommitment,
 };
 use bellpepper_core::{Circuit, ConstraintSystem};
+use core::marker::PhantomData;
 use ff::Field;
 use once_cell::sync::OnceCell;
 use rayon::prelude::*;
@@ -81,7 +82,6 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> VerifierKey<G, EE> {
 #[serde(bound = "")]
 pub struct UniformVerifierKey<G: Group, EE: EvaluationEngineTrait<G>> {
     vk_ee: EE::VerifierKey,
-    S: R1CSShape<G>,        // The full shape
     S_single: R1CSShape<G>, // A single step's shape
     num_steps: usize,       // Number of steps
     #[serde(skip, default = "OnceCell::new")]
@@ -91,21 +91,25 @@ pub struct UniformVerifierKey<G: Group, EE: EvaluationEngineTrait<G>> {
 impl<G: Group, EE: EvaluationEngineTrait<G>> SimpleDigestible for UniformVerifierKey<G, EE> {}
 
 impl<G: Group, EE: EvaluationEngineTrait<G>> UniformVerifierKey<G, EE> {
-    fn new(
-        shape: R1CSShape<G>,
-        vk_ee: EE::VerifierKey,
-        shape_single: R1CSShape<G>,
-        num_steps: usize,
-    ) -> Self {
+    fn new(vk_ee: EE::VerifierKey, shape_single: R1CSShape<G>, num_steps: usize) -> Self {
         UniformVerifierKey {
             vk_ee,
-            S: shape,
             S_single: shape_single,
-            num_steps: num_steps,
+            num_steps,
             digest: OnceCell::new(),
         }
     }
 
+    /// Returns the number of constraints of the full (repeated) shape
+    fn num_cons(&self) -> usize {
+        self.S_single.num_cons * self.num_steps
+    }
+
+    /// Returns the number of witness variables of the full (repeated) shape
+    fn num_vars(&self) -> usize {
+        self.S_single.num_vars * self.num_steps
+    }
+
     /// Returns the digest of the verifier's key.
     pub fn digest(&self) -> G::Scalar {
         self.digest
@@ -124,19 +128,24 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> UniformVerifierKey<G, EE> {
 /// the commitment to a vector viewed as a polynomial commitment
 #[derive(Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct RelaxedR1CSSNARK<G: Group, EE: EvaluationEngineTrait<G>> {
+pub struct RelaxedR1CSSNARK<
+    G: Group,
+    EE: EvaluationEngineTrait<G>,
+    TE: TranscriptEngineTrait<G> = Keccak256Transcript<G>,
+> {
     comm_W: CompressedCommitment<G>,
     sc_proof_outer: SumcheckProof<G>,
     claims_outer: (G::Scalar, G::Scalar, G::Scalar),
     eval_E: G::Scalar,
     sc_proof_inner: SumcheckProof<G>,
     eval_W: G::Scalar,
-    sc_proof_batch: SumcheckProof<G>,
-    evals_batch: Vec<G::Scalar>,
-    eval_arg: EE::EvaluationArgument,
+    eval_arg: BatchEvaluationArgument<G, EE>,
+    _p: PhantomData<TE>,
 }
 
-impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for RelaxedR1CSSNARK<G, EE> {
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>> RelaxedR1CSSNARKTrait<G>
+    for RelaxedR1CSSNARK<G, EE, TE>
+{
     type ProverKey = ProverKey<G, EE>;
     type VerifierKey = UniformVerifierKey<G, EE>;
 
@@ -145,13 +154,13 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
     ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError> {
         let mut cs: ShapeCS<G> = ShapeCS::new();
         let _ = circuit.synthesize(&mut cs);
-        let (S, ck) = cs.r1cs_shape();
+        let (S, ck) = cs.r1cs_shape()?;
 
         let (pk_ee, vk_ee) = EE::setup(&ck);
 
         let span = tracing::span!(tracing::Level::INFO, "setup vk ");
         let _guard = span.enter();
-        let vk: UniformVerifierKey<G, EE> = UniformVerifierKey::new(S.clone(), vk_ee, S.clone(), 1);
+        let vk: UniformVerifierKey<G, EE> = UniformVerifierKey::new(vk_ee, S.clone(), 1);
         drop(_guard);
         drop(span);
 
@@ -189,7 +198,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
         );
 
         let W = W.pad(&pk.S); // pad the witness
-        let mut transcript = G::TE::new(b"RelaxedR1CSSNARK");
+        let mut transcript = TE::new(b"RelaxedR1CSSNARK");
 
         // sanity check that R1CSShape has certain size characteristics
         pk.S.check_regular_shape();
@@ -198,12 +207,9 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
         transcript.absorb(b"vk", &pk.vk_digest);
         transcript.absorb(b"U", &U);
 
-        // compute the full satisfying assignment by concatenating W.W, U.u, and U.X
-        let span = tracing::span!(tracing::Level::TRACE, "concatenation");
-        let _enter = span.enter();
-        let mut z = [W.W.clone(), vec![U.u], U.X.clone()].concat();
-        drop(_enter);
-        drop(span);
+        // the satisfying assignment is z = (W.W, U.u, U.X), which is never materialized,
+        // so the witness is not copied
+        let io = [vec![U.u], U.X.clone()].concat();
 
         let (num_rounds_x, num_rounds_y) = (
             usize::try_from(pk.S.num_cons.ilog2()).unwrap(),
@@ -215,11 +221,11 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
             .map(|_i| transcript.squeeze(b"t"))
             .collect::<Result<Vec<G::Scalar>, SpartanError>>()?;
 
-        let mut poly_tau = MultilinearPolynomial::new(EqPolynomial::new(tau).evals());
         let (mut poly_Az, mut poly_Bz, poly_Cz, mut poly_uCz_E) = {
             let span = tracing::span!(tracing::Level::TRACE, "poly_generation");
             let _enter = span.enter();
-            let (poly_Az, poly_Bz, poly_Cz) = pk.S.multiply_vec(&z)?;
+            let (poly_Az, poly_Bz, poly_Cz) =
+                pk.S.multiply_vec_uniform(1, &R1CSStateWiring::default(), false, &W.W, &io)?;
             let poly_uCz_E = (0..pk.S.num_cons)
                 .into_par_iter()
                 .map(|i| U.u * poly_Cz[i] + W.E[i])
@@ -232,22 +238,14 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
             )
         };
 
-        let comb_func_outer = |poly_A_comp: &G::Scalar,
-                               poly_B_comp: &G::Scalar,
-                               poly_C_comp: &G::Scalar,
-                               poly_D_comp: &G::Scalar|
-         -> G::Scalar {
-            *poly_A_comp * (*poly_B_comp * *poly_C_comp - *poly_D_comp)
-        };
+        // the entries of Az, Bz, and uCz + E are small integers for boolean-heavy circuits
         let (sc_proof_outer, r_x, claims_outer) =
-            SumcheckProof::prove_cubic_with_additive_term(
+            SumcheckProof::prove_cubic_with_additive_term_small_value(
                 &G::Scalar::ZERO, // claim is zero
-                num_rounds_x,
-                &mut poly_tau,
+                &tau,
                 &mut poly_Az,
                 &mut poly_Bz,
                 &mut poly_uCz_E,
-                comb_func_outer,
                 &mut transcript,
             )?;
 
@@ -256,8 +254,11 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
 
         // claims from the end of sum-check
         let (claim_Az, claim_Bz): (G::Scalar, G::Scalar) = (claims_outer[1], claims_outer[2]);
-        let claim_Cz = poly_Cz.evaluate_with_chi(&evals_rx);                               // evaluate at r_x
-        let eval_E = MultilinearPolynomial::new(W.E.clone()).evaluate_with_chi(&evals_rx); // evaluate at r_x
+        let claim_Cz = poly_Cz.evaluate_with_chi(&evals_rx);                  // evaluate at r_x
+        let eval_E = MultilinearPolynomial::evaluate_with(&W.E, &r_x);        // evaluate at r_x
+
+        // only the bound tables of the outer sum-check are needed from here on
+        drop((poly_Az, poly_Bz, poly_Cz, poly_uCz_E));
         transcript.absorb(
             b"claims_outer",
             &[claim_Az, claim_Bz, claim_Cz, eval_E].as_slice(),
@@ -331,26 +332,19 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
         drop(_enter);
         drop(span);
 
-        let poly_z = {
-            z.resize(pk.S.num_vars * 2, G::Scalar::ZERO);
-            z
-        };
-
-        let comb_func = |poly_A_comp: &G::Scalar, poly_B_comp: &G::Scalar| -> G::Scalar {
-            *poly_A_comp * *poly_B_comp
-        };
+        // z = (W.W, U.u, U.X) padded to 2 * num_vars is read from the witness and the IO entries,
+        // so only poly_ABC is allocated in full
+        let io_entries = pk.S.io_entries_uniform(1, false, &U.u, &U.X);
         let (sc_proof_inner, r_y, _claims_inner) =
-            SumcheckProof::prove_quad(
+            SumcheckProof::prove_quad_with_witness(
                 &claim_inner_joint,
                 num_rounds_y,
                 &mut MultilinearPolynomial::new(poly_ABC),
-                &mut MultilinearPolynomial::new(poly_z),
-                comb_func,
+                &W.W,
+                &io_entries,
                 &mut transcript,
             )?;
 
-        // add additional claims about W and E polynomials to the list from CC
-        let mut w_u_vec = Vec::new();
         let span = tracing::span!(
             tracing::Level::TRACE,
             "MultilinearPolynomial::evaluate_with"
@@ -360,120 +354,17 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
         drop(_enter);
         drop(span);
 
-
-        let (WW, WE) = (W.W, W.E);
-        w_u_vec.push((
-            PolyEvalWitness { p: WW },
-            PolyEvalInstance {
-                c: U.comm_W.clone(),
-                x: r_y[1..].to_vec(),
-                e: eval_W,
-            },
-        ));
-
-        w_u_vec.push((
-            PolyEvalWitness { p: WE },
-            PolyEvalInstance {
-                c: U.comm_E.clone(),
-                x: r_x,
-                e: eval_E,
-            },
-        ));
-
-        // We will now reduce a vector of claims of evaluations at different points into claims about them at the same point.
-        // For example, eval_W =? W(r_y[1..]) and eval_E =? E(r_x) into
-        // two claims: eval_W_prime =? W(rz) and eval_E_prime =? E(rz)
-        // We can them combine the two into one: eval_W_prime + gamma * eval_E_prime =? (W + gamma*E)(rz),
-        // where gamma is a public challenge
-        // Since commitments to W and E are homomorphic, the verifier can compute a commitment
-        // to the batched polynomial.
-        assert!(w_u_vec.len() >= 2);
-
-        let span = tracing::span!(tracing::Level::TRACE, "padding");
-        let _enter = span.enter();
-        let (w_vec, u_vec): (Vec<PolyEvalWitness<G>>, Vec<PolyEvalInstance<G>>) =
-            w_u_vec.into_iter().unzip();
-        let w_vec_padded = PolyEvalWitness::pad(&w_vec); // pad the polynomials to be of the same size
-        let u_vec_padded = PolyEvalInstance::pad(&u_vec); // pad the evaluation points
-        drop(_enter);
-        drop(span);
-
-        // generate a challenge
-        let span = tracing::span!(tracing::Level::TRACE, "rho_challenge_gen");
-        let _enter = span.enter();
-        let rho = transcript.squeeze(b"r")?;
-        let num_claims = w_vec_padded.len();
-        let powers_of_rho = powers::<G>(&rho, num_claims);
-        let claim_batch_joint =
-            u_vec_padded
-                .iter()
-                .zip(powers_of_rho.iter())
-                .map(|(u, p)| u.e * p)
-                .sum();
-        drop(_enter);
-        drop(span);
-
-        let span = tracing::span!(tracing::Level::TRACE, "poly_construction");
-        let _enter = span.enter();
-        let mut polys_left: Vec<MultilinearPolynomial<G::Scalar>> = w_vec_padded
-            .iter()
-            .map(|w| MultilinearPolynomial::new(w.p.clone()))
-            .collect();
-        let mut polys_right: Vec<MultilinearPolynomial<G::Scalar>> = u_vec_padded
-            .iter()
-            .map(|u| MultilinearPolynomial::new(EqPolynomial::new(u.x.clone()).evals()))
-            .collect();
-        drop(_enter);
-        drop(span);
-
-        let num_rounds_z = u_vec_padded[0].x.len();
-        let comb_func = |poly_A_comp: &G::Scalar, poly_B_comp: &G::Scalar| -> G::Scalar {
-            *poly_A_comp * *poly_B_comp
-        };
-        let (sc_proof_batch, r_z, claims_batch) = SumcheckProof::prove_quad_batch(
-            &claim_batch_joint,
-            num_rounds_z,
-            &mut polys_left,
-            &mut polys_right,
-            &powers_of_rho,
-            comb_func,
+        // prove the claims about W and E polynomials in a batch
+        let eval_arg = EE::prove_batch(
+            &pk.ck,
+            &pk.pk_ee,
             &mut transcript,
+            &[U.comm_W.clone(), U.comm_E.clone()],
+            &[W.W, W.E],
+            &[r_y[1..].to_vec(), r_x],
+            &[eval_W, eval_E],
         )?;
 
-        let (claims_batch_left, _): (Vec<G::Scalar>, Vec<G::Scalar>) = claims_batch;
-
-        transcript.absorb(b"l", &claims_batch_left.as_slice());
-
-        // we now combine evaluation claims at the same point rz into one
-        let gamma = transcript.squeeze(b"g")?;
-        let powers_of_gamma: Vec<G::Scalar> = powers::<G>(&gamma, num_claims);
-        let span = tracing::span!(tracing::Level::TRACE, "combine_evals");
-        let _enter = span.enter();
-        let comm_joint = u_vec_padded
-            .iter()
-            .zip(powers_of_gamma.iter())
-            .map(|(u, g_i)| u.c.clone() * *g_i)
-            .fold(Commitment::<G>::default(), |acc, item| acc + item);
-        let poly_joint = PolyEvalWitness::weighted_sum(&w_vec_padded, &powers_of_gamma);
-        let eval_joint = claims_batch_left
-            .iter()
-            .zip(powers_of_gamma.iter())
-            .map(|(e, g_i)| *e * *g_i)
-            .sum();
-        drop(_enter);
-        drop(span);
-
-        let eval_arg =
-            EE::prove(
-                &pk.ck,
-                &pk.pk_ee,
-                &mut transcript,
-                &comm_joint,
-                &poly_joint.p,
-                &r_z,
-                &eval_joint,
-            )?;
-
         Ok(RelaxedR1CSSNARK {
             comm_W: U.comm_W.compress(),
             sc_proof_outer,
@@ -481,9 +372,8 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
             eval_E,
             sc_proof_inner,
             eval_W,
-            sc_proof_batch,
-            evals_batch: claims_batch_left,
             eval_arg,
+            _p: PhantomData,
         })
     }
 
@@ -494,15 +384,15 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
         let comm_W = Commitment::<G>::decompress(&self.comm_W)?;
         let U = RelaxedR1CSInstance::from_r1cs_instance_unchecked(&comm_W, io);
 
-        let mut transcript = G::TE::new(b"RelaxedR1CSSNARK");
+        let mut transcript = TE::new(b"RelaxedR1CSSNARK");
 
         // append the digest of R1CS matrices and the RelaxedR1CSInstance to the transcript
         transcript.absorb(b"vk", &vk.digest());
         transcript.absorb(b"U", &U);
 
         let (num_rounds_x, num_rounds_y) = (
-            usize::try_from(vk.S.num_cons.ilog2()).unwrap(),
-            (usize::try_from(vk.S.num_vars.ilog2()).unwrap() + 1),
+            usize::try_from(vk.num_cons().ilog2()).unwrap(),
+            (usize::try_from(vk.num_vars().ilog2()).unwrap() + 1),
         );
 
         // outer sum-check
@@ -512,7 +402,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
 
         let (claim_outer_final, r_x) =
             self.sc_proof_outer
-                .verify(G::Scalar::ZERO, num_rounds_x, 3, &mut transcript)?;
+                .verify_with_eq(G::Scalar::ZERO, &tau, &mut transcript)?;
 
         // verify claim_outer_final
         let (claim_Az, claim_Bz, claim_Cz) = self.claims_outer;
@@ -554,123 +444,29 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for Relaxe
                         .map(|i| (i + 1, U.X[i]))
                         .collect::<Vec<(usize, G::Scalar)>>(),
                 );
-                SparsePolynomial::new(usize::try_from(vk.S.num_vars.ilog2()).unwrap(), poly_X)
+                SparsePolynomial::new(usize::try_from(vk.num_vars().ilog2()).unwrap(), poly_X)
                     .evaluate(&r_y[1..])
             };
             (G::Scalar::ONE - r_y[0]) * self.eval_W + r_y[0] * eval_X
         };
 
-        // compute evaluations of R1CS matrices
-        let multi_evaluate = |M_vec: &[&[(usize, usize, G::Scalar)]],
-                              r_x: &[G::Scalar],
-                              r_y: &[G::Scalar]|
-         -> Vec<G::Scalar> {
-            let evaluate_with_table = |M: &[(usize, usize, G::Scalar)],
-                                       T_x: &[G::Scalar],
-                                       T_y: &[G::Scalar]|
-             -> G::Scalar {
-                (0..M.len())
-                    .into_par_iter()
-                    .map(|i| {
-                        let (row, col, val) = M[i];
-                        T_x[row] * T_y[col] * val
-                    })
-                    .sum()
-            };
+        // compute evaluations of R1CS matrices from a single step's shape, whose steps are not wired together
+        let (eval_A, eval_B, eval_C) =
+            vk.S_single
+                .evaluate_uniform(vk.num_steps, &R1CSStateWiring::default(), false, &r_x, &r_y);
 
-            let (T_x, T_y) = rayon::join(
-                || EqPolynomial::new(r_x.to_vec()).evals(),
-                || EqPolynomial::new(r_y.to_vec()).evals(),
-            );
-
-            (0..M_vec.len())
-                .into_par_iter()
-                .map(|i| evaluate_with_table(M_vec[i], &T_x, &T_y))
-                .collect()
-        };
-
-        let evals = multi_evaluate(&[&vk.S.A, &vk.S.B, &vk.S.C], &r_x, &r_y);
-
-        let claim_inner_final_expected = (evals[0] + r * evals[1] + r * r * evals[2]) * eval_Z;
+        let claim_inner_final_expected = (eval_A + r * eval_B + r * r * eval_C) * eval_Z;
         if claim_inner_final != claim_inner_final_expected {
             return Err(SpartanError::InvalidSumcheckProof);
         }
 
-        // add claims about W and E polynomials
-        let u_vec: Vec<PolyEvalInstance<G>> = vec![
-            PolyEvalInstance {
-                c: U.comm_W.clone(),
-                x: r_y[1..].to_vec(),
-                e: self.eval_W,
-            },
-            PolyEvalInstance {
-                c: U.comm_E.clone(),
-                x: r_x,
-                e: self.eval_E,
-            },
-        ];
-
-        let u_vec_padded = PolyEvalInstance::pad(&u_vec); // pad the evaluation points
-
-        // generate a challenge
-        let rho = transcript.squeeze(b"r")?;
-        let num_claims = u_vec.len();
-        let powers_of_rho = powers::<G>(&rho, num_claims);
-        let claim_batch_joint =
-            u_vec
-                .iter()
-                .zip(powers_of_rho.iter())
-                .map(|(u, p)| u.e * p)
-                .sum();
-
-        let num_rounds_z = u_vec_padded[0].x.len();
-        let (claim_batch_final, r_z) =
-            self.sc_proof_batch
-                .verify(claim_batch_joint, num_rounds_z, 2, &mut transcript)?;
-
-        let claim_batch_final_expected = {
-            let poly_rz = EqPolynomial::new(r_z.clone());
-            let evals = u_vec_padded
-                .iter()
-                .map(|u| poly_rz.evaluate(&u.x))
-                .collect::<Vec<G::Scalar>>();
-
-            evals
-                .iter()
-                .zip(self.evals_batch.iter())
-                .zip(powers_of_rho.iter())
-                .map(|((e_i, p_i), rho_i)| *e_i * *p_i * rho_i)
-                .sum()
-        };
-
-        if claim_batch_final != claim_batch_final_expected {
-            return Err(SpartanError::InvalidSumcheckProof);
-        }
-
-        transcript.absorb(b"l", &self.evals_batch.as_slice());
-
-        // we now combine evaluation claims at the same point rz into one
-        let gamma = transcript.squeeze(b"g")?;
-        let powers_of_gamma: Vec<G::Scalar> = powers::<G>(&gamma, num_claims);
-        let comm_joint = u_vec_padded
-            .iter()
-            .zip(powers_of_gamma.iter())
-            .map(|(u, g_i)| u.c.clone() * *g_i)
-            .fold(Commitment::<G>::default(), |acc, item| acc + item);
-        let eval_joint = self
-            .evals_batch
-            .iter()
-            .zip(powers_of_gamma.iter())
-            .map(|(e, g_i)| *e * *g_i)
-            .sum();
-
-        // verify
-        EE::verify(
+        // verify the claims about W and E polynomials in a batch
+        EE::verify_batch(
             &vk.vk_ee,
             &mut transcript,
-            &comm_joint,
-            &r_z,
-            &eval_joint,
+            &[U.comm_W.clone(), U.comm_E.clone()],
+            &[r_y[1..].to_vec(), r_x],
+            &[self.eval_W, self.eval_E],
             &self.eval_arg,
         )?;
 
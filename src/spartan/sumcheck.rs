@@ -1,32 +1,49 @@
+//! This module implements the sum-check protocol over multilinear polynomials.
+//! `SumcheckProof::prove` proves the sum of any polynomial combination of multilinear polynomials
+//! over the Boolean hypercube, so protocols built on Spartan's transcript (e.g., lookup or memory checks)
+//! can reuse it; the remaining provers are specialized to the shapes used by the SNARKs.
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::type_complexity)]
 use crate::errors::SpartanError;
+use crate::provider::pedersen::{
+  Commitment as PedersenCommitment, CommitmentKey as PedersenCommitmentKey,
+  CompressedCommitment as PedersenCompressedCommitment,
+};
+use crate::spartan::nizk::{commit, random_scalar, DotProductProof};
 use crate::spartan::polys::{
+  eq::{EqPolynomial, SplitEqPolynomial},
   multilinear::MultilinearPolynomial,
   univariate::{CompressedUniPoly, UniPoly},
 };
-use crate::traits::{Group, TranscriptEngineTrait};
-use ff::Field;
+use crate::traits::{commitment::CommitmentTrait, Group, PrimeGroup, TranscriptEngineTrait};
+use bitvec::field::BitField;
+use core::ops::{Add, Range, Sub};
+use ff::{Field, PrimeField, PrimeFieldBits};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// A sum-check proof, which consists of the prover's univariate polynomial in each round
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub(crate) struct SumcheckProof<G: Group> {
+pub struct SumcheckProof<G: Group> {
   compressed_polys: Vec<CompressedUniPoly<G::Scalar>>,
 }
 
 impl<G: Group> SumcheckProof<G> {
+  /// Creates a proof from the compressed round polynomials of a sum-check
   pub fn new(compressed_polys: Vec<CompressedUniPoly<G::Scalar>>) -> Self {
     Self { compressed_polys }
   }
 
-  pub fn verify(
+  /// Verifies the proof that `claim` is a sum over `num_rounds` variables of a polynomial of degree `degree_bound`.
+  /// Returns the claimed evaluation of the summed polynomial at the verifier's challenges and the challenges,
+  /// which the caller checks against the evaluations of the polynomials it combines
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
     &self,
     claim: G::Scalar,
     num_rounds: usize,
     degree_bound: usize,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<(G::Scalar, Vec<G::Scalar>), SpartanError> {
     let mut e = claim;
     let mut r: Vec<G::Scalar> = Vec::new();
@@ -63,6 +80,51 @@ impl<G: Group> SumcheckProof<G> {
     Ok((e, r))
   }
 
+  /// Verifies a proof of `prove_cubic_with_eq` that `claim` is the sum of `eq(tau, x) * g(x)` for a quadratic `g`,
+  /// rebuilding each round polynomial `l(X) * t(X)` from the linear factor `l` of `eq` and the quadratic `t` in the proof.
+  /// Returns the claimed evaluation of the summed polynomial at the verifier's challenges and the challenges
+  pub fn verify_with_eq<TE: TranscriptEngineTrait<G>>(
+    &self,
+    claim: G::Scalar,
+    tau: &[G::Scalar],
+    transcript: &mut TE,
+  ) -> Result<(G::Scalar, Vec<G::Scalar>), SpartanError> {
+    let mut e = claim;
+    let mut r: Vec<G::Scalar> = Vec::new();
+
+    // verify that there is a univariate polynomial for each round
+    if self.compressed_polys.len() != tau.len() {
+      return Err(SpartanError::InvalidSumcheckProof);
+    }
+
+    // eq(tau, .) over the bound variables
+    let mut eq_bound = G::Scalar::ONE;
+    for (poly, tau_i) in self.compressed_polys.iter().zip(tau.iter()) {
+      let l = (eq_bound * (G::Scalar::ONE - tau_i), eq_bound * tau_i);
+      let poly = poly
+        .decompress_with_linear_factor(&e, &l)
+        .ok_or(SpartanError::InvalidSumcheckProof)?;
+
+      // verify degree bound
+      if poly.degree() != 2 {
+        return Err(SpartanError::InvalidSumcheckProof);
+      }
+
+      // append the prover's message to the transcript
+      transcript.absorb(b"p", &poly);
+
+      //derive the verifier's challenge for the next round
+      let r_i = transcript.squeeze(b"c")?;
+      r.push(r_i);
+
+      // evaluate the round polynomial l(X) * t(X) at r_i
+      eq_bound = l.0 + (l.1 - l.0) * r_i;
+      e = eq_bound * poly.evaluate(&r_i);
+    }
+
+    Ok((e, r))
+  }
+
   #[inline]
   #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::compute_eval_points_quadratic")]
   pub(in crate::spartan) fn compute_eval_points_quadratic<F>(
@@ -92,14 +154,115 @@ impl<G: Group> SumcheckProof<G> {
       )
   }
 
+  /// Proves that `claim` is the sum of `comb_func(p_1(x), ..., p_k(x))` over all `x` in the Boolean hypercube,
+  /// where `polys` holds the multilinear polynomials `p_1, ..., p_k` over the same number of variables
+  /// and `comb_func` is a polynomial of total degree `degree`.
+  /// Returns the proof, the verifier's challenges `r`, and the evaluations `p_1(r), ..., p_k(r)`.
+  /// The polynomials are bound to the challenges in place
+  #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove")]
+  pub fn prove<F, TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    degree: usize,
+    polys: &mut [MultilinearPolynomial<G::Scalar>],
+    comb_func: F,
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
+  where
+    F: Fn(&[G::Scalar]) -> G::Scalar + Sync,
+  {
+    if degree == 0 || polys.is_empty() || polys.iter().any(|p| p.len() != polys[0].len()) {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    let num_rounds = polys[0].get_num_vars();
+    let mut r: Vec<G::Scalar> = Vec::new();
+    let mut compressed_polys: Vec<CompressedUniPoly<G::Scalar>> = Vec::new();
+    let mut claim_per_round = *claim;
+    for _ in 0..num_rounds {
+      let poly = {
+        // the evaluation at 1 follows from the claim
+        let mut evals = Self::compute_eval_points(polys, degree, &comb_func);
+        evals.insert(1, claim_per_round - evals[0]);
+        UniPoly::from_evals(&evals)
+      };
+
+      // append the prover's message to the transcript
+      transcript.absorb(b"p", &poly);
+
+      //derive the verifier's challenge for the next round
+      let r_i = transcript.squeeze(b"c")?;
+      r.push(r_i);
+      compressed_polys.push(poly.compress());
+
+      // Set up next round
+      claim_per_round = poly.evaluate(&r_i);
+
+      // bound all tables to the verifier's challenege
+      polys.iter_mut().for_each(|p| p.bound_poly_var_top(&r_i));
+    }
+
+    let evals = polys.iter().map(|p| p[0]).collect();
+    Ok((SumcheckProof { compressed_polys }, r, evals))
+  }
+
+  /// returns the evaluations of the round polynomial at 0, 2, 3, ..., `degree`
+  fn compute_eval_points<F>(
+    polys: &[MultilinearPolynomial<G::Scalar>],
+    degree: usize,
+    comb_func: &F,
+  ) -> Vec<G::Scalar>
+  where
+    F: Fn(&[G::Scalar]) -> G::Scalar + Sync,
+  {
+    let len = polys[0].len() / 2;
+    (0..len)
+      .into_par_iter()
+      .fold(
+        // each thread accumulates its evaluations and reuses its buffers for the points and the differences
+        || {
+          (
+            vec![G::Scalar::ZERO; degree],
+            Vec::with_capacity(polys.len()),
+            Vec::with_capacity(polys.len()),
+          )
+        },
+        |(mut evals, mut point, mut diffs), i| {
+          // the polynomials at (t, i) for t = 0, 1, ..., degree, computed incrementally from t = 0
+          point.clear();
+          point.extend(polys.iter().map(|p| p[i]));
+          diffs.clear();
+          diffs.extend(polys.iter().map(|p| p[len + i] - p[i]));
+
+          evals[0] += comb_func(&point);
+          for t in 1..=degree {
+            point
+              .iter_mut()
+              .zip(diffs.iter())
+              .for_each(|(p, d)| *p += d);
+            if t > 1 {
+              evals[t - 1] += comb_func(&point);
+            }
+          }
+          (evals, point, diffs)
+        },
+      )
+      .map(|(evals, _, _)| evals)
+      .reduce(
+        || vec![G::Scalar::ZERO; degree],
+        |a, b| a.iter().zip(b.iter()).map(|(a, b)| *a + b).collect(),
+      )
+  }
+
+  /// Proves the sum of `comb_func(A(x), B(x))` for a quadratic `comb_func`;
+  /// returns the proof, the challenges, and the evaluations of `A` and `B` at the challenges
   #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_quad")]
-  pub fn prove_quad<F>(
+  pub fn prove_quad<F, TE: TranscriptEngineTrait<G>>(
     claim: &G::Scalar,
     num_rounds: usize,
     poly_A: &mut MultilinearPolynomial<G::Scalar>,
     poly_B: &mut MultilinearPolynomial<G::Scalar>,
     comb_func: F,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
   where
     F: Fn(&G::Scalar, &G::Scalar) -> G::Scalar + Sync,
@@ -140,15 +303,107 @@ impl<G: Group> SumcheckProof<G> {
     ))
   }
 
+  /// Proves the sum of `A(x) * z(x)` like `prove_quad`, where `z = (W, io)` has the length of `A`:
+  /// its first half is `W`, and its second half is zero except for the entries `io`.
+  /// The first round is computed from `W` directly, and `z` is only materialized once it is bound
+  /// to the first challenge, so `W` is borrowed rather than padded into a copy twice its size
+  #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_quad_with_witness")]
+  pub fn prove_quad_with_witness<TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    num_rounds: usize,
+    poly_A: &mut MultilinearPolynomial<G::Scalar>,
+    W: &[G::Scalar],
+    io: &[(usize, G::Scalar)],
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError> {
+    let n = W.len();
+    if num_rounds == 0 || poly_A.len() != 1 << num_rounds || 2 * n != poly_A.len() {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    if io.iter().any(|(i, _)| *i >= n) {
+      return Err(SpartanError::InvalidIndex);
+    }
+
+    let poly = {
+      // z(2, x) is 2 * io(x) - W(x), where io is sparse
+      let (A_lo, A_hi) = poly_A.Z.split_at(n);
+      let (eval_point_0, eval_point_2_W) = (0..n)
+        .into_par_iter()
+        .map(|i| {
+          let A_bound_point = A_hi[i] + A_hi[i] - A_lo[i];
+          (A_lo[i] * W[i], A_bound_point * W[i])
+        })
+        .reduce(
+          || (G::Scalar::ZERO, G::Scalar::ZERO),
+          |a, b| (a.0 + b.0, a.1 + b.1),
+        );
+      let eval_point_2_io = io
+        .iter()
+        .map(|(i, v)| (A_hi[*i] + A_hi[*i] - A_lo[*i]) * v.double())
+        .sum::<G::Scalar>();
+
+      let evals = vec![
+        eval_point_0,
+        *claim - eval_point_0,
+        eval_point_2_io - eval_point_2_W,
+      ];
+      UniPoly::from_evals(&evals)
+    };
+
+    // append the prover's message to the transcript
+    transcript.absorb(b"p", &poly);
+
+    //derive the verifier's challenge for the next round
+    let r_0 = transcript.squeeze(b"c")?;
+    let claim_per_round = poly.evaluate(&r_0);
+
+    // bound both tables to the verifier's challenge
+    let mut poly_z = {
+      let mut z = W
+        .par_iter()
+        .map(|w| *w - r_0 * w)
+        .collect::<Vec<G::Scalar>>();
+      for (i, v) in io {
+        z[*i] += r_0 * v;
+      }
+      MultilinearPolynomial::new(z)
+    };
+    poly_A.bound_poly_var_top(&r_0);
+
+    let (proof, r_rest, evals) = Self::prove_quad(
+      &claim_per_round,
+      num_rounds - 1,
+      poly_A,
+      &mut poly_z,
+      |A: &G::Scalar, z: &G::Scalar| *A * *z,
+      transcript,
+    )?;
+
+    let mut polys = vec![poly.compress()];
+    polys.extend(proof.compressed_polys);
+    let mut r = vec![r_0];
+    r.extend(r_rest);
+
+    Ok((
+      SumcheckProof {
+        compressed_polys: polys,
+      },
+      r,
+      evals,
+    ))
+  }
+
+  /// Proves a random linear combination, with coefficients `coeffs`, of several quadratic sum-check instances
+  /// over the same number of variables
   #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_quad_batch")]
-  pub fn prove_quad_batch<F>(
+  pub fn prove_quad_batch<F, TE: TranscriptEngineTrait<G>>(
     claim: &G::Scalar,
     num_rounds: usize,
-    poly_A_vec: &mut Vec<MultilinearPolynomial<G::Scalar>>,
-    poly_B_vec: &mut Vec<MultilinearPolynomial<G::Scalar>>,
+    poly_A_vec: &mut [MultilinearPolynomial<G::Scalar>],
+    poly_B_vec: &mut [MultilinearPolynomial<G::Scalar>],
     coeffs: &[G::Scalar],
     comb_func: F,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<(Self, Vec<G::Scalar>, (Vec<G::Scalar>, Vec<G::Scalar>)), SpartanError>
   where
     F: Fn(&G::Scalar, &G::Scalar) -> G::Scalar + Sync,
@@ -246,8 +501,10 @@ impl<G: Group> SumcheckProof<G> {
       )
   }
 
+  /// Proves the sum of `comb_func(A(x), B(x), C(x), D(x))` for a cubic `comb_func`, such as `A * (B * C - D)`;
+  /// returns the proof, the challenges, and the evaluations of the four polynomials at the challenges
   #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_cubic_with_additive_term")]
-  pub fn prove_cubic_with_additive_term<F>(
+  pub fn prove_cubic_with_additive_term<F, TE: TranscriptEngineTrait<G>>(
     claim: &G::Scalar,
     num_rounds: usize,
     poly_A: &mut MultilinearPolynomial<G::Scalar>,
@@ -255,7 +512,7 @@ impl<G: Group> SumcheckProof<G> {
     poly_C: &mut MultilinearPolynomial<G::Scalar>,
     poly_D: &mut MultilinearPolynomial<G::Scalar>,
     comb_func: F,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
   ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
   where
     F: Fn(&G::Scalar, &G::Scalar, &G::Scalar, &G::Scalar) -> G::Scalar + Sync,
@@ -305,4 +562,1272 @@ impl<G: Group> SumcheckProof<G> {
       vec![poly_A[0], poly_B[0], poly_C[0], poly_D[0]],
     ))
   }
+
+  /// Returns the evaluations at 0, 2, and 3 of the round polynomial of `eq(tau, x) * comb_func(B(x), C(x), D(x))`
+  /// for a quadratic `comb_func`, given the claimed sum `claim` of the round polynomial over {0, 1}
+  #[inline]
+  pub(in crate::spartan) fn compute_eval_points_cubic_with_eq<F>(
+    claim: &G::Scalar,
+    eq: &SplitEqPolynomial<G::Scalar>,
+    poly_B: &MultilinearPolynomial<G::Scalar>,
+    poly_C: &MultilinearPolynomial<G::Scalar>,
+    poly_D: &MultilinearPolynomial<G::Scalar>,
+    comb_func: &F,
+  ) -> (G::Scalar, G::Scalar, G::Scalar)
+  where
+    F: Fn(&G::Scalar, &G::Scalar, &G::Scalar) -> G::Scalar + Sync,
+  {
+    let t =
+      Self::compute_eval_points_quadratic_with_eq(claim, eq, poly_B, poly_C, poly_D, comb_func);
+    let evals = eq.round_evals(claim, &t);
+    (evals[0], evals[2], evals[3])
+  }
+
+  /// Returns the evaluations at 0, 1, and 2 of the quadratic `t` such that the round polynomial of
+  /// `eq(tau, x) * comb_func(B(x), C(x), D(x))` is `l(X) * t(X)`, where `l` is the linear factor of `eq`,
+  /// given the claimed sum `claim` of the round polynomial over {0, 1}.
+  /// The tables are only used to evaluate `t` at 0 and 2
+  #[inline]
+  #[tracing::instrument(
+    skip_all,
+    name = "Spartan2::sumcheck::compute_eval_points_quadratic_with_eq"
+  )]
+  pub(in crate::spartan) fn compute_eval_points_quadratic_with_eq<F>(
+    claim: &G::Scalar,
+    eq: &SplitEqPolynomial<G::Scalar>,
+    poly_B: &MultilinearPolynomial<G::Scalar>,
+    poly_C: &MultilinearPolynomial<G::Scalar>,
+    poly_D: &MultilinearPolynomial<G::Scalar>,
+    comb_func: &F,
+  ) -> [G::Scalar; 3]
+  where
+    F: Fn(&G::Scalar, &G::Scalar, &G::Scalar) -> G::Scalar + Sync,
+  {
+    let len = poly_B.len() / 2;
+    let (E_out, E_in) = (eq.E_out(), eq.E_in());
+
+    // sums `E_out * E_in * f` over the points after the current variable, with one multiplication by E_out per point of E_out
+    let sum_with_eq = |f: &(dyn Fn(usize) -> (G::Scalar, G::Scalar) + Sync)| {
+      (0..E_out.len())
+        .into_par_iter()
+        .map(|x_out| {
+          let (sum_0, sum_1) = (0..E_in.len())
+            .into_par_iter()
+            .map(|x_in| {
+              let (v_0, v_1) = f(x_out * E_in.len() + x_in);
+              (E_in[x_in] * v_0, E_in[x_in] * v_1)
+            })
+            .reduce(
+              || (G::Scalar::ZERO, G::Scalar::ZERO),
+              |a, b| (a.0 + b.0, a.1 + b.1),
+            );
+          (E_out[x_out] * sum_0, E_out[x_out] * sum_1)
+        })
+        .reduce(
+          || (G::Scalar::ZERO, G::Scalar::ZERO),
+          |a, b| (a.0 + b.0, a.1 + b.1),
+        )
+    };
+
+    let (t_0, t_2) = sum_with_eq(&|i| {
+      // eval 0: bound_func is A(low)
+      let eval_point_0 = comb_func(&poly_B[i], &poly_C[i], &poly_D[i]);
+
+      // eval 2: bound_func is -A(low) + 2*A(high)
+      let poly_B_bound_point = poly_B[len + i] + poly_B[len + i] - poly_B[i];
+      let poly_C_bound_point = poly_C[len + i] + poly_C[len + i] - poly_C[i];
+      let poly_D_bound_point = poly_D[len + i] + poly_D[len + i] - poly_D[i];
+      let eval_point_2 = comb_func(
+        &poly_B_bound_point,
+        &poly_C_bound_point,
+        &poly_D_bound_point,
+      );
+      (eval_point_0, eval_point_2)
+    });
+
+    // the evaluation at 1 follows from the claim, unless the linear factor vanishes at 1
+    let t_1 = eq.quadratic_eval_1(claim, &t_0).unwrap_or_else(|| {
+      sum_with_eq(&|i| {
+        let eval_point_1 = comb_func(&poly_B[len + i], &poly_C[len + i], &poly_D[len + i]);
+        (eval_point_1, G::Scalar::ZERO)
+      })
+      .0
+    });
+
+    [t_0, t_1, t_2]
+  }
+
+  /// Proves the sum of `eq(tau, x) * comb_func(B(x), C(x), D(x))` for a quadratic `comb_func`, such as `B * C - D`,
+  /// where `eq` holds `eq(tau, .)` in factored form, over all the unbound variables of `eq`.
+  /// The round polynomial is `l(X) * t(X)`, where the verifier knows the linear factor `l` of `eq`, so each round
+  /// sends the quadratic `t` alone, and the eq table is neither materialized nor bound. The proof is checked
+  /// with `verify_with_eq`. Returns the proof, the challenges, and the evaluations of `eq(tau, .)`, B, C, and D
+  /// at the challenges
+  #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_cubic_with_eq")]
+  pub fn prove_cubic_with_eq<F, TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    eq: &mut SplitEqPolynomial<G::Scalar>,
+    poly_B: &mut MultilinearPolynomial<G::Scalar>,
+    poly_C: &mut MultilinearPolynomial<G::Scalar>,
+    poly_D: &mut MultilinearPolynomial<G::Scalar>,
+    comb_func: F,
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
+  where
+    F: Fn(&G::Scalar, &G::Scalar, &G::Scalar) -> G::Scalar + Sync,
+  {
+    if [&*poly_B, &*poly_C, &*poly_D]
+      .iter()
+      .any(|p| p.len() != eq.len())
+    {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    let mut r: Vec<G::Scalar> = Vec::new();
+    let mut polys: Vec<CompressedUniPoly<G::Scalar>> = Vec::new();
+    let mut claim_per_round = *claim;
+
+    while !eq.is_empty() {
+      let l = eq.linear_factor();
+      let poly = UniPoly::from_evals(&Self::compute_eval_points_quadratic_with_eq(
+        &claim_per_round,
+        eq,
+        poly_B,
+        poly_C,
+        poly_D,
+        &comb_func,
+      ));
+
+      // append the prover's message to the transcript
+      transcript.absorb(b"p", &poly);
+
+      //derive the verifier's challenge for the next round
+      let r_i = transcript.squeeze(b"c")?;
+      r.push(r_i);
+      polys.push(poly.compress_with_linear_factor(&l));
+
+      // Set up next round
+      claim_per_round = (l.0 + (l.1 - l.0) * r_i) * poly.evaluate(&r_i);
+
+      // bound all tables to the verifier's challenege
+      eq.bound_poly_var_top(&r_i);
+      poly_B.bound_poly_var_top(&r_i);
+      poly_C.bound_poly_var_top(&r_i);
+      poly_D.bound_poly_var_top(&r_i);
+    }
+
+    Ok((
+      SumcheckProof {
+        compressed_polys: polys,
+      },
+      r,
+      vec![eq.eval(), poly_B[0], poly_C[0], poly_D[0]],
+    ))
+  }
+
+  /// Proves the sum of `eq(tau, x) * (Az(x) * Bz(x) - Cz(x))`, the outer sum-check of Spartan.
+  /// Produces the same proof as `prove_cubic_with_eq` with `eq = eq(tau, .)` and `comb_func = B * C - D`,
+  /// and returns the evaluations of `eq(tau, .)`, Az, Bz, and Cz at the challenges.
+  ///
+  /// The first `SMALL_VALUE_ROUNDS` rounds use the small-value optimization. Entries of Az, Bz, and Cz
+  /// of boolean-heavy circuits are small integers, so the products in those rounds are computed with
+  /// machine-word arithmetic over the extensions of the tables to `{0, 1, 2}^SMALL_VALUE_ROUNDS`,
+  /// and the tables are bound to the first challenges in a single pass afterwards.
+  /// Entries that are not small fall back to field arithmetic. The remaining rounds run over the bound tables
+  /// with `prove_cubic_with_eq`
+  #[tracing::instrument(
+    skip_all,
+    name = "Spartan2::sumcheck::prove_cubic_with_additive_term_small_value"
+  )]
+  pub fn prove_cubic_with_additive_term_small_value<TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    tau: &[G::Scalar],
+    poly_Az: &mut MultilinearPolynomial<G::Scalar>,
+    poly_Bz: &mut MultilinearPolynomial<G::Scalar>,
+    poly_Cz: &mut MultilinearPolynomial<G::Scalar>,
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError> {
+    let num_rounds = tau.len();
+    if [&*poly_Az, &*poly_Bz, &*poly_Cz]
+      .iter()
+      .any(|p| p.len() != 1 << num_rounds)
+    {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // the first `ell` variables are handled with small values; `x_b` ranges over the remaining ones
+    let ell = SMALL_VALUE_ROUNDS.min(num_rounds);
+    let grid = Grid::new(ell);
+    let eq_b = EqPolynomial::new(tau[ell..].to_vec()).compute_factored_evals();
+
+    let tables = [&poly_Az.Z[..], &poly_Bz.Z[..], &poly_Cz.Z[..]];
+    let small = tables.map(|t| t.par_iter().map(small_value).collect::<Vec<i64>>());
+    let Q = Self::small_value_accumulators(&grid, tables, &small, &eq_b, 0);
+
+    let (mut polys, mut r, claim_per_round, mut eq) =
+      Self::small_value_rounds(claim, tau, &grid, &Q, transcript)?;
+
+    // bind all tables to the challenges of the small-value rounds at once
+    let eq_r = EqPolynomial::new(r.clone()).evals();
+    let [bound_Az, bound_Bz, bound_Cz] = Self::small_value_bind(tables, &small, &eq_r);
+    drop(small);
+    *poly_Az = MultilinearPolynomial::new(bound_Az);
+    *poly_Bz = MultilinearPolynomial::new(bound_Bz);
+    *poly_Cz = MultilinearPolynomial::new(bound_Cz);
+
+    let (proof, r_rest, claims) = Self::prove_cubic_with_eq(
+      &claim_per_round,
+      &mut eq,
+      poly_Az,
+      poly_Bz,
+      poly_Cz,
+      |B: &G::Scalar, C: &G::Scalar, D: &G::Scalar| *B * *C - *D,
+      transcript,
+    )?;
+    polys.extend(proof.compressed_polys);
+    r.extend(r_rest);
+
+    Ok((
+      SumcheckProof {
+        compressed_polys: polys,
+      },
+      r,
+      claims,
+    ))
+  }
+
+  /// Produces the same proof as `prove_cubic_with_additive_term_small_value`, without materializing Az, Bz, and Cz
+  /// over `tau.len()` variables in full. `rows(range)` returns the rows `range` of the three tables, and at most
+  /// about `max_rows` rows of each table are held at once. Every row is produced twice, once for the small-value rounds
+  /// and once to bind the tables to their challenges, after which the remaining rounds run over tables
+  /// that are `2^SMALL_VALUE_ROUNDS` times smaller
+  #[tracing::instrument(
+    skip_all,
+    name = "Spartan2::sumcheck::prove_cubic_with_additive_term_small_value_streaming"
+  )]
+  pub fn prove_cubic_with_additive_term_small_value_streaming<R, TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    tau: &[G::Scalar],
+    max_rows: usize,
+    rows: R,
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>
+  where
+    R: Fn(Range<usize>) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>, Vec<G::Scalar>), SpartanError>,
+  {
+    let num_rounds = tau.len();
+    let ell = SMALL_VALUE_ROUNDS.min(num_rounds);
+    let (num_b, size_b) = (1 << ell, 1 << (num_rounds - ell));
+    let grid = Grid::new(ell);
+    let eq_b = EqPolynomial::new(tau[ell..].to_vec()).compute_factored_evals();
+
+    // a chunk holds the rows `b * size_b + x_b` for every `b` and for `x_b` in a range of length `chunk_len`,
+    // laid out like the full tables with `size_b` replaced by the length of the range
+    let chunk_len = (max_rows / num_b).clamp(1, size_b);
+    let chunk = |x_b: Range<usize>| -> Result<([Vec<G::Scalar>; 3], [Vec<i64>; 3]), SpartanError> {
+      let mut tables: [Vec<G::Scalar>; 3] =
+        core::array::from_fn(|_| Vec::with_capacity(num_b * x_b.len()));
+      for b in 0..num_b {
+        let (Az, Bz, Cz) = rows(b * size_b + x_b.start..b * size_b + x_b.end)?;
+        if [&Az, &Bz, &Cz].iter().any(|t| t.len() != x_b.len()) {
+          return Err(SpartanError::InvalidInputLength);
+        }
+        tables[0].extend(Az);
+        tables[1].extend(Bz);
+        tables[2].extend(Cz);
+      }
+      let small = tables
+        .each_ref()
+        .map(|t| t.par_iter().map(small_value).collect::<Vec<i64>>());
+      Ok((tables, small))
+    };
+    let chunks = || {
+      (0..size_b)
+        .step_by(chunk_len)
+        .map(|x| x..(x + chunk_len).min(size_b))
+    };
+
+    let mut Q = vec![G::Scalar::ZERO; grid.size()];
+    for x_b in chunks() {
+      let offset = x_b.start;
+      let (tables, small) = chunk(x_b)?;
+      let Q_chunk = Self::small_value_accumulators(
+        &grid,
+        tables.each_ref().map(|t| &t[..]),
+        &small,
+        &eq_b,
+        offset,
+      );
+      Q.iter_mut()
+        .zip(Q_chunk)
+        .for_each(|(q, q_chunk)| *q += q_chunk);
+    }
+
+    let (mut polys, mut r, claim_per_round, mut eq) =
+      Self::small_value_rounds(claim, tau, &grid, &Q, transcript)?;
+
+    // produce every chunk again, and bind it to the challenges of the small-value rounds
+    let eq_r = EqPolynomial::new(r.clone()).evals();
+    let mut bound: [Vec<G::Scalar>; 3] = core::array::from_fn(|_| Vec::with_capacity(size_b));
+    for x_b in chunks() {
+      let (tables, small) = chunk(x_b)?;
+      let bound_chunk = Self::small_value_bind(tables.each_ref().map(|t| &t[..]), &small, &eq_r);
+      for (b, b_chunk) in bound.iter_mut().zip(bound_chunk) {
+        b.extend(b_chunk);
+      }
+    }
+    let [bound_Az, bound_Bz, bound_Cz] = bound;
+
+    let (proof, r_rest, claims) = Self::prove_cubic_with_eq(
+      &claim_per_round,
+      &mut eq,
+      &mut MultilinearPolynomial::new(bound_Az),
+      &mut MultilinearPolynomial::new(bound_Bz),
+      &mut MultilinearPolynomial::new(bound_Cz),
+      |B: &G::Scalar, C: &G::Scalar, D: &G::Scalar| *B * *C - *D,
+      transcript,
+    )?;
+    polys.extend(proof.compressed_polys);
+    r.extend(r_rest);
+
+    Ok((
+      SumcheckProof {
+        compressed_polys: polys,
+      },
+      r,
+      claims,
+    ))
+  }
+
+  /// Computes Q(z) = sum over x_b of eq(tau[ell..], x_b) * (Az * Bz - Cz)(z, x_b) for every z in `{0, 1, 2}^ell`,
+  /// where x_b ranges over `offset..offset + len` and entry `b * len + (x_b - offset)` of the tables holds the entry at `(b, x_b)`.
+  /// `small` holds the entries of the tables as small values
+  fn small_value_accumulators(
+    grid: &Grid,
+    tables: [&[G::Scalar]; 3],
+    small: &[Vec<i64>; 3],
+    eq_b: &(Vec<G::Scalar>, Vec<G::Scalar>),
+    offset: usize,
+  ) -> Vec<G::Scalar> {
+    let span = tracing::span!(tracing::Level::TRACE, "small_value_accumulators");
+    let _enter = span.enter();
+    let (eq_b_out, eq_b_in) = eq_b;
+    let num_b = grid.num_points();
+    let len = tables[0].len() / num_b;
+    (0..len)
+      .into_par_iter()
+      .fold(
+        || GridScratch::<G::Scalar>::new(grid),
+        |mut s, x| {
+          let index = |b: usize| b * len + x;
+          let x_b = offset + x;
+          let eq_x_b = eq_b_out[x_b / eq_b_in.len()] * eq_b_in[x_b % eq_b_in.len()];
+          if (0..num_b).all(|b| small.iter().all(|t| t[index(b)] != NOT_SMALL)) {
+            for (t, g) in small.iter().zip(s.small.iter_mut()) {
+              let evals = (0..num_b).map(|b| t[index(b)]).collect::<Vec<_>>();
+              grid.extend(&evals, g);
+            }
+            for z in 0..grid.size() {
+              let v =
+                i128::from(s.small[0][z]) * i128::from(s.small[1][z]) - i128::from(s.small[2][z]);
+              if v != 0 {
+                s.acc[z] += mul_small(&eq_x_b, v);
+              }
+            }
+          } else {
+            for (t, g) in tables.iter().zip(s.field.iter_mut()) {
+              let evals = (0..num_b).map(|b| t[index(b)]).collect::<Vec<_>>();
+              grid.extend(&evals, g);
+            }
+            for z in 0..grid.size() {
+              s.acc[z] += eq_x_b * (s.field[0][z] * s.field[1][z] - s.field[2][z]);
+            }
+          }
+          s
+        },
+      )
+      .map(|s| s.acc)
+      .reduce(
+        || vec![G::Scalar::ZERO; grid.size()],
+        |a, b| a.iter().zip(b.iter()).map(|(a, b)| *a + b).collect(),
+      )
+  }
+
+  /// Runs the small-value rounds from the accumulators `Q`; returns the round polynomials, the challenges,
+  /// the claim for the next round, and `eq(tau, .)` bound to the challenges
+  fn small_value_rounds<TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    tau: &[G::Scalar],
+    grid: &Grid,
+    Q: &[G::Scalar],
+    transcript: &mut TE,
+  ) -> Result<
+    (
+      Vec<CompressedUniPoly<G::Scalar>>,
+      Vec<G::Scalar>,
+      G::Scalar,
+      SplitEqPolynomial<G::Scalar>,
+    ),
+    SpartanError,
+  > {
+    let ell = grid.num_vars();
+    let mut r: Vec<G::Scalar> = Vec::new();
+    let mut polys: Vec<CompressedUniPoly<G::Scalar>> = Vec::new();
+    let mut claim_per_round = *claim;
+    let mut eq = SplitEqPolynomial::new(tau.to_vec());
+    let two = G::Scalar::from(2);
+    let inv_two = two.invert().unwrap();
+
+    for j in 0..ell {
+      let poly = {
+        // the round polynomial is eq(tau[..j], r) * eq(tau[j], X) * t(X) for a quadratic t, which the prover sends, where
+        // t(X) = sum over x_a and x_b of eq(tau[j + 1..], (x_a, x_b)) * (Az * Bz - Cz)(r, X, x_a, x_b),
+        // and the products at r follow from those on {0, 1, 2}^j by Lagrange interpolation
+        let lagrange = r.iter().fold(vec![G::Scalar::ONE], |w, r_k| {
+          let l = [
+            (*r_k - G::Scalar::ONE) * (*r_k - two) * inv_two,
+            *r_k * (two - r_k),
+            *r_k * (*r_k - G::Scalar::ONE) * inv_two,
+          ];
+          w.iter()
+            .flat_map(|w| l.iter().map(move |l| *w * l))
+            .collect()
+        });
+        let eq_a = EqPolynomial::new(tau[j + 1..ell].to_vec()).evals();
+        let stride = grid.size() / 3usize.pow(j as u32 + 1);
+        let t: [G::Scalar; 3] = core::array::from_fn(|x| {
+          lagrange
+            .iter()
+            .enumerate()
+            .map(|(y, l)| {
+              *l * eq_a
+                .iter()
+                .enumerate()
+                .map(|(x_a, e)| *e * Q[(3 * y + x) * stride + to_base3(x_a)])
+                .sum::<G::Scalar>()
+            })
+            .sum()
+        });
+
+        UniPoly::from_evals(&t)
+      };
+
+      // append the prover's message to the transcript
+      transcript.absorb(b"p", &poly);
+
+      //derive the verifier's challenge for the next round
+      let r_j = transcript.squeeze(b"c")?;
+      r.push(r_j);
+      let l = eq.linear_factor();
+      polys.push(poly.compress_with_linear_factor(&l));
+
+      // Set up next round
+      claim_per_round = (l.0 + (l.1 - l.0) * r_j) * poly.evaluate(&r_j);
+      eq.bound_poly_var_top(&r_j);
+    }
+
+    Ok((polys, r, claim_per_round, eq))
+  }
+
+  /// Binds the first variables of the tables, laid out as in `small_value_accumulators`, to the challenges
+  /// whose eq evaluations are `eq_r`
+  fn small_value_bind(
+    tables: [&[G::Scalar]; 3],
+    small: &[Vec<i64>; 3],
+    eq_r: &[G::Scalar],
+  ) -> [Vec<G::Scalar>; 3] {
+    let span = tracing::span!(tracing::Level::TRACE, "small_value_bind");
+    let _enter = span.enter();
+    let num_b = eq_r.len();
+    let len = tables[0].len() / num_b;
+    core::array::from_fn(|k| {
+      let (p, t) = (tables[k], &small[k]);
+      (0..len)
+        .into_par_iter()
+        .map(|x| {
+          (0..num_b)
+            .map(|b| {
+              let i = b * len + x;
+              if t[i] == NOT_SMALL {
+                eq_r[b] * p[i]
+              } else {
+                mul_small(&eq_r[b], i128::from(t[i]))
+              }
+            })
+            .sum()
+        })
+        .collect()
+    })
+  }
+}
+
+/// The number of rounds of `prove_cubic_with_additive_term_small_value` that use small-value arithmetic
+pub(crate) const SMALL_VALUE_ROUNDS: usize = 3;
+
+/// Entries of absolute value below `2^SMALL_VALUE_BITS` are small. Their extensions to `{0, 1, 2}^SMALL_VALUE_ROUNDS`
+/// fit in an `i64`, and products of those in an `i128`
+const SMALL_VALUE_BITS: usize = 40;
+
+/// Marks an entry that is not small
+const NOT_SMALL: i64 = i64::MIN;
+
+/// Returns `s` as a signed integer if it is small, and `NOT_SMALL` otherwise.
+/// Converts `s` out of the field once, and finds a small negation from the limbs of `p - s`
+fn small_value<F: PrimeFieldBits>(s: &F) -> i64 {
+  if s.is_zero_vartime() {
+    return 0;
+  } else if *s == F::ONE {
+    return 1;
+  }
+
+  let bits = s.to_le_bits();
+  if !bits[SMALL_VALUE_BITS..].any() {
+    return bits[..SMALL_VALUE_BITS].load_le::<u64>() as i64;
+  }
+
+  // -s is small if p - s vanishes above its lowest limb and that limb is below 2^SMALL_VALUE_BITS
+  let modulus = F::char_le_bits();
+  let mut borrow = false;
+  let mut low = 0;
+  for (k, (p, s)) in modulus.chunks(64).zip(bits.chunks(64)).enumerate() {
+    let (diff, b_1) = p.load_le::<u64>().overflowing_sub(s.load_le::<u64>());
+    let (diff, b_2) = diff.overflowing_sub(u64::from(borrow));
+    borrow = b_1 || b_2;
+    if k == 0 {
+      low = diff;
+    } else if diff != 0 {
+      return NOT_SMALL;
+    }
+  }
+  if low >> SMALL_VALUE_BITS == 0 {
+    -(low as i64)
+  } else {
+    NOT_SMALL
+  }
+}
+
+/// Computes `e * v` for a small integer `v`, with additions alone when `v` is tiny
+fn mul_small<F: PrimeField>(e: &F, v: i128) -> F {
+  let abs = v.unsigned_abs();
+  let prod = match abs {
+    0 => return F::ZERO,
+    1 => *e,
+    2 => e.double(),
+    3 => e.double() + e,
+    4 => e.double().double(),
+    _ => match u64::try_from(abs) {
+      Ok(abs) => *e * F::from(abs),
+      Err(_) => *e * F::from_u128(abs),
+    },
+  };
+  if v < 0 {
+    -prod
+  } else {
+    prod
+  }
+}
+
+/// Reads the bits of `b` as base-3 digits, which maps a point of `{0, 1}^k` to its index in `{0, 1, 2}^k`
+fn to_base3(b: usize) -> usize {
+  (0..usize::BITS - b.leading_zeros())
+    .rev()
+    .fold(0, |acc, i| 3 * acc + ((b >> i) & 1))
+}
+
+/// Extends the evaluations of a multilinear polynomial over `{0, 1}^ell` to the grid `{0, 1, 2}^ell`,
+/// whose points are indexed in base 3 with the first variable as the most significant digit
+struct Grid {
+  ell: usize,
+  size: usize,
+  // pairs `(z, w)` such that the value at `z` is twice the value at `z - w` minus the value at `z - 2w`,
+  // ordered so that both are known when `z` is reached
+  steps: Vec<(usize, usize)>,
+}
+
+impl Grid {
+  fn new(ell: usize) -> Self {
+    let size = 3usize.pow(ell as u32);
+    let is_boolean = |z: usize| {
+      let mut z = z;
+      while z > 0 {
+        if z % 3 == 2 {
+          return false;
+        }
+        z /= 3;
+      }
+      true
+    };
+    let steps = (0..ell)
+      .flat_map(|k| {
+        let w = size / 3usize.pow(k as u32 + 1);
+        (0..size)
+          .filter(move |z| (z / w) % 3 == 2 && is_boolean(z % w))
+          .map(move |z| (z, w))
+      })
+      .collect();
+    Self { ell, size, steps }
+  }
+
+  fn num_vars(&self) -> usize {
+    self.ell
+  }
+
+  /// the number of points of `{0, 1}^ell`
+  fn num_points(&self) -> usize {
+    1 << self.ell
+  }
+
+  fn size(&self) -> usize {
+    self.size
+  }
+
+  fn extend<T: Copy + Add<Output = T> + Sub<Output = T>>(&self, evals: &[T], grid: &mut [T]) {
+    for (b, e) in evals.iter().enumerate() {
+      grid[to_base3(b)] = *e;
+    }
+    for &(z, w) in &self.steps {
+      grid[z] = grid[z - w] + grid[z - w] - grid[z - 2 * w];
+    }
+  }
+}
+
+/// Per-thread buffers of `prove_cubic_with_additive_term_small_value`
+struct GridScratch<F> {
+  acc: Vec<F>,
+  small: [Vec<i64>; 3],
+  field: [Vec<F>; 3],
+}
+
+impl<F: Field> GridScratch<F> {
+  fn new(grid: &Grid) -> Self {
+    Self {
+      acc: vec![F::ZERO; grid.size()],
+      small: core::array::from_fn(|_| vec![0; grid.size()]),
+      field: core::array::from_fn(|_| vec![F::ZERO; grid.size()]),
+    }
+  }
+}
+
+/// A zero-knowledge variant of `SumcheckProof`. Instead of the round polynomials,
+/// the prover sends hiding commitments to their coefficients and to their evaluations at the
+/// verifier's challenges, along with a dot-product proof per round that the committed polynomial
+/// is consistent with the committed claim of the previous round. The verifier ends up with a
+/// hiding commitment to the final claim rather than the claim itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct ZKSumcheckProof<G: PrimeGroup> {
+  comm_polys: Vec<PedersenCompressedCommitment<G>>,
+  comm_evals: Vec<PedersenCompressedCommitment<G>>,
+  proofs: Vec<DotProductProof<G>>,
+}
+
+impl<G: PrimeGroup> ZKSumcheckProof<G> {
+  /// Returns the public vector `a` such that `<a, coeffs> = w0 * (poly(0) + poly(1)) + w1 * poly(r)`
+  fn round_weights(degree: usize, r: &G::Scalar, w: &(G::Scalar, G::Scalar)) -> Vec<G::Scalar> {
+    let mut a = Vec::with_capacity(degree + 1);
+    let mut power = G::Scalar::ONE;
+    for i in 0..=degree {
+      let a_sc = if i == 0 { G::Scalar::from(2) } else { G::Scalar::ONE };
+      a.push(w.0 * a_sc + w.1 * power);
+      power *= r;
+    }
+    a
+  }
+
+  pub fn verify<TE: TranscriptEngineTrait<G>>(
+    &self,
+    comm_claim: &PedersenCommitment<G>,
+    num_rounds: usize,
+    degree_bound: usize,
+    ck_1: &PedersenCommitmentKey<G>,
+    ck_n: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+  ) -> Result<(PedersenCommitment<G>, Vec<G::Scalar>), SpartanError> {
+    // verify that there is a committed univariate polynomial for each round
+    if self.comm_polys.len() != num_rounds
+      || self.comm_evals.len() != num_rounds
+      || self.proofs.len() != num_rounds
+    {
+      return Err(SpartanError::InvalidSumcheckProof);
+    }
+
+    let mut comm_claim_per_round = *comm_claim;
+    let mut r: Vec<G::Scalar> = Vec::new();
+    for i in 0..num_rounds {
+      let comm_poly = PedersenCommitment::<G>::decompress(&self.comm_polys[i])?;
+      let comm_eval = PedersenCommitment::<G>::decompress(&self.comm_evals[i])?;
+
+      // append the prover's message to the transcript
+      transcript.absorb(b"comm_poly", &comm_poly);
+
+      // derive the verifier's challenge for the next round
+      let r_i = transcript.squeeze(b"c")?;
+      r.push(r_i);
+
+      // check that poly(0) + poly(1) is the previous claim and poly(r_i) is the new claim
+      // with a single dot-product proof over a random linear combination of the two checks
+      transcript.absorb(b"comm_claim", &comm_claim_per_round);
+      transcript.absorb(b"comm_eval", &comm_eval);
+      let w = (transcript.squeeze(b"w")?, transcript.squeeze(b"w")?);
+      let a = Self::round_weights(degree_bound, &r_i, &w);
+      let comm_target = comm_claim_per_round * w.0 + comm_eval * w.1;
+
+      self.proofs[i]
+        .verify(ck_n, ck_1, h, transcript, &a, &comm_poly, &comm_target)
+        .map_err(|_| SpartanError::InvalidSumcheckProof)?;
+
+      comm_claim_per_round = comm_eval;
+    }
+
+    Ok((comm_claim_per_round, r))
+  }
+
+  /// Commits to the round polynomial and proves its consistency with the current claim.
+  /// Returns the verifier's challenge along with the new claim and its blinding factor.
+  fn prove_round<TE: TranscriptEngineTrait<G>>(
+    poly: &UniPoly<G::Scalar>,
+    claim: &G::Scalar,
+    blind_claim: &G::Scalar,
+    comm_claim: &PedersenCommitment<G>,
+    ck_1: &PedersenCommitmentKey<G>,
+    ck_n: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+  ) -> Result<
+    (
+      G::Scalar,
+      G::Scalar,
+      G::Scalar,
+      PedersenCommitment<G>,
+      PedersenCompressedCommitment<G>,
+      DotProductProof<G>,
+    ),
+    SpartanError,
+  > {
+    let blind_poly = random_scalar::<G>();
+    let comm_poly = commit(ck_n, h, poly.coeffs(), &blind_poly);
+
+    // append the prover's message to the transcript
+    transcript.absorb(b"comm_poly", &comm_poly);
+
+    //derive the verifier's challenge for the next round
+    let r_i = transcript.squeeze(b"c")?;
+
+    let eval = poly.evaluate(&r_i);
+    let blind_eval = random_scalar::<G>();
+    let comm_eval = commit(ck_1, h, &[eval], &blind_eval);
+
+    transcript.absorb(b"comm_claim", comm_claim);
+    transcript.absorb(b"comm_eval", &comm_eval);
+    let w = (transcript.squeeze(b"w")?, transcript.squeeze(b"w")?);
+    let a = Self::round_weights(poly.degree(), &r_i, &w);
+    let target = w.0 * claim + w.1 * eval;
+    let blind_target = w.0 * blind_claim + w.1 * blind_eval;
+
+    let (proof, _comm_poly, _comm_target) = DotProductProof::prove(
+      ck_n,
+      ck_1,
+      h,
+      transcript,
+      poly.coeffs(),
+      &blind_poly,
+      &a,
+      &target,
+      &blind_target,
+    )?;
+
+    Ok((r_i, eval, blind_eval, comm_eval, comm_poly.compress(), proof))
+  }
+
+  #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_quad_zk")]
+  pub fn prove_quad<F, TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    blind_claim: &G::Scalar,
+    num_rounds: usize,
+    poly_A: &mut MultilinearPolynomial<G::Scalar>,
+    poly_B: &mut MultilinearPolynomial<G::Scalar>,
+    comb_func: F,
+    ck_1: &PedersenCommitmentKey<G>,
+    ck_n: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>, G::Scalar), SpartanError>
+  where
+    F: Fn(&G::Scalar, &G::Scalar) -> G::Scalar + Sync,
+  {
+    let mut r: Vec<G::Scalar> = Vec::new();
+    let (mut comm_polys, mut comm_evals, mut proofs) = (Vec::new(), Vec::new(), Vec::new());
+    let mut claim_per_round = *claim;
+    let mut blind_claim_per_round = *blind_claim;
+    let mut comm_claim_per_round = commit(ck_1, h, &[*claim], blind_claim);
+    for _ in 0..num_rounds {
+      let poly = {
+        let (eval_point_0, eval_point_2) =
+          SumcheckProof::<G>::compute_eval_points_quadratic(poly_A, poly_B, &comb_func);
+
+        let evals = vec![eval_point_0, claim_per_round - eval_point_0, eval_point_2];
+        UniPoly::from_evals(&evals)
+      };
+
+      let (r_i, eval, blind_eval, comm_eval, comm_poly, proof) = Self::prove_round(
+        &poly,
+        &claim_per_round,
+        &blind_claim_per_round,
+        &comm_claim_per_round,
+        ck_1,
+        ck_n,
+        h,
+        transcript,
+      )?;
+      r.push(r_i);
+      comm_polys.push(comm_poly);
+      comm_evals.push(comm_eval.compress());
+      proofs.push(proof);
+
+      // Set up next round
+      claim_per_round = eval;
+      blind_claim_per_round = blind_eval;
+      comm_claim_per_round = comm_eval;
+
+      // bound all tables to the verifier's challenege
+      rayon::join(|| poly_A.bound_poly_var_top(&r_i), || poly_B.bound_poly_var_top(&r_i));
+    }
+
+    Ok((
+      ZKSumcheckProof {
+        comm_polys,
+        comm_evals,
+        proofs,
+      },
+      r,
+      vec![poly_A[0], poly_B[0]],
+      blind_claim_per_round,
+    ))
+  }
+
+  /// Proves the sum of `eq(tau, x) * comb_func(B(x), C(x), D(x))` for a quadratic `comb_func`, like
+  /// `SumcheckProof::prove_cubic_with_eq` but committing to the full cubic round polynomials;
+  /// also returns the blinding factor of the final claim
+  #[tracing::instrument(skip_all, name = "Spartan2::sumcheck::prove_cubic_with_eq_zk")]
+  pub fn prove_cubic_with_eq<F, TE: TranscriptEngineTrait<G>>(
+    claim: &G::Scalar,
+    blind_claim: &G::Scalar,
+    eq: &mut SplitEqPolynomial<G::Scalar>,
+    poly_B: &mut MultilinearPolynomial<G::Scalar>,
+    poly_C: &mut MultilinearPolynomial<G::Scalar>,
+    poly_D: &mut MultilinearPolynomial<G::Scalar>,
+    comb_func: F,
+    ck_1: &PedersenCommitmentKey<G>,
+    ck_n: &PedersenCommitmentKey<G>,
+    h: &PedersenCommitmentKey<G>,
+    transcript: &mut TE,
+  ) -> Result<(Self, Vec<G::Scalar>, Vec<G::Scalar>, G::Scalar), SpartanError>
+  where
+    F: Fn(&G::Scalar, &G::Scalar, &G::Scalar) -> G::Scalar + Sync,
+  {
+    if [&*poly_B, &*poly_C, &*poly_D]
+      .iter()
+      .any(|p| p.len() != eq.len())
+    {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    let mut r: Vec<G::Scalar> = Vec::new();
+    let (mut comm_polys, mut comm_evals, mut proofs) = (Vec::new(), Vec::new(), Vec::new());
+    let mut claim_per_round = *claim;
+    let mut blind_claim_per_round = *blind_claim;
+    let mut comm_claim_per_round = commit(ck_1, h, &[*claim], blind_claim);
+
+    while !eq.is_empty() {
+      let poly = {
+        let (eval_point_0, eval_point_2, eval_point_3) =
+          SumcheckProof::<G>::compute_eval_points_cubic_with_eq(
+            &claim_per_round,
+            eq,
+            poly_B,
+            poly_C,
+            poly_D,
+            &comb_func,
+          );
+
+        let evals = vec![
+          eval_point_0,
+          claim_per_round - eval_point_0,
+          eval_point_2,
+          eval_point_3,
+        ];
+        UniPoly::from_evals(&evals)
+      };
+
+      let (r_i, eval, blind_eval, comm_eval, comm_poly, proof) = Self::prove_round(
+        &poly,
+        &claim_per_round,
+        &blind_claim_per_round,
+        &comm_claim_per_round,
+        ck_1,
+        ck_n,
+        h,
+        transcript,
+      )?;
+      r.push(r_i);
+      comm_polys.push(comm_poly);
+      comm_evals.push(comm_eval.compress());
+      proofs.push(proof);
+
+      // Set up next round
+      claim_per_round = eval;
+      blind_claim_per_round = blind_eval;
+      comm_claim_per_round = comm_eval;
+
+      // bound all tables to the verifier's challenege
+      eq.bound_poly_var_top(&r_i);
+      poly_B.bound_poly_var_top(&r_i);
+      poly_C.bound_poly_var_top(&r_i);
+      poly_D.bound_poly_var_top(&r_i);
+    }
+
+    Ok((
+      ZKSumcheckProof {
+        comm_polys,
+        comm_evals,
+        proofs,
+      },
+      r,
+      vec![eq.eval(), poly_B[0], poly_C[0], poly_D[0]],
+      blind_claim_per_round,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::provider::{bn256_grumpkin::bn256, keccak::Keccak256Transcript};
+  use rand::Rng;
+  use rand_core::OsRng;
+
+  type G = bn256::Point;
+  type F = bn256::Scalar;
+
+  fn random_polys(num_polys: usize, num_vars: usize) -> Vec<MultilinearPolynomial<F>> {
+    (0..num_polys)
+      .map(|_| {
+        MultilinearPolynomial::new((0..1 << num_vars).map(|_| F::random(OsRng)).collect())
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_sumcheck_prove_matches_prove_quad() {
+    let polys = random_polys(2, 4);
+    let claim = (0..16).map(|i| polys[0][i] * polys[1][i]).sum();
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let (mut A, mut B) = (polys[0].clone(), polys[1].clone());
+    let comb_func = |a: &F, b: &F| *a * b;
+    let (_, r_quad, evals_quad) =
+      SumcheckProof::prove_quad(&claim, 4, &mut A, &mut B, comb_func, &mut transcript).unwrap();
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let mut polys = polys;
+    let comb_func = |v: &[F]| v[0] * v[1];
+    let (_, r, evals) =
+      SumcheckProof::<G>::prove(&claim, 2, &mut polys, comb_func, &mut transcript).unwrap();
+
+    assert_eq!(r, r_quad);
+    assert_eq!(evals, evals_quad);
+  }
+
+  #[test]
+  fn test_sumcheck_small_value_matches_cubic() {
+    // Az and Bz mix bits, small negative and positive values, and a few large values; Cz = Az * Bz except at a
+    // few entries, so the claim is not zero
+    let mut rng = rand::thread_rng();
+    let mut entry = |i: usize| match i % 7 {
+      0 => F::random(OsRng),
+      1 => -F::from(rng.gen_range(0..1u64 << 20)),
+      2 => F::from(rng.gen_range(0..1u64 << 39)),
+      _ => F::from(rng.gen_range(0..2u64)),
+    };
+    for num_vars in [1, 2, 3, 4, 6] {
+      let Az = (0..1 << num_vars).map(|i| entry(3 * i)).collect::<Vec<_>>();
+      let Bz = (0..1 << num_vars)
+        .map(|i| entry(5 * i + 1))
+        .collect::<Vec<_>>();
+      let mut Cz = (0..1 << num_vars)
+        .map(|i| Az[i] * Bz[i])
+        .collect::<Vec<_>>();
+      Cz[1] += F::ONE;
+
+      let tau = (0..num_vars).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+      let eq = EqPolynomial::new(tau.clone()).evals();
+      let claim = (0..1 << num_vars)
+        .map(|i| eq[i] * (Az[i] * Bz[i] - Cz[i]))
+        .sum::<F>();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (proof, r, claims) = SumcheckProof::<G>::prove_cubic_with_eq(
+        &claim,
+        &mut SplitEqPolynomial::new(tau.clone()),
+        &mut MultilinearPolynomial::new(Az.clone()),
+        &mut MultilinearPolynomial::new(Bz.clone()),
+        &mut MultilinearPolynomial::new(Cz.clone()),
+        |B: &F, C: &F, D: &F| *B * *C - *D,
+        &mut transcript,
+      )
+      .unwrap();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (proof_small, r_small, claims_small) =
+        SumcheckProof::<G>::prove_cubic_with_additive_term_small_value(
+          &claim,
+          &tau,
+          &mut MultilinearPolynomial::new(Az),
+          &mut MultilinearPolynomial::new(Bz),
+          &mut MultilinearPolynomial::new(Cz),
+          &mut transcript,
+        )
+        .unwrap();
+
+      assert_eq!(
+        bincode::serialize(&proof).unwrap(),
+        bincode::serialize(&proof_small).unwrap()
+      );
+      assert_eq!(r, r_small);
+      assert_eq!(claims, claims_small);
+    }
+  }
+
+  #[test]
+  fn test_sumcheck_small_value_streaming() {
+    // the same tables as in-memory, produced in chunks of rows of every size
+    let mut rng = rand::thread_rng();
+    let mut entry = |i: usize| match i % 5 {
+      0 => F::random(OsRng),
+      1 => -F::from(rng.gen_range(0..1u64 << 20)),
+      _ => F::from(rng.gen_range(0..2u64)),
+    };
+    for num_vars in [1, 3, 4, 6] {
+      let tables = (0..3)
+        .map(|k| {
+          (0..1 << num_vars)
+            .map(|i| entry(3 * i + k))
+            .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+      let tau = (0..num_vars).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+      let eq = EqPolynomial::new(tau.clone()).evals();
+      let claim = (0..1 << num_vars)
+        .map(|i| eq[i] * (tables[0][i] * tables[1][i] - tables[2][i]))
+        .sum::<F>();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (proof, r, claims) = SumcheckProof::<G>::prove_cubic_with_additive_term_small_value(
+        &claim,
+        &tau,
+        &mut MultilinearPolynomial::new(tables[0].clone()),
+        &mut MultilinearPolynomial::new(tables[1].clone()),
+        &mut MultilinearPolynomial::new(tables[2].clone()),
+        &mut transcript,
+      )
+      .unwrap();
+
+      for max_rows in [0, 5, 16, 1 << num_vars] {
+        let rows = |rows: Range<usize>| {
+          Ok((
+            tables[0][rows.clone()].to_vec(),
+            tables[1][rows.clone()].to_vec(),
+            tables[2][rows].to_vec(),
+          ))
+        };
+        let mut transcript = Keccak256Transcript::<G>::new(b"test");
+        let (proof_streaming, r_streaming, claims_streaming) =
+          SumcheckProof::<G>::prove_cubic_with_additive_term_small_value_streaming(
+            &claim,
+            &tau,
+            max_rows,
+            rows,
+            &mut transcript,
+          )
+          .unwrap();
+
+        assert_eq!(
+          bincode::serialize(&proof).unwrap(),
+          bincode::serialize(&proof_streaming).unwrap()
+        );
+        assert_eq!(r, r_streaming);
+        assert_eq!(claims, claims_streaming);
+      }
+    }
+  }
+
+  #[test]
+  fn test_sumcheck_prove_quad_with_witness() {
+    for num_vars in [2, 3, 5] {
+      let n = 1 << (num_vars - 1);
+      let A = (0..2 * n).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+      let W = (0..n).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+      let io = vec![(0, F::ONE), (n - 1, F::random(OsRng))];
+
+      let mut z = [W.clone(), vec![F::ZERO; n]].concat();
+      for (i, v) in &io {
+        z[n + i] = *v;
+      }
+      let claim = (0..2 * n).map(|i| A[i] * z[i]).sum::<F>();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (proof, r, claims) = SumcheckProof::<G>::prove_quad(
+        &claim,
+        num_vars,
+        &mut MultilinearPolynomial::new(A.clone()),
+        &mut MultilinearPolynomial::new(z),
+        |A: &F, z: &F| *A * *z,
+        &mut transcript,
+      )
+      .unwrap();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (proof_W, r_W, claims_W) = SumcheckProof::<G>::prove_quad_with_witness(
+        &claim,
+        num_vars,
+        &mut MultilinearPolynomial::new(A),
+        &W,
+        &io,
+        &mut transcript,
+      )
+      .unwrap();
+
+      assert_eq!(
+        bincode::serialize(&proof).unwrap(),
+        bincode::serialize(&proof_W).unwrap()
+      );
+      assert_eq!(r, r_W);
+      assert_eq!(claims, claims_W);
+    }
+  }
+
+  #[test]
+  fn test_sumcheck_prove_cubic_with_eq() {
+    let num_vars = 5;
+    let polys = random_polys(3, num_vars);
+    let comb_func = |b: &F, c: &F, d: &F| *b * c - d;
+
+    // the degenerate case of a zero entry in tau is covered as well
+    let mut tau = (0..num_vars).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+    for zero_index in [None, Some(0), Some(3)] {
+      if let Some(i) = zero_index {
+        tau[i] = F::ZERO;
+      }
+      let eq = EqPolynomial::new(tau.clone()).evals();
+      let claim = (0..1 << num_vars)
+        .map(|i| eq[i] * comb_func(&polys[0][i], &polys[1][i], &polys[2][i]))
+        .sum::<F>();
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (proof, r, claims) = SumcheckProof::<G>::prove_cubic_with_eq(
+        &claim,
+        &mut SplitEqPolynomial::new(tau.clone()),
+        &mut polys[0].clone(),
+        &mut polys[1].clone(),
+        &mut polys[2].clone(),
+        comb_func,
+        &mut transcript,
+      )
+      .unwrap();
+      assert_eq!(claims[0], EqPolynomial::new(tau.clone()).evaluate(&r));
+      for (claim, poly) in claims[1..].iter().zip(polys.iter()) {
+        assert_eq!(*claim, poly.evaluate(&r));
+      }
+
+      // the verifier rebuilds every round polynomial from its quadratic factor
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (claim_final, r_verifier) = proof.verify_with_eq(claim, &tau, &mut transcript).unwrap();
+      assert_eq!(r, r_verifier);
+      assert_eq!(
+        claim_final,
+        claims[0] * comb_func(&claims[1], &claims[2], &claims[3])
+      );
+
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      let (claim_final, _) = proof
+        .verify_with_eq(claim + F::ONE, &tau, &mut transcript)
+        .unwrap();
+      assert_ne!(
+        claim_final,
+        claims[0] * comb_func(&claims[1], &claims[2], &claims[3])
+      );
+      let mut transcript = Keccak256Transcript::<G>::new(b"test");
+      assert!(proof
+        .verify_with_eq(claim, &tau[1..], &mut transcript)
+        .is_err());
+    }
+  }
+
+  #[test]
+  fn test_small_value() {
+    assert_eq!(small_value(&F::ZERO), 0);
+    assert_eq!(small_value(&-F::from(5)), -5);
+    assert_eq!(
+      small_value(&F::from((1 << SMALL_VALUE_BITS) - 1)),
+      (1 << SMALL_VALUE_BITS) - 1
+    );
+    assert_eq!(small_value(&F::from(1 << SMALL_VALUE_BITS)), NOT_SMALL);
+    assert_eq!(
+      small_value(&-F::from((1 << SMALL_VALUE_BITS) - 1)),
+      1 - (1 << SMALL_VALUE_BITS)
+    );
+    assert_eq!(small_value(&-F::from(1 << SMALL_VALUE_BITS)), NOT_SMALL);
+    assert_eq!(small_value(&-F::from_u128(1 << 64)), NOT_SMALL);
+    assert_eq!(mul_small(&F::from(7), -3), -F::from(21));
+    assert_eq!(
+      mul_small(&F::from(7), 1 << 70),
+      F::from(7) * F::from_u128(1 << 70)
+    );
+  }
+
+  #[test]
+  fn test_sumcheck_prove_high_degree() {
+    let (num_polys, num_vars) = (5, 5);
+    let polys = random_polys(num_polys, num_vars);
+    let comb_func = |v: &[F]| v.iter().product::<F>();
+    let claim = (0..1 << num_vars)
+      .map(|i| comb_func(&polys.iter().map(|p| p[i]).collect::<Vec<_>>()))
+      .sum::<F>();
+
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let mut bound_polys = polys.clone();
+    let (proof, r, evals) = SumcheckProof::<G>::prove(
+      &claim,
+      num_polys,
+      &mut bound_polys,
+      comb_func,
+      &mut transcript,
+    )
+    .unwrap();
+    for (p, e) in polys.iter().zip(evals.iter()) {
+      assert_eq!(p.evaluate(&r), *e);
+    }
+
+    // the verifier derives the same challenges and a final claim consistent with the evaluations
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let (e, r_verifier) = proof
+      .verify(claim, num_vars, num_polys, &mut transcript)
+      .unwrap();
+    assert_eq!(r_verifier, r);
+    assert_eq!(e, comb_func(&evals));
+
+    // a wrong claim is caught by the final check
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    let (e, _) = proof
+      .verify(claim + F::ONE, num_vars, num_polys, &mut transcript)
+      .unwrap();
+    assert_ne!(e, comb_func(&evals));
+
+    // so is a wrong degree
+    let mut transcript = Keccak256Transcript::<G>::new(b"test");
+    assert!(proof
+      .verify(claim, num_vars, num_polys - 1, &mut transcript)
+      .is_err());
+  }
 }
@@ -1,37 +1,40 @@
 //! This module implements `RelaxedR1CSSNARKTrait` using Spartan that is generic
 //! over the polynomial commitment and evaluation argument (i.e., a PCS)
-//! This version of Spartan does not use preprocessing so the verifier keeps the entire
-//! description of R1CS matrices. This is essentially optimal for the verifier when using
-//! an IPA-based polynomial commitment scheme.
+//! This version of Spartan does not use preprocessing so the verifier evaluates the R1CS
+//! matrices itself. For a uniform computation, the prover and the verifier only keep a single
+//! step's matrices, from which those of all steps are evaluated (see `R1CSShape`'s uniform methods).
+//! This is essentially optimal for the verifier when using an IPA-based polynomial commitment scheme.
 //! The difference between this file and snark.rs is that it trims out the "Relaxed" parts 
 //! and only works with (normal) R1CS, making it more efficient.
 //! This basic R1CSStruct also implements "uniform" and "precommitted" traits. 
 
 use crate::{
   bellpepper::{
-    r1cs::{SpartanShape, SpartanWitness},
+    r1cs::{synthesize_step, synthesize_steps, synthesize_uniform, SpartanShape},
     shape_cs::ShapeCS,
     solver::SatisfyingAssignment,
   },
   digest::{DigestComputer, SimpleDigestible},
   errors::SpartanError,
-  r1cs::{R1CSShape, R1CSInstance},
+  provider::keccak::Keccak256Transcript,
+  r1cs::{R1CSShape, R1CSInstance, R1CSStateWiring},
   spartan::{
     polys::{eq::EqPolynomial, multilinear::MultilinearPolynomial, multilinear::SparsePolynomial},
-    sumcheck::SumcheckProof,
+    sumcheck::{SumcheckProof, SMALL_VALUE_ROUNDS},
     // PolyEvalInstance, PolyEvalWitness,
   },
   traits::{
-    commitment::CommitmentTrait, evaluation::EvaluationEngineTrait, snark::RelaxedR1CSSNARKTrait, 
+    circuit::StepCircuit,
+    commitment::{CommitmentEngineTrait, CommitmentTrait}, evaluation::EvaluationEngineTrait, snark::RelaxedR1CSSNARKTrait, 
     upsnark::{UniformSNARKTrait, PrecommittedSNARKTrait}, 
     Group, TranscriptEngineTrait,
   },
-  Commitment, CommitmentKey, CompressedCommitment,
+  Commitment, CommitmentKey, CompressedCommitment, CE,
 };
 use bellpepper_core::{Circuit, ConstraintSystem};
+use core::{marker::PhantomData, mem::size_of};
 use ff::Field;
 use once_cell::sync::OnceCell;
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// A type that represents the prover's key
@@ -40,8 +43,62 @@ use serde::{Deserialize, Serialize};
 pub struct ProverKey<G: Group, EE: EvaluationEngineTrait<G>> {
   ck: CommitmentKey<G>,
   pk_ee: EE::ProverKey,
-  S: R1CSShape<G>,
-  vk_digest: G::Scalar, // digest of the verifier's key
+  S_single: R1CSShape<G>, // A single step's shape
+  num_aux: usize,         // Number of witness variables of a single step, before padding
+  num_steps: usize,       // Number of steps
+  wiring: R1CSStateWiring, // Wiring of the state of consecutive steps
+  io_per_step: bool,      // Whether every step has its own public inputs
+  vk_digest: G::Scalar,   // digest of the verifier's key
+  memory_budget: Option<usize>, // Bytes of the prover's tables, if bounded (see `set_memory_budget`)
+}
+
+impl<G: Group, EE: EvaluationEngineTrait<G>> ProverKey<G, EE> {
+  /// Returns the number of constraints of the full (repeated) shape
+  fn num_cons(&self) -> usize {
+    self.S_single.num_cons * self.num_steps.next_power_of_two()
+  }
+
+  /// Returns the number of witness variables of the full (repeated) shape
+  fn num_vars(&self) -> usize {
+    self.S_single.num_vars * self.num_steps.next_power_of_two()
+  }
+
+  /// Returns the bytes of the prover's tables as `(outer, per_point, inner)`. The outer sum-check holds `outer`
+  /// bytes for the bound Az, Bz, and Cz, plus `per_point` bytes for every point `x_b` of a chunk, which holds
+  /// the rows `b * size_b + x_b` of Az, Bz, and Cz for every `b` as scalars and as small values, along with
+  /// the rows being produced and bound. The inner sum-check holds `inner` bytes for (A, B, C) bound to
+  /// the rows and z bound to the first challenge
+  fn memory_costs(&self) -> (usize, usize, usize) {
+    let s = size_of::<G::Scalar>();
+    let num_b = 1 << SMALL_VALUE_ROUNDS.min(self.num_cons().ilog2() as usize);
+    let size_b = self.num_cons() / num_b;
+    (
+      3 * size_b * s,
+      3 * num_b * (s + size_of::<i64>()) + 6 * s,
+      3 * self.num_vars() * s,
+    )
+  }
+
+  /// Returns the smallest memory budget, with a single point per chunk
+  fn min_memory_budget(&self) -> usize {
+    let (outer, per_point, inner) = self.memory_costs();
+    (outer + per_point).max(inner)
+  }
+
+  /// Returns the number of rows of Az, Bz, and Cz per chunk under `budget`, which is at least `min_memory_budget`
+  fn chunk_rows(&self, budget: usize) -> usize {
+    let (outer, per_point, _) = self.memory_costs();
+    let num_b = 1 << SMALL_VALUE_ROUNDS.min(self.num_cons().ilog2() as usize);
+    let chunk_len = ((budget - outer) / per_point).clamp(1, self.num_cons() / num_b);
+    chunk_len * num_b
+  }
+
+  /// Pads the witness of all steps to the witness of the full shape
+  fn pad_witness(&self, W: &[G::Scalar]) -> Result<Vec<G::Scalar>, SpartanError> {
+    self
+      .S_single
+      .pad_witness_uniform(self.num_steps, self.num_aux, W)
+  }
 }
 
 /// A type that represents the verifier's key
@@ -83,109 +140,185 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> VerifierKey<G, EE> {
 #[serde(bound = "")]
 pub struct UniformVerifierKey<G: Group, EE: EvaluationEngineTrait<G>> {
   vk_ee: EE::VerifierKey,
-  S: R1CSShape<G>, // The full shape
   S_single: R1CSShape<G>, // A single step's shape
   num_steps: usize, // Number of steps
+  wiring: R1CSStateWiring, // Wiring of the state of consecutive steps
+  io_per_step: bool, // Whether every step has its own public inputs
   #[serde(skip, default = "OnceCell::new")]
   digest: OnceCell<G::Scalar>,
 }
 
 impl<G: Group, EE: EvaluationEngineTrait<G>> SimpleDigestible for UniformVerifierKey<G, EE> {}
 
+/// A verifier's key together with the layout of the steps, which is digested in place of the
+/// verifier's key, so that keys for different layouts of the same single step never share a digest
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct UniformLayoutKey<'a, G: Group, EE: EvaluationEngineTrait<G>> {
+  vk: VerifierKey<G, EE>,
+  num_steps: usize,
+  wiring: &'a R1CSStateWiring,
+  io_per_step: bool,
+}
+
+impl<'a, G: Group, EE: EvaluationEngineTrait<G>> SimpleDigestible for UniformLayoutKey<'a, G, EE> {}
+
 impl<G: Group, EE: EvaluationEngineTrait<G>> UniformVerifierKey<G, EE> {
-  fn new(shape: R1CSShape<G>, vk_ee: EE::VerifierKey, shape_single: R1CSShape<G>, num_steps: usize) -> Self {
+  fn new(
+    vk_ee: EE::VerifierKey,
+    shape_single: R1CSShape<G>,
+    num_steps: usize,
+    wiring: R1CSStateWiring,
+    io_per_step: bool,
+  ) -> Self {
     UniformVerifierKey {
       vk_ee,
-      S: shape,
       S_single: shape_single,
-      num_steps: num_steps,
+      num_steps,
+      wiring,
+      io_per_step,
       digest: OnceCell::new(),
     }
   }
 
+  /// Returns the number of constraints of the full (repeated) shape
+  fn num_cons(&self) -> usize {
+    self.S_single.num_cons * self.num_steps.next_power_of_two()
+  }
+
+  /// Returns the number of witness variables of the full (repeated) shape
+  fn num_vars(&self) -> usize {
+    self.S_single.num_vars * self.num_steps.next_power_of_two()
+  }
+
   /// Returns the digest of the verifier's key.
   pub fn digest(&self) -> G::Scalar {
     self
       .digest
       .get_or_try_init(|| {
-        let vk = VerifierKey::<G, EE>::new(self.S_single.clone(), self.vk_ee.clone()); 
-        let dc = DigestComputer::<G::Scalar, _>::new(&vk);
-        dc.digest()
+        let vk = UniformLayoutKey {
+          vk: VerifierKey::<G, EE>::new(self.S_single.clone(), self.vk_ee.clone()),
+          num_steps: self.num_steps,
+          wiring: &self.wiring,
+          io_per_step: self.io_per_step,
+        };
+        DigestComputer::<G::Scalar, _>::new(&vk).digest()
       })
       .cloned()
       .expect("Failure to retrieve digest!")
   }
 }
 
+/// Produces the keys for `num_steps` steps of `S_single`, each with `num_aux` witness variables,
+/// whose witness is committed with `ck`
+fn setup_with_shape<G: Group, EE: EvaluationEngineTrait<G>>(
+  S_single: R1CSShape<G>,
+  num_aux: usize,
+  num_steps: usize,
+  wiring: R1CSStateWiring,
+  io_per_step: bool,
+  ck: CommitmentKey<G>,
+) -> Result<(ProverKey<G, EE>, UniformVerifierKey<G, EE>), SpartanError> {
+  if num_steps == 0 {
+    return Err(SpartanError::InvalidNumSteps);
+  }
+
+  let (pk_ee, vk_ee) = EE::setup(&ck);
+
+  let vk: UniformVerifierKey<G, EE> =
+    UniformVerifierKey::new(vk_ee, S_single.clone(), num_steps, wiring.clone(), io_per_step);
+
+  let pk = ProverKey {
+    ck,
+    pk_ee,
+    S_single,
+    num_aux,
+    num_steps,
+    wiring,
+    io_per_step,
+    vk_digest: vk.digest(),
+    memory_budget: None,
+  };
+
+  Ok((pk, vk))
+}
+
+/// Returns a size of the process in bytes, such as `VmHWM` or `VmRSS`, on platforms that report it in `/proc/self/status`
+fn status_bytes(field: &str) -> Option<usize> {
+  let status = std::fs::read_to_string("/proc/self/status").ok()?;
+  let kb = status
+    .lines()
+    .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))?
+    .trim()
+    .strip_suffix("kB")?
+    .trim()
+    .parse::<usize>()
+    .ok()?;
+  Some(kb * 1024)
+}
+
+/// Reports the peak resident set size of the process at the end of a phase of the prover, along with
+/// its increase over the resident set size `start` at the start of the proof. The peak is that of the
+/// process's lifetime, so the increase is that of the proof only if nothing before it peaked higher
+fn trace_peak_rss(phase: &str, start: Option<usize>) {
+  if let Some(bytes) = status_bytes("VmHWM") {
+    let increase = start.map(|start| bytes.saturating_sub(start));
+    tracing::info!(
+      phase,
+      peak_rss = bytes,
+      peak_rss_increase = increase,
+      "Spartan2::UPSnark::peak_rss"
+    );
+  }
+}
+
 /// A succinct proof of knowledge of a witness to a relaxed R1CS instance
 /// The proof is produced using Spartan's combination of the sum-check and
 /// the commitment to a vector viewed as a polynomial commitment
 #[derive(Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct R1CSSNARK<G: Group, EE: EvaluationEngineTrait<G>> {
+pub struct R1CSSNARK<
+  G: Group,
+  EE: EvaluationEngineTrait<G>,
+  TE: TranscriptEngineTrait<G> = Keccak256Transcript<G>,
+> {
   comm_W: CompressedCommitment<G>,
   sc_proof_outer: SumcheckProof<G>,
   claims_outer: (G::Scalar, G::Scalar, G::Scalar),
   sc_proof_inner: SumcheckProof<G>,
   eval_W: G::Scalar,
   eval_arg: EE::EvaluationArgument,
+  _p: PhantomData<TE>,
 }
 
-impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSNARK<G, EE> {
-  type ProverKey = ProverKey<G, EE>;
-  type VerifierKey = UniformVerifierKey<G, EE>;
-
-  fn setup<C: Circuit<G::Scalar>>(
-    circuit: C,
-  ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError> {
-    let mut cs: ShapeCS<G> = ShapeCS::new();
-    let _ = circuit.synthesize(&mut cs);
-    let (S, ck) = cs.r1cs_shape();
-
-    let (pk_ee, vk_ee) = EE::setup(&ck);
-
-    let vk: UniformVerifierKey<G, EE> = UniformVerifierKey::new(S.clone(), vk_ee, S.clone(), 1);
-
-    let pk = ProverKey {
-      ck,
-      pk_ee,
-      S,
-      vk_digest: vk.digest(),
-    };
-
-    Ok((pk, vk))
-  }
-
-  /// produces a succinct proof of satisfiability of a `RelaxedR1CS` instance
-  #[tracing::instrument(skip_all, name = "Spartan2::UPSnark::prove")]
-  fn prove<C: Circuit<G::Scalar>>(pk: &Self::ProverKey, circuit: C) -> Result<Self, SpartanError> {
-    let mut cs: SatisfyingAssignment<G> = SatisfyingAssignment::new();
-    let _ = circuit.synthesize(&mut cs);
-
-    // Commits to witness (expensive)
-    let (u, w) = cs
-      .r1cs_instance_and_witness(&pk.S, &pk.ck)
-      .map_err(|_e| SpartanError::UnSat)?;
-
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>>
+  R1CSSNARK<G, EE, TE>
+{
+  /// produces a proof for the instance `u`, whose padded witness `W` is already committed in `u.comm_W`
+  fn prove_instance(
+    pk: &ProverKey<G, EE>,
+    u: &R1CSInstance<G>,
+    W: &[G::Scalar],
+  ) -> Result<Self, SpartanError> {
     let non_commitment_span = tracing::span!(tracing::Level::INFO, "PostCommitProve");
     let _guard = non_commitment_span.enter();
+    let rss_start = status_bytes("VmRSS");
 
-    let W = w.pad(&pk.S); // pad the witness
-    let mut transcript = G::TE::new(b"R1CSSNARK");
+    let mut transcript = TE::new(b"R1CSSNARK");
 
     // sanity check that R1CSShape has certain size characteristics
-    pk.S.check_regular_shape();
+    pk.S_single.check_regular_shape();
 
     // append the digest of vk (which includes R1CS matrices) and the RelaxedR1CSInstance to the transcript
     transcript.absorb(b"vk", &pk.vk_digest);
-    transcript.absorb(b"U", &u);
+    transcript.absorb(b"U", u);
 
-    // compute the full satisfying assignment by concatenating W.W, U.u, and U.X
-    let mut z = [w.W.clone(), vec![1.into()], u.X.clone()].concat();
+    // the satisfying assignment is z = (W, 1, X); the witness is borrowed rather than copied into z
+    let io = [&[G::Scalar::ONE], u.X.as_slice()].concat();
 
     let (num_rounds_x, num_rounds_y) = (
-      usize::try_from(pk.S.num_cons.ilog2()).unwrap(),
-      (usize::try_from(pk.S.num_vars.ilog2()).unwrap() + 1),
+      usize::try_from(pk.num_cons().ilog2()).unwrap(),
+      (usize::try_from(pk.num_vars().ilog2()).unwrap() + 1),
     );
 
     // outer sum-check
@@ -193,32 +326,43 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
       .map(|_i| transcript.squeeze(b"t"))
       .collect::<Result<Vec<G::Scalar>, SpartanError>>()?;
 
-    let mut poly_tau = MultilinearPolynomial::new(EqPolynomial::new(tau).evals());
-    let (mut poly_Az, mut poly_Bz, mut poly_Cz) = {
-      let (poly_Az, poly_Bz, poly_Cz) = pk.S.multiply_vec(&z)?;
-      (
-        MultilinearPolynomial::new(poly_Az),
-        MultilinearPolynomial::new(poly_Bz),
-        MultilinearPolynomial::new(poly_Cz),
-      )
+    // the entries of Az, Bz, and Cz are small integers for boolean-heavy circuits
+    let (sc_proof_outer, r_x, claims_outer) = match pk.memory_budget {
+      None => {
+        // computed step by step from a single step's shape
+        let (poly_Az, poly_Bz, poly_Cz) =
+          pk.S_single
+            .multiply_vec_uniform(pk.num_steps, &pk.wiring, pk.io_per_step, W, &io)?;
+        SumcheckProof::prove_cubic_with_additive_term_small_value(
+          &G::Scalar::ZERO, // claim is zero
+          &tau,
+          &mut MultilinearPolynomial::new(poly_Az),
+          &mut MultilinearPolynomial::new(poly_Bz),
+          &mut MultilinearPolynomial::new(poly_Cz),
+          &mut transcript,
+        )?
+      }
+      Some(budget) => {
+        // Az, Bz, and Cz are produced in chunks of rows that fit the budget next to the bound tables
+        SumcheckProof::prove_cubic_with_additive_term_small_value_streaming(
+          &G::Scalar::ZERO, // claim is zero
+          &tau,
+          pk.chunk_rows(budget),
+          |rows| {
+            pk.S_single.multiply_rows_uniform(
+              pk.num_steps,
+              &pk.wiring,
+              pk.io_per_step,
+              W,
+              &io,
+              rows,
+            )
+          },
+          &mut transcript,
+        )?
+      }
     };
-
-    let comb_func_outer =
-      |poly_A_comp: &G::Scalar,
-       poly_B_comp: &G::Scalar,
-       poly_C_comp: &G::Scalar,
-       poly_D_comp: &G::Scalar|
-       -> G::Scalar { *poly_A_comp * (*poly_B_comp * *poly_C_comp - *poly_D_comp) };
-    let (sc_proof_outer, r_x, claims_outer) = SumcheckProof::prove_cubic_with_additive_term(
-      &G::Scalar::ZERO, // claim is zero
-      num_rounds_x,
-      &mut poly_tau,
-      &mut poly_Az,
-      &mut poly_Bz,
-      &mut poly_Cz,
-      comb_func_outer,
-      &mut transcript,
-    )?;
+    trace_peak_rss("outer sum-check", rss_start);
 
     // claims from the end of sum-check
     let (claim_Az, claim_Bz): (G::Scalar, G::Scalar) = (claims_outer[1], claims_outer[2]);
@@ -232,86 +376,29 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
     let r = transcript.squeeze(b"r")?;
     let claim_inner_joint = claim_Az + r * claim_Bz + r * r * claim_Cz;
 
-    let span = tracing::span!(tracing::Level::TRACE, "poly_ABC");
-    let _enter = span.enter();
-    let poly_ABC = {
-      // compute the initial evaluation table for R(\tau, x)
-      let evals_rx = EqPolynomial::new(r_x.clone()).evals();
-
-      // Bounds "row" variables of (A, B, C) matrices viewed as 2d multilinear polynomials
-      let compute_eval_table_sparse =
-        |S: &R1CSShape<G>, rx: &[G::Scalar]| -> (Vec<G::Scalar>, Vec<G::Scalar>, Vec<G::Scalar>) {
-          assert_eq!(rx.len(), S.num_cons);
-
-          let inner = |M: &Vec<(usize, usize, G::Scalar)>, M_evals: &mut Vec<G::Scalar>| {
-            for (row, col, val) in M {
-              if val.eq(&G::Scalar::ONE) {
-                M_evals[*col] += rx[*row];
-              } else {
-                let m = rx[*row] * val;
-                M_evals[*col] += m;
-              }
-            }
-          };
-
-          let (mut A_evals, mut B_evals, mut C_evals) = (
-            vec![G::Scalar::ZERO; 2 * S.num_vars],
-            vec![G::Scalar::ZERO; 2 * S.num_vars],
-            vec![G::Scalar::ZERO; 2 * S.num_vars]
-          );
-          rayon::join(
-            || inner(&pk.S.A, &mut A_evals),
-            || rayon::join(
-              || inner(&pk.S.B, &mut B_evals),
-              || inner(&pk.S.C, &mut C_evals),
-            ),
-          );
-
-          (A_evals, B_evals, C_evals)
-        };
+    // Bounds "row" variables of (A, B, C) matrices viewed as 2d multilinear polynomials
+    let poly_ABC =
+      pk.S_single
+        .bind_row_vars_uniform(pk.num_steps, &pk.wiring, pk.io_per_step, &r_x, &r);
 
-      let (evals_A, evals_B, evals_C) = compute_eval_table_sparse(&pk.S, &evals_rx);
+    // (1, X) is laid out after the witness as in the full (repeated) shape
+    let io_entries =
+      pk.S_single
+        .io_entries_uniform(pk.num_steps, pk.io_per_step, &G::Scalar::ONE, &u.X);
 
-      assert_eq!(evals_A.len(), evals_B.len());
-      assert_eq!(evals_A.len(), evals_C.len());
-
-      let span_e = tracing::span!(tracing::Level::TRACE, "eval_combo_old");
-      let _enter_e = span_e.enter();
-      let r_sq = r * r;
-      let thing = (0..evals_A.len())
-        .into_par_iter()
-        .map(|i| {
-          evals_A[i] + evals_B[i] * r + evals_C[i] * r_sq
-        })
-        .collect::<Vec<G::Scalar>>();
-      drop(_enter_e);
-      drop(span_e);
-
-      thing
-    };
-    drop(_enter);
-    drop(span);
-
-    let poly_z = {
-      z.resize(pk.S.num_vars * 2, G::Scalar::ZERO);
-      z
-    };
-
-    let comb_func = |poly_A_comp: &G::Scalar, poly_B_comp: &G::Scalar| -> G::Scalar {
-      *poly_A_comp * *poly_B_comp
-    };
-    let (sc_proof_inner, r_y, _claims_inner) = SumcheckProof::prove_quad(
+    let (sc_proof_inner, r_y, _claims_inner) = SumcheckProof::prove_quad_with_witness(
       &claim_inner_joint,
       num_rounds_y,
       &mut MultilinearPolynomial::new(poly_ABC),
-      &mut MultilinearPolynomial::new(poly_z),
-      comb_func,
+      W,
+      &io_entries,
       &mut transcript,
     )?;
+    trace_peak_rss("inner sum-check", rss_start);
 
     let span = tracing::span!(tracing::Level::TRACE, "MultilinearPolynomial::evaluate_with");
     let _enter = span.enter();
-    let eval_W = MultilinearPolynomial::evaluate_with(&W.W, &r_y[1..]);
+    let eval_W = MultilinearPolynomial::evaluate_with(W, &r_y[1..]);
     drop(_enter);
     drop(span);
 
@@ -320,10 +407,11 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
       &pk.pk_ee,
       &mut transcript,
       &u.comm_W,
-      &W.W.clone(),
-      &r_y[1..].to_vec(),
+      W,
+      &r_y[1..],
       &eval_W,
     )?;
+    trace_peak_rss("evaluation argument", rss_start);
 
     Ok(R1CSSNARK {
       comm_W: u.comm_W.compress(),
@@ -332,25 +420,25 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
       sc_proof_inner,
       eval_W,
       eval_arg,
+      _p: PhantomData,
     })
   }
 
-  /// verifies a proof of satisfiability of a `RelaxedR1CS` instance
-  #[tracing::instrument(skip_all, name = "SNARK::verify")]
-  fn verify(&self, vk: &Self::VerifierKey, io: &[G::Scalar]) -> Result<(), SpartanError> {
-    // construct an instance using the provided commitment to the witness and IO
-    let comm_W = Commitment::<G>::decompress(&self.comm_W)?;
-    let u = R1CSInstance::new(&vk.S, &comm_W, io)?;
-
-    let mut transcript = G::TE::new(b"R1CSSNARK");
+  /// verifies the proof against the instance `u`
+  fn verify_instance(
+    &self,
+    vk: &UniformVerifierKey<G, EE>,
+    u: &R1CSInstance<G>,
+  ) -> Result<(), SpartanError> {
+    let mut transcript = TE::new(b"R1CSSNARK");
 
     // append the digest of R1CS matrices and the RelaxedR1CSInstance to the transcript
     transcript.absorb(b"vk", &vk.digest());
-    transcript.absorb(b"U", &u);
+    transcript.absorb(b"U", u);
 
     let (num_rounds_x, num_rounds_y) = (
-      usize::try_from(vk.S.num_cons.ilog2()).unwrap(),
-      (usize::try_from(vk.S.num_vars.ilog2()).unwrap() + 1),
+      usize::try_from(vk.num_cons().ilog2()).unwrap(),
+      (usize::try_from(vk.num_vars().ilog2()).unwrap() + 1),
     );
 
     // outer sum-check
@@ -361,7 +449,7 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
     let (claim_outer_final, r_x) =
       self
         .sc_proof_outer
-        .verify(G::Scalar::ZERO, num_rounds_x, 3, &mut transcript)?;
+        .verify_with_eq(G::Scalar::ZERO, &tau, &mut transcript)?;
 
     // verify claim_outer_final
     let (claim_Az, claim_Bz, claim_Cz) = self.claims_outer;
@@ -395,50 +483,22 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
     // verify claim_inner_final
     let eval_Z = {
       let eval_X = {
-        // constant term
-        let mut poly_X = vec![(0, 1.into())];
-        //remaining inputs
-        poly_X.extend(
-          (0..u.X.len())
-            .map(|i| (i + 1, u.X[i]))
-            .collect::<Vec<(usize, G::Scalar)>>(),
-        );
-        SparsePolynomial::new(usize::try_from(vk.S.num_vars.ilog2()).unwrap(), poly_X)
+        // the constant term and the inputs, laid out as in the full (repeated) shape
+        let poly_X =
+          vk.S_single
+            .io_entries_uniform(vk.num_steps, vk.io_per_step, &G::Scalar::ONE, &u.X);
+        SparsePolynomial::new(usize::try_from(vk.num_vars().ilog2()).unwrap(), poly_X)
           .evaluate(&r_y[1..])
       };
       (G::Scalar::ONE - r_y[0]) * self.eval_W + r_y[0] * eval_X
     };
 
-    // compute evaluations of R1CS matrices
-    let multi_evaluate = |M_vec: &[&[(usize, usize, G::Scalar)]],
-                          r_x: &[G::Scalar],
-                          r_y: &[G::Scalar]|
-     -> Vec<G::Scalar> {
-      let evaluate_with_table =
-        |M: &[(usize, usize, G::Scalar)], T_x: &[G::Scalar], T_y: &[G::Scalar]| -> G::Scalar {
-          (0..M.len())
-            .into_par_iter()
-            .map(|i| {
-              let (row, col, val) = M[i];
-              T_x[row] * T_y[col] * val
-            })
-            .sum()
-        };
-
-      let (T_x, T_y) = rayon::join(
-        || EqPolynomial::new(r_x.to_vec()).evals(),
-        || EqPolynomial::new(r_y.to_vec()).evals(),
-      );
-
-      (0..M_vec.len())
-        .into_par_iter()
-        .map(|i| evaluate_with_table(M_vec[i], &T_x, &T_y))
-        .collect()
-    };
-
-    let evals = multi_evaluate(&[&vk.S.A, &vk.S.B, &vk.S.C], &r_x, &r_y);
+    // compute evaluations of R1CS matrices from a single step's shape
+    let (eval_A, eval_B, eval_C) = vk
+      .S_single
+      .evaluate_uniform(vk.num_steps, &vk.wiring, vk.io_per_step, &r_x, &r_y);
 
-    let claim_inner_final_expected = (evals[0] + r * evals[1] + r * r * evals[2]) * eval_Z;
+    let claim_inner_final_expected = (eval_A + r * eval_B + r * r * eval_C) * eval_Z;
     if claim_inner_final != claim_inner_final_expected {
       return Err(SpartanError::InvalidSumcheckProof);
     }
@@ -447,8 +507,8 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
     EE::verify(
       &vk.vk_ee,
       &mut transcript,
-      &u.comm_W.clone(),
-      &r_y[1..].to_vec(),
+      &u.comm_W,
+      &r_y[1..],
       &self.eval_W,
       &self.eval_arg,
     )?;
@@ -457,7 +517,56 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> RelaxedR1CSSNARKTrait<G> for R1CSSN
   }
 }
 
-impl<G: Group, EE: EvaluationEngineTrait<G>> UniformSNARKTrait<G> for R1CSSNARK<G, EE> {
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>> RelaxedR1CSSNARKTrait<G>
+  for R1CSSNARK<G, EE, TE>
+{
+  type ProverKey = ProverKey<G, EE>;
+  type VerifierKey = UniformVerifierKey<G, EE>;
+
+  fn setup<C: Circuit<G::Scalar>>(
+    circuit: C,
+  ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let _ = circuit.synthesize(&mut cs);
+    let (S, ck) = cs.r1cs_shape()?;
+
+    setup_with_shape(S, cs.num_aux(), 1, R1CSStateWiring::default(), false, ck)
+  }
+
+  /// produces a succinct proof of satisfiability of a `RelaxedR1CS` instance
+  #[tracing::instrument(skip_all, name = "Spartan2::UPSnark::prove")]
+  fn prove<C: Circuit<G::Scalar>>(pk: &Self::ProverKey, circuit: C) -> Result<Self, SpartanError> {
+    let mut cs: SatisfyingAssignment<G> = SatisfyingAssignment::new();
+    let _ = circuit.synthesize(&mut cs);
+
+    // Commits to witness (expensive)
+    let W = pk.pad_witness(&cs.aux_assignment)?;
+    let comm_W = CE::<G>::commit_small(&pk.ck, &W);
+    let u = R1CSInstance::<G>::new_uniform(
+      &pk.S_single,
+      pk.num_steps,
+      pk.io_per_step,
+      &comm_W,
+      &cs.input_assignment[1..],
+    )?;
+
+    Self::prove_instance(pk, &u, &W)
+  }
+
+  /// verifies a proof of satisfiability of a `RelaxedR1CS` instance
+  #[tracing::instrument(skip_all, name = "SNARK::verify")]
+  fn verify(&self, vk: &Self::VerifierKey, io: &[G::Scalar]) -> Result<(), SpartanError> {
+    // construct an instance using the provided commitment to the witness and IO
+    let comm_W = Commitment::<G>::decompress(&self.comm_W)?;
+    let u = R1CSInstance::new_uniform(&vk.S_single, vk.num_steps, vk.io_per_step, &comm_W, io)?;
+
+    self.verify_instance(vk, &u)
+  }
+}
+
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>> UniformSNARKTrait<G>
+  for R1CSSNARK<G, EE, TE>
+{
   #[tracing::instrument(skip_all, name = "SNARK::setup_uniform")]
   fn setup_uniform<C: Circuit<G::Scalar>>(
     circuit: C,
@@ -465,46 +574,164 @@ impl<G: Group, EE: EvaluationEngineTrait<G>> UniformSNARKTrait<G> for R1CSSNARK<
   ) -> Result<(ProverKey<G, EE>, UniformVerifierKey<G, EE>), SpartanError> {
     let mut cs: ShapeCS<G> = ShapeCS::new();
     let _ = circuit.synthesize(&mut cs);
-    // let (S, S_single, ck) = cs.r1cs_shape_uniform(num_steps);
-    let (S, S_single, ck) = cs.r1cs_shape_uniform(num_steps);
+    let (S_single, ck) = cs.r1cs_shape_uniform(num_steps)?;
 
-    let (pk_ee, vk_ee) = EE::setup(&ck);
+    setup_with_shape(S_single, cs.num_aux(), num_steps, R1CSStateWiring::default(), false, ck)
+  }
 
-    let vk: UniformVerifierKey<G, EE> = UniformVerifierKey::new(S.clone(), vk_ee, S_single, num_steps);
+  #[tracing::instrument(skip_all, name = "SNARK::setup_uniform_with_io_per_step")]
+  fn setup_uniform_with_io_per_step<C: Circuit<G::Scalar>>(
+    circuit: C,
+    num_steps: usize,
+  ) -> Result<(ProverKey<G, EE>, UniformVerifierKey<G, EE>), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let _ = circuit.synthesize(&mut cs);
+    let (S_single, ck) = cs.r1cs_shape_uniform(num_steps)?;
 
-    let pk = ProverKey {
-      ck,
-      pk_ee,
-      S,
-      vk_digest: vk.digest(),
-    };
+    setup_with_shape(S_single, cs.num_aux(), num_steps, R1CSStateWiring::default(), true, ck)
+  }
+
+  /// produces a succinct proof of satisfiability of a uniform circuit, synthesizing each step in parallel
+  #[tracing::instrument(skip_all, name = "Spartan2::UPSnark::prove_uniform")]
+  fn prove_uniform<C: Circuit<G::Scalar> + Send>(
+    pk: &ProverKey<G, EE>,
+    steps: Vec<C>,
+  ) -> Result<Self, SpartanError> {
+    if steps.len() != pk.num_steps {
+      return Err(SpartanError::InvalidNumSteps);
+    }
+    // the steps of a key with state wiring start from an initial state (see `prove_uniform_with_state`)
+    if pk.wiring.arity() != 0 {
+      return Err(SpartanError::InvalidInitialInputLength);
+    }
 
-    Ok((pk, vk))
+    // Commits to witness (expensive)
+    let (W, X) = synthesize_uniform::<G, _>(&pk.S_single, pk.num_aux, pk.io_per_step, steps)?;
+    let W = pk.pad_witness(&W)?;
+    let comm_W = CE::<G>::commit_small(&pk.ck, &W);
+    let u = R1CSInstance::<G>::new_uniform(&pk.S_single, pk.num_steps, pk.io_per_step, &comm_W, &X)?;
+
+    Self::prove_instance(pk, &u, &W)
+  }
+
+  fn set_memory_budget(
+    pk: &mut ProverKey<G, EE>,
+    budget: Option<usize>,
+  ) -> Result<(), SpartanError> {
+    if budget.is_some_and(|budget| budget < pk.min_memory_budget()) {
+      return Err(SpartanError::InvalidMemoryBudget);
+    }
+    pk.memory_budget = budget;
+    Ok(())
+  }
+
+  fn min_memory_budget(pk: &ProverKey<G, EE>) -> Option<usize> {
+    Some(pk.min_memory_budget())
+  }
+
+  #[tracing::instrument(skip_all, name = "SNARK::setup_uniform_with_state")]
+  fn setup_uniform_with_state<SC: StepCircuit<G::Scalar>>(
+    step: &SC,
+    num_steps: usize,
+  ) -> Result<(ProverKey<G, EE>, UniformVerifierKey<G, EE>), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let (z_in, z_out) = synthesize_step(&mut cs, step, &vec![G::Scalar::ZERO; step.arity()])?;
+    let (S_single, wiring, ck) = cs.r1cs_shape_uniform_with_state(num_steps, &z_in, &z_out)?;
+
+    setup_with_shape(S_single, cs.num_aux(), num_steps, wiring, false, ck)
+  }
+
+  /// produces a succinct proof of an execution of `steps` from `z0`, along with the final state
+  #[tracing::instrument(skip_all, name = "Spartan2::UPSnark::prove_uniform_with_state")]
+  fn prove_uniform_with_state<SC: StepCircuit<G::Scalar>>(
+    pk: &ProverKey<G, EE>,
+    steps: &[SC],
+    z0: &[G::Scalar],
+  ) -> Result<(Self, Vec<G::Scalar>), SpartanError> {
+    if steps.len() != pk.num_steps {
+      return Err(SpartanError::InvalidNumSteps);
+    }
+    if z0.len() != pk.wiring.arity() {
+      return Err(SpartanError::InvalidInitialInputLength);
+    }
+
+    // Commits to witness (expensive)
+    let (W, zN) = synthesize_steps::<G, _>(&pk.S_single, pk.num_aux, &pk.wiring, steps, z0)?;
+    let W = pk.pad_witness(&W)?;
+    let comm_W = CE::<G>::commit_small(&pk.ck, &W);
+    let u = R1CSInstance::<G>::new_uniform(
+      &pk.S_single,
+      pk.num_steps,
+      pk.io_per_step,
+      &comm_W,
+      &[z0, &zN].concat(),
+    )?;
+
+    Ok((Self::prove_instance(pk, &u, &W)?, zN))
   }
 }
 
 
-impl<G: Group, EE: EvaluationEngineTrait<G>> PrecommittedSNARKTrait<G> for R1CSSNARK<G, EE> {
-  #[tracing::instrument(skip_all, name = "SNARK::setup_uniform")]
+impl<G: Group, EE: EvaluationEngineTrait<G>, TE: TranscriptEngineTrait<G>> PrecommittedSNARKTrait<G>
+  for R1CSSNARK<G, EE, TE>
+{
+  #[tracing::instrument(skip_all, name = "SNARK::setup_precommitted")]
   fn setup_precommitted<C: Circuit<G::Scalar>>(
     circuit: C,
     num_steps: usize, 
+    ck: CommitmentKey<G>,
   ) -> Result<(ProverKey<G, EE>, UniformVerifierKey<G, EE>), SpartanError> {
     let mut cs: ShapeCS<G> = ShapeCS::new();
     let _ = circuit.synthesize(&mut cs);
-    let (S, S_single, ck) = cs.r1cs_shape_uniform(num_steps); // TODO(arasuarun): replace with precommitted version
+    // the witness is committed with `ck`, so no commitment key is sampled for the shape
+    let S_single = cs.r1cs_shape_single()?;
+
+    setup_with_shape(S_single, cs.num_aux(), num_steps, R1CSStateWiring::default(), false, ck)
+  }
+
+  /// produces a succinct proof of satisfiability of an `R1CS` instance whose witness is committed externally.
+  /// The circuit is only synthesized to obtain the witness, which must be the one committed to in `comm_W`
+  #[tracing::instrument(skip_all, name = "Spartan2::UPSnark::prove_precommitted")]
+  fn prove_precommitted<C: Circuit<G::Scalar>>(
+    pk: &ProverKey<G, EE>,
+    circuit: C,
+    comm_W: Commitment<G>,
+  ) -> Result<Self, SpartanError> {
+    let mut cs: SatisfyingAssignment<G> = SatisfyingAssignment::new();
+    let _ = circuit.synthesize(&mut cs);
 
-    let (pk_ee, vk_ee) = EE::setup(&ck);
+    let W = pk.pad_witness(&cs.aux_assignment)?;
 
-    let vk: UniformVerifierKey<G, EE> = UniformVerifierKey::new(S.clone(), vk_ee, S_single, num_steps);
+    // a commitment to another witness yields a proof that does not verify, so only debug builds
+    // pay for committing to the witness again to catch the mistake early
+    debug_assert!(
+      CE::<G>::commit(&pk.ck, &W) == comm_W,
+      "comm_W is not a commitment to the padded witness under the prover key's commitment key"
+    );
 
-    let pk = ProverKey {
-      ck,
-      pk_ee,
-      S,
-      vk_digest: vk.digest(),
-    };
+    let u = R1CSInstance::<G>::new_uniform(
+      &pk.S_single,
+      pk.num_steps,
+      pk.io_per_step,
+      &comm_W,
+      &cs.input_assignment[1..],
+    )?;
 
-    Ok((pk, vk))
+    Self::prove_instance(pk, &u, &W)
   }
-}
\ No newline at end of file
+
+  /// verifies a proof of satisfiability of an `R1CS` instance against an external commitment to its witness
+  fn verify_precommitted(
+    &self,
+    vk: &UniformVerifierKey<G, EE>,
+    io: &[G::Scalar],
+    comm_W: &Commitment<G>,
+  ) -> Result<(), SpartanError> {
+    if self.comm_W != comm_W.compress() {
+      return Err(SpartanError::ProofVerifyError);
+    }
+
+    let u = R1CSInstance::new_uniform(&vk.S_single, vk.num_steps, vk.io_per_step, comm_W, io)?;
+    self.verify_instance(vk, &u)
+  }
+}
@@ -0,0 +1,844 @@
+//! This module implements an opt-in zero-knowledge mode for the SNARKs in snark.rs and upsnark.rs.
+//! It is specialized to the Hyrax polynomial commitment scheme, whose row commitments can be hidden
+//! with a blinding factor. Compared to the non-zero-knowledge SNARKs:
+//! - the witness is committed with `HyraxCommitmentEngine::commit_hiding`,
+//! - both sum-checks are proven with `ZKSumcheckProof`, which only reveals hiding commitments to the round polynomials,
+//! - the claims at the end of the sum-checks are revealed as hiding commitments and related with the sigma protocols in nizk.rs,
+//! - the witness is opened with a hiding dot-product argument (`HyraxEvaluationEngine::prove_zk`).
+//!
+//! As a result, the proof reveals nothing about the witness beyond the validity of the statement.
+#![allow(clippy::too_many_arguments)]
+use crate::{
+  bellpepper::{
+    r1cs::{synthesize_step, synthesize_steps, synthesize_uniform, SpartanShape},
+    shape_cs::ShapeCS,
+    solver::SatisfyingAssignment,
+  },
+  digest::{DigestComputer, SimpleDigestible},
+  errors::SpartanError,
+  provider::{
+    hyrax_pc::{
+      HyraxBlinds, HyraxCommitment, HyraxCommitmentEngine, HyraxEvaluationEngine,
+      HyraxVerifierKey, HyraxZKEvaluationArgument,
+    },
+    keccak::Keccak256Transcript,
+    pedersen::{
+      Commitment as PedersenCommitment, CommitmentKey as PedersenCommitmentKey,
+      CompressedCommitment as PedersenCompressedCommitment,
+    },
+  },
+  r1cs::{R1CSInstance, R1CSShape, R1CSStateWiring, RelaxedR1CSInstance},
+  spartan::{
+    nizk::{commit, random_scalar, EqualityProof, KnowledgeProof, ProductProof},
+    polys::{
+      eq::{EqPolynomial, SplitEqPolynomial},
+      multilinear::MultilinearPolynomial,
+      multilinear::SparsePolynomial,
+    },
+    sumcheck::ZKSumcheckProof,
+  },
+  traits::{
+    circuit::StepCircuit,
+    commitment::CommitmentTrait,
+    evaluation::EvaluationEngineTrait,
+    snark::RelaxedR1CSSNARKTrait,
+    upsnark::UniformSNARKTrait,
+    PrimeGroup, TranscriptEngineTrait,
+  },
+  CommitmentKey, CompressedCommitment,
+};
+use bellpepper_core::{Circuit, ConstraintSystem};
+use core::marker::PhantomData;
+use ff::Field;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+/// Pedersen generators used by the zero-knowledge sum-checks and sigma protocols
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct ZKGens<G: PrimeGroup> {
+  ck_1: PedersenCommitmentKey<G>,  // commits to scalars
+  ck_sc: PedersenCommitmentKey<G>, // commits to the round polynomials of the sum-checks
+  h: PedersenCommitmentKey<G>,     // blinding generator, shared with the Hyrax commitment key
+}
+
+impl<G> ZKGens<G>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+{
+  fn new(ck: &CommitmentKey<G>) -> Self {
+    ZKGens {
+      ck_1: PedersenCommitmentKey::from_label(b"zk_gens_1", 1),
+      ck_sc: PedersenCommitmentKey::from_label(b"zk_gens_sc", 4), // the sum-checks are at most cubic
+      h: ck.blinding_key().clone(),
+    }
+  }
+}
+
+/// A type that represents the prover's key
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProverKey<G: PrimeGroup> {
+  ck: CommitmentKey<G>,
+  gens: ZKGens<G>,
+  S_single: R1CSShape<G>,  // A single step's shape
+  num_aux: usize,          // Number of witness variables of a single step, before padding
+  num_steps: usize,        // Number of steps
+  wiring: R1CSStateWiring, // Wiring of the state of consecutive steps
+  io_per_step: bool,       // Whether every step has its own public inputs
+  vk_digest: G::Scalar,    // digest of the verifier's key
+}
+
+impl<G: PrimeGroup> ProverKey<G> {
+  /// Returns the number of constraints of the full (repeated) shape
+  fn num_cons(&self) -> usize {
+    self.S_single.num_cons * self.num_steps.next_power_of_two()
+  }
+
+  /// Returns the number of witness variables of the full (repeated) shape
+  fn num_vars(&self) -> usize {
+    self.S_single.num_vars * self.num_steps.next_power_of_two()
+  }
+}
+
+/// A type that represents the verifier's key
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifierKey<G: PrimeGroup> {
+  vk_ee: HyraxVerifierKey<G>,
+  gens: ZKGens<G>,
+  S_single: R1CSShape<G>,  // A single step's shape
+  num_steps: usize,        // Number of steps
+  wiring: R1CSStateWiring, // Wiring of the state of consecutive steps
+  io_per_step: bool,       // Whether every step has its own public inputs
+  #[serde(skip, default = "OnceCell::new")]
+  digest: OnceCell<G::Scalar>,
+}
+
+impl<G: PrimeGroup> SimpleDigestible for VerifierKey<G> {}
+
+impl<G: PrimeGroup> VerifierKey<G> {
+  /// Returns the number of constraints of the full (repeated) shape
+  fn num_cons(&self) -> usize {
+    self.S_single.num_cons * self.num_steps.next_power_of_two()
+  }
+
+  /// Returns the number of witness variables of the full (repeated) shape
+  fn num_vars(&self) -> usize {
+    self.S_single.num_vars * self.num_steps.next_power_of_two()
+  }
+
+  /// Returns the digest of the verifier's key.
+  pub fn digest(&self) -> G::Scalar {
+    self
+      .digest
+      .get_or_try_init(|| {
+        let dc = DigestComputer::<G::Scalar, _>::new(self);
+        dc.digest()
+      })
+      .cloned()
+      .expect("Failure to retrieve digest!")
+  }
+}
+
+fn setup_with_shape<G>(
+  S_single: R1CSShape<G>,
+  num_aux: usize,
+  num_steps: usize,
+  wiring: R1CSStateWiring,
+  io_per_step: bool,
+  ck: CommitmentKey<G>,
+) -> Result<(ProverKey<G>, VerifierKey<G>), SpartanError>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+{
+  if num_steps == 0 {
+    return Err(SpartanError::InvalidNumSteps);
+  }
+
+  let (_pk_ee, vk_ee) = HyraxEvaluationEngine::<G>::setup(&ck);
+  let gens = ZKGens::new(&ck);
+
+  let vk = VerifierKey {
+    vk_ee,
+    gens: gens.clone(),
+    S_single: S_single.clone(),
+    num_steps,
+    wiring: wiring.clone(),
+    io_per_step,
+    digest: OnceCell::new(),
+  };
+
+  let pk = ProverKey {
+    ck,
+    gens,
+    S_single,
+    num_aux,
+    num_steps,
+    wiring,
+    io_per_step,
+    vk_digest: vk.digest(),
+  };
+
+  Ok((pk, vk))
+}
+
+/// Synthesizes the circuit and commits to the witness with a hiding commitment
+fn synthesize_and_commit<G, C>(
+  pk: &ProverKey<G>,
+  circuit: C,
+) -> Result<(Vec<G::Scalar>, Vec<G::Scalar>, HyraxCommitment<G>, HyraxBlinds<G>), SpartanError>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+  C: Circuit<G::Scalar>,
+{
+  let mut cs: SatisfyingAssignment<G> = SatisfyingAssignment::new();
+  let _ = circuit.synthesize(&mut cs);
+
+  let X = cs.input_assignment[1..].to_vec();
+  let (W, comm_W, blinds_W) = commit_witness(pk, cs.aux_assignment)?;
+
+  Ok((W, X, comm_W, blinds_W))
+}
+
+/// Pads the witness of all steps and commits to it with a hiding commitment
+fn commit_witness<G>(
+  pk: &ProverKey<G>,
+  W: Vec<G::Scalar>,
+) -> Result<(Vec<G::Scalar>, HyraxCommitment<G>, HyraxBlinds<G>), SpartanError>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+{
+  let W = pk
+    .S_single
+    .pad_witness_uniform(pk.num_steps, pk.num_aux, &W)?;
+
+  // Commits to witness (expensive)
+  let (comm_W, blinds_W) = HyraxCommitmentEngine::commit_hiding(&pk.ck, &W)?;
+
+  Ok((W, comm_W, blinds_W))
+}
+
+/// Evaluates the MLE of (u, X) at `r_y`, where `u` occupies the first slot and `X` is laid out
+/// as in the shape obtained by repeating `S_single` for `num_steps` steps
+fn evaluate_io<G: PrimeGroup>(
+  S_single: &R1CSShape<G>,
+  num_steps: usize,
+  io_per_step: bool,
+  u: &G::Scalar,
+  X: &[G::Scalar],
+  r_y: &[G::Scalar],
+) -> G::Scalar {
+  let num_vars = S_single.num_vars * num_steps.next_power_of_two();
+  let poly_X = S_single.io_entries_uniform(num_steps, io_per_step, u, X);
+  SparsePolynomial::new(usize::try_from(num_vars.ilog2()).unwrap(), poly_X).evaluate(r_y)
+}
+
+/// A zero-knowledge proof that z = (W, u, X) satisfies Az * Bz = u * Cz,
+/// where W is only known to the verifier through a hiding commitment
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct ZKR1CSProof<G: PrimeGroup> {
+  sc_proof_outer: ZKSumcheckProof<G>,
+  comm_Az: PedersenCompressedCommitment<G>,
+  comm_Bz: PedersenCompressedCommitment<G>,
+  comm_Cz: PedersenCompressedCommitment<G>,
+  comm_prod_Az_Bz: PedersenCompressedCommitment<G>,
+  pok_Cz: KnowledgeProof<G>,
+  proof_prod: ProductProof<G>,
+  proof_eq_outer: EqualityProof<G>,
+  sc_proof_inner: ZKSumcheckProof<G>,
+  comm_eval_W: PedersenCompressedCommitment<G>,
+  eval_arg: HyraxZKEvaluationArgument<G>,
+  proof_eq_inner: EqualityProof<G>,
+}
+
+impl<G> ZKR1CSProof<G>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+{
+  #[tracing::instrument(skip_all, name = "ZKR1CSProof::prove")]
+  fn prove<TE: TranscriptEngineTrait<G>>(
+    pk: &ProverKey<G>,
+    u: &G::Scalar,
+    X: &[G::Scalar],
+    W: &[G::Scalar],
+    comm_W: &HyraxCommitment<G>,
+    blinds_W: &HyraxBlinds<G>,
+    transcript: &mut TE,
+  ) -> Result<Self, SpartanError> {
+    let (S, gens) = (&pk.S_single, &pk.gens);
+
+    // sanity check that R1CSShape has certain size characteristics
+    S.check_regular_shape();
+
+    let (num_rounds_x, num_rounds_y) = (
+      usize::try_from(pk.num_cons().ilog2()).unwrap(),
+      (usize::try_from(pk.num_vars().ilog2()).unwrap() + 1),
+    );
+
+    // compute the full satisfying assignment by concatenating W, u, and X
+    let mut z = [W.to_vec(), vec![*u], X.to_vec()].concat();
+
+    // outer sum-check
+    let tau = (0..num_rounds_x)
+      .map(|_i| transcript.squeeze(b"t"))
+      .collect::<Result<Vec<G::Scalar>, SpartanError>>()?;
+
+    let mut eq_tau = SplitEqPolynomial::new(tau);
+    let (mut poly_Az, mut poly_Bz, mut poly_Cz) = {
+      let (W, io) = z.split_at(W.len());
+      let (poly_Az, poly_Bz, poly_Cz) =
+        S.multiply_vec_uniform(pk.num_steps, &pk.wiring, pk.io_per_step, W, io)?;
+      (
+        MultilinearPolynomial::new(poly_Az),
+        MultilinearPolynomial::new(poly_Bz),
+        MultilinearPolynomial::new(poly_Cz),
+      )
+    };
+
+    let comb_func_outer = |poly_B_comp: &G::Scalar,
+                           poly_C_comp: &G::Scalar,
+                           poly_D_comp: &G::Scalar|
+     -> G::Scalar { *poly_B_comp * *poly_C_comp - *u * *poly_D_comp };
+    let (sc_proof_outer, r_x, claims_outer, blind_claim_outer) =
+      ZKSumcheckProof::prove_cubic_with_eq(
+        &G::Scalar::ZERO, // claim is zero
+        &G::Scalar::ZERO, // and so is its blinding factor
+        &mut eq_tau,
+        &mut poly_Az,
+        &mut poly_Bz,
+        &mut poly_Cz,
+        comb_func_outer,
+        &gens.ck_1,
+        &gens.ck_sc,
+        &gens.h,
+        transcript,
+      )?;
+
+    // commit to the claims from the end of sum-check
+    let (taus_bound_rx, claim_Az, claim_Bz, claim_Cz) =
+      (claims_outer[0], claims_outer[1], claims_outer[2], claims_outer[3]);
+    let claim_prod = claim_Az * claim_Bz;
+    let (blind_Az, blind_Bz, blind_Cz, blind_prod) = (
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+      random_scalar::<G>(),
+    );
+    let comm_Az = commit(&gens.ck_1, &gens.h, &[claim_Az], &blind_Az);
+    let comm_Bz = commit(&gens.ck_1, &gens.h, &[claim_Bz], &blind_Bz);
+    let comm_Cz = commit(&gens.ck_1, &gens.h, &[claim_Cz], &blind_Cz);
+    let comm_prod = commit(&gens.ck_1, &gens.h, &[claim_prod], &blind_prod);
+    transcript.absorb(
+      b"claims_outer",
+      &[comm_Az, comm_Bz, comm_Cz, comm_prod].as_slice(),
+    );
+
+    let pok_Cz = KnowledgeProof::prove(
+      &gens.ck_1,
+      &gens.h,
+      transcript,
+      &comm_Cz,
+      &claim_Cz,
+      &blind_Cz,
+    )?;
+    let proof_prod = ProductProof::prove(
+      &gens.ck_1,
+      &gens.h,
+      transcript,
+      &comm_Az,
+      &comm_Bz,
+      &comm_prod,
+      &claim_Az,
+      &blind_Az,
+      &claim_Bz,
+      &blind_Bz,
+      &blind_prod,
+    )?;
+
+    // show that the final claim of the outer sum-check is tau(r_x) * (Az * Bz - u * Cz)
+    let comm_claim_outer_final = commit(
+      &gens.ck_1,
+      &gens.h,
+      &[taus_bound_rx * (claim_prod - *u * claim_Cz)],
+      &blind_claim_outer,
+    );
+    let comm_claim_outer_final_expected = (comm_prod + comm_Cz * (-*u)) * taus_bound_rx;
+    let proof_eq_outer = EqualityProof::prove(
+      &gens.h,
+      transcript,
+      &comm_claim_outer_final,
+      &comm_claim_outer_final_expected,
+      &blind_claim_outer,
+      &(taus_bound_rx * (blind_prod - *u * blind_Cz)),
+    )?;
+
+    // inner sum-check
+    let r = transcript.squeeze(b"r")?;
+    let claim_inner_joint = claim_Az + r * claim_Bz + r * r * claim_Cz;
+    let blind_inner_joint = blind_Az + r * blind_Bz + r * r * blind_Cz;
+
+    let poly_ABC = S.bind_row_vars_uniform(pk.num_steps, &pk.wiring, pk.io_per_step, &r_x, &r);
+    let poly_z = {
+      // lay out (u, X) after the witness as in the full (repeated) shape
+      z.truncate(W.len());
+      z.resize(pk.num_vars() * 2, G::Scalar::ZERO);
+      for (i, v) in S.io_entries_uniform(pk.num_steps, pk.io_per_step, u, X) {
+        z[W.len() + i] = v;
+      }
+      z
+    };
+
+    let comb_func = |poly_A_comp: &G::Scalar, poly_B_comp: &G::Scalar| -> G::Scalar {
+      *poly_A_comp * *poly_B_comp
+    };
+    let (sc_proof_inner, r_y, claims_inner, blind_claim_inner) = ZKSumcheckProof::prove_quad(
+      &claim_inner_joint,
+      &blind_inner_joint,
+      num_rounds_y,
+      &mut MultilinearPolynomial::new(poly_ABC),
+      &mut MultilinearPolynomial::new(poly_z),
+      comb_func,
+      &gens.ck_1,
+      &gens.ck_sc,
+      &gens.h,
+      transcript,
+    )?;
+
+    // open the committed witness at r_y[1..] in zero-knowledge
+    let eval_W = MultilinearPolynomial::evaluate_with(W, &r_y[1..]);
+    let blind_eval_W = random_scalar::<G>();
+    let comm_eval_W = commit(&gens.ck_1, &gens.h, &[eval_W], &blind_eval_W);
+    let eval_arg = HyraxEvaluationEngine::prove_zk(
+      &pk.ck,
+      &gens.ck_1,
+      transcript,
+      comm_W,
+      blinds_W,
+      W,
+      &r_y[1..],
+      &eval_W,
+      &blind_eval_W,
+    )?;
+
+    // show that the final claim of the inner sum-check is (A + r * B + r^2 * C)(r_x, r_y) * Z(r_y)
+    let eval_ABC = claims_inner[0];
+    let eval_Z = claims_inner[1];
+    let comm_claim_inner_final = commit(
+      &gens.ck_1,
+      &gens.h,
+      &[eval_ABC * eval_Z],
+      &blind_claim_inner,
+    );
+    let comm_eval_Z = comm_eval_W * (G::Scalar::ONE - r_y[0])
+      + commit(
+        &gens.ck_1,
+        &gens.h,
+        &[r_y[0] * evaluate_io(S, pk.num_steps, pk.io_per_step, u, X, &r_y[1..])],
+        &G::Scalar::ZERO,
+      );
+    let proof_eq_inner = EqualityProof::prove(
+      &gens.h,
+      transcript,
+      &comm_claim_inner_final,
+      &(comm_eval_Z * eval_ABC),
+      &blind_claim_inner,
+      &(eval_ABC * (G::Scalar::ONE - r_y[0]) * blind_eval_W),
+    )?;
+
+    Ok(ZKR1CSProof {
+      sc_proof_outer,
+      comm_Az: comm_Az.compress(),
+      comm_Bz: comm_Bz.compress(),
+      comm_Cz: comm_Cz.compress(),
+      comm_prod_Az_Bz: comm_prod.compress(),
+      pok_Cz,
+      proof_prod,
+      proof_eq_outer,
+      sc_proof_inner,
+      comm_eval_W: comm_eval_W.compress(),
+      eval_arg,
+      proof_eq_inner,
+    })
+  }
+
+  fn verify<TE: TranscriptEngineTrait<G>>(
+    &self,
+    vk: &VerifierKey<G>,
+    u: &G::Scalar,
+    X: &[G::Scalar],
+    comm_W: &HyraxCommitment<G>,
+    transcript: &mut TE,
+  ) -> Result<(), SpartanError> {
+    let gens = &vk.gens;
+
+    let (num_rounds_x, num_rounds_y) = (
+      usize::try_from(vk.num_cons().ilog2()).unwrap(),
+      (usize::try_from(vk.num_vars().ilog2()).unwrap() + 1),
+    );
+
+    // outer sum-check
+    let tau = (0..num_rounds_x)
+      .map(|_i| transcript.squeeze(b"t"))
+      .collect::<Result<Vec<G::Scalar>, SpartanError>>()?;
+
+    let (comm_claim_outer_final, r_x) = self.sc_proof_outer.verify(
+      &PedersenCommitment::<G>::default(), // a commitment to zero with a zero blinding factor
+      num_rounds_x,
+      3,
+      &gens.ck_1,
+      &gens.ck_sc,
+      &gens.h,
+      transcript,
+    )?;
+
+    let comm_Az = PedersenCommitment::<G>::decompress(&self.comm_Az)?;
+    let comm_Bz = PedersenCommitment::<G>::decompress(&self.comm_Bz)?;
+    let comm_Cz = PedersenCommitment::<G>::decompress(&self.comm_Cz)?;
+    let comm_prod = PedersenCommitment::<G>::decompress(&self.comm_prod_Az_Bz)?;
+    transcript.absorb(
+      b"claims_outer",
+      &[comm_Az, comm_Bz, comm_Cz, comm_prod].as_slice(),
+    );
+
+    self
+      .pok_Cz
+      .verify(&gens.ck_1, &gens.h, transcript, &comm_Cz)?;
+    self.proof_prod.verify(
+      &gens.ck_1,
+      &gens.h,
+      transcript,
+      &comm_Az,
+      &comm_Bz,
+      &comm_prod,
+    )?;
+
+    // verify the final claim of the outer sum-check
+    let taus_bound_rx = EqPolynomial::new(tau).evaluate(&r_x);
+    let comm_claim_outer_final_expected = (comm_prod + comm_Cz * (-*u)) * taus_bound_rx;
+    self
+      .proof_eq_outer
+      .verify(
+        &gens.h,
+        transcript,
+        &comm_claim_outer_final,
+        &comm_claim_outer_final_expected,
+      )
+      .map_err(|_| SpartanError::InvalidSumcheckProof)?;
+
+    // inner sum-check
+    let r = transcript.squeeze(b"r")?;
+    let comm_claim_inner_joint = comm_Az + comm_Bz * r + comm_Cz * (r * r);
+
+    let (comm_claim_inner_final, r_y) = self.sc_proof_inner.verify(
+      &comm_claim_inner_joint,
+      num_rounds_y,
+      2,
+      &gens.ck_1,
+      &gens.ck_sc,
+      &gens.h,
+      transcript,
+    )?;
+
+    // verify the opening of the committed witness
+    let comm_eval_W = PedersenCommitment::<G>::decompress(&self.comm_eval_W)?;
+    HyraxEvaluationEngine::verify_zk(
+      &vk.vk_ee,
+      &gens.ck_1,
+      transcript,
+      comm_W,
+      &r_y[1..],
+      &comm_eval_W,
+      &self.eval_arg,
+    )?;
+
+    // verify the final claim of the inner sum-check
+    let (eval_A, eval_B, eval_C) = vk.S_single
+      .evaluate_uniform(vk.num_steps, &vk.wiring, vk.io_per_step, &r_x, &r_y);
+    let eval_ABC = eval_A + r * eval_B + r * r * eval_C;
+    let comm_eval_Z = comm_eval_W * (G::Scalar::ONE - r_y[0])
+      + commit(
+        &gens.ck_1,
+        &gens.h,
+        &[r_y[0] * evaluate_io(&vk.S_single, vk.num_steps, vk.io_per_step, u, X, &r_y[1..])],
+        &G::Scalar::ZERO,
+      );
+    self
+      .proof_eq_inner
+      .verify(
+        &gens.h,
+        transcript,
+        &comm_claim_inner_final,
+        &(comm_eval_Z * eval_ABC),
+      )
+      .map_err(|_| SpartanError::InvalidSumcheckProof)?;
+
+    Ok(())
+  }
+}
+
+/// A zero-knowledge variant of `upsnark::R1CSSNARK`:
+/// a proof of knowledge of a witness to an R1CS instance that hides the witness
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct R1CSSNARK<G: PrimeGroup, TE: TranscriptEngineTrait<G> = Keccak256Transcript<G>> {
+  comm_W: CompressedCommitment<G>,
+  proof: ZKR1CSProof<G>,
+  _p: PhantomData<TE>,
+}
+
+impl<G, TE> R1CSSNARK<G, TE>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+  TE: TranscriptEngineTrait<G>,
+{
+  /// produces a proof for the instance (`comm_W`, `X`), whose padded witness `W` is committed with `blinds_W`
+  fn prove_committed(
+    pk: &ProverKey<G>,
+    W: &[G::Scalar],
+    X: &[G::Scalar],
+    comm_W: HyraxCommitment<G>,
+    blinds_W: &HyraxBlinds<G>,
+  ) -> Result<Self, SpartanError> {
+    let u = R1CSInstance::new_uniform(&pk.S_single, pk.num_steps, pk.io_per_step, &comm_W, X)?;
+
+    let mut transcript = TE::new(b"ZKR1CSSNARK");
+    transcript.absorb(b"vk", &pk.vk_digest);
+    transcript.absorb(b"U", &u);
+
+    let proof = ZKR1CSProof::prove(
+      pk,
+      &G::Scalar::ONE,
+      &u.X,
+      W,
+      &u.comm_W,
+      blinds_W,
+      &mut transcript,
+    )?;
+
+    Ok(R1CSSNARK {
+      comm_W: u.comm_W.compress(),
+      proof,
+      _p: PhantomData,
+    })
+  }
+}
+
+impl<G, TE> RelaxedR1CSSNARKTrait<G> for R1CSSNARK<G, TE>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+  TE: TranscriptEngineTrait<G>,
+{
+  type ProverKey = ProverKey<G>;
+  type VerifierKey = VerifierKey<G>;
+
+  fn setup<C: Circuit<G::Scalar>>(
+    circuit: C,
+  ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let _ = circuit.synthesize(&mut cs);
+    let (S, ck) = cs.r1cs_shape()?;
+
+    setup_with_shape(S, cs.num_aux(), 1, R1CSStateWiring::default(), false, ck)
+  }
+
+  /// produces a zero-knowledge succinct proof of satisfiability of an `R1CS` instance
+  #[tracing::instrument(skip_all, name = "Spartan2::ZKR1CSSNARK::prove")]
+  fn prove<C: Circuit<G::Scalar>>(pk: &Self::ProverKey, circuit: C) -> Result<Self, SpartanError> {
+    let (W, X, comm_W, blinds_W) = synthesize_and_commit(pk, circuit)?;
+
+    Self::prove_committed(pk, &W, &X, comm_W, &blinds_W)
+  }
+
+  /// verifies a zero-knowledge proof of satisfiability of an `R1CS` instance
+  fn verify(&self, vk: &Self::VerifierKey, io: &[G::Scalar]) -> Result<(), SpartanError> {
+    // construct an instance using the provided commitment to the witness and IO
+    let comm_W = HyraxCommitment::<G>::decompress(&self.comm_W)?;
+    let u = R1CSInstance::new_uniform(&vk.S_single, vk.num_steps, vk.io_per_step, &comm_W, io)?;
+
+    let mut transcript = TE::new(b"ZKR1CSSNARK");
+    transcript.absorb(b"vk", &vk.digest());
+    transcript.absorb(b"U", &u);
+
+    self
+      .proof
+      .verify(vk, &G::Scalar::ONE, &u.X, &u.comm_W, &mut transcript)
+  }
+}
+
+impl<G, TE> UniformSNARKTrait<G> for R1CSSNARK<G, TE>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+  TE: TranscriptEngineTrait<G>,
+{
+  #[tracing::instrument(skip_all, name = "ZKR1CSSNARK::setup_uniform")]
+  fn setup_uniform<C: Circuit<G::Scalar>>(
+    circuit: C,
+    num_steps: usize,
+  ) -> Result<(ProverKey<G>, VerifierKey<G>), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let _ = circuit.synthesize(&mut cs);
+    let (S_single, ck) = cs.r1cs_shape_uniform(num_steps)?;
+
+    setup_with_shape(
+      S_single,
+      cs.num_aux(),
+      num_steps,
+      R1CSStateWiring::default(),
+      false,
+      ck,
+    )
+  }
+
+  #[tracing::instrument(skip_all, name = "ZKR1CSSNARK::setup_uniform_with_io_per_step")]
+  fn setup_uniform_with_io_per_step<C: Circuit<G::Scalar>>(
+    circuit: C,
+    num_steps: usize,
+  ) -> Result<(ProverKey<G>, VerifierKey<G>), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let _ = circuit.synthesize(&mut cs);
+    let (S_single, ck) = cs.r1cs_shape_uniform(num_steps)?;
+
+    setup_with_shape(
+      S_single,
+      cs.num_aux(),
+      num_steps,
+      R1CSStateWiring::default(),
+      true,
+      ck,
+    )
+  }
+
+  /// produces a zero-knowledge proof of satisfiability of a uniform circuit, synthesizing each step in parallel
+  #[tracing::instrument(skip_all, name = "Spartan2::ZKR1CSSNARK::prove_uniform")]
+  fn prove_uniform<C: Circuit<G::Scalar> + Send>(
+    pk: &ProverKey<G>,
+    steps: Vec<C>,
+  ) -> Result<Self, SpartanError> {
+    if steps.len() != pk.num_steps {
+      return Err(SpartanError::InvalidNumSteps);
+    }
+    // the steps of a key with state wiring start from an initial state (see `prove_uniform_with_state`)
+    if pk.wiring.arity() != 0 {
+      return Err(SpartanError::InvalidInitialInputLength);
+    }
+
+    let (W, X) = synthesize_uniform::<G, _>(&pk.S_single, pk.num_aux, pk.io_per_step, steps)?;
+    let (W, comm_W, blinds_W) = commit_witness(pk, W)?;
+
+    Self::prove_committed(pk, &W, &X, comm_W, &blinds_W)
+  }
+
+  #[tracing::instrument(skip_all, name = "ZKR1CSSNARK::setup_uniform_with_state")]
+  fn setup_uniform_with_state<SC: StepCircuit<G::Scalar>>(
+    step: &SC,
+    num_steps: usize,
+  ) -> Result<(ProverKey<G>, VerifierKey<G>), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let (z_in, z_out) = synthesize_step(&mut cs, step, &vec![G::Scalar::ZERO; step.arity()])?;
+    let (S_single, wiring, ck) = cs.r1cs_shape_uniform_with_state(num_steps, &z_in, &z_out)?;
+
+    setup_with_shape(S_single, cs.num_aux(), num_steps, wiring, false, ck)
+  }
+
+  /// produces a zero-knowledge proof of an execution of `steps` from `z0`, along with the final state
+  #[tracing::instrument(skip_all, name = "Spartan2::ZKR1CSSNARK::prove_uniform_with_state")]
+  fn prove_uniform_with_state<SC: StepCircuit<G::Scalar>>(
+    pk: &ProverKey<G>,
+    steps: &[SC],
+    z0: &[G::Scalar],
+  ) -> Result<(Self, Vec<G::Scalar>), SpartanError> {
+    if steps.len() != pk.num_steps {
+      return Err(SpartanError::InvalidNumSteps);
+    }
+    if z0.len() != pk.wiring.arity() {
+      return Err(SpartanError::InvalidInitialInputLength);
+    }
+
+    let (W, zN) = synthesize_steps::<G, _>(&pk.S_single, pk.num_aux, &pk.wiring, steps, z0)?;
+    let (W, comm_W, blinds_W) = commit_witness(pk, W)?;
+    let X = [z0, &zN].concat();
+
+    Ok((Self::prove_committed(pk, &W, &X, comm_W, &blinds_W)?, zN))
+  }
+}
+
+/// A zero-knowledge variant of `snark::RelaxedR1CSSNARK`:
+/// a proof of knowledge of a witness to a relaxed R1CS instance that hides the witness.
+/// Like its counterpart, it proves instances obtained from a circuit, so the error vector is zero.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RelaxedR1CSSNARK<G: PrimeGroup, TE: TranscriptEngineTrait<G> = Keccak256Transcript<G>> {
+  comm_W: CompressedCommitment<G>,
+  proof: ZKR1CSProof<G>,
+  _p: PhantomData<TE>,
+}
+
+impl<G, TE> RelaxedR1CSSNARKTrait<G> for RelaxedR1CSSNARK<G, TE>
+where
+  G: PrimeGroup<CE = HyraxCommitmentEngine<G>>,
+  TE: TranscriptEngineTrait<G>,
+{
+  type ProverKey = ProverKey<G>;
+  type VerifierKey = VerifierKey<G>;
+
+  fn setup<C: Circuit<G::Scalar>>(
+    circuit: C,
+  ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError> {
+    let mut cs: ShapeCS<G> = ShapeCS::new();
+    let _ = circuit.synthesize(&mut cs);
+    let (S, ck) = cs.r1cs_shape()?;
+
+    setup_with_shape(S, cs.num_aux(), 1, R1CSStateWiring::default(), false, ck)
+  }
+
+  /// produces a zero-knowledge succinct proof of satisfiability of a `RelaxedR1CS` instance
+  #[tracing::instrument(skip_all, name = "Spartan2::ZKRelaxedR1CSSNARK::prove")]
+  fn prove<C: Circuit<G::Scalar>>(pk: &Self::ProverKey, circuit: C) -> Result<Self, SpartanError> {
+    let (W, X, comm_W, blinds_W) = synthesize_and_commit(pk, circuit)?;
+    if X.len() != pk.S_single.num_io {
+      return Err(SpartanError::InvalidInputLength);
+    }
+    let U = RelaxedR1CSInstance::from_r1cs_instance_unchecked(&comm_W, &X);
+
+    let mut transcript = TE::new(b"ZKRelaxedR1CSSNARK");
+    transcript.absorb(b"vk", &pk.vk_digest);
+    transcript.absorb(b"U", &U);
+
+    let proof = ZKR1CSProof::prove(
+      pk,
+      &U.u,
+      &U.X,
+      &W,
+      &U.comm_W,
+      &blinds_W,
+      &mut transcript,
+    )?;
+
+    Ok(RelaxedR1CSSNARK {
+      comm_W: U.comm_W.compress(),
+      proof,
+      _p: PhantomData,
+    })
+  }
+
+  /// verifies a zero-knowledge proof of satisfiability of a `RelaxedR1CS` instance
+  fn verify(&self, vk: &Self::VerifierKey, io: &[G::Scalar]) -> Result<(), SpartanError> {
+    if io.len() != vk.S_single.num_io {
+      return Err(SpartanError::InvalidInputLength);
+    }
+
+    // construct an instance using the provided commitment to the witness and IO
+    let comm_W = HyraxCommitment::<G>::decompress(&self.comm_W)?;
+    let U = RelaxedR1CSInstance::from_r1cs_instance_unchecked(&comm_W, io);
+
+    let mut transcript = TE::new(b"ZKRelaxedR1CSSNARK");
+    transcript.absorb(b"vk", &vk.digest());
+    transcript.absorb(b"U", &U);
+
+    self
+      .proof
+      .verify(vk, &U.u, &U.X, &U.comm_W, &mut transcript)
+  }
+}
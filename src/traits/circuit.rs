@@ -0,0 +1,20 @@
+//! This module defines traits that a step function used with the uniform SNARKs must implement
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+/// A helper trait for a step of a state machine, which maps the state `z_i` to the next state `z_{i+1}`.
+/// Repeating it for `num_steps` steps proves an execution of the state machine from `z_0` to `z_N`.
+pub trait StepCircuit<F: PrimeField>: Send + Sync + Clone {
+  /// Return the number of elements of the state, which is passed unchanged from the
+  /// output of a step to the input of the next step
+  fn arity(&self) -> usize;
+
+  /// Synthesize the circuit for a computation step and return the variables
+  /// that hold the next state. The step must not allocate public inputs, since the
+  /// initial and final states are the public IO of the uniform circuit.
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<F>],
+  ) -> Result<Vec<AllocatedNum<F>>, SynthesisError>;
+}
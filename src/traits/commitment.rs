@@ -49,9 +49,6 @@ pub trait CommitmentTrait<G: Group>:
   + TranscriptReprTrait<G>
   + Serialize
   + for<'de> Deserialize<'de>
-  + CommitmentOps
-  + CommitmentOpsOwned
-  + ScalarMul<G::Scalar>
 {
   /// Holds the type of the compressed commitment
   type CompressedCommitment: Clone
@@ -69,6 +66,24 @@ pub trait CommitmentTrait<G: Group>:
 
   /// Decompresses a compressed commitment into a commitment
   fn decompress(c: &Self::CompressedCommitment) -> Result<Self, SpartanError>;
+
+  /// Returns the linear combination of the commitments with the provided coefficients, which
+  /// commits to the same combination of the committed vectors.
+  /// Commitments that are not homomorphic return `SpartanError::NonHomomorphicCommitment`
+  fn combine(comms: &[Self], coeffs: &[G::Scalar]) -> Result<Self, SpartanError>;
+}
+
+/// This trait defines the behavior of additively homomorphic commitments, whose linear combinations
+/// commit to the same combinations of the committed vectors.
+/// Only folding instances and combining evaluation claims at the same point require it
+pub trait HomomorphicCommitmentTrait<G: Group>:
+  CommitmentTrait<G> + CommitmentOps + CommitmentOpsOwned + ScalarMul<G::Scalar>
+{
+}
+
+impl<G: Group, T> HomomorphicCommitmentTrait<G> for T where
+  T: CommitmentTrait<G> + CommitmentOps + CommitmentOpsOwned + ScalarMul<G::Scalar>
+{
 }
 
 /// A trait that ties different pieces of the commitment generation together
@@ -81,9 +96,17 @@ pub trait CommitmentEngineTrait<G: Group>:
   /// Holds the type of the commitment
   type Commitment: CommitmentTrait<G>;
 
-  /// Samples a new commitment key of a specified size
-  fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey;
+  /// Samples a new commitment key of a specified size, or returns an error if the engine needs a key
+  /// from a trusted setup instead
+  fn setup(label: &'static [u8], n: usize) -> Result<Self::CommitmentKey, SpartanError>;
 
   /// Commits to the provided vector using the provided generators
   fn commit(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment;
+
+  /// Commits to a vector whose entries are mostly zero or small integers, such as a witness
+  /// of boolean gadgets. The result is the same as that of `commit`, and engines that can
+  /// exploit such entries override this default
+  fn commit_small(ck: &Self::CommitmentKey, v: &[G::Scalar]) -> Self::Commitment {
+    Self::commit(ck, v)
+  }
 }
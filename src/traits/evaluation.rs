@@ -3,7 +3,8 @@
 //! and a commitment provided by the commitment engine is treated as a multilinear polynomial commitment
 use crate::{
   errors::SpartanError,
-  traits::{commitment::CommitmentEngineTrait, Group},
+  spartan::{self, sumcheck::SumcheckProof},
+  traits::{commitment::CommitmentEngineTrait, Group, TranscriptEngineTrait},
 };
 use serde::{Deserialize, Serialize};
 
@@ -27,10 +28,10 @@ pub trait EvaluationEngineTrait<G: Group>: Clone + Send + Sync {
   ) -> (Self::ProverKey, Self::VerifierKey);
 
   /// A method to prove the evaluation of a multilinear polynomial
-  fn prove(
+  fn prove<TE: TranscriptEngineTrait<G>>(
     ck: &<<G as Group>::CE as CommitmentEngineTrait<G>>::CommitmentKey,
     pk: &Self::ProverKey,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
     comm: &<<G as Group>::CE as CommitmentEngineTrait<G>>::Commitment,
     poly: &[G::Scalar],
     point: &[G::Scalar],
@@ -38,12 +39,50 @@ pub trait EvaluationEngineTrait<G: Group>: Clone + Send + Sync {
   ) -> Result<Self::EvaluationArgument, SpartanError>;
 
   /// A method to verify the purported evaluation of a multilinear polynomials
-  fn verify(
+  fn verify<TE: TranscriptEngineTrait<G>>(
     vk: &Self::VerifierKey,
-    transcript: &mut G::TE,
+    transcript: &mut TE,
     comm: &<<G as Group>::CE as CommitmentEngineTrait<G>>::Commitment,
     point: &[G::Scalar],
     eval: &G::Scalar,
     arg: &Self::EvaluationArgument,
   ) -> Result<(), SpartanError>;
+
+  /// A method to prove the evaluations of several multilinear polynomials, each at its own point.
+  /// Shorter polynomials are padded with zeros, so their points are padded with leading zeros.
+  /// By default, a sum-check reduces the claims to claims at a common point, where a random
+  /// linear combination of the polynomials is opened with `prove`. The default requires
+  /// homomorphic commitments, so engines over other commitments override it
+  fn prove_batch<TE: TranscriptEngineTrait<G>>(
+    ck: &<<G as Group>::CE as CommitmentEngineTrait<G>>::CommitmentKey,
+    pk: &Self::ProverKey,
+    transcript: &mut TE,
+    comms: &[<<G as Group>::CE as CommitmentEngineTrait<G>>::Commitment],
+    polys: &[Vec<G::Scalar>],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+  ) -> Result<BatchEvaluationArgument<G, Self>, SpartanError> {
+    spartan::prove_batch::<G, Self, _>(ck, pk, transcript, comms, polys, points, evals)
+  }
+
+  /// A method to verify the purported evaluations of several multilinear polynomials, each at its own point
+  fn verify_batch<TE: TranscriptEngineTrait<G>>(
+    vk: &Self::VerifierKey,
+    transcript: &mut TE,
+    comms: &[<<G as Group>::CE as CommitmentEngineTrait<G>>::Commitment],
+    points: &[Vec<G::Scalar>],
+    evals: &[G::Scalar],
+    arg: &BatchEvaluationArgument<G, Self>,
+  ) -> Result<(), SpartanError> {
+    spartan::verify_batch::<G, Self, _>(vk, transcript, comms, points, evals, arg)
+  }
+}
+
+/// An argument for the evaluations of several multilinear polynomials at possibly different points
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BatchEvaluationArgument<G: Group, EE: EvaluationEngineTrait<G>> {
+  pub(crate) sc_proof: SumcheckProof<G>,  // reduces the claims to claims at a common point
+  pub(crate) evals: Vec<G::Scalar>,       // evaluations of the polynomials at the common point
+  pub(crate) arg: EE::EvaluationArgument, // evaluation argument for the combination of the polynomials
 }
@@ -1,33 +1,47 @@
 //! This module defines various traits required by the users of the library to implement.
 use crate::errors::SpartanError;
 use core::{
+  cmp::max,
   fmt::Debug,
   ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
 };
-use ff::{PrimeField, PrimeFieldBits};
+use ff::{Field, PrimeField, PrimeFieldBits};
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
+pub mod circuit;
 pub mod commitment;
 
 use commitment::CommitmentEngineTrait;
 
-/// Represents an element of a group
-/// This is currently tailored for an elliptic curve group
+/// Represents a group by its scalar field and a commitment engine over its scalars, which is all
+/// that the SNARKs use. Commitment engines that are not built from the group operations, such as
+/// Ligero, only need this trait; others require `PrimeGroup`
 pub trait Group:
-  Clone
-  + Copy
-  + Debug
-  + Eq
-  + Sized
+  Clone + Copy + Debug + Eq + Sized + Send + Sync + Serialize + for<'de> Deserialize<'de>
+{
+  /// A type representing an element of the scalar field of the group
+  type Scalar: PrimeField
+    + PrimeFieldBits
+    + PrimeFieldExt
+    + Send
+    + Sync
+    + TranscriptReprTrait<Self>
+    + Serialize
+    + for<'de> Deserialize<'de>;
+
+  /// A type that defines a commitment engine over scalars in the group
+  type CE: CommitmentEngineTrait<Self> + Serialize + for<'de> Deserialize<'de>;
+}
+
+/// Represents an element of a group of prime order
+/// This is currently tailored for an elliptic curve group
+pub trait PrimeGroup:
+  Group
   + GroupOps
   + GroupOpsOwned
   + ScalarMul<<Self as Group>::Scalar>
   + ScalarMulOwned<<Self as Group>::Scalar>
-  + Send
-  + Sync
-  + Serialize
-  + for<'de> Deserialize<'de>
 {
   /// A type representing an element of the base field of the group
   type Base: PrimeField
@@ -36,16 +50,6 @@ pub trait Group:
     + Serialize
     + for<'de> Deserialize<'de>;
 
-  /// A type representing an element of the scalar field of the group
-  type Scalar: PrimeField
-    + PrimeFieldBits
-    + PrimeFieldExt
-    + Send
-    + Sync
-    + TranscriptReprTrait<Self>
-    + Serialize
-    + for<'de> Deserialize<'de>;
-
   /// A type representing the compressed version of the group element
   type CompressedGroupElement: CompressedGroup<GroupElement = Self>
     + Serialize
@@ -54,12 +58,6 @@ pub trait Group:
   /// A type representing preprocessed group element
   type PreprocessedGroupElement: Clone + Debug + Send + Sync + Serialize + for<'de> Deserialize<'de>;
 
-  /// A type that provides a generic Fiat-Shamir transcript to be used when externalizing proofs
-  type TE: TranscriptEngineTrait<Self>;
-
-  /// A type that defines a commitment engine over scalars in the group
-  type CE: CommitmentEngineTrait<Self> + Serialize + for<'de> Deserialize<'de>;
-
   /// A method to compute a multiexponentation
   fn vartime_multiscalar_mul(
     scalars: &[Self::Scalar],
@@ -72,10 +70,14 @@ pub trait Group:
   /// Produces a preprocessed element
   fn preprocessed(&self) -> Self::PreprocessedGroupElement;
 
+  /// Converts a preprocessed element back into a group element
+  fn from_preprocessed(p: &Self::PreprocessedGroupElement) -> Self;
+
   /// Produce a vector of group elements using a static label
   fn from_label(label: &'static [u8], n: usize) -> Vec<Self::PreprocessedGroupElement>;
 
-  /// Returns the affine coordinates (x, y, infinty) for the point
+  /// Returns the affine coordinates (x, y, infinty) for the point.
+  /// Groups without affine coordinates return the canonical encoding of the point as x and zero as y
   fn to_coordinates(&self) -> (Self::Base, Self::Base, bool);
 
   /// Returns an element that is the additive identity of the group
@@ -84,8 +86,9 @@ pub trait Group:
   /// Returns the generator of the group
   fn get_generator() -> Self;
 
-  /// Returns A, B, and the order of the group as a big integer
-  fn get_curve_params() -> (Self::Base, Self::Base, BigInt);
+  /// Returns A, B, and the order of the group as a big integer,
+  /// or `None` for groups that are not short Weierstrass curves
+  fn get_curve_params() -> Option<(Self::Base, Self::Base, BigInt)>;
 }
 
 /// Represents a compressed version of a group element
@@ -103,7 +106,7 @@ pub trait CompressedGroup:
   + 'static
 {
   /// A type that holds the decompressed version of the compressed group element
-  type GroupElement: Group + Serialize + for<'de> Deserialize<'de>;
+  type GroupElement: PrimeGroup + Serialize + for<'de> Deserialize<'de>;
 
   /// Decompresses the compressed group element
   fn decompress(&self) -> Option<Self::GroupElement>;
@@ -138,9 +141,59 @@ impl<T, Rhs, Output> ScalarMulOwned<Rhs, Output> for T where T: for<'r> ScalarMu
 pub trait TranscriptReprTrait<G: Group>: Send + Sync {
   /// returns a byte representation of self to be added to the transcript
   fn to_transcript_bytes(&self) -> Vec<u8>;
+
+  /// returns a representation of self as scalars, for transcript engines that hash field elements.
+  /// By default, this packs the byte representation into scalars
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    bytes_to_transcript_scalars::<G::Scalar>(&self.to_transcript_bytes())
+  }
 }
 
-/// This trait defines the behavior of a transcript engine compatible with Spartan
+/// Packs bytes into field elements, `F::CAPACITY / 8` bytes at a time in little-endian order
+pub fn bytes_to_transcript_scalars<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+  let chunk_size = (F::CAPACITY / 8) as usize;
+  bytes
+    .chunks(chunk_size)
+    .map(|chunk| {
+      chunk
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, b| acc * F::from(256) + F::from(*b as u64))
+    })
+    .collect()
+}
+
+/// Maps an element of `F` to scalars of `G`. This is the element itself if the modulus of `F` is at
+/// most that of `G::Scalar`, and otherwise its little-endian limbs of `G::Scalar::CAPACITY` bits.
+/// The number of scalars only depends on the two fields, so encodings of different values never collide
+pub fn field_to_transcript_scalars<G: Group, F: PrimeFieldBits>(f: &F) -> Vec<G::Scalar> {
+  let char_f = F::char_le_bits().iter().by_vals().collect::<Vec<_>>();
+  let char_s = G::Scalar::char_le_bits().iter().by_vals().collect::<Vec<_>>();
+  let bit = |bits: &[bool], i: usize| bits.get(i).copied().unwrap_or(false);
+  // compare the two moduli from the most significant bit down
+  let fits = (0..max(char_f.len(), char_s.len()))
+    .rev()
+    .find(|&i| bit(&char_f, i) != bit(&char_s, i))
+    .is_none_or(|i| !bit(&char_f, i));
+
+  let bits = f.to_le_bits().iter().by_vals().collect::<Vec<_>>();
+  let limb_size = if fits {
+    bits.len()
+  } else {
+    G::Scalar::CAPACITY as usize
+  };
+  bits
+    .chunks(limb_size)
+    .map(|limb| {
+      limb.iter().rev().fold(G::Scalar::ZERO, |acc, b| {
+        acc.double() + if *b { G::Scalar::ONE } else { G::Scalar::ZERO }
+      })
+    })
+    .collect()
+}
+
+/// This trait defines the behavior of a transcript engine compatible with Spartan.
+/// It is independent of the group: SNARKs take it as a type parameter, which defaults to Keccak256
 pub trait TranscriptEngineTrait<G: Group>: Send + Sync {
   /// initializes the transcript
   fn new(label: &'static [u8]) -> Self;
@@ -170,6 +223,13 @@ impl<G: Group, T: TranscriptReprTrait<G>> TranscriptReprTrait<G> for &[T] {
       .flatten()
       .collect::<Vec<u8>>()
   }
+
+  fn to_transcript_scalars(&self) -> Vec<G::Scalar> {
+    self
+      .iter()
+      .flat_map(|t| t.to_transcript_scalars())
+      .collect()
+  }
 }
 
 pub mod evaluation;
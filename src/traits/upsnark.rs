@@ -1,5 +1,9 @@
 //! This module defines a collection of traits that define the behavior of a zkSNARK for RelaxedR1CS
-use crate::{errors::SpartanError, traits::Group}; //, CommitmentKey, Commitment};
+use crate::{
+  errors::SpartanError,
+  traits::{circuit::StepCircuit, Group},
+  Commitment, CommitmentKey,
+};
 use bellpepper_core::Circuit;
 use serde::{Deserialize, Serialize};
 use crate::traits::snark::RelaxedR1CSSNARKTrait;
@@ -13,6 +17,58 @@ pub trait UniformSNARKTrait<G: Group>:
     circuit: C,
     num_steps: usize,
   ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError>;
+
+  /// Produces the keys for a uniform circuit repeated for `num_steps` steps, where every step has its own
+  /// public inputs. The IO passed to `verify` holds the inputs of each step one after the other
+  fn setup_uniform_with_io_per_step<C: Circuit<G::Scalar>>(
+    circuit: C,
+    num_steps: usize,
+  ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError>;
+
+  /// Produces a new SNARK for a uniform circuit from one circuit per step, whose witnesses are generated in parallel.
+  /// Each step must have the shape of the circuit passed to `setup_uniform` or `setup_uniform_with_io_per_step`
+  fn prove_uniform<C: Circuit<G::Scalar> + Send>(
+    pk: &Self::ProverKey,
+    steps: Vec<C>,
+  ) -> Result<Self, SpartanError>;
+
+  /// Bounds the memory of the tables that the prover builds from the witness to about `budget` bytes,
+  /// or lifts the bound with `None`. The witness, the keys, and the evaluation argument are not counted.
+  /// Under a budget, Az, Bz, and Cz are produced in chunks and dropped once they are bound, at the cost of
+  /// computing them twice; the proofs are the same. The budget is saved with the prover key.
+  /// Returns `InvalidMemoryBudget` if the budget is below `min_memory_budget`, or if the SNARK has no
+  /// memory-bounded prover, which is the default
+  fn set_memory_budget(
+    _pk: &mut Self::ProverKey,
+    budget: Option<usize>,
+  ) -> Result<(), SpartanError> {
+    match budget {
+      None => Ok(()),
+      Some(_) => Err(SpartanError::InvalidMemoryBudget),
+    }
+  }
+
+  /// Returns the smallest budget that `set_memory_budget` accepts, which holds the tables that are not
+  /// produced in chunks and a single row of Az, Bz, and Cz per chunk, or `None` if the SNARK has no
+  /// memory-bounded prover, which is the default
+  fn min_memory_budget(_pk: &Self::ProverKey) -> Option<usize> {
+    None
+  }
+
+  /// Produces the keys for `num_steps` steps of a state machine, where the state written by a step
+  /// is the state read by the next one. The initial and final states are the public IO
+  fn setup_uniform_with_state<SC: StepCircuit<G::Scalar>>(
+    step: &SC,
+    num_steps: usize,
+  ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError>;
+
+  /// Produces a new SNARK for the execution of `steps` from the initial state `z0`, and returns the final state.
+  /// The SNARK is verified with `verify` on the IO `(z0, zN)`
+  fn prove_uniform_with_state<SC: StepCircuit<G::Scalar>>(
+    pk: &Self::ProverKey,
+    steps: &[SC],
+    z0: &[G::Scalar],
+  ) -> Result<(Self, Vec<G::Scalar>), SpartanError>;
 }
 
 /// The witness commitments and generators are passed in externally
@@ -23,9 +79,24 @@ pub trait PrecommittedSNARKTrait<G: Group>:
   fn setup_precommitted<C: Circuit<G::Scalar>>(
     circuit: C,
     num_steps: usize,
+    ck: CommitmentKey<G>,
   ) -> Result<(Self::ProverKey, Self::VerifierKey), SpartanError>;
 
-//   /// Produces a new SNARK for a relaxed R1CS
-//   // fn prove_precommitted<C: Circuit<G::Scalar>>(pk: &Self::ProverKey, circuit: C, comm_W: Commitment<G>) -> Result<Self, SpartanError>;
-//   fn prove_precommitted<C: Circuit<G::Scalar>>(pk: &Self::ProverKey, circuit: C, comm_W: Commitment<G>) -> Result<Self, SpartanError>;
-}
\ No newline at end of file
+  /// Produces a new SNARK for an R1CS whose (padded) witness was committed to externally as `comm_W`
+  /// The padded witness holds each step's witness in its own block of the single step's padded size.
+  /// `comm_W` must be a commitment to it under the key passed to `setup_precommitted`, which debug
+  /// builds assert; otherwise the proof does not verify
+  fn prove_precommitted<C: Circuit<G::Scalar>>(
+    pk: &Self::ProverKey,
+    circuit: C,
+    comm_W: Commitment<G>,
+  ) -> Result<Self, SpartanError>;
+
+  /// Verifies a SNARK produced by `prove_precommitted` against the external commitment `comm_W`
+  fn verify_precommitted(
+    &self,
+    vk: &Self::VerifierKey,
+    io: &[G::Scalar],
+    comm_W: &Commitment<G>,
+  ) -> Result<(), SpartanError>;
+}
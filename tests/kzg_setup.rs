@@ -0,0 +1,22 @@
+//! Checks that KZG commitment keys are not sampled by the library itself outside of its own tests,
+//! which build the crate with `cfg(test)` and so cannot observe this
+use spartan2::{
+  errors::SpartanError,
+  provider::{bn256_kzg::Point, kzg_pc::KZGCommitmentKey},
+  traits::{commitment::CommitmentEngineTrait, Group},
+};
+
+type G = Point;
+
+#[test]
+fn test_kzg_setup_needs_ceremony() {
+  assert!(matches!(
+    <G as Group>::CE::setup(b"ck", 1 << 4),
+    Err(SpartanError::MissingCommitmentKey)
+  ));
+
+  // a key with a known trapdoor can still be produced for benchmarks
+  let ck = KZGCommitmentKey::<G>::insecure_setup(1 << 4);
+  let v = vec![<G as Group>::Scalar::from(1u64); 1 << 4];
+  let _ = <G as Group>::CE::commit(&ck, &v);
+}
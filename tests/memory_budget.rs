@@ -0,0 +1,121 @@
+//! Checks that the uniform prover stays within its memory budget, as reported by the prover's
+//! peak resident set size. It runs in a process of its own, so other tests do not add to the peak
+#![cfg(all(target_os = "linux", target_env = "gnu"))]
+use bellpepper_core::{num::AllocatedNum, Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use spartan2::{
+  errors::SpartanError, provider::hyrax_pc::HyraxEvaluationEngine, spartan::upsnark::R1CSSNARK,
+  SNARK,
+};
+use std::{
+  io::Write,
+  sync::{Arc, Mutex},
+};
+
+type G = pasta_curves::pallas::Point;
+type EE = HyraxEvaluationEngine<G>;
+type S = R1CSSNARK<G, EE>;
+
+/// A step with many constraints on a single variable, whose rows of Az, Bz, and Cz are small values
+#[derive(Clone)]
+struct BooleanStep {
+  num_cons: usize,
+}
+
+impl<Scalar: PrimeField> Circuit<Scalar> for BooleanStep {
+  fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+    let x = AllocatedNum::alloc(cs.namespace(|| "x"), || Ok(Scalar::ONE))?;
+    for i in 0..self.num_cons {
+      cs.enforce(
+        || format!("x is boolean {i}"),
+        |lc| lc + x.get_variable(),
+        |lc| lc + x.get_variable(),
+        |lc| lc + x.get_variable(),
+      );
+    }
+    Ok(())
+  }
+}
+
+// glibc's `M_MMAP_THRESHOLD`, which `mallopt` fixes instead of raising it to the size of the largest freed table
+const M_MMAP_THRESHOLD: i32 = -3;
+
+extern "C" {
+  fn mallopt(param: i32, value: i32) -> i32;
+}
+
+/// A writer that collects the output of the tracing subscriber
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self.0.lock().unwrap().write(buf)
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+/// Proves `steps` under `budget`, and returns the increase of the peak resident set size that the prover
+/// reports at the end of the sum-checks, which cover the tables that the budget bounds
+fn peak_increase(budget: Option<usize>, steps: &[BooleanStep]) -> usize {
+  let (mut pk, vk) =
+    SNARK::<G, S, BooleanStep>::setup_uniform(steps[0].clone(), steps.len()).unwrap();
+  pk.set_memory_budget(budget).unwrap();
+
+  let logs = Logs::default();
+  let writer = logs.clone();
+  let subscriber = tracing_subscriber::fmt()
+    .with_ansi(false)
+    .with_writer(move || writer.clone())
+    .finish();
+  let snark = tracing::subscriber::with_default(subscriber, || {
+    SNARK::prove_uniform(&pk, steps.to_vec()).unwrap()
+  });
+  assert!(snark.verify(&vk, &[]).is_ok());
+
+  let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+  logs
+    .lines()
+    .filter(|line| line.contains("Spartan2::UPSnark::peak_rss"))
+    .find(|line| line.contains("phase=\"inner sum-check\""))
+    .and_then(|line| line.split("peak_rss_increase=").nth(1))
+    .and_then(|rest| rest.split_whitespace().next())
+    .and_then(|bytes| bytes.parse().ok())
+    .expect("the prover reports the increase of its peak resident set size")
+}
+
+#[test]
+fn test_memory_budget_bounds_peak_rss() {
+  // large tables are mapped and unmapped, so the memory of tables dropped in one phase of the prover
+  // is not retained by the allocator, and the peak of a later phase is not added on top of it
+  assert_eq!(unsafe { mallopt(M_MMAP_THRESHOLD, 1 << 20) }, 1);
+
+  // 2^18 rows over 2^10 steps, so Az, Bz, and Cz take 24 MiB as scalars
+  let steps = vec![BooleanStep { num_cons: 1 << 8 }; 1 << 10];
+
+  let (mut pk, _vk) =
+    SNARK::<G, S, BooleanStep>::setup_uniform(steps[0].clone(), steps.len()).unwrap();
+  let min = pk.min_memory_budget().unwrap();
+  assert!(matches!(
+    pk.set_memory_budget(Some(min - 1)),
+    Err(SpartanError::InvalidMemoryBudget)
+  ));
+
+  // the allocator and the pages of the code and the stacks touched by the prover are not part of the budget
+  let slack = 2 << 20;
+  let budget = min + (1 << 20);
+  // the prover reports the peak of the process, so the bounded proof goes first
+  let bounded = peak_increase(Some(budget), &steps);
+  let unbounded = peak_increase(None, &steps);
+  assert!(
+    bounded <= budget + slack,
+    "peak increase of {bounded} bytes exceeds the budget of {budget} bytes"
+  );
+  assert!(
+    unbounded > budget + slack,
+    "peak increase of {unbounded} bytes without a budget fits the budget of {budget} bytes"
+  );
+}